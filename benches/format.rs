@@ -0,0 +1,67 @@
+//! Displaying colors in custom formats, run with `cargo bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use epick::bench::{Color, ColorFormat, CustomColorFormat, FormatOptions, Rgb};
+
+const FORMATS: [(&str, &str); 3] = [
+    ("rgb", "rgb({r255}, {g255}, {b255})"),
+    ("lab", "lab({lab_l:.1}% {lab_a:.1} {lab_b:.1})"),
+    (
+        "every space",
+        "{hsl_h360:.0} {hwb_w100:.0} {lch_uv_c:.1} {hpluv_p:.1} {cam16ucs_a:.2} {ictcp_i:.3} \
+         {hdr_nits:.0} {xyy_x:.4} {c100:.0}",
+    ),
+];
+
+/// A palette of the size shown in the palettes window
+fn palette() -> Vec<Color> {
+    (0..256u32)
+        .map(|i| Rgb::new_scaled(i as u8, (i * 7 % 256) as u8, (i * 13 % 256) as u8).into())
+        .collect()
+}
+
+fn display(c: &mut Criterion) {
+    let colors = palette();
+    let mut group = c.benchmark_group("display 256 colors");
+    for (name, format) in FORMATS {
+        group.bench_with_input(
+            BenchmarkId::new("parsed every time", name),
+            format,
+            |b, format| {
+                b.iter(|| {
+                    for color in &colors {
+                        let format = CustomColorFormat::parse(format).unwrap();
+                        black_box(
+                            format
+                                .format_color(color, &FormatOptions::default())
+                                .unwrap(),
+                        );
+                    }
+                })
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("cached", name), format, |b, format| {
+            b.iter(|| {
+                for color in &colors {
+                    black_box(
+                        color.display(ColorFormat::Custom(format), &FormatOptions::default()),
+                    );
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, format) in FORMATS {
+        group.bench_with_input(BenchmarkId::from_parameter(name), format, |b, format| {
+            b.iter(|| CustomColorFormat::parse(black_box(format)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, display, parse);
+criterion_main!(benches);
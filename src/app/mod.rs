@@ -5,7 +5,7 @@ mod sidepanel;
 pub mod window;
 
 use crate::{
-    color::{Color, ColorHarmony, Gradient},
+    color::{ColorHarmony, Gradient},
     context::{AppCtx, FrameCtx},
     error::{append_global_error, DisplayError, ERROR_STACK},
     keybinding::{default_keybindings, KeyBindings},
@@ -20,7 +20,11 @@ use crate::{
     },
     zoom_picker::ZoomPicker,
 };
-use window::{ExportWindow, HelpWindow, HuesWindow, SettingsWindow, ShadesWindow, TintsWindow};
+use palette::{PaletteOps, PaletteSearch, PaletteView};
+use window::{
+    ExportWindow, HelpWindow, HuesWindow, IlluminantPreviewWindow, RestoreWindow, SettingsWindow,
+    ShadesWindow, TintsWindow,
+};
 
 use eframe::{CreationContext, Storage, Theme};
 use egui::{
@@ -57,12 +61,17 @@ pub struct Windows {
     pub hues: HuesWindow,
     pub tints: TintsWindow,
     pub shades: ShadesWindow,
+    pub illuminant_preview: IlluminantPreviewWindow,
+    pub restore: RestoreWindow,
 }
 
 pub struct App {
     pub display_errors: Vec<DisplayError>,
     pub windows: Windows,
     pub zoom_picker: ZoomPicker,
+    pub palette_search: PaletteSearch,
+    pub palette_ops: PaletteOps,
+    pub palette_view: PaletteView,
 }
 
 impl eframe::App for App {
@@ -90,6 +99,8 @@ impl eframe::App for App {
                 .set_pixels_per_point(ctx.app.settings.pixels_per_point);
 
             ctx.app.check_settings_change();
+            #[cfg(not(target_arch = "wasm32"))]
+            ctx.app.sync_palette_dir();
 
             self.top_panel(&mut ctx);
 
@@ -122,6 +133,7 @@ impl eframe::App for App {
 
             if ctx.egui.memory().focus().is_none() {
                 self.check_keys_pressed(&mut ctx);
+                self.check_paste(&mut ctx);
             }
 
             // No need to repaint in wasm, there is no way to pick color from under the cursor anyway
@@ -146,7 +158,7 @@ impl eframe::App for App {
     }
 
     fn save(&mut self, storage: &mut dyn Storage) {
-        if let Some(ctx) = CONTEXT.get().and_then(|ctx| ctx.read().ok()) {
+        if let Some(mut ctx) = CONTEXT.get().and_then(|ctx| ctx.write().ok()) {
             ctx.save_palettes(storage);
             settings::save_global(&ctx.settings, storage);
         }
@@ -166,6 +178,9 @@ impl App {
             display_errors: Default::default(),
             windows: Windows::default(),
             zoom_picker: ZoomPicker::default(),
+            palette_search: PaletteSearch::default(),
+            palette_ops: PaletteOps::default(),
+            palette_view: PaletteView::default(),
         });
 
         let prefer_dark = context
@@ -227,9 +242,31 @@ impl App {
         }
     }
 
+    /// Uses a color pasted outside of a text field as the current color
+    fn check_paste(&mut self, ctx: &mut FrameCtx) {
+        let pasted: Vec<String> = ctx
+            .egui
+            .input()
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            })
+            .collect();
+        for text in pasted {
+            match ctx.app.parse_color_text(&text) {
+                Some(color) => ctx.app.picker.set_cur_color(color),
+                None => {
+                    append_global_error(format!("The pasted text `{}` is not a color", text.trim()))
+                }
+            }
+        }
+    }
+
     fn hex_input(&self, ctx: &mut FrameCtx<'_>, ui: &mut Ui) {
         CollapsingHeader::new("Text input").show(ui, |ui| {
-            ui.label("Enter a hex color: ");
+            ui.label("Enter a hex code, a color name or a color in a saved custom format: ");
             ui.horizontal(|ui| {
                 let resp = ui.text_edit_singleline(&mut ctx.app.picker.hex_color);
                 if (resp.lost_focus() && ui.input().key_pressed(egui::Key::Enter))
@@ -239,14 +276,13 @@ impl App {
                         .on_hover_cursor(CursorIcon::PointingHand)
                         .clicked()
                 {
-                    if ctx.app.picker.hex_color.len() < 6 {
+                    if ctx.app.picker.hex_color.trim().is_empty() {
                         append_global_error("Enter a color first (ex. ab12ff #1200ff)".to_owned());
-                    } else if let Some(color) =
-                        Color::from_hex(ctx.app.picker.hex_color.trim_start_matches('#'))
+                    } else if let Some(color) = ctx.app.parse_color_text(&ctx.app.picker.hex_color)
                     {
                         ctx.app.picker.set_cur_color(color);
                     } else {
-                        append_global_error("The entered hex color is not valid".to_owned());
+                        append_global_error("The entered color is not valid".to_owned());
                     }
                 }
                 if ui
@@ -406,15 +442,20 @@ impl App {
 
     fn display_windows(&mut self, ctx: &mut FrameCtx<'_>) {
         self.windows.settings.display(ctx);
+        let options = ctx.app.format_options();
         self.windows.settings.custom_formats_window.display(
             &mut ctx.app.settings,
+            &options,
             ctx.egui,
             ctx.app.picker.current_color,
+            ctx.app.palettes.current(),
         );
         self.windows.settings.palette_formats_window.display(ctx);
         if let Err(e) = self.windows.export.display(ctx) {
             append_global_error(e);
         }
+        self.windows.illuminant_preview.display(ctx);
+        self.windows.restore.display(ctx);
 
         self.shades_window(ctx);
         self.tints_window(ctx);
@@ -517,6 +558,15 @@ impl App {
                     .build();
                 ui.horizontal(|ui| {
                     cb.display(ctx, ui);
+                    if ctx.app.settings.hdr_mode {
+                        let preview = ColorBox::builder()
+                            .size((CURRENT_COLOR_BOX_SIZE, CURRENT_COLOR_BOX_SIZE))
+                            .color(ctx.app.picker.hdr_preview())
+                            .hover_help("Tone mapped preview of the HDR color")
+                            .border(true)
+                            .build();
+                        preview.display(ctx, ui);
+                    }
                 });
 
                 self.zoom_picker.display(ctx, ui);
@@ -553,18 +603,42 @@ impl App {
             if ctx.app.settings.color_spaces.hsl {
                 ctx.app.picker.hsl_sliders(ui);
             }
+            if ctx.app.settings.color_spaces.hwb {
+                ctx.app.picker.hwb_sliders(ui);
+            }
             if ctx.app.settings.color_spaces.luv {
                 ctx.app.picker.luv_sliders(ui);
             }
             if ctx.app.settings.color_spaces.lch_uv {
                 ctx.app.picker.lch_uv_sliders(ui);
             }
+            if ctx.app.settings.color_spaces.hsluv {
+                ctx.app.picker.hsluv_sliders(ui);
+            }
+            if ctx.app.settings.color_spaces.hpluv {
+                ctx.app.picker.hpluv_sliders(ui);
+            }
             if ctx.app.settings.color_spaces.lab {
                 ctx.app.picker.lab_sliders(ui);
             }
             if ctx.app.settings.color_spaces.lch_ab {
                 ctx.app.picker.lch_ab_sliders(ui);
             }
+            if ctx.app.settings.color_spaces.cam16 {
+                ctx.app.picker.cam16_sliders(ui);
+            }
+            if ctx.app.settings.color_spaces.cam16_ucs {
+                ctx.app.picker.cam16_ucs_sliders(ui);
+            }
+            if ctx.app.settings.color_spaces.ycbcr {
+                ctx.app.picker.ycbcr_sliders(ui);
+            }
+            if ctx.app.settings.color_spaces.ictcp {
+                ctx.app.picker.ictcp_sliders(ui);
+            }
+            if ctx.app.settings.hdr_mode {
+                ctx.app.picker.hdr_sliders(ui);
+            }
         });
     }
 }
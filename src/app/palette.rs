@@ -1,15 +1,185 @@
 use crate::{
     app::App,
-    color::NamedPalette,
-    context::FrameCtx,
+    color::{
+        import_tokens, DeltaE, NamedPalette, Palette, PaletteFilter, PaletteMeta, PaletteSort,
+        Palettes,
+    },
+    context::{AppCtx, FrameCtx},
+    format_timestamp,
+    math::Float,
     save_to_clipboard,
     ui::{
         colorbox::{ColorBox, COLORBOX_DRAG_TOOLTIP},
-        drag_source, drop_target, icon, DragInfo, SPACE,
+        drag_source, drop_target, icon, DragInfo, HALF_SPACE, SPACE,
     },
 };
 
-use egui::{CursorIcon, Id, Label, RichText, ScrollArea, Ui};
+use anyhow::{anyhow, Result};
+use egui::{
+    CollapsingHeader, Color32, ComboBox, CursorIcon, Grid, Id, Label, RichText, ScrollArea, Slider,
+    TextEdit, Ui,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::color::{import_extensions, import_palette_file};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::append_global_error;
+#[cfg(not(target_arch = "wasm32"))]
+use anyhow::Context;
+
+const SEARCH_COLOR_SIZE: f32 = 20.;
+
+#[cfg(not(target_arch = "wasm32"))]
+const SHARE_HINT: &str = "Copy a palette code that can be imported by others";
+#[cfg(target_arch = "wasm32")]
+const SHARE_HINT: &str = "Put a palette code in the page address to share the palette";
+
+/// Parses a palette code, design tokens or colors pasted by the user
+fn parse_import(text: &str, app: &AppCtx) -> Result<Vec<NamedPalette>> {
+    if NamedPalette::is_code(text) {
+        return NamedPalette::from_code(text).map(|palette| vec![palette]);
+    }
+    match import_tokens(text, "tokens") {
+        Ok(palettes) => Ok(palettes),
+        Err(e) if text.trim_start().starts_with('{') => Err(e),
+        Err(_) => import_color_lines(text, app).map(|palette| vec![palette]),
+    }
+}
+
+/// Reads a palette of colors written one per line as hex codes, names or in a saved custom
+/// color format
+fn import_color_lines(text: &str, app: &AppCtx) -> Result<NamedPalette> {
+    let lines: Vec<_> = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect();
+    let colors = app.parse_color_texts(lines.iter().map(|(_, line)| *line));
+    let mut palette = NamedPalette::new("colors", Palette::default());
+    for ((i, line), color) in lines.into_iter().zip(colors) {
+        let color = color.ok_or_else(|| {
+            anyhow!(
+                "line {} `{}` is not a palette code, design tokens or a color",
+                i + 1,
+                line.trim()
+            )
+        })?;
+        palette.palette.add(color);
+    }
+    Ok(palette)
+}
+
+/// Adds imported palettes and switches to the first one
+fn add_imported(ctx: &mut FrameCtx<'_>, palettes: Vec<NamedPalette>) {
+    let mut first = None;
+    for palette in palettes {
+        let name = ctx.app.palettes.add_unique(palette);
+        first.get_or_insert(name);
+    }
+    if let Some(name) = first {
+        ctx.app.palettes.move_to_name(name);
+    }
+}
+
+/// Lets the user pick a palette file of another application or design tokens and adds its
+/// palettes
+#[cfg(not(target_arch = "wasm32"))]
+fn import_palette_file_dialog(ctx: &mut FrameCtx<'_>) -> Result<()> {
+    let location = std::env::current_dir()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let path = match native_dialog::FileDialog::new()
+        .set_location(&location)
+        .add_filter("Palette files", &import_extensions())
+        .show_open_single_file()?
+    {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    let data =
+        std::fs::read(&path).with_context(|| format!("failed to read `{}`", path.display()))?;
+    let palettes = import_palette_file(&path, &data)
+        .with_context(|| format!("failed to import `{}`", path.display()))?;
+    add_imported(ctx, palettes);
+    Ok(())
+}
+
+/// Copies the code of the palette to the clipboard, on the web it's put in the URL hash instead
+fn share_palette(palette: &NamedPalette) {
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = save_to_clipboard(palette.to_code());
+    #[cfg(target_arch = "wasm32")]
+    crate::set_url_hash(&palette.to_code());
+}
+
+/// State of the search for palette colors similar to a given one
+#[derive(Debug)]
+pub struct PaletteSearch {
+    /// Hex code or name of the searched color
+    pub query: String,
+    pub threshold: Float,
+    pub metric: DeltaE,
+}
+
+/// Change of a palette selected in the palette operations menu
+enum PaletteEdit {
+    Sort(PaletteSort),
+    SmoothOrder,
+    Reverse,
+    Dedupe,
+}
+
+/// Options of the palette operations menu
+#[derive(Debug)]
+pub struct PaletteOps {
+    /// Largest difference between colors considered duplicates
+    pub tolerance: Float,
+    pub metric: DeltaE,
+}
+
+/// State of the folders, tag filters and palette import of the palettes tab
+#[derive(Debug, Default)]
+pub struct PaletteView {
+    /// Tags a palette must have to be displayed
+    pub tags: Vec<String>,
+    /// Tag being typed in the palette details menu
+    pub new_tag: String,
+    /// Palette code or design tokens being imported
+    pub code: String,
+}
+
+impl PaletteView {
+    fn shows(&self, meta: &PaletteMeta) -> bool {
+        self.tags.iter().all(|tag| meta.has_tag(tag))
+    }
+}
+
+/// Positions of the palettes dragged and hovered in the palettes tab
+#[derive(Default)]
+struct PaletteRows {
+    src: Option<usize>,
+    dst: Option<usize>,
+}
+
+impl Default for PaletteOps {
+    fn default() -> Self {
+        Self {
+            tolerance: 2.,
+            metric: DeltaE::default(),
+        }
+    }
+}
+
+impl Default for PaletteSearch {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            threshold: 10.,
+            metric: DeltaE::default(),
+        }
+    }
+}
 
 impl App {
     pub fn palettes_ui(&mut self, ctx: &mut FrameCtx<'_>, ui: &mut Ui) {
@@ -33,31 +203,294 @@ impl App {
                 {
                     ctx.app.palettes.append_empty();
                 }
+                self.palette_import_menu(ctx, ui);
+                if ui
+                    .button(icon::RESTORE)
+                    .on_hover_text("Restore deleted palettes and backups")
+                    .clicked()
+                {
+                    self.windows.restore.show = true;
+                }
             });
+            #[cfg(not(target_arch = "wasm32"))]
+            self.palette_conflicts_ui(ctx, ui);
+            ui.add_space(HALF_SPACE);
+            self.palette_search_ui(ctx, ui);
+            self.palette_tags_ui(ctx, ui);
             ui.add_space(SPACE);
-            let mut palette_src_row = None;
-            let mut palette_dst_row = None;
-
-            let current = ctx.app.palettes.current_idx();
-            for (i, palette) in ctx.app.palettes.clone().iter().enumerate() {
-                let active = current == i;
-                let resp = self.display_palette(palette, active, ctx, ui);
-                if ctx.egui.memory().is_anything_being_dragged() {
-                    if resp.inner.is_drag_source {
-                        palette_src_row = Some(i);
-                    } else if resp.inner.is_drop_target {
-                        palette_dst_row = Some(i);
+
+            let palettes = ctx.app.palettes.clone();
+            let folders = palettes.folders();
+            let mut rows = PaletteRows::default();
+            self.display_folder("", &palettes, &folders, &mut rows, ctx, ui);
+            if let (Some(src_row), Some(dst_row)) = (rows.src, rows.dst) {
+                if ui.input().pointer.any_released() {
+                    // the palettes trade places so they also trade folders
+                    ctx.app.palettes.swap(src_row, dst_row);
+                    ctx.app.palettes.move_to_idx(src_row);
+                    ctx.app.palettes.current_mut().meta.folder =
+                        palettes[src_row].meta.folder.clone();
+                    ctx.app.palettes.move_to_idx(dst_row);
+                    ctx.app.palettes.current_mut().meta.folder =
+                        palettes[dst_row].meta.folder.clone();
+                }
+            }
+        });
+    }
+
+    /// Displays the palettes of `folder` followed by its subfolders as collapsible groups
+    fn display_folder(
+        &mut self,
+        folder: &str,
+        palettes: &Palettes,
+        folders: &[String],
+        rows: &mut PaletteRows,
+        ctx: &mut FrameCtx<'_>,
+        ui: &mut Ui,
+    ) {
+        let current = palettes.current_idx();
+        for (i, palette) in palettes.iter().enumerate() {
+            if palette.meta.folder_path() != folder || !self.palette_view.shows(&palette.meta) {
+                continue;
+            }
+            let resp = self.display_palette(palette, current == i, ctx, ui);
+            if ctx.egui.memory().is_anything_being_dragged() {
+                if resp.inner.is_drag_source {
+                    rows.src = Some(i);
+                } else if resp.inner.is_drop_target {
+                    rows.dst = Some(i);
+                }
+            }
+        }
+
+        for subfolder in folders {
+            let (parent, name) = subfolder.rsplit_once('/').unwrap_or(("", subfolder));
+            if parent != folder {
+                continue;
+            }
+            let count = palettes
+                .iter()
+                .filter(|p| p.meta.is_in_folder(subfolder) && self.palette_view.shows(&p.meta))
+                .count();
+            if count == 0 {
+                continue;
+            }
+            CollapsingHeader::new(format!("{} ({})", name, count))
+                .id_source(("palette-folder", subfolder))
+                .default_open(true)
+                .show(ui, |ui| {
+                    self.display_folder(subfolder, palettes, folders, rows, ctx, ui);
+                });
+        }
+    }
+
+    fn palette_import_menu(&mut self, ctx: &mut FrameCtx<'_>, ui: &mut Ui) {
+        let code = &mut self.palette_view.code;
+        ui.menu_button(icon::IMPORT, |ui| {
+            ui.label("Palette code, design tokens or colors, one per line");
+            ui.add(TextEdit::multiline(code).desired_rows(3));
+            let result = (!code.trim().is_empty()).then(|| parse_import(code, ctx.app));
+            match &result {
+                Some(Err(e)) => {
+                    ui.colored_label(Color32::RED, e.to_string());
+                }
+                Some(Ok(palettes)) => {
+                    for palette in palettes {
+                        ui.label(format!(
+                            "{} with {} colors",
+                            palette.name,
+                            palette.palette.len()
+                        ));
                     }
                 }
+                None => {}
             }
-            if let Some(src_row) = palette_src_row {
-                if let Some(dst_row) = palette_dst_row {
-                    if ui.input().pointer.any_released() {
-                        ctx.app.palettes.swap(src_row, dst_row);
-                        ctx.app.palettes.move_to_idx(dst_row);
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(matches!(result, Some(Ok(_))), egui::Button::new("Import"))
+                    .clicked()
+                {
+                    if let Some(Ok(palettes)) = result {
+                        add_imported(ctx, palettes);
+                        code.clear();
+                        ui.close_menu();
+                    }
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui
+                    .button("Open file…")
+                    .on_hover_text(
+                        "Import GIMP, Inkscape, Krita, LibreOffice, Paint.NET, Procreate or \
+                         Photoshop palettes or design tokens",
+                    )
+                    .clicked()
+                {
+                    ui.close_menu();
+                    if let Err(e) = import_palette_file_dialog(ctx) {
+                        append_global_error(e);
                     }
                 }
+            });
+        })
+        .response
+        .on_hover_text("Import palettes from a palette code, design tokens or a list of colors");
+    }
+
+    fn palette_tags_ui(&mut self, ctx: &mut FrameCtx<'_>, ui: &mut Ui) {
+        let tags = ctx.app.palettes.tags();
+        let view = &mut self.palette_view;
+        view.tags.retain(|tag| tags.contains(tag));
+        if tags.is_empty() {
+            return;
+        }
+        ui.add_space(HALF_SPACE);
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Tags: ");
+            for tag in tags {
+                let selected = view.tags.contains(&tag);
+                if ui
+                    .selectable_label(selected, &tag)
+                    .on_hover_text("Show only palettes with this tag")
+                    .clicked()
+                {
+                    if selected {
+                        view.tags.retain(|it| it != &tag);
+                    } else {
+                        view.tags.push(tag);
+                    }
+                }
+            }
+            if !view.tags.is_empty()
+                && ui
+                    .button(icon::CLEAR)
+                    .on_hover_text("Show all palettes")
+                    .clicked()
+            {
+                view.tags.clear();
+            }
+        });
+    }
+
+    /// Lets the user choose between the palettes of the app and the files of the palettes
+    /// directory when both changed.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn palette_conflicts_ui(&mut self, ctx: &mut FrameCtx<'_>, ui: &mut Ui) {
+        let store = match ctx.app.palette_dir.as_mut() {
+            Some(store) => store,
+            None => return,
+        };
+        let mut resolution = None;
+        for (i, conflict) in store.conflicts().iter().enumerate() {
+            ui.horizontal_wrapped(|ui| {
+                let change = if conflict.theirs.is_some() {
+                    "changed"
+                } else {
+                    "deleted"
+                };
+                ui.colored_label(
+                    Color32::RED,
+                    format!(
+                        "`{}` was changed here and its file `{}` was {}",
+                        conflict.name,
+                        conflict.path.display(),
+                        change
+                    ),
+                );
+                if ui
+                    .button("Keep mine")
+                    .on_hover_text("Overwrite the file with the palette from the app")
+                    .clicked()
+                {
+                    resolution = Some((i, true));
+                }
+                if ui
+                    .button("Use theirs")
+                    .on_hover_text("Replace the palette with the one from the file")
+                    .clicked()
+                {
+                    resolution = Some((i, false));
+                }
+            });
+        }
+        match resolution {
+            Some((i, true)) => store.keep_mine(i),
+            Some((i, false)) => store.use_theirs(i, &mut ctx.app.palettes),
+            None => {}
+        }
+    }
+
+    fn palette_search_ui(&mut self, ctx: &mut FrameCtx<'_>, ui: &mut Ui) {
+        CollapsingHeader::new("Search by color").show(ui, |ui| {
+            let search = &mut self.palette_search;
+            ui.horizontal(|ui| {
+                ui.label("Color: ");
+                ui.text_edit_singleline(&mut search.query)
+                    .on_hover_text("Hex code or name of the color");
+                if ui
+                    .button(icon::PLAY)
+                    .on_hover_text("Search for the current color")
+                    .on_hover_cursor(CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    search.query = ctx.app.picker.current_color.as_hex();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.add(Slider::new(&mut search.threshold, 0. ..=50.).text("max difference"));
+                ComboBox::from_id_source("palette-search-metric")
+                    .selected_text(search.metric.as_ref())
+                    .show_ui(ui, |ui| {
+                        for metric in [DeltaE::Cie76, DeltaE::Cie94, DeltaE::Ciede2000] {
+                            ui.selectable_value(&mut search.metric, metric, metric.as_ref());
+                        }
+                    });
+            });
+
+            if search.query.trim().is_empty() {
+                return;
+            }
+            let color = match ctx.app.parse_color_text(&search.query) {
+                Some(color) => color,
+                None => {
+                    ui.colored_label(Color32::RED, "Not a valid hex code, color name or color");
+                    return;
+                }
+            };
+            let found = ctx.app.palettes.find_similar(
+                &color,
+                search.threshold,
+                search.metric,
+                ctx.app.settings.rgb_working_space,
+                ctx.app.settings.illuminant,
+                ctx.app.settings.chromatic_adaptation_method,
+            );
+            if found.is_empty() {
+                ui.label("No similar colors found");
+                return;
             }
+            let metric = search.metric;
+
+            ui.label(format!("Found {} colors", found.len()));
+            Grid::new("palette-search-results")
+                .spacing((SPACE, HALF_SPACE))
+                .show(ui, |ui| {
+                    for it in &found {
+                        ColorBox::builder()
+                            .size((SEARCH_COLOR_SIZE, SEARCH_COLOR_SIZE))
+                            .color(it.color)
+                            .build()
+                            .display(ctx, ui);
+                        if ui
+                            .link(format!("{} #{}", it.palette, it.index + 1))
+                            .on_hover_text("Use this palette")
+                            .clicked()
+                        {
+                            ctx.app.palettes.move_to_name(&it.palette);
+                        }
+                        ui.label(format!("{} {:.2}", metric.as_ref(), it.delta_e));
+                        ui.end_row();
+                    }
+                });
         });
     }
 
@@ -83,7 +516,13 @@ impl App {
                         label = label.strong().heading();
                     }
                     ui.vertical(|ui| {
-                        ui.add(Label::new(label));
+                        let resp = ui.add(Label::new(label));
+                        if !palette.meta.description.is_empty() {
+                            resp.on_hover_text(&palette.meta.description);
+                        }
+                        if !palette.meta.tags.is_empty() {
+                            ui.weak(palette.meta.tags.join(", "));
+                        }
                         self.display_palette_colors(palette, ctx, ui);
                         ui.add_space(SPACE);
                     });
@@ -126,6 +565,25 @@ impl App {
                 self.windows.export.show = true;
                 self.windows.export.export_palette = Some(palette.clone());
             }
+            if ui
+                .button(icon::ILLUMINANT)
+                .on_hover_text("Preview under a different illuminant")
+                .on_hover_cursor(CursorIcon::PointingHand)
+                .clicked()
+            {
+                self.windows.illuminant_preview.show = true;
+                self.windows.illuminant_preview.palette = Some(palette.clone());
+            }
+            self.palette_details_menu(palette, ctx, ui);
+            self.palette_ops_menu(palette, ctx, ui);
+            if ui
+                .button(icon::SHARE)
+                .on_hover_text(SHARE_HINT)
+                .on_hover_cursor(CursorIcon::Alias)
+                .clicked()
+            {
+                share_palette(palette);
+            }
             if ui
                 .button(icon::COPY)
                 .on_hover_text("Copy all colors to clipboard")
@@ -134,13 +592,12 @@ impl App {
             {
                 let _ = save_to_clipboard(palette.display(
                     &ctx.app.settings.palette_clipboard_format,
-                    ctx.app.settings.rgb_working_space,
-                    ctx.app.settings.illuminant,
+                    &ctx.app.format_options(),
                 ));
             }
             if ui
                 .button(icon::DELETE)
-                .on_hover_text("Delete this palette")
+                .on_hover_text("Move this palette to the trash")
                 .clicked()
             {
                 ctx.app.palettes.remove(palette);
@@ -148,6 +605,149 @@ impl App {
         })
     }
 
+    fn palette_details_menu(
+        &mut self,
+        palette: &NamedPalette,
+        ctx: &mut FrameCtx<'_>,
+        ui: &mut Ui,
+    ) {
+        let new_tag = &mut self.palette_view.new_tag;
+
+        ui.menu_button(icon::INFO, |ui| {
+            let mut meta = palette.meta.clone();
+            ui.label("Description");
+            ui.text_edit_multiline(&mut meta.description);
+            Grid::new("palette-details").show(ui, |ui| {
+                ui.label("Folder");
+                ui.text_edit_singleline(&mut meta.folder)
+                    .on_hover_text("Separate nested folders with `/`, like `brand/web`");
+                ui.end_row();
+                ui.label("Source");
+                ui.text_edit_singleline(&mut meta.source);
+                ui.end_row();
+                ui.label("Tags");
+                ui.horizontal(|ui| {
+                    let resp = ui.text_edit_singleline(new_tag);
+                    let submitted = resp.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+                    if (ui.button(icon::ADD).on_hover_text("Add tag").clicked() || submitted)
+                        && meta.add_tag(new_tag)
+                    {
+                        new_tag.clear();
+                    }
+                });
+                ui.end_row();
+            });
+            ui.horizontal_wrapped(|ui| {
+                for tag in &palette.meta.tags {
+                    if ui
+                        .button(format!("{} {}", tag, icon::DELETE))
+                        .on_hover_text("Remove this tag")
+                        .clicked()
+                    {
+                        meta.remove_tag(tag);
+                    }
+                }
+            });
+            if meta.created > 0 {
+                ui.label(format!("Created {}", format_timestamp(meta.created)));
+            }
+            if meta.modified > 0 {
+                ui.label(format!("Modified {}", format_timestamp(meta.modified)));
+            }
+
+            if meta != palette.meta {
+                ctx.app.palettes.move_to_name(&palette.name);
+                let current = ctx.app.palettes.current_mut();
+                current.meta = meta;
+                current.touch();
+            }
+        })
+        .response
+        .on_hover_text("Details");
+    }
+
+    fn palette_ops_menu(&mut self, palette: &NamedPalette, ctx: &mut FrameCtx<'_>, ui: &mut Ui) {
+        let ws = ctx.app.settings.rgb_working_space;
+        let illuminant = ctx.app.settings.illuminant;
+        let method = ctx.app.settings.chromatic_adaptation_method;
+        let ops = &mut self.palette_ops;
+
+        ui.menu_button(icon::TOOLS, |ui| {
+            let mut edit = None;
+            ui.menu_button("Sort by", |ui| {
+                for by in [
+                    PaletteSort::Hue,
+                    PaletteSort::Lightness,
+                    PaletteSort::Chroma,
+                    PaletteSort::Luminance,
+                ] {
+                    if ui.button(by.as_ref()).clicked() {
+                        edit = Some(PaletteEdit::Sort(by));
+                    }
+                }
+            });
+            if ui
+                .button("Smooth order")
+                .on_hover_text("Order the colors so that similar ones are next to each other")
+                .clicked()
+            {
+                edit = Some(PaletteEdit::SmoothOrder);
+            }
+            if ui.button("Reverse").clicked() {
+                edit = Some(PaletteEdit::Reverse);
+            }
+            ui.separator();
+            ui.add(Slider::new(&mut ops.tolerance, 0. ..=10.).text("tolerance"));
+            ui.horizontal(|ui| {
+                for metric in [DeltaE::Cie76, DeltaE::Cie94, DeltaE::Ciede2000] {
+                    ui.selectable_value(&mut ops.metric, metric, metric.as_ref());
+                }
+            });
+            if ui
+                .button("Remove duplicates")
+                .on_hover_text("Remove colors within the tolerance of a color before them")
+                .clicked()
+            {
+                edit = Some(PaletteEdit::Dedupe);
+            }
+            ui.separator();
+            ui.menu_button("New palette with", |ui| {
+                for filter in [
+                    PaletteFilter::Saturated,
+                    PaletteFilter::Muted,
+                    PaletteFilter::Light,
+                    PaletteFilter::Dark,
+                ] {
+                    if ui.button(format!("{} colors", filter.as_ref())).clicked() {
+                        ctx.app.palettes.add_unique(palette.derived(
+                            format!("{} ({})", palette.name, filter.as_ref()),
+                            palette.palette.filtered(filter, ws, illuminant, method),
+                        ));
+                        ui.close_menu();
+                    }
+                }
+            });
+
+            if let Some(edit) = edit {
+                ctx.app.palettes.move_to_name(&palette.name);
+                let current = ctx.app.palettes.current_mut();
+                let it = &mut current.palette;
+                match edit {
+                    PaletteEdit::Sort(by) => it.sort(by, ws, illuminant, method),
+                    PaletteEdit::SmoothOrder => it.sort_smooth(ops.metric, ws, illuminant, method),
+                    PaletteEdit::Reverse => it.reverse(),
+                    PaletteEdit::Dedupe => {
+                        it.dedupe(ops.tolerance, ops.metric, ws, illuminant, method);
+                    }
+                }
+                current.touch();
+                ui.close_menu();
+            }
+        })
+        .response
+        .on_hover_text("Sort, filter and remove duplicates");
+    }
+
     fn display_palette_colors(
         &mut self,
         palette: &NamedPalette,
@@ -170,7 +770,10 @@ impl App {
                                 ))
                                 .color(*color)
                                 .label(ctx.app.palettes_tab_display_label)
-                                .hover_help(COLORBOX_DRAG_TOOLTIP)
+                                .hover_help(match palette.color_name(color) {
+                                    Some(name) => format!("{name}\n\n{COLORBOX_DRAG_TOOLTIP}"),
+                                    None => COLORBOX_DRAG_TOOLTIP.to_string(),
+                                })
                                 .build();
                             ui.vertical(|ui| {
                                 cb.display(ctx, ui);
@@ -189,9 +792,10 @@ impl App {
                     if let Some(dst_row) = color_dst_row {
                         if ui.input().pointer.any_released() {
                             ctx.app.palettes.move_to_name(&palette.name);
-                            let palette = &mut ctx.app.palettes.current_mut().palette;
-                            if let Some(it) = palette.remove_pos(src_row) {
-                                palette.insert(dst_row, it);
+                            let current = ctx.app.palettes.current_mut();
+                            if let Some(it) = current.palette.remove_pos(src_row) {
+                                current.palette.insert(dst_row, it);
+                                current.touch();
                             }
                         }
                     }
@@ -1,11 +1,46 @@
 use crate::{
     app::{App, FrameCtx},
+    color::DeltaE,
     render::render_color,
     save_to_clipboard,
     ui::{colors::*, drag_source, drop_target, icon, HALF_SPACE, SPACE},
 };
 
-use egui::{style::Margin, vec2, CursorIcon, Id, Label, RichText, ScrollArea, Ui};
+use egui::{
+    style::Margin, vec2, CursorIcon, Id, Label, RichText, ScrollArea, Slider, TextEdit, Ui,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{color::Cgats, error::append_global_error};
+#[cfg(not(target_arch = "wasm32"))]
+use anyhow::{Context, Result};
+
+/// Operation combining the current palette with another one into a new palette
+#[derive(Clone, Copy)]
+enum PaletteSetOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl PaletteSetOp {
+    fn label(&self) -> &str {
+        match self {
+            PaletteSetOp::Union => "Merge with",
+            PaletteSetOp::Intersection => "Intersect with",
+            PaletteSetOp::Difference => "Subtract",
+        }
+    }
+
+    /// Joins the names of the palettes in the name of the result
+    fn symbol(&self) -> &str {
+        match self {
+            PaletteSetOp::Union => "+",
+            PaletteSetOp::Intersection => "&",
+            PaletteSetOp::Difference => "-",
+        }
+    }
+}
 
 impl App {
     const MAX_NAME_LEN: usize = 15;
@@ -98,7 +133,9 @@ impl App {
                 .on_hover_cursor(CursorIcon::PointingHand)
                 .clicked()
             {
-                ctx.app.palettes.current_mut().palette.clear();
+                let current = ctx.app.palettes.current_mut();
+                current.palette.clear();
+                current.touch();
             }
             if ui
                 .button(icon::EXPORT)
@@ -109,6 +146,17 @@ impl App {
                 self.windows.export.show = true;
                 self.windows.export.export_palette = Some(ctx.app.palettes.current().clone());
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui
+                .button(icon::IMPORT)
+                .on_hover_text("Import measurements from a CGATS file")
+                .on_hover_cursor(CursorIcon::PointingHand)
+                .clicked()
+            {
+                if let Err(e) = Self::import_measurements(ctx) {
+                    append_global_error(e);
+                }
+            }
             if ui
                 .button(icon::COPY)
                 .on_hover_text("Copy all colors to clipboard")
@@ -117,8 +165,7 @@ impl App {
             {
                 let _ = save_to_clipboard(ctx.app.palettes.current().display(
                     &ctx.app.settings.palette_clipboard_format,
-                    ctx.app.settings.rgb_working_space,
-                    ctx.app.settings.illuminant,
+                    &ctx.app.format_options(),
                 ));
             }
             #[allow(clippy::collapsible_if)]
@@ -131,9 +178,10 @@ impl App {
                 ctx.app.sidepanel.edit_palette_name = !ctx.app.sidepanel.edit_palette_name;
                 ctx.app.sidepanel.trigger_edit_focus = ctx.app.sidepanel.edit_palette_name;
             }
+            self.side_panel_palette_ops(ctx, ui);
             if ui
                 .button(icon::DELETE)
-                .on_hover_text("Delete current palette")
+                .on_hover_text("Move current palette to the trash")
                 .clicked()
             {
                 ctx.app.palettes.remove_current();
@@ -141,6 +189,126 @@ impl App {
         })
     }
 
+    fn side_panel_palette_ops(&mut self, ctx: &mut FrameCtx<'_>, ui: &mut Ui) {
+        let ws = ctx.app.settings.rgb_working_space;
+        let illuminant = ctx.app.settings.illuminant;
+        let method = ctx.app.settings.chromatic_adaptation_method;
+        let ops = &mut self.palette_ops;
+
+        ui.menu_button(icon::TOOLS, |ui| {
+            let current_idx = ctx.app.palettes.current_idx();
+            let current = ctx.app.palettes.current().clone();
+            let mut created = vec![];
+
+            if ui.button("Duplicate").clicked() {
+                if let Some(name) = ctx.app.palettes.duplicate(current_idx) {
+                    ctx.app.palettes.move_to_name(name);
+                }
+                ui.close_menu();
+            }
+            ui.separator();
+            ui.add(Slider::new(&mut ops.tolerance, 0. ..=10.).text("tolerance"))
+                .on_hover_text("Largest difference of colors treated as the same color");
+            ui.horizontal(|ui| {
+                for metric in [DeltaE::Cie76, DeltaE::Cie94, DeltaE::Ciede2000] {
+                    ui.selectable_value(&mut ops.metric, metric, metric.as_ref());
+                }
+            });
+            for op in [
+                PaletteSetOp::Union,
+                PaletteSetOp::Intersection,
+                PaletteSetOp::Difference,
+            ] {
+                ui.menu_button(op.label(), |ui| {
+                    for other in ctx.app.palettes.iter() {
+                        if other.name == current.name || !ui.button(&other.name).clicked() {
+                            continue;
+                        }
+                        let (a, b) = (&current.palette, &other.palette);
+                        let (tolerance, metric) = (ops.tolerance, ops.metric);
+                        let palette = match op {
+                            PaletteSetOp::Union => {
+                                a.union(b, tolerance, metric, ws, illuminant, method)
+                            }
+                            PaletteSetOp::Intersection => {
+                                a.intersection(b, tolerance, metric, ws, illuminant, method)
+                            }
+                            PaletteSetOp::Difference => {
+                                a.difference(b, tolerance, metric, ws, illuminant, method)
+                            }
+                        };
+                        created.push(current.derived(
+                            format!("{} {} {}", current.name, op.symbol(), other.name),
+                            palette,
+                        ));
+                        ui.close_menu();
+                    }
+                });
+            }
+            ui.separator();
+            if ui
+                .button("Split by hue families")
+                .on_hover_text("Create a palette for every hue family of the colors")
+                .clicked()
+            {
+                for (family, palette) in current.palette.split_by_hue(ws, illuminant, method) {
+                    created.push(
+                        current.derived(format!("{} ({})", current.name, family.as_ref()), palette),
+                    );
+                }
+                ui.close_menu();
+            }
+
+            let names: Vec<_> = created
+                .into_iter()
+                .map(|palette| ctx.app.palettes.add_unique(palette))
+                .collect();
+            if let Some(name) = names.first() {
+                ctx.app.palettes.move_to_name(name);
+            }
+        })
+        .response
+        .on_hover_text("Duplicate, combine or split the palette");
+    }
+
+    /// Lets the user pick a CGATS measurement file and adds its samples as a new palette.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import_measurements(ctx: &mut FrameCtx<'_>) -> Result<()> {
+        let location = std::env::current_dir()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let path = match native_dialog::FileDialog::new()
+            .set_location(&location)
+            .add_filter("CGATS measurement data", &["txt", "cgats", "cgt"])
+            .show_open_single_file()?
+        {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let data = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read `{}`", path.display()))?;
+        let cgats =
+            Cgats::parse(&data).with_context(|| format!("failed to parse `{}`", path.display()))?;
+        let name = cgats.descriptor.clone().unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+        let settings = &ctx.app.settings;
+        let mut palette = cgats.to_named_palette(
+            ctx.app.palettes.unique_name(name),
+            settings.illuminant,
+            settings.observer,
+            settings.rgb_working_space,
+            settings.chromatic_adaptation_method,
+        )?;
+        palette.meta.source = path.display().to_string();
+        ctx.app.palettes.add(palette);
+        ctx.app.palettes.move_to_last();
+        Ok(())
+    }
+
     fn side_panel_palette_name(
         &mut self,
         ctx: &mut FrameCtx<'_>,
@@ -165,7 +333,10 @@ impl App {
                     resp.response.request_focus();
                     ctx.app.sidepanel.trigger_edit_focus = false;
                 }
-                current_palette.name = edit_name;
+                if current_palette.name != edit_name {
+                    current_palette.name = edit_name;
+                    current_palette.touch();
+                }
                 if ui
                     .button(icon::APPLY)
                     .on_hover_text("Finish editing")
@@ -233,11 +404,32 @@ impl App {
                                     .on_hover_cursor(CursorIcon::PointingHand)
                                     .clicked()
                                 {
-                                    ctx.app.palettes.current_mut().palette.remove(color);
+                                    let current = ctx.app.palettes.current_mut();
+                                    current.palette.remove(color);
+                                    current.touch();
                                 }
                             });
                             ui.vertical(|ui| {
                                 ui.monospace(color_str);
+                                let mut name = current_palette
+                                    .color_name(color)
+                                    .unwrap_or_default()
+                                    .to_string();
+                                let name_edit = TextEdit::singleline(&mut name)
+                                    .hint_text("name")
+                                    .desired_width(box_width);
+                                let mut name_help =
+                                    "Name of the color used when exporting tokens".to_string();
+                                let aliases = current_palette.color_aliases(color);
+                                if !aliases.is_empty() {
+                                    name_help.push_str("\n\nAlso exported as ");
+                                    name_help.push_str(&aliases.join(", "));
+                                }
+                                if ui.add(name_edit).on_hover_text(name_help).changed() {
+                                    let current = ctx.app.palettes.current_mut();
+                                    current.set_color_name(color, name);
+                                    current.touch();
+                                }
                                 let help = format!(
                                     "{}\n\nDrag and drop to change the order of colors",
                                     color_str
@@ -275,9 +467,10 @@ impl App {
         if let Some(src_row) = src_row {
             if let Some(dst_row) = dst_row {
                 if ui.input().pointer.any_released() {
-                    let palette = &mut ctx.app.palettes.current_mut().palette;
-                    if let Some(it) = palette.remove_pos(src_row) {
-                        palette.insert(dst_row, it);
+                    let current = ctx.app.palettes.current_mut();
+                    if let Some(it) = current.palette.remove_pos(src_row) {
+                        current.palette.insert(dst_row, it);
+                        current.touch();
                     }
                 }
             }
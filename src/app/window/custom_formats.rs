@@ -1,9 +1,12 @@
-use egui::{Button, Key, TextBuffer, TextEdit, Window};
+use egui::{Button, Color32, Key, ScrollArea, TextBuffer, TextEdit, Window};
 
 use crate::{
     app::settings::{ColorDisplayFmtEnum, Settings},
-    color::{Color, ColorFormat},
-    ui::icon,
+    color::{Color, ColorFormat, CustomColorFormat, FormatKind, FormatOptions, NamedPalette},
+    ui::{
+        format_editor::{field_reference, format_completions, format_edit, format_error},
+        icon, SPACE,
+    },
 };
 
 #[derive(Default, Debug)]
@@ -19,8 +22,10 @@ impl CustomFormatsWindow {
     pub(crate) fn display(
         &mut self,
         settings: &mut Settings,
+        options: &FormatOptions,
         ctx: &egui::Context,
         preview_color: Color,
+        palette: &NamedPalette,
     ) {
         Window::new("Custom color formats")
             .open(&mut self.show)
@@ -57,11 +62,15 @@ impl CustomFormatsWindow {
                                     self.new_key = k.clone();
                                 }
                             }
-                            let edit_re = ui.text_edit_singleline(v);
+                            let edit_re = format_edit(
+                                ui,
+                                v,
+                                ui.make_persistent_id(("format", k)),
+                                FormatKind::Color,
+                                false,
+                            );
                             if edit_re.gained_focus() {
                                 self.highlighted_key = k.clone();
-                            } else if *k == self.highlighted_key && edit_re.lost_focus() {
-                                self.highlighted_key.clear();
                             }
 
                             if !retain {
@@ -111,19 +120,62 @@ impl CustomFormatsWindow {
                     });
                 if replace {
                     let value = settings.saved_color_formats.remove(&self.edit_key).unwrap();
+                    if self.highlighted_key == self.edit_key {
+                        self.highlighted_key = self.new_key.clone();
+                    }
                     settings
                         .saved_color_formats
                         .insert(self.new_key.take(), value);
                     self.edit_key.clear();
                 }
-                if !self.highlighted_key.is_empty() {
+                if let Some(format) = settings.saved_color_formats.get_mut(&self.highlighted_key) {
+                    let id = ui.make_persistent_id(("format", &self.highlighted_key));
+                    format_completions(ui, format, id, FormatKind::Color);
+                    field_reference(ui, format, id, FormatKind::Color);
+
                     ui.heading("Preview");
-                    let preview_string = preview_color.display(
-                        ColorFormat::Custom(&settings.saved_color_formats[&self.highlighted_key]),
-                        settings.rgb_working_space,
-                        settings.illuminant,
-                    );
-                    ui.label(preview_string);
+                    if let Err(e) = CustomColorFormat::validate(format) {
+                        format_error(ui, &e);
+                        return;
+                    }
+                    let display = |color: &Color| {
+                        color.display(
+                            ColorFormat::Custom(format),
+                            options,
+                        )
+                    };
+                    let preview_string = display(&preview_color);
+                    ui.monospace(&preview_string);
+                    match CustomColorFormat::cached(format).and_then(|format| format.matcher()) {
+                        Ok(matcher) => {
+                            let color = matcher.parse(
+                                &preview_string,
+                                options,
+                            );
+                            match color {
+                                Some(color) => {
+                                    ui.label(format!("Reads back as {}", color.as_hex()))
+                                }
+                                None => ui.label("The preview can't be read back"),
+                            };
+                        }
+                        Err(e) => {
+                            ui.colored_label(Color32::RED, format!("Can't be read back, {}", e));
+                        }
+                    }
+
+                    if !palette.palette.is_empty() {
+                        ui.add_space(SPACE);
+                        ui.label(format!("Palette {}", palette.name));
+                        ScrollArea::vertical()
+                            .id_source("custom_format_palette_preview")
+                            .max_height(200.)
+                            .show(ui, |ui| {
+                                for color in palette.palette.iter() {
+                                    ui.monospace(display(color));
+                                }
+                            });
+                    }
                 }
             });
     }
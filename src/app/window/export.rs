@@ -1,12 +1,20 @@
 use crate::{
     app::window::{self, WINDOW_X_OFFSET, WINDOW_Y_OFFSET},
-    color::{NamedPalette, PaletteFormat},
+    color::{
+        NamedPalette, PaletteFormat, PlatformExport, PlatformFormat, SheetFormat, SheetLayout,
+        SwatchFormat, SwatchSheet, TokenCase, TokenExport, TokenFormat, TokenNameSource,
+        TokenValue, XcodeColorSpace,
+    },
     context::FrameCtx,
 };
 
 use anyhow::Result;
-use egui::{color::Color32, ComboBox, CursorIcon, Window};
-use std::{env, fs, path::PathBuf};
+use egui::{color::Color32, ComboBox, CursorIcon, DragValue, Ui, Window};
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
 
 #[cfg(not(target_arch = "wasm32"))]
 use egui::TextEdit;
@@ -63,8 +71,32 @@ impl ExportWindow {
                                         PaletteFormat::HexList,
                                         PaletteFormat::HexList.as_ref(),
                                     );
+                                    swatch_format_selection(&mut self.format, true, ui);
+                                    token_format_selection(&mut self.format, ui);
+                                    platform_format_selection(&mut self.format, true, ui);
+                                    sheet_format_selection(&mut self.format, ui);
                                 });
                         });
+                        match &mut self.format {
+                            PaletteFormat::Tokens(tokens) => {
+                                token_export_options(
+                                    tokens,
+                                    &ctx.app.settings.saved_color_formats,
+                                    ui,
+                                );
+                            }
+                            PaletteFormat::Platform(platform) => {
+                                platform_export_options(platform, ui);
+                            }
+                            PaletteFormat::Sheet(sheet) => {
+                                sheet_export_options(
+                                    sheet,
+                                    &ctx.app.settings.saved_color_formats,
+                                    ui,
+                                );
+                            }
+                            _ => {}
+                        }
                         if let Some(palette) = &self.export_palette {
                             ui.scope(|ui| {
                                 ui.label("Name: ");
@@ -88,12 +120,17 @@ impl ExportWindow {
                                         "".into()
                                     };
 
-                                    match native_dialog::FileDialog::new()
+                                    let selected = [self.format.extension()];
+                                    let formats = PaletteFormat::builtin();
+                                    let extensions: Vec<_> =
+                                        formats.iter().map(|format| [format.extension()]).collect();
+                                    let mut dialog = native_dialog::FileDialog::new()
                                         .set_location(&location)
-                                        .add_filter("GIMP Palette", &["gpl"])
-                                        .add_filter("Text file", &["txt"])
-                                        .show_save_single_file()
-                                    {
+                                        .add_filter(self.format.as_ref(), &selected);
+                                    for (format, extension) in formats.iter().zip(&extensions) {
+                                        dialog = dialog.add_filter(format.as_ref(), extension);
+                                    }
+                                    match dialog.show_save_single_file() {
                                         Ok(Some(path)) => {
                                             self.path = path.to_string_lossy().to_string()
                                         }
@@ -119,15 +156,11 @@ impl ExportWindow {
                                 .on_hover_cursor(CursorIcon::PointingHand)
                                 .clicked()
                             {
-                                let generated_palette = palette.display(
-                                    &self.format,
-                                    ctx.app.settings.rgb_working_space,
-                                    ctx.app.settings.illuminant,
-                                );
-                                let p = PathBuf::from(&self.path);
-                                let filename =
-                                    format!("{}.{}", &palette.name, self.format.extension());
-                                if let Err(e) = fs::write(p.join(filename), generated_palette) {
+                                let files =
+                                    palette.export_files(&self.format, &ctx.app.format_options());
+                                if let Err(e) = files
+                                    .and_then(|files| write_files(Path::new(&self.path), files))
+                                {
                                     self.export_status = Err(e.to_string());
                                 } else {
                                     self.export_status = Ok("export succesful".to_string());
@@ -145,3 +178,201 @@ impl ExportWindow {
         Ok(())
     }
 }
+
+fn write_files(dir: &Path, files: Vec<(PathBuf, Vec<u8>)>) -> Result<()> {
+    for (path, contents) in files {
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+    }
+    Ok(())
+}
+
+/// Adds a selectable entry for the palette formats of other applications, binary formats are
+/// left out unless `binary` is set.
+pub fn swatch_format_selection(format: &mut PaletteFormat, binary: bool, ui: &mut Ui) {
+    for swatches in SwatchFormat::ALL {
+        if binary || swatches.is_text() {
+            ui.selectable_value(format, PaletteFormat::Swatches(swatches), swatches.as_ref());
+        }
+    }
+}
+
+/// Adds a selectable entry for every mobile platform format, formats exported as directories
+/// are left out unless `directories` is set.
+pub fn platform_format_selection(format: &mut PaletteFormat, directories: bool, ui: &mut Ui) {
+    for platform_format in PlatformFormat::ALL {
+        if !directories && !platform_format.is_single_file() {
+            continue;
+        }
+        let selected = matches!(
+            format,
+            PaletteFormat::Platform(platform) if platform.format == platform_format
+        );
+        if ui
+            .selectable_label(selected, platform_format.as_ref())
+            .clicked()
+        {
+            match format {
+                PaletteFormat::Platform(platform) => platform.format = platform_format,
+                _ => {
+                    *format = PaletteFormat::Platform(PlatformExport {
+                        format: platform_format,
+                        ..PlatformExport::default()
+                    })
+                }
+            }
+        }
+    }
+}
+
+pub fn platform_export_options(platform: &mut PlatformExport, ui: &mut Ui) {
+    color_names_selection(&mut platform.names, ui);
+    if platform.format == PlatformFormat::Xcode {
+        ComboBox::from_label("color space")
+            .selected_text(platform.color_space.as_ref())
+            .show_ui(ui, |ui| {
+                for space in [XcodeColorSpace::Srgb, XcodeColorSpace::DisplayP3] {
+                    ui.selectable_value(&mut platform.color_space, space, space.as_ref());
+                }
+            });
+    }
+}
+
+fn color_names_selection(names: &mut TokenNameSource, ui: &mut Ui) {
+    ComboBox::from_label("color names")
+        .selected_text(names.as_ref())
+        .show_ui(ui, |ui| {
+            for source in [
+                TokenNameSource::Index,
+                TokenNameSource::ColorName,
+                TokenNameSource::Hex,
+            ] {
+                ui.selectable_value(names, source, source.as_ref());
+            }
+        })
+        .response
+        .on_hover_text("Names of the colors that weren't named in the palette");
+}
+
+/// Adds a selectable entry for every design token format, keeping the naming options of the
+/// currently selected token format.
+pub fn token_format_selection(format: &mut PaletteFormat, ui: &mut Ui) {
+    for token_format in TokenFormat::ALL {
+        let selected =
+            matches!(format, PaletteFormat::Tokens(tokens) if tokens.format == token_format);
+        if ui
+            .selectable_label(selected, token_format.as_ref())
+            .clicked()
+        {
+            match format {
+                PaletteFormat::Tokens(tokens) => tokens.format = token_format,
+                _ => {
+                    *format = PaletteFormat::Tokens(TokenExport {
+                        format: token_format,
+                        ..TokenExport::default()
+                    })
+                }
+            }
+        }
+    }
+}
+
+pub fn token_export_options(
+    tokens: &mut TokenExport,
+    saved_color_formats: &HashMap<String, String>,
+    ui: &mut Ui,
+) {
+    color_names_selection(&mut tokens.names, ui);
+    ComboBox::from_label("name case")
+        .selected_text(tokens.case.as_ref())
+        .show_ui(ui, |ui| {
+            for case in [
+                TokenCase::Kebab,
+                TokenCase::Snake,
+                TokenCase::Camel,
+                TokenCase::Pascal,
+            ] {
+                ui.selectable_value(&mut tokens.case, case, case.as_ref());
+            }
+        });
+    color_value_selection(&mut tokens.value, saved_color_formats, ui);
+    ui.checkbox(&mut tokens.group, "Group tokens under the palette name");
+}
+
+fn color_value_selection(
+    value: &mut TokenValue,
+    saved_color_formats: &HashMap<String, String>,
+    ui: &mut Ui,
+) {
+    ComboBox::from_label("color value")
+        .selected_text(value.as_ref())
+        .show_ui(ui, |ui| {
+            for builtin in [
+                TokenValue::Hex,
+                TokenValue::HexUppercase,
+                TokenValue::CssRgb,
+                TokenValue::CssHsl,
+            ] {
+                let label = builtin.as_ref().to_string();
+                ui.selectable_value(value, builtin, label);
+            }
+            let mut saved: Vec<_> = saved_color_formats.iter().collect();
+            saved.sort();
+            for (name, fmt) in saved {
+                ui.selectable_value(value, TokenValue::Custom(name.clone(), fmt.clone()), name);
+            }
+        });
+}
+
+/// Adds a selectable entry for every swatch sheet image format, keeping the layout options of
+/// the currently selected sheet.
+pub fn sheet_format_selection(format: &mut PaletteFormat, ui: &mut Ui) {
+    for sheet_format in SheetFormat::ALL {
+        let selected =
+            matches!(format, PaletteFormat::Sheet(sheet) if sheet.format == sheet_format);
+        if ui
+            .selectable_label(selected, sheet_format.as_ref())
+            .clicked()
+        {
+            match format {
+                PaletteFormat::Sheet(sheet) => sheet.format = sheet_format,
+                _ => {
+                    *format = PaletteFormat::Sheet(SwatchSheet {
+                        format: sheet_format,
+                        ..SwatchSheet::default()
+                    })
+                }
+            }
+        }
+    }
+}
+
+pub fn sheet_export_options(
+    sheet: &mut SwatchSheet,
+    saved_color_formats: &HashMap<String, String>,
+    ui: &mut Ui,
+) {
+    ComboBox::from_label("layout")
+        .selected_text(sheet.layout.as_ref())
+        .show_ui(ui, |ui| {
+            for layout in SheetLayout::ALL {
+                ui.selectable_value(&mut sheet.layout, layout, layout.as_ref());
+            }
+        });
+    ui.horizontal(|ui| {
+        ui.label("swatch size");
+        ui.add(DragValue::new(&mut sheet.swatch_size).clamp_range(16..=1024));
+        if sheet.layout != SheetLayout::Strip {
+            ui.label("columns");
+            ui.add(DragValue::new(&mut sheet.columns).clamp_range(1..=64));
+        }
+    });
+    ui.checkbox(&mut sheet.names, "Label swatches with color names");
+    ui.checkbox(&mut sheet.values, "Label swatches with color values");
+    if sheet.values {
+        color_value_selection(&mut sheet.value, saved_color_formats, ui);
+    }
+}
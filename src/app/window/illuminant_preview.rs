@@ -0,0 +1,158 @@
+use crate::{
+    app::window::{self, WINDOW_X_OFFSET, WINDOW_Y_OFFSET},
+    color::{DeltaE, Illuminant, IlluminantChange, NamedPalette},
+    context::FrameCtx,
+    math::Float,
+    ui::{colorbox::ColorBox, HALF_SPACE, SPACE},
+};
+
+use egui::{Color32, ComboBox, Grid, RichText, ScrollArea, Slider, Ui, Window};
+
+/// Illuminants with spectral data available that can be used for the preview
+const ILLUMINANTS: [Illuminant; 7] = [
+    Illuminant::A,
+    Illuminant::D50,
+    Illuminant::D55,
+    Illuminant::D65,
+    Illuminant::D75,
+    Illuminant::E,
+    Illuminant::F11,
+];
+
+/// Color differences above this value are highlighted, roughly the just noticeable difference
+const NOTICEABLE_DELTA_E: Float = 2.3;
+
+#[derive(Debug)]
+pub struct IlluminantPreviewWindow {
+    pub show: bool,
+    pub palette: Option<NamedPalette>,
+    pub source: Illuminant,
+    pub target: Illuminant,
+    pub metric: DeltaE,
+    pub color_size: f32,
+}
+
+impl Default for IlluminantPreviewWindow {
+    fn default() -> Self {
+        Self {
+            show: false,
+            palette: None,
+            source: Illuminant::D65,
+            target: Illuminant::A,
+            metric: DeltaE::default(),
+            color_size: 50.,
+        }
+    }
+}
+
+impl IlluminantPreviewWindow {
+    pub fn display(&mut self, ctx: &mut FrameCtx<'_>) {
+        if self.show {
+            let offset = ctx.egui.style().spacing.slider_width * WINDOW_X_OFFSET;
+            let mut show = true;
+            let is_dark_mode = ctx.egui.style().visuals.dark_mode;
+            Window::new("illuminant preview")
+                .frame(window::default_frame(is_dark_mode))
+                .open(&mut show)
+                .default_pos((offset, WINDOW_Y_OFFSET))
+                .show(ctx.egui, |ui| {
+                    window::apply_default_style(ui, is_dark_mode);
+                    self.options(ui);
+                    ui.add_space(SPACE);
+                    self.colors(ctx, ui);
+                });
+
+            if !show {
+                self.show = false;
+                self.palette = None;
+            }
+        }
+    }
+
+    fn options(&mut self, ui: &mut Ui) {
+        fn illuminant_combo(ui: &mut Ui, label: &str, illuminant: &mut Illuminant) {
+            ComboBox::from_label(label)
+                .selected_text(illuminant.as_ref())
+                .show_ui(ui, |ui| {
+                    for it in ILLUMINANTS {
+                        ui.selectable_value(illuminant, it, it.as_ref());
+                    }
+                });
+        }
+
+        ui.horizontal(|ui| {
+            illuminant_combo(ui, "source", &mut self.source);
+            ui.add_space(HALF_SPACE);
+            illuminant_combo(ui, "target", &mut self.target);
+        });
+        ComboBox::from_label("color difference")
+            .selected_text(self.metric.as_ref())
+            .show_ui(ui, |ui| {
+                for it in [DeltaE::Cie76, DeltaE::Cie94, DeltaE::Ciede2000] {
+                    ui.selectable_value(&mut self.metric, it, it.as_ref());
+                }
+            });
+        ui.add(
+            Slider::new(&mut self.color_size, 20.0..=100.)
+                .clamp_to_range(true)
+                .text("color size"),
+        );
+    }
+
+    fn colors(&mut self, ctx: &mut FrameCtx<'_>, ui: &mut Ui) {
+        let palette = match &self.palette {
+            Some(palette) => palette,
+            None => return,
+        };
+        let change = IlluminantChange {
+            source: self.source,
+            target: self.target,
+            observer: ctx.app.settings.observer,
+            method: ctx.app.settings.chromatic_adaptation_method,
+            metric: self.metric,
+        };
+        let ws = ctx.app.settings.rgb_working_space;
+        let shifts: Vec<_> = palette
+            .palette
+            .iter()
+            .filter_map(|color| change.apply(color, ws))
+            .collect();
+
+        ui.label(RichText::new(&palette.name).heading());
+        if shifts.is_empty() {
+            ui.label("Palette is empty");
+            return;
+        }
+        let mean = shifts.iter().map(|s| s.delta_e).sum::<Float>() / shifts.len() as Float;
+        ui.label(format!("mean {}: {:.2}", self.metric.as_ref(), mean));
+        ui.add_space(HALF_SPACE);
+
+        let size = (self.color_size, self.color_size);
+        ScrollArea::vertical().show(ui, |ui| {
+            Grid::new("illuminant-preview-colors")
+                .spacing((SPACE, HALF_SPACE))
+                .show(ui, |ui| {
+                    ui.label(self.source.as_ref());
+                    ui.label(self.target.as_ref());
+                    ui.label(self.metric.as_ref());
+                    ui.end_row();
+                    for shift in &shifts {
+                        for color in [shift.source, shift.target] {
+                            ColorBox::builder()
+                                .size(size)
+                                .color(color)
+                                .build()
+                                .display(ctx, ui);
+                        }
+                        let delta_e = format!("{:.2}", shift.delta_e);
+                        if shift.delta_e > NOTICEABLE_DELTA_E {
+                            ui.colored_label(Color32::RED, delta_e);
+                        } else {
+                            ui.label(delta_e);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
@@ -1,17 +1,24 @@
 mod custom_formats;
 mod export;
 mod help;
+mod illuminant_preview;
 mod palette_formats;
+mod restore;
 mod settings;
 
-use crate::ui::colors::*;
+use crate::{math::Float, ui::colors::*};
 
 pub use custom_formats::CustomFormatsWindow;
 use egui::{style::Margin, Frame, Rounding, Slider, Stroke, Ui};
 use epaint::Shadow;
-pub use export::ExportWindow;
+pub use export::{
+    platform_export_options, platform_format_selection, swatch_format_selection,
+    token_export_options, token_format_selection, ExportWindow,
+};
 pub use help::HelpWindow;
+pub use illuminant_preview::IlluminantPreviewWindow;
 pub use palette_formats::PaletteFormatsWindow;
+pub use restore::RestoreWindow;
 pub use settings::SettingsWindow;
 
 pub const WINDOW_X_OFFSET: f32 = 10.;
@@ -118,7 +125,7 @@ pub struct HuesWindow {
     pub is_open: bool,
     pub num_of_hues: u8,
     pub hue_color_size: f32,
-    pub hues_step: f32,
+    pub hues_step: Float,
 }
 
 impl Default for HuesWindow {
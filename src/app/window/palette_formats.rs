@@ -1,9 +1,12 @@
 use egui::{ComboBox, Window};
 
 use crate::{
-    color::{CustomPaletteFormat, PaletteFormat},
+    color::{CustomPaletteFormat, FormatKind, PaletteFormat},
     context::FrameCtx,
-    ui::{icon, SPACE},
+    ui::{
+        format_editor::{field_reference, format_completions, format_edit, format_error},
+        icon, SPACE,
+    },
 };
 
 #[derive(Default, Clone, Debug)]
@@ -114,6 +117,7 @@ impl PaletteFormatsWindow {
                 } = current.clone();
 
                 ui.add_space(SPACE);
+                let entry_id = ui.make_persistent_id("palette_format_entry");
                 egui::Grid::new("palette_format_edit_grid")
                     .num_columns(2)
                     .show(ui, |ui| {
@@ -122,34 +126,73 @@ impl PaletteFormatsWindow {
                         ui.end_row();
 
                         ui.label("Prefix: ");
-                        egui::TextEdit::multiline(&mut current.format.prefix)
-                            .desired_rows(1)
-                            .show(ui);
+                        format_edit(
+                            ui,
+                            &mut current.format.prefix,
+                            ui.make_persistent_id("palette_format_prefix"),
+                            FormatKind::Palette,
+                            true,
+                        );
                         ui.end_row();
 
-                        ui.label("Color format: ");
-                        egui::TextEdit::multiline(&mut current.format.entry_format)
-                            .desired_rows(1)
-                            .show(ui);
+                        ui.label("Color format: ").on_hover_text(
+                            "Repeated for every color. Besides the color fields it can use \
+                             {index}, {number}, {entry_name}, {palette_name} and {count}, \
+                             and conditions like {if !last}...{else}...{end} with first, \
+                             last or named.",
+                        );
+                        format_edit(
+                            ui,
+                            &mut current.format.entry_format,
+                            entry_id,
+                            FormatKind::Palette,
+                            true,
+                        );
+                        ui.end_row();
+
+                        ui.label("Separator: ")
+                            .on_hover_text("Put between the colors");
+                        format_edit(
+                            ui,
+                            &mut current.format.separator,
+                            ui.make_persistent_id("palette_format_separator"),
+                            FormatKind::Palette,
+                            true,
+                        );
                         ui.end_row();
 
                         ui.label("Suffix: ");
-                        egui::TextEdit::multiline(&mut current.format.suffix)
-                            .desired_rows(1)
-                            .show(ui);
+                        format_edit(
+                            ui,
+                            &mut current.format.suffix,
+                            ui.make_persistent_id("palette_format_suffix"),
+                            FormatKind::Palette,
+                            true,
+                        );
                         ui.end_row();
                     });
+                format_completions(
+                    ui,
+                    &mut current.format.entry_format,
+                    entry_id,
+                    FormatKind::Palette,
+                );
+                field_reference(
+                    ui,
+                    &mut current.format.entry_format,
+                    entry_id,
+                    FormatKind::Palette,
+                );
 
                 let mut preview = current
                     .format
-                    .format_palette(
-                        &ctx.app.palettes.current().palette,
-                        ctx.app.settings.rgb_working_space,
-                        ctx.app.settings.illuminant,
-                    )
+                    .format_palette(ctx.app.palettes.current(), &ctx.app.format_options())
                     .unwrap_or_default();
 
                 ui.add_space(SPACE);
+                if let Err(e) = current.format.validate() {
+                    format_error(ui, &e);
+                }
                 ui.label("Preview");
                 egui::TextEdit::multiline(&mut preview)
                     .interactive(false)
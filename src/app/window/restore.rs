@@ -0,0 +1,211 @@
+use crate::{
+    app::window::{self, WINDOW_X_OFFSET, WINDOW_Y_OFFSET},
+    color::{NamedPalette, Palette},
+    context::FrameCtx,
+    error::append_global_error,
+    format_timestamp,
+    ui::{colorbox::ColorBox, HALF_SPACE, SPACE},
+};
+
+use egui::{Grid, RichText, ScrollArea, Ui, Window};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::color::{PaletteBackup, Palettes};
+
+const PREVIEW_COLOR_SIZE: f32 = 16.;
+/// Number of colors of a palette shown in the previews
+const PREVIEW_COLORS: usize = 12;
+
+#[derive(Debug, Default)]
+pub struct RestoreWindow {
+    pub show: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    backups: Option<Vec<PaletteBackup>>,
+    /// Backup selected for the preview with its palettes
+    #[cfg(not(target_arch = "wasm32"))]
+    preview: Option<(PaletteBackup, Palettes)>,
+}
+
+impl RestoreWindow {
+    pub fn display(&mut self, ctx: &mut FrameCtx<'_>) {
+        if self.show {
+            let offset = ctx.egui.style().spacing.slider_width * WINDOW_X_OFFSET;
+            let mut show = true;
+            let is_dark_mode = ctx.egui.style().visuals.dark_mode;
+            Window::new("restore palettes")
+                .frame(window::default_frame(is_dark_mode))
+                .open(&mut show)
+                .default_pos((offset, WINDOW_Y_OFFSET))
+                .show(ctx.egui, |ui| {
+                    window::apply_default_style(ui, is_dark_mode);
+                    ScrollArea::vertical().show(ui, |ui| {
+                        self.trash(ctx, ui);
+                        #[cfg(not(target_arch = "wasm32"))]
+                        {
+                            ui.add_space(SPACE);
+                            self.backups(ctx, ui);
+                        }
+                    });
+                });
+
+            if !show {
+                self.show = false;
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    self.backups = None;
+                    self.preview = None;
+                }
+            }
+        }
+    }
+
+    fn trash(&mut self, ctx: &mut FrameCtx<'_>, ui: &mut Ui) {
+        ui.label(RichText::new("Trash").heading());
+        if ctx.app.palettes.trash().is_empty() {
+            ui.label("The trash is empty");
+            return;
+        }
+        let mut restore = None;
+        let mut delete = None;
+        let trash = ctx.app.palettes.trash().to_vec();
+        Grid::new("restore-trash")
+            .spacing((SPACE, HALF_SPACE))
+            .show(ui, |ui| {
+                // the most recently deleted first
+                for (i, trashed) in trash.iter().enumerate().rev() {
+                    ui.label(&trashed.palette.name);
+                    ui.label(format_timestamp(trashed.deleted));
+                    preview_colors(&trashed.palette.palette, ctx, ui);
+                    if ui.button("Restore").clicked() {
+                        restore = Some(i);
+                    }
+                    if ui
+                        .button("Delete")
+                        .on_hover_text("Delete forever")
+                        .clicked()
+                    {
+                        delete = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(i) = restore {
+            if let Some(name) = ctx.app.palettes.restore(i) {
+                ctx.app.palettes.move_to_name(name);
+            }
+        }
+        if let Some(i) = delete {
+            ctx.app.palettes.delete_from_trash(i);
+        }
+        ui.add_space(HALF_SPACE);
+        if ui.button("Empty trash").clicked() {
+            ctx.app.palettes.empty_trash();
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn backups(&mut self, ctx: &mut FrameCtx<'_>, ui: &mut Ui) {
+        let dir = match Palettes::dir("epick") {
+            Some(dir) => dir.join(Palettes::BACKUP_DIR),
+            None => return,
+        };
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Backups").heading());
+            if ui.button("Refresh").clicked() {
+                self.backups = None;
+            }
+        });
+        ui.label(format!(
+            "The palettes are backed up at most every {} minutes when saved, the last {} backups \
+             are kept in {}",
+            Palettes::BACKUP_INTERVAL / 60,
+            Palettes::MAX_BACKUPS,
+            dir.display()
+        ));
+        let backups = self
+            .backups
+            .get_or_insert_with(|| match Palettes::backups(&dir) {
+                Ok(backups) => backups,
+                Err(e) => {
+                    append_global_error(format!("failed to list backups, {e:?}"));
+                    vec![]
+                }
+            });
+        if backups.is_empty() {
+            ui.label("No backups yet");
+            return;
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            for backup in backups.iter() {
+                let selected = self
+                    .preview
+                    .as_ref()
+                    .is_some_and(|(previewed, _)| previewed == backup);
+                if ui
+                    .selectable_label(selected, format_timestamp(backup.timestamp))
+                    .clicked()
+                {
+                    match Palettes::load(&backup.path) {
+                        Ok(palettes) => self.preview = Some((backup.clone(), palettes)),
+                        Err(e) => append_global_error(format!("failed to load backup, {e:?}")),
+                    }
+                }
+            }
+        });
+
+        let palettes = match &self.preview {
+            Some((_, palettes)) => palettes,
+            None => return,
+        };
+        ui.add_space(HALF_SPACE);
+        let mut restore: Vec<NamedPalette> = vec![];
+        Grid::new("restore-backup")
+            .spacing((SPACE, HALF_SPACE))
+            .show(ui, |ui| {
+                for palette in palettes.iter() {
+                    ui.label(&palette.name);
+                    preview_colors(&palette.palette, ctx, ui);
+                    if ui
+                        .button("Restore")
+                        .on_hover_text("Add this palette to the current palettes")
+                        .clicked()
+                    {
+                        restore.push(palette.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+        if ui
+            .button("Restore all")
+            .on_hover_text("Add the palettes of this backup that differ from the current ones")
+            .clicked()
+        {
+            restore.extend(
+                palettes
+                    .iter()
+                    .filter(|p| !ctx.app.palettes.iter().any(|current| current == *p))
+                    .cloned(),
+            );
+        }
+        for palette in restore {
+            ctx.app.palettes.add_unique(palette);
+        }
+    }
+}
+
+fn preview_colors(palette: &Palette, ctx: &mut FrameCtx<'_>, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.;
+        for color in palette.iter().take(PREVIEW_COLORS) {
+            ColorBox::builder()
+                .size((PREVIEW_COLOR_SIZE, PREVIEW_COLOR_SIZE))
+                .color(*color)
+                .build()
+                .display(ctx, ui);
+        }
+        if palette.len() > PREVIEW_COLORS {
+            ui.label(format!(" +{}", palette.len() - PREVIEW_COLORS));
+        }
+    });
+}
@@ -3,7 +3,11 @@ use crate::{
         window::{self, WINDOW_X_OFFSET, WINDOW_Y_OFFSET},
         AppCtx,
     },
-    color::{ChromaticAdaptationMethod, ColorHarmony, Illuminant, PaletteFormat, RgbWorkingSpace},
+    color::{
+        ChromaticAdaptationMethod, ColorHarmony, ColorNames, DeltaE, Illuminant, Observer,
+        PaletteFileFormat, PaletteFormat, RgbWorkingSpace, Surround, TransferFunction, YCbCrRange,
+        YCbCrStandard, COLOR_NAMES,
+    },
     context::FrameCtx,
     settings::{ColorDisplayFmtEnum, Settings},
     ui::{DOUBLE_SPACE, HALF_SPACE, SPACE},
@@ -17,7 +21,10 @@ use egui::CursorIcon;
 #[cfg(not(target_arch = "wasm32"))]
 use std::fs;
 
-use crate::app::window::{CustomFormatsWindow, PaletteFormatsWindow};
+use crate::app::window::{
+    platform_export_options, platform_format_selection, swatch_format_selection,
+    token_export_options, token_format_selection, CustomFormatsWindow, PaletteFormatsWindow,
+};
 
 const UI_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.25..=5.0;
 
@@ -77,11 +84,26 @@ impl SettingsWindow {
                     ui.add_space(HALF_SPACE);
                     self.illuminant(ctx.app, ui);
                     ui.add_space(HALF_SPACE);
+                    self.observer(ctx.app, ui);
+                    ui.add_space(HALF_SPACE);
                     self.chromatic_adaptation_method(ctx.app, ui);
                     ui.add_space(HALF_SPACE);
+                    self.viewing_conditions(ctx.app, ui);
+                    ui.add_space(HALF_SPACE);
+                    self.ycbcr(ctx.app, ui);
+                    ui.add_space(HALF_SPACE);
+                    self.hdr(ctx.app, ui);
+                    ui.add_space(HALF_SPACE);
+                    self.color_names(ctx.app, ui);
+                    ui.add_space(HALF_SPACE);
                     self.color_harmony(ctx.app, ui);
                     ui.add_space(HALF_SPACE);
                     ui.checkbox(&mut ctx.app.settings.cache_colors, "Cache colors");
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        ui.add_space(HALF_SPACE);
+                        self.palettes_dir(ctx.app, ui);
+                    }
                     ui.add_space(DOUBLE_SPACE);
                     self.color_spaces(ctx.app, ui);
                     ui.add_space(SPACE);
@@ -169,6 +191,7 @@ impl SettingsWindow {
             ui.checkbox(&mut app_ctx.settings.color_spaces.cmyk, "CMYK");
             ui.checkbox(&mut app_ctx.settings.color_spaces.hsv, "HSV");
             ui.checkbox(&mut app_ctx.settings.color_spaces.hsl, "HSL");
+            ui.checkbox(&mut app_ctx.settings.color_spaces.hwb, "HWB");
         });
         ui.add_space(SPACE);
         ui.label("CIE Color spaces:");
@@ -177,9 +200,59 @@ impl SettingsWindow {
             ui.checkbox(&mut app_ctx.settings.color_spaces.lch_uv, "LCH(uv)");
             ui.checkbox(&mut app_ctx.settings.color_spaces.lab, "Lab");
             ui.checkbox(&mut app_ctx.settings.color_spaces.lch_ab, "LCH(ab)");
+            ui.checkbox(&mut app_ctx.settings.color_spaces.hsluv, "HSLuv");
+            ui.checkbox(&mut app_ctx.settings.color_spaces.hpluv, "HPLuv");
+        });
+        ui.add_space(SPACE);
+        ui.label("Color appearance models:");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut app_ctx.settings.color_spaces.cam16, "CAM16");
+            ui.checkbox(&mut app_ctx.settings.color_spaces.cam16_ucs, "CAM16-UCS");
+        });
+        ui.add_space(SPACE);
+        ui.label("Video color spaces:");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut app_ctx.settings.color_spaces.ycbcr, "YCbCr");
+            ui.checkbox(&mut app_ctx.settings.color_spaces.ictcp, "ICtCp");
         });
     }
 
+    fn viewing_conditions(&mut self, app_ctx: &mut AppCtx, ui: &mut Ui) {
+        let vc = &mut app_ctx.settings.viewing_conditions;
+        egui::CollapsingHeader::new("CAM16 viewing conditions")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut vc.adapting_luminance, 0.0..=1000.)
+                        .text("adapting luminance (cd/m²)"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut vc.background_luminance, 1.0..=100.)
+                        .text("background luminance"),
+                );
+                ComboBox::from_label("Surround")
+                    .selected_text(vc.surround.as_ref())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut vc.surround,
+                            Surround::Average,
+                            Surround::Average.as_ref(),
+                        );
+                        ui.selectable_value(
+                            &mut vc.surround,
+                            Surround::Dim,
+                            Surround::Dim.as_ref(),
+                        );
+                        ui.selectable_value(
+                            &mut vc.surround,
+                            Surround::Dark,
+                            Surround::Dark.as_ref(),
+                        );
+                    });
+                ui.checkbox(&mut vc.discounting, "Discount the illuminant");
+            });
+    }
+
     fn illuminant(&mut self, app_ctx: &mut AppCtx, ui: &mut Ui) {
         ComboBox::from_label("Illuminant")
             .selected_text(app_ctx.settings.illuminant.as_ref())
@@ -242,6 +315,150 @@ impl SettingsWindow {
             });
     }
 
+    fn observer(&mut self, app_ctx: &mut AppCtx, ui: &mut Ui) {
+        ComboBox::from_label("Standard observer")
+            .selected_text(app_ctx.settings.observer.as_ref())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(
+                    &mut app_ctx.settings.observer,
+                    Observer::Cie1931,
+                    Observer::Cie1931.as_ref(),
+                );
+                ui.selectable_value(
+                    &mut app_ctx.settings.observer,
+                    Observer::Cie1964,
+                    Observer::Cie1964.as_ref(),
+                );
+            });
+    }
+
+    fn ycbcr(&mut self, app_ctx: &mut AppCtx, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ComboBox::from_label("YCbCr standard")
+                .selected_text(app_ctx.settings.ycbcr_standard.as_ref())
+                .show_ui(ui, |ui| {
+                    for standard in [
+                        YCbCrStandard::Bt601,
+                        YCbCrStandard::Bt709,
+                        YCbCrStandard::Bt2020,
+                    ] {
+                        ui.selectable_value(
+                            &mut app_ctx.settings.ycbcr_standard,
+                            standard,
+                            standard.as_ref(),
+                        );
+                    }
+                });
+            ComboBox::from_label("range")
+                .selected_text(app_ctx.settings.ycbcr_range.as_ref())
+                .show_ui(ui, |ui| {
+                    for range in [YCbCrRange::Full, YCbCrRange::Limited] {
+                        ui.selectable_value(
+                            &mut app_ctx.settings.ycbcr_range,
+                            range,
+                            range.as_ref(),
+                        );
+                    }
+                });
+        });
+    }
+
+    fn hdr(&mut self, app_ctx: &mut AppCtx, ui: &mut Ui) {
+        ui.checkbox(&mut app_ctx.settings.hdr_mode, "HDR mode");
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::Slider::new(&mut app_ctx.settings.reference_white, 80.0..=1000.)
+                    .text("reference white (cd/m²)"),
+            );
+            ComboBox::from_label("transfer function")
+                .selected_text(app_ctx.settings.transfer_function.as_ref())
+                .show_ui(ui, |ui| {
+                    for transfer in [TransferFunction::Pq, TransferFunction::Hlg] {
+                        ui.selectable_value(
+                            &mut app_ctx.settings.transfer_function,
+                            transfer,
+                            transfer.as_ref(),
+                        );
+                    }
+                });
+        });
+    }
+
+    fn color_names(&mut self, app_ctx: &mut AppCtx, ui: &mut Ui) {
+        let count = COLOR_NAMES
+            .read()
+            .map(|names| names.len())
+            .unwrap_or_default();
+        ComboBox::from_label("Color name metric")
+            .selected_text(app_ctx.settings.color_name_metric.as_ref())
+            .show_ui(ui, |ui| {
+                for metric in [DeltaE::Cie76, DeltaE::Cie94, DeltaE::Ciede2000] {
+                    ui.selectable_value(
+                        &mut app_ctx.settings.color_name_metric,
+                        metric,
+                        metric.as_ref(),
+                    );
+                }
+            })
+            .response
+            .on_hover_text(format!(
+                "{} names loaded. Additional lists of `name,hex` CSV or JSON files are read from \
+                 the `{}` directory in the configuration directory on startup.",
+                count,
+                ColorNames::DIR_NAME
+            ));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn palettes_dir(&mut self, app_ctx: &mut AppCtx, ui: &mut Ui) {
+        let settings = &mut app_ctx.settings;
+        ui.horizontal(|ui| {
+            ui.label("Palettes directory: ");
+            match &settings.palettes_dir {
+                Some(dir) => ui.monospace(dir.display().to_string()),
+                None => ui.label("none"),
+            }
+            .on_hover_text(
+                "Every palette is stored in a separate file in this directory. Changes made to \
+                 the files by other programs are loaded automatically.",
+            );
+            if ui
+                .button("Choose")
+                .on_hover_cursor(CursorIcon::PointingHand)
+                .clicked()
+            {
+                match native_dialog::FileDialog::new().show_open_single_dir() {
+                    Ok(Some(dir)) => settings.palettes_dir = Some(dir),
+                    Ok(None) => {}
+                    Err(e) => self.set_error(e),
+                }
+            }
+            if settings.palettes_dir.is_some()
+                && ui
+                    .button("Use a single file")
+                    .on_hover_text("Store all palettes in a single file in the cache directory")
+                    .clicked()
+            {
+                settings.palettes_dir = None;
+            }
+        });
+        if settings.palettes_dir.is_some() {
+            ComboBox::from_label("Palette file format")
+                .selected_text(settings.palette_file_format.as_ref())
+                .show_ui(ui, |ui| {
+                    for format in [PaletteFileFormat::Yaml, PaletteFileFormat::Json] {
+                        ui.selectable_value(
+                            &mut settings.palette_file_format,
+                            format,
+                            format.as_ref(),
+                        );
+                    }
+                })
+                .response
+                .on_hover_text("Format of new palette files, existing files keep their format");
+        }
+    }
+
     fn chromatic_adaptation_method(&mut self, app_ctx: &mut AppCtx, ui: &mut Ui) {
         ComboBox::from_label("Chromatic adaptation method")
             .selected_text(app_ctx.settings.chromatic_adaptation_method.as_ref())
@@ -261,6 +478,26 @@ impl SettingsWindow {
                     ChromaticAdaptationMethod::XYZScaling,
                     ChromaticAdaptationMethod::XYZScaling.as_ref(),
                 );
+                ui.selectable_value(
+                    &mut app_ctx.settings.chromatic_adaptation_method,
+                    ChromaticAdaptationMethod::Cat02,
+                    ChromaticAdaptationMethod::Cat02.as_ref(),
+                );
+                ui.selectable_value(
+                    &mut app_ctx.settings.chromatic_adaptation_method,
+                    ChromaticAdaptationMethod::Cat16,
+                    ChromaticAdaptationMethod::Cat16.as_ref(),
+                );
+                ui.selectable_value(
+                    &mut app_ctx.settings.chromatic_adaptation_method,
+                    ChromaticAdaptationMethod::Sharp,
+                    ChromaticAdaptationMethod::Sharp.as_ref(),
+                );
+                ui.selectable_value(
+                    &mut app_ctx.settings.chromatic_adaptation_method,
+                    ChromaticAdaptationMethod::CmcCat2000,
+                    ChromaticAdaptationMethod::CmcCat2000.as_ref(),
+                );
             });
     }
 
@@ -368,7 +605,21 @@ impl SettingsWindow {
                         name,
                     );
                 }
+                swatch_format_selection(&mut app_ctx.settings.palette_clipboard_format, false, ui);
+                token_format_selection(&mut app_ctx.settings.palette_clipboard_format, ui);
+                platform_format_selection(
+                    &mut app_ctx.settings.palette_clipboard_format,
+                    false,
+                    ui,
+                );
             });
+        match &mut app_ctx.settings.palette_clipboard_format {
+            PaletteFormat::Tokens(tokens) => {
+                token_export_options(tokens, &app_ctx.settings.saved_color_formats, ui);
+            }
+            PaletteFormat::Platform(platform) => platform_export_options(platform, ui),
+            _ => {}
+        }
         ui.checkbox(
             &mut app_ctx.settings.auto_copy_picked_color,
             "Auto copy picked color",
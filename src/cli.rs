@@ -0,0 +1,395 @@
+//! Palette operations run from the command line without opening the window, for example
+//! `epick palette merge a.gpl b.gpl -o merged.gpl`.
+
+use crate::{
+    color::{
+        import_palette_file, DeltaE, FormatOptions, NamedPalette, PaletteFormat, SwatchFormat,
+        TokenExport, TokenFormat,
+    },
+    math::Float,
+    settings::{self, Settings},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const USAGE: &str = "\
+usage:
+    epick                                              open the color picker
+    epick palette merge <in> <in> -o <out> [options]   colors of both palettes
+    epick palette intersect <in> <in> -o <out> [options]
+                                                       colors of the first palette that are in the second
+    epick palette diff <in> <in> -o <out> [options]    colors of the first palette missing from the second
+    epick palette split-hue <in> -o <out>              one palette per hue family, written to
+                                                       <out stem>-<family>.<out extension>
+
+options:
+    -t, --tolerance <ΔE>    largest difference of colors treated as the same color (default 2)
+    -m, --metric <metric>   76, 94 or 2000 (default 2000)
+
+The formats of the palettes are chosen by the file extensions, the colors are compared with the
+working space, illuminant and chromatic adaptation method from the settings.";
+
+/// Palette operation of the command line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PaletteCommand {
+    Merge,
+    Intersect,
+    Diff,
+    SplitHue,
+}
+
+impl PaletteCommand {
+    fn inputs(&self) -> usize {
+        match self {
+            PaletteCommand::SplitHue => 1,
+            _ => 2,
+        }
+    }
+
+    /// Joins the names of the palettes in the name of the result, like the side panel does
+    fn symbol(&self) -> &str {
+        match self {
+            PaletteCommand::Merge => "+",
+            PaletteCommand::Intersect => "&",
+            PaletteCommand::Diff | PaletteCommand::SplitHue => "-",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct PaletteArgs {
+    command: PaletteCommand,
+    inputs: Vec<PathBuf>,
+    output: PathBuf,
+    tolerance: Float,
+    metric: DeltaE,
+}
+
+/// Runs the command of the command line `args` that follow the program name. Returns `None`
+/// when the arguments don't name a command and the color picker should be opened instead.
+pub fn run(args: &[String]) -> Option<Result<()>> {
+    match args.first().map(String::as_str) {
+        Some("palette") => Some(parse_palette_args(&args[1..]).and_then(|args| {
+            let settings = settings::load_global(None).unwrap_or_default();
+            run_palette(&args, &settings)
+        })),
+        Some("help" | "-h" | "--help") => {
+            println!("{}", USAGE);
+            Some(Ok(()))
+        }
+        _ => None,
+    }
+}
+
+fn parse_palette_args(args: &[String]) -> Result<PaletteArgs> {
+    let mut args = args.iter();
+    let command = match args.next().map(String::as_str) {
+        Some("merge") => PaletteCommand::Merge,
+        Some("intersect") => PaletteCommand::Intersect,
+        Some("diff") => PaletteCommand::Diff,
+        Some("split-hue") => PaletteCommand::SplitHue,
+        Some(command) => bail!("unknown palette command `{}`\n\n{}", command, USAGE),
+        None => bail!("missing palette command\n\n{}", USAGE),
+    };
+
+    let mut inputs = vec![];
+    let mut output = None;
+    let mut tolerance = 2.;
+    let mut metric = DeltaE::default();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow!("missing value of `{}`", arg))
+        };
+        match arg.as_str() {
+            "-o" | "--output" => output = Some(PathBuf::from(value()?)),
+            "-t" | "--tolerance" => {
+                let value = value()?;
+                tolerance = value
+                    .parse()
+                    .ok()
+                    .filter(|tolerance: &Float| *tolerance >= 0.)
+                    .ok_or_else(|| anyhow!("invalid tolerance `{}`", value))?;
+            }
+            "-m" | "--metric" => {
+                metric = match value()?.trim_start_matches("cie") {
+                    "76" => DeltaE::Cie76,
+                    "94" => DeltaE::Cie94,
+                    "2000" | "de2000" => DeltaE::Ciede2000,
+                    metric => bail!("unknown color difference metric `{}`", metric),
+                }
+            }
+            flag if flag.starts_with('-') && flag.len() > 1 => bail!("unknown option `{}`", flag),
+            input => inputs.push(PathBuf::from(input)),
+        }
+    }
+
+    if inputs.len() != command.inputs() {
+        bail!(
+            "expected {} input palette files but got {}\n\n{}",
+            command.inputs(),
+            inputs.len(),
+            USAGE
+        );
+    }
+    Ok(PaletteArgs {
+        command,
+        inputs,
+        output: output.ok_or_else(|| anyhow!("missing output file `-o <out>`\n\n{}", USAGE))?,
+        tolerance,
+        metric,
+    })
+}
+
+fn run_palette(args: &PaletteArgs, settings: &Settings) -> Result<()> {
+    let ws = settings.rgb_working_space;
+    let illuminant = settings.illuminant;
+    let method = settings.chromatic_adaptation_method;
+    let options = FormatOptions {
+        ws,
+        illuminant,
+        reference_white: settings.reference_white,
+        method,
+        viewing_conditions: settings.viewing_conditions,
+        hdr_exposure: 0.,
+    };
+    let format = output_format(&args.output)?;
+    let inputs = args
+        .inputs
+        .iter()
+        .map(|path| read_palette(path))
+        .collect::<Result<Vec<_>>>()?;
+
+    let a = &inputs[0];
+    let (tolerance, metric) = (args.tolerance, args.metric);
+    let palette = match args.command {
+        PaletteCommand::SplitHue => {
+            for (family, palette) in a.palette.split_by_hue(ws, illuminant, method) {
+                let palette = a.derived(format!("{} ({})", a.name, family.as_ref()), palette);
+                let path = family_path(&args.output, family.as_ref());
+                write_palette(&path, &palette, &format, &options)?;
+            }
+            return Ok(());
+        }
+        PaletteCommand::Merge => a.palette.union(
+            &inputs[1].palette,
+            tolerance,
+            metric,
+            ws,
+            illuminant,
+            method,
+        ),
+        PaletteCommand::Intersect => a.palette.intersection(
+            &inputs[1].palette,
+            tolerance,
+            metric,
+            ws,
+            illuminant,
+            method,
+        ),
+        PaletteCommand::Diff => a.palette.difference(
+            &inputs[1].palette,
+            tolerance,
+            metric,
+            ws,
+            illuminant,
+            method,
+        ),
+    };
+    let name = format!("{} {} {}", a.name, args.command.symbol(), inputs[1].name);
+    write_palette(&args.output, &a.derived(name, palette), &format, &options)
+}
+
+/// Reads the only palette of a palette file
+fn read_palette(path: &Path) -> Result<NamedPalette> {
+    let data = fs::read(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+    let mut palettes = import_palette_file(path, &data)
+        .with_context(|| format!("failed to import `{}`", path.display()))?;
+    if palettes.len() != 1 {
+        bail!(
+            "`{}` contains {} palettes, expected one",
+            path.display(),
+            palettes.len()
+        );
+    }
+    Ok(palettes.remove(0))
+}
+
+/// Returns the export format of a palette file with the extension of `path`, the same formats
+/// that [`import_palette_file`] reads
+fn output_format(path: &Path) -> Result<PaletteFormat> {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    if extension == "gpl" {
+        return Ok(PaletteFormat::Gimp);
+    }
+    if let Some(format) = TokenFormat::ALL
+        .into_iter()
+        .find(|format| format.extension() == extension)
+    {
+        return Ok(PaletteFormat::Tokens(TokenExport {
+            format,
+            ..Default::default()
+        }));
+    }
+    SwatchFormat::ALL
+        .into_iter()
+        .find(|format| format.extension() == extension)
+        .map(PaletteFormat::Swatches)
+        .ok_or_else(|| anyhow!("unsupported palette file extension `{}`", extension))
+}
+
+fn write_palette(
+    path: &Path,
+    palette: &NamedPalette,
+    format: &PaletteFormat,
+    options: &FormatOptions,
+) -> Result<()> {
+    let (_, data) = palette
+        .export_files(format, options)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("nothing to write to `{}`", path.display()))?;
+    fs::write(path, data).with_context(|| format!("failed to write `{}`", path.display()))?;
+    println!("{}: {} colors", path.display(), palette.palette.len());
+    Ok(())
+}
+
+/// `out.gpl` with the family `red` becomes `out-red.gpl`
+fn family_path(output: &Path, family: &str) -> PathBuf {
+    let stem = output
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut name = format!("{}-{}", stem, family);
+    if let Some(extension) = output.extension() {
+        name.push('.');
+        name.push_str(&extension.to_string_lossy());
+    }
+    output.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    fn gpl(name: &str, colors: &[(u8, u8, u8)]) -> String {
+        let mut gpl = format!("GIMP Palette\nName: {}\n#\n", name);
+        for (r, g, b) in colors {
+            gpl.push_str(&format!("{} {} {}\n", r, g, b));
+        }
+        gpl
+    }
+
+    fn hexes(path: &Path) -> Vec<String> {
+        read_palette(path)
+            .unwrap()
+            .palette
+            .iter()
+            .map(|color| color.as_hex())
+            .collect()
+    }
+
+    #[test]
+    fn parses_palette_args() {
+        assert!(run(&args("")).is_none());
+        assert_eq!(
+            parse_palette_args(&args("diff a.gpl b.kpl -o out.css -t 0.5 --metric 2000")).unwrap(),
+            PaletteArgs {
+                command: PaletteCommand::Diff,
+                inputs: vec!["a.gpl".into(), "b.kpl".into()],
+                output: "out.css".into(),
+                tolerance: 0.5,
+                metric: DeltaE::Ciede2000,
+            }
+        );
+        let error = |args_: &str| parse_palette_args(&args(args_)).unwrap_err().to_string();
+        assert!(error("merge a.gpl -o out.gpl").starts_with("expected 2 input palette files"));
+        assert!(error("split-hue a.gpl").starts_with("missing output file"));
+        assert!(error("blend a.gpl b.gpl").starts_with("unknown palette command `blend`"));
+        assert_eq!(error("merge a.gpl b.gpl -o"), "missing value of `-o`");
+        assert_eq!(error("merge a.gpl b.gpl -t x"), "invalid tolerance `x`");
+    }
+
+    #[test]
+    fn runs_palette_operations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        fs::write(
+            path("a.gpl"),
+            gpl("a", &[(255, 0, 0), (0, 0, 255), (0, 255, 0)]),
+        )
+        .unwrap();
+        fs::write(path("b.gpl"), gpl("b", &[(255, 1, 0), (255, 255, 0)])).unwrap();
+        let run = |command: &str| {
+            let a = path("a.gpl").display().to_string();
+            let b = path("b.gpl").display().to_string();
+            let mut args = args(command);
+            args.splice(2..2, [a, b]);
+            run_palette(
+                &parse_palette_args(&args[1..]).unwrap(),
+                &Settings::default(),
+            )
+        };
+
+        let output = path("merged.gpl").display().to_string();
+        run(&format!("palette merge -o {}", output)).unwrap();
+        assert_eq!(
+            hexes(&path("merged.gpl")),
+            ["#ff0000", "#0000ff", "#00ff00", "#ffff00"]
+        );
+        assert_eq!(read_palette(&path("merged.gpl")).unwrap().name, "a + b");
+
+        let output = path("common.kpl").display().to_string();
+        run(&format!("palette intersect -o {}", output)).unwrap();
+        assert_eq!(hexes(&path("common.kpl")), ["#ff0000"]);
+
+        let output = path("rest.txt").display().to_string();
+        run(&format!("palette diff -o {} -t 0", output)).unwrap();
+        assert_eq!(hexes(&path("rest.txt")), ["#ff0000", "#0000ff", "#00ff00"]);
+
+        let output = path("families.gpl").display().to_string();
+        let args = args(&format!(
+            "split-hue {} -o {}",
+            path("a.gpl").display(),
+            output
+        ));
+        run_palette(&parse_palette_args(&args).unwrap(), &Settings::default()).unwrap();
+        assert_eq!(hexes(&path("families-red.gpl")), ["#ff0000"]);
+        assert_eq!(hexes(&path("families-green.gpl")), ["#00ff00"]);
+        assert_eq!(hexes(&path("families-blue.gpl")), ["#0000ff"]);
+    }
+
+    #[test]
+    fn chooses_the_output_format_by_extension() {
+        assert_eq!(
+            output_format(Path::new("out.GPL")).unwrap(),
+            PaletteFormat::Gimp
+        );
+        assert!(matches!(
+            output_format(Path::new("out.scss")).unwrap(),
+            PaletteFormat::Tokens(TokenExport {
+                format: TokenFormat::Scss,
+                ..
+            })
+        ));
+        assert_eq!(
+            output_format(Path::new("out.aco")).unwrap(),
+            PaletteFormat::Swatches(SwatchFormat::Photoshop)
+        );
+        assert_eq!(
+            output_format(Path::new("out.png")).unwrap_err().to_string(),
+            "unsupported palette file extension `png`"
+        );
+    }
+}
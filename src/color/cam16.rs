@@ -0,0 +1,413 @@
+use crate::{
+    color::{ChromaticAdaptationMethod, Illuminant, Xyz},
+    math::{self, Float, Matrix1x3, Matrix3},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Surround of the viewing field as defined by CIECAM02/CAM16.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Surround {
+    #[default]
+    Average,
+    Dim,
+    Dark,
+}
+
+impl Surround {
+    /// Returns the (F, c, Nc) surround parameters
+    fn parameters(&self) -> (Float, Float, Float) {
+        match self {
+            Surround::Average => (1.0, 0.69, 1.0),
+            Surround::Dim => (0.9, 0.59, 0.9),
+            Surround::Dark => (0.8, 0.525, 0.8),
+        }
+    }
+}
+
+impl AsRef<str> for Surround {
+    fn as_ref(&self) -> &str {
+        match self {
+            Surround::Average => "average",
+            Surround::Dim => "dim",
+            Surround::Dark => "dark",
+        }
+    }
+}
+
+/// Viewing conditions used by the CAM16 color appearance model. The adopted white is passed
+/// separately as an [`Illuminant`].
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ViewingConditions {
+    /// Luminance of the adapting field in cd/m²
+    pub adapting_luminance: Float,
+    /// Relative luminance of the background in the range 0.0 ..= 100.0
+    pub background_luminance: Float,
+    pub surround: Surround,
+    /// Assume complete adaptation to the white point
+    pub discounting: bool,
+}
+
+impl Default for ViewingConditions {
+    fn default() -> Self {
+        Self {
+            // 64 lux ambient illumination with a 20% gray background
+            adapting_luminance: 64. / math::consts::PI * 0.2,
+            background_luminance: 20.,
+            surround: Surround::default(),
+            discounting: false,
+        }
+    }
+}
+
+/// Values derived from the viewing conditions and the white point that are shared by both
+/// directions of the model.
+struct Environment {
+    d_rgb: [Float; 3],
+    fl: Float,
+    n: Float,
+    z: Float,
+    nbb: Float,
+    c: Float,
+    nc: Float,
+    aw: Float,
+}
+
+impl Environment {
+    fn new(white: Illuminant, vc: &ViewingConditions) -> Self {
+        let (f, c, nc) = vc.surround.parameters();
+        let white = white.xyz();
+        let yw = white.y() * 100.;
+        let rgb_w = m16() * Matrix1x3::from([white.x() * 100., yw, white.z() * 100.]);
+
+        let la = vc.adapting_luminance;
+        let k = 1. / (5. * la + 1.);
+        let k4 = k.powi(4);
+        let fl = 0.2 * k4 * (5. * la) + 0.1 * (1. - k4).powi(2) * (5. * la).cbrt();
+
+        let d = if vc.discounting {
+            1.
+        } else {
+            (f * (1. - (1. / 3.6) * ((-la - 42.) / 92.).exp())).clamp(0., 1.)
+        };
+        let d_rgb = [
+            d * yw / rgb_w[0] + 1. - d,
+            d * yw / rgb_w[1] + 1. - d,
+            d * yw / rgb_w[2] + 1. - d,
+        ];
+
+        let n = vc.background_luminance / yw;
+        let z = 1.48 + n.sqrt();
+        let nbb = 0.725 * n.powf(-0.2);
+
+        let rgb_aw = [
+            compress(d_rgb[0] * rgb_w[0], fl),
+            compress(d_rgb[1] * rgb_w[1], fl),
+            compress(d_rgb[2] * rgb_w[2], fl),
+        ];
+        let aw = (2. * rgb_aw[0] + rgb_aw[1] + 0.05 * rgb_aw[2] - 0.305) * nbb;
+
+        Self {
+            d_rgb,
+            fl,
+            n,
+            z,
+            nbb,
+            c,
+            nc,
+            aw,
+        }
+    }
+
+    fn chroma_factor(&self) -> Float {
+        (1.64 - (0.29 as Float).powf(self.n)).powf(0.73)
+    }
+}
+
+fn m16() -> Matrix3 {
+    ChromaticAdaptationMethod::Cat16.adaptation_matrix()
+}
+
+/// Post-adaptation non-linear response compression
+fn compress(channel: Float, fl: Float) -> Float {
+    let t = (fl * channel.abs() / 100.).powf(0.42);
+    channel.signum() * 400. * t / (t + 27.13) + 0.1
+}
+
+/// Inverse of the post-adaptation non-linear response compression
+fn decompress(channel: Float, fl: Float) -> Float {
+    let channel = channel - 0.1;
+    let abs = channel.abs().min(399.999);
+    channel.signum() * (100. / fl) * ((27.13 * abs) / (400. - abs)).powf(1. / 0.42)
+}
+
+fn eccentricity(hue: Float) -> Float {
+    0.25 * ((hue.to_radians() + 2.).cos() + 3.8)
+}
+
+/// Color in the CAM16 color appearance model.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Cam16 {
+    j: Float,
+    c: Float,
+    h: Float,
+    m: Float,
+    s: Float,
+    q: Float,
+}
+
+impl Cam16 {
+    #[inline(always)]
+    /// Returns Lightness
+    pub fn j(&self) -> Float {
+        self.j
+    }
+
+    #[inline(always)]
+    /// Returns Chroma
+    pub fn c(&self) -> Float {
+        self.c
+    }
+
+    #[inline(always)]
+    /// Returns Hue angle in the range of 0.0 ..= 360.0
+    pub fn h(&self) -> Float {
+        self.h
+    }
+
+    #[inline(always)]
+    /// Returns Colorfulness
+    pub fn m(&self) -> Float {
+        self.m
+    }
+
+    #[inline(always)]
+    /// Returns Saturation
+    pub fn s(&self) -> Float {
+        self.s
+    }
+
+    #[inline(always)]
+    /// Returns Brightness
+    pub fn q(&self) -> Float {
+        self.q
+    }
+
+    /// Creates a color from its lightness, chroma and hue correlates. The remaining correlates
+    /// are derived from the viewing conditions.
+    pub fn from_jch(
+        j: Float,
+        c: Float,
+        h: Float,
+        white: Illuminant,
+        viewing_conditions: &ViewingConditions,
+    ) -> Self {
+        let env = Environment::new(white, viewing_conditions);
+        Self::from_jch_env(j, c, h, &env)
+    }
+
+    fn from_jch_env(j: Float, c: Float, h: Float, env: &Environment) -> Self {
+        let j = if j.is_nan() { 0. } else { j.max(0.) };
+        let c = if c.is_nan() { 0. } else { c.max(0.) };
+        let h = if h.is_nan() { 0. } else { h.rem_euclid(360.) };
+
+        let fl_4 = env.fl.powf(0.25);
+        let q = (4. / env.c) * (j / 100.).sqrt() * (env.aw + 4.) * fl_4;
+        let m = c * fl_4;
+        let s = if q == 0. { 0. } else { 100. * (m / q).sqrt() };
+
+        Self { j, c, h, m, s, q }
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    pub fn from_xyz(color: Xyz, white: Illuminant, viewing_conditions: &ViewingConditions) -> Self {
+        let env = Environment::new(white, viewing_conditions);
+        let rgb = m16() * Matrix1x3::from([color.x() * 100., color.y() * 100., color.z() * 100.]);
+
+        let r = compress(env.d_rgb[0] * rgb[0], env.fl);
+        let g = compress(env.d_rgb[1] * rgb[1], env.fl);
+        let b = compress(env.d_rgb[2] * rgb[2], env.fl);
+
+        let a = r - 12. * g / 11. + b / 11.;
+        let bb = (r + g - 2. * b) / 9.;
+        let h = Float::atan2(bb, a).to_degrees().rem_euclid(360.);
+
+        let achromatic = (2. * r + g + 0.05 * b - 0.305) * env.nbb;
+        let j = if achromatic > 0. {
+            100. * (achromatic / env.aw).powf(env.c * env.z)
+        } else {
+            0.
+        };
+
+        let t = (50000. / 13. * env.nc * env.nbb * eccentricity(h) * (a * a + bb * bb).sqrt())
+            / (r + g + 21. / 20. * b);
+        let c = t.powf(0.9) * (j / 100.).sqrt() * env.chroma_factor();
+
+        Self::from_jch_env(j, c, h, &env)
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    pub fn to_xyz(self, white: Illuminant, viewing_conditions: &ViewingConditions) -> Xyz {
+        let env = Environment::new(white, viewing_conditions);
+        if self.j <= 0. {
+            return Xyz::new(0., 0., 0.);
+        }
+
+        let t = (self.c / ((self.j / 100.).sqrt() * env.chroma_factor())).powf(1. / 0.9);
+        let achromatic = env.aw * (self.j / 100.).powf(1. / (env.c * env.z));
+        let p2 = achromatic / env.nbb + 0.305;
+        let p3 = 21. / 20.;
+
+        let hr = self.h.to_radians();
+        let (sin, cos) = hr.sin_cos();
+
+        let (a, b) = if t == 0. {
+            (0., 0.)
+        } else {
+            let p1 = (50000. / 13. * env.nc * env.nbb * eccentricity(self.h)) / t;
+            if sin.abs() >= cos.abs() {
+                let p4 = p1 / sin;
+                let b = p2 * (2. + p3) * (460. / 1403.)
+                    / (p4 + (2. + p3) * (220. / 1403.) * (cos / sin) - 27. / 1403.
+                        + p3 * (6300. / 1403.));
+                (b * cos / sin, b)
+            } else {
+                let p5 = p1 / cos;
+                let a = p2 * (2. + p3) * (460. / 1403.)
+                    / (p5 + (2. + p3) * (220. / 1403.)
+                        - (27. / 1403. - p3 * (6300. / 1403.)) * (sin / cos));
+                (a, a * sin / cos)
+            }
+        };
+
+        let r = (460. * p2 + 451. * a + 288. * b) / 1403.;
+        let g = (460. * p2 - 891. * a - 261. * b) / 1403.;
+        let bb = (460. * p2 - 220. * a - 6300. * b) / 1403.;
+
+        let rgb = Matrix1x3::from([
+            decompress(r, env.fl) / env.d_rgb[0],
+            decompress(g, env.fl) / env.d_rgb[1],
+            decompress(bb, env.fl) / env.d_rgb[2],
+        ]);
+        let xyz = m16().inverse().expect("inverse CAT16 matrix") * rgb;
+
+        Xyz::new(xyz[0] / 100., xyz[1] / 100., xyz[2] / 100.)
+    }
+}
+
+/// Color in the perceptually uniform CAM16-UCS space.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Cam16Ucs {
+    j: Float,
+    a: Float,
+    b: Float,
+}
+
+impl Cam16Ucs {
+    const C1: Float = 0.007;
+    const C2: Float = 0.0228;
+
+    pub fn new(j: Float, a: Float, b: Float) -> Self {
+        let j = if j.is_nan() { 0. } else { j };
+        let a = if a.is_nan() { 0. } else { a };
+        let b = if b.is_nan() { 0. } else { b };
+
+        Self { j, a, b }
+    }
+
+    #[inline(always)]
+    /// Returns Lightness J'
+    pub fn j(&self) -> Float {
+        self.j
+    }
+
+    #[inline(always)]
+    /// Returns a' coordinate
+    pub fn a(&self) -> Float {
+        self.a
+    }
+
+    #[inline(always)]
+    /// Returns b' coordinate
+    pub fn b(&self) -> Float {
+        self.b
+    }
+
+    pub fn from_xyz(color: Xyz, white: Illuminant, viewing_conditions: &ViewingConditions) -> Self {
+        Cam16::from_xyz(color, white, viewing_conditions).into()
+    }
+
+    pub fn to_cam16(self, white: Illuminant, viewing_conditions: &ViewingConditions) -> Cam16 {
+        let env = Environment::new(white, viewing_conditions);
+        let j = -self.j / (Self::C1 * self.j - 1. - 100. * Self::C1);
+        let m_ucs = (self.a.powi(2) + self.b.powi(2)).sqrt();
+        let m = ((Self::C2 * m_ucs).exp() - 1.) / Self::C2;
+        let h = Float::atan2(self.b, self.a).to_degrees().rem_euclid(360.);
+
+        Cam16::from_jch_env(j, m / env.fl.powf(0.25), h, &env)
+    }
+
+    pub fn to_xyz(self, white: Illuminant, viewing_conditions: &ViewingConditions) -> Xyz {
+        self.to_cam16(white, viewing_conditions)
+            .to_xyz(white, viewing_conditions)
+    }
+}
+
+//####################################################################################################
+
+impl From<Cam16> for Cam16Ucs {
+    fn from(color: Cam16) -> Self {
+        let j = (1. + 100. * Self::C1) * color.j() / (1. + Self::C1 * color.j());
+        let m = (1. + Self::C2 * color.m()).ln() / Self::C2;
+        let h = color.h().to_radians();
+
+        Self::new(j, m * h.cos(), m * h.sin())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference_conditions() -> ViewingConditions {
+        ViewingConditions {
+            adapting_luminance: 318.31,
+            background_luminance: 20.,
+            surround: Surround::Average,
+            discounting: false,
+        }
+    }
+
+    #[test]
+    fn xyz_to_cam16() {
+        let xyz = Xyz::new(0.4124, 0.2126, 0.0193);
+        let got = Cam16::from_xyz(xyz, Illuminant::D65, &reference_conditions());
+
+        assert!((got.j() - 46.4942).abs() < 1e-2, "{:?}", got);
+        assert!((got.c() - 114.2741).abs() < 1e-2, "{:?}", got);
+        assert!((got.h() - 27.3993).abs() < 1e-2, "{:?}", got);
+    }
+
+    #[test]
+    fn cam16_roundtrip() {
+        let vc = ViewingConditions::default();
+        for xyz in [
+            Xyz::new(0.4124, 0.2126, 0.0193),
+            Xyz::new(0.3576, 0.7152, 0.1192),
+            Xyz::new(0.1805, 0.0722, 0.9505),
+            Xyz::new(0.2, 0.3, 0.4),
+        ] {
+            let cam = Cam16::from_xyz(xyz, Illuminant::D65, &vc);
+            let got = cam.to_xyz(Illuminant::D65, &vc);
+            assert!((got.x() - xyz.x()).abs() < 1e-3, "{:?} {:?}", got, xyz);
+            assert!((got.y() - xyz.y()).abs() < 1e-3, "{:?} {:?}", got, xyz);
+            assert!((got.z() - xyz.z()).abs() < 1e-3, "{:?} {:?}", got, xyz);
+
+            let ucs = Cam16Ucs::from(cam);
+            let got = ucs.to_cam16(Illuminant::D65, &vc);
+            assert!((got.j() - cam.j()).abs() < 1e-2);
+            assert!((got.m() - cam.m()).abs() < 1e-2);
+        }
+    }
+}
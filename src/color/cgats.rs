@@ -0,0 +1,491 @@
+use crate::{
+    color::{
+        spectral::{Observer, Spectrum},
+        CIEColor, ChromaticAdaptationMethod, Color, Illuminant, Lab, NamedPalette, Palette, Rgb,
+        RgbWorkingSpace, Xyz,
+    },
+    math::Float,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+/// Measured values of a single sample in a CGATS file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Measurement {
+    /// Spectral reflectance factor in the 0.0 ..= 1.0 range
+    Spectral(Spectrum),
+    /// XYZ tristimulus values scaled so that the measurement white has `Y = 1`
+    Xyz(Xyz),
+    Lab(Lab),
+    Rgb(Rgb),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CgatsSample {
+    pub name: Option<String>,
+    pub measurement: Measurement,
+}
+
+/// Contents of a CGATS.17 measurement file as exported by spectrophotometer software.
+///
+/// Only the data fields that describe a color are kept. When a sample carries several
+/// representations they are preferred in the following order: spectral, XYZ, Lab, RGB.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cgats {
+    pub descriptor: Option<String>,
+    /// Illuminant the XYZ and Lab values were computed for, D50 unless specified in the file
+    pub illuminant: Illuminant,
+    /// Observer the XYZ and Lab values were computed for, 2° unless specified in the file
+    pub observer: Observer,
+    pub samples: Vec<CgatsSample>,
+}
+
+/// Column indices of the supported data fields
+#[derive(Default)]
+struct Fields {
+    id: Option<usize>,
+    name: Option<usize>,
+    spectral: Vec<(Float, usize)>,
+    xyz: [Option<usize>; 3],
+    lab: [Option<usize>; 3],
+    rgb: [Option<usize>; 3],
+}
+
+impl Fields {
+    fn parse(names: &[String]) -> Result<Self> {
+        let mut fields = Fields::default();
+        for (i, name) in names.iter().enumerate() {
+            let name = name.to_uppercase();
+            match name.as_str() {
+                "SAMPLE_ID" => fields.id = Some(i),
+                "SAMPLE_NAME" => fields.name = Some(i),
+                "XYZ_X" => fields.xyz[0] = Some(i),
+                "XYZ_Y" => fields.xyz[1] = Some(i),
+                "XYZ_Z" => fields.xyz[2] = Some(i),
+                "LAB_L" => fields.lab[0] = Some(i),
+                "LAB_A" => fields.lab[1] = Some(i),
+                "LAB_B" => fields.lab[2] = Some(i),
+                "RGB_R" => fields.rgb[0] = Some(i),
+                "RGB_G" => fields.rgb[1] = Some(i),
+                "RGB_B" => fields.rgb[2] = Some(i),
+                _ => {
+                    if let Some(wavelength) = spectral_wavelength(&name) {
+                        fields.spectral.push((wavelength, i));
+                    }
+                }
+            }
+        }
+        fields
+            .spectral
+            .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        if fields.spectral.len() == 1 {
+            bail!("spectral data needs at least two wavelengths");
+        }
+        if let [(first, _), (second, _), ..] = fields.spectral[..] {
+            let interval = second - first;
+            if fields
+                .spectral
+                .windows(2)
+                .any(|w| ((w[1].0 - w[0].0) - interval).abs() > Float::EPSILON)
+            {
+                bail!("spectral data must be sampled at a constant interval");
+            }
+        }
+
+        if fields.spectral.is_empty()
+            && fields.xyz.iter().any(Option::is_none)
+            && fields.lab.iter().any(Option::is_none)
+            && fields.rgb.iter().any(Option::is_none)
+        {
+            bail!("no spectral, XYZ, Lab or RGB data fields found");
+        }
+
+        Ok(fields)
+    }
+}
+
+/// Extracts the wavelength from field names like `SPECTRAL_NM380`, `SPECTRAL_380` or `nm380`.
+fn spectral_wavelength(field: &str) -> Option<Float> {
+    let wavelength = field
+        .strip_prefix("SPECTRAL_NM_")
+        .or_else(|| field.strip_prefix("SPECTRAL_NM"))
+        .or_else(|| field.strip_prefix("SPECTRAL_"))
+        .or_else(|| field.strip_prefix("NM"))?;
+    wavelength.parse().ok()
+}
+
+/// Splits a line into whitespace separated tokens keeping quoted strings together.
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut token = String::new();
+        if c == '"' {
+            for c in chars.by_ref() {
+                if c == '"' {
+                    break;
+                }
+                token.push(c);
+            }
+        } else {
+            token.push(c);
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                token.push(c);
+            }
+        }
+        tokens.push(token);
+    }
+    tokens
+}
+
+fn parse_number(token: &str, line: usize) -> Result<Float> {
+    // some locales export decimal commas
+    token
+        .replace(',', ".")
+        .parse()
+        .with_context(|| format!("invalid number `{}` on line {}", token, line))
+}
+
+impl Cgats {
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut descriptor = None;
+        let mut illuminant = Illuminant::D50;
+        let mut observer = Observer::Cie1931;
+        let mut field_names = Vec::new();
+        let mut rows = Vec::new();
+        let mut in_format = false;
+        let mut in_data = false;
+
+        for (n, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_whitespace().next().unwrap_or_default() {
+                "BEGIN_DATA_FORMAT" => in_format = true,
+                "END_DATA_FORMAT" => in_format = false,
+                "BEGIN_DATA" => in_data = true,
+                "END_DATA" => in_data = false,
+                _ if in_format => field_names.extend(tokenize(line)),
+                _ if in_data => rows.push((n + 1, tokenize(line))),
+                keyword => {
+                    let value = tokenize(line).into_iter().nth(1).unwrap_or_default();
+                    match keyword {
+                        "DESCRIPTOR" if !value.is_empty() => descriptor = Some(value),
+                        "ILLUMINATION_NAME" => {
+                            illuminant = parse_illuminant(&value).unwrap_or(illuminant)
+                        }
+                        "OBSERVER_ANGLE" if value.starts_with("10") => observer = Observer::Cie1964,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        if field_names.is_empty() {
+            bail!("missing data format, is this a CGATS file?");
+        }
+        let fields = Fields::parse(&field_names)?;
+
+        let mut samples = Vec::with_capacity(rows.len());
+        let mut spectra = Vec::new();
+        for (line, row) in &rows {
+            if row.len() != field_names.len() {
+                bail!(
+                    "line {} has {} values but {} fields are defined",
+                    line,
+                    row.len(),
+                    field_names.len()
+                );
+            }
+            let name = fields
+                .name
+                .or(fields.id)
+                .map(|i| row[i].clone())
+                .filter(|name| !name.is_empty());
+            let triplet = |idx: &[Option<usize>; 3]| -> Result<Option<(Float, Float, Float)>> {
+                match idx {
+                    [Some(a), Some(b), Some(c)] => Ok(Some((
+                        parse_number(&row[*a], *line)?,
+                        parse_number(&row[*b], *line)?,
+                        parse_number(&row[*c], *line)?,
+                    ))),
+                    _ => Ok(None),
+                }
+            };
+
+            let measurement = if !fields.spectral.is_empty() {
+                let values = fields
+                    .spectral
+                    .iter()
+                    .map(|(_, i)| parse_number(&row[*i], *line))
+                    .collect::<Result<Vec<_>>>()?;
+                spectra.push(samples.len());
+                let start = fields.spectral[0].0;
+                let interval = fields.spectral[1].0 - start;
+                Measurement::Spectral(Spectrum::new(start, interval, values))
+            } else if let Some((x, y, z)) = triplet(&fields.xyz)? {
+                Measurement::Xyz(Xyz::new(x / 100., y / 100., z / 100.))
+            } else if let Some((l, a, b)) = triplet(&fields.lab)? {
+                Measurement::Lab(Lab::new(l, a, b))
+            } else if let Some((r, g, b)) = triplet(&fields.rgb)? {
+                Measurement::Rgb(Rgb::new(r / 255., g / 255., b / 255.))
+            } else {
+                return Err(anyhow!("no color data on line {}", line));
+            };
+            samples.push(CgatsSample { name, measurement });
+        }
+
+        // Reflectance is exported either as a factor or in percent, the latter is detected by
+        // any value being well above 1.
+        let is_percent = spectra.iter().any(|i| match &samples[*i].measurement {
+            Measurement::Spectral(spectrum) => spectrum.values().iter().any(|v| *v > 1.5),
+            _ => false,
+        });
+        if is_percent {
+            for sample in &mut samples {
+                if let Measurement::Spectral(spectrum) = &sample.measurement {
+                    let values = spectrum.values().iter().map(|v| v / 100.).collect();
+                    sample.measurement = Measurement::Spectral(Spectrum::new(
+                        spectrum.start(),
+                        spectrum.interval(),
+                        values,
+                    ));
+                }
+            }
+        }
+
+        Ok(Self {
+            descriptor,
+            illuminant,
+            observer,
+            samples,
+        })
+    }
+
+    /// Converts all samples to colors in the given working space. Spectral samples are
+    /// integrated under `illuminant` as seen by `observer`, every measurement is then adapted
+    /// from its white point to the reference white of the working space.
+    pub fn to_palette(
+        &self,
+        illuminant: Illuminant,
+        observer: Observer,
+        ws: RgbWorkingSpace,
+        method: ChromaticAdaptationMethod,
+    ) -> Result<Palette> {
+        let spd = illuminant
+            .spd()
+            .ok_or_else(|| anyhow!("no spectral data for illuminant {}", illuminant.as_ref()))?;
+        let spectral_white = spd.white_point(observer);
+        // XYZ and Lab values are relative to the same white so that samples carrying either give
+        // the same color
+        let measurement_white = self.measurement_white();
+        let ws_white = ws.reference_illuminant().xyz();
+
+        Ok(self
+            .samples
+            .iter()
+            .map(|sample| {
+                let rgb = match &sample.measurement {
+                    Measurement::Spectral(spectrum) => spectrum
+                        .to_xyz(&spd, observer)
+                        .adapt(method, spectral_white, ws_white)
+                        .to_rgb(ws),
+                    Measurement::Xyz(xyz) => {
+                        xyz.adapt(method, measurement_white, ws_white).to_rgb(ws)
+                    }
+                    Measurement::Lab(lab) => lab
+                        .to_xyz_relative_to(measurement_white)
+                        .adapt(method, measurement_white, ws_white)
+                        .to_rgb(ws),
+                    Measurement::Rgb(rgb) => *rgb,
+                };
+                Color::Rgb(rgb)
+            })
+            .collect())
+    }
+
+    /// Returns the white of the illuminant and observer declared in the file that the XYZ and
+    /// Lab values were computed for. The tabulated white of the illuminant is used if it has no
+    /// spectral data.
+    pub fn measurement_white(&self) -> Xyz {
+        self.illuminant
+            .spd()
+            .map(|spd| spd.white_point(self.observer))
+            .unwrap_or_else(|| self.illuminant.xyz())
+    }
+
+    pub fn to_named_palette(
+        &self,
+        name: impl Into<String>,
+        illuminant: Illuminant,
+        observer: Observer,
+        ws: RgbWorkingSpace,
+        method: ChromaticAdaptationMethod,
+    ) -> Result<NamedPalette> {
+        Ok(NamedPalette::new(
+            name,
+            self.to_palette(illuminant, observer, ws, method)?,
+        ))
+    }
+}
+
+fn parse_illuminant(name: &str) -> Option<Illuminant> {
+    use Illuminant::*;
+    Some(match name.to_uppercase().as_str() {
+        "A" => A,
+        "B" => B,
+        "C" => C,
+        "D50" => D50,
+        "D55" => D55,
+        "D65" => D65,
+        "D75" => D75,
+        "E" => E,
+        "F2" => F2,
+        "F7" => F7,
+        "F11" => F11,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPECTRAL: &str = r#"CGATS.17
+ORIGINATOR	"i1Profiler"
+DESCRIPTOR	"Brand swatches"
+CREATED	"2022-10-01"
+NUMBER_OF_FIELDS	9
+BEGIN_DATA_FORMAT
+SAMPLE_ID	SAMPLE_NAME	SPECTRAL_NM400	SPECTRAL_NM450	SPECTRAL_NM500	SPECTRAL_NM550	SPECTRAL_NM600	SPECTRAL_NM650	SPECTRAL_NM700
+END_DATA_FORMAT
+NUMBER_OF_SETS	2
+BEGIN_DATA
+1	"White"	90.0	90.0	90.0	90.0	90.0	90.0	90.0
+2	"Red"	5.0	4.0	4.0	5.0	70.0	85.0	88.0
+END_DATA
+"#;
+
+    const LAB: &str = r#"CGATS.17
+# exported from a spot color library
+ILLUMINATION_NAME "D50"
+OBSERVER_ANGLE "2"
+BEGIN_DATA_FORMAT
+SAMPLE_ID LAB_L LAB_A LAB_B
+END_DATA_FORMAT
+BEGIN_DATA
+A1 100 0 0
+A2 0,0 0,0 0,0
+END_DATA
+"#;
+
+    #[test]
+    fn parses_spectral() {
+        let cgats = Cgats::parse(SPECTRAL).unwrap();
+        assert_eq!(cgats.descriptor.as_deref(), Some("Brand swatches"));
+        assert_eq!(cgats.samples.len(), 2);
+        assert_eq!(cgats.samples[1].name.as_deref(), Some("Red"));
+        match &cgats.samples[0].measurement {
+            Measurement::Spectral(spectrum) => {
+                assert_eq!(spectrum.start(), 400.);
+                assert_eq!(spectrum.interval(), 50.);
+                assert_eq!(spectrum.values(), &[0.9; 7]);
+            }
+            m => panic!("unexpected measurement {:?}", m),
+        }
+
+        let palette = cgats
+            .to_named_palette(
+                "swatches",
+                Illuminant::D65,
+                Observer::Cie1931,
+                RgbWorkingSpace::SRGB,
+                ChromaticAdaptationMethod::Bradford,
+            )
+            .unwrap();
+        let colors: Vec<_> = palette.palette.iter().map(Color::as_hex).collect();
+        // a flat reflectance under the reference white of sRGB is a neutral gray
+        assert_eq!(colors[0], "#f3f3f3");
+        let (r, g, b) = palette
+            .palette
+            .iter()
+            .nth(1)
+            .unwrap()
+            .as_rgb_triplet_scaled();
+        assert!(r > 200 && g < 100 && b < 100);
+    }
+
+    #[test]
+    fn parses_lab() {
+        let cgats = Cgats::parse(LAB).unwrap();
+        assert_eq!(cgats.illuminant, Illuminant::D50);
+        assert_eq!(cgats.samples[0].name.as_deref(), Some("A1"));
+        assert_eq!(
+            cgats.samples[1].measurement,
+            Measurement::Lab(Lab::new(0., 0., 0.))
+        );
+
+        let palette = cgats
+            .to_palette(
+                Illuminant::D65,
+                Observer::Cie1931,
+                RgbWorkingSpace::SRGB,
+                ChromaticAdaptationMethod::Bradford,
+            )
+            .unwrap();
+        let colors: Vec<_> = palette.iter().map(Color::as_rgb_triplet_scaled).collect();
+        assert!(colors[0].0 >= 254 && colors[0].1 >= 254 && colors[0].2 >= 254);
+        assert_eq!(colors[1], (0, 0, 0));
+    }
+
+    #[test]
+    fn uses_one_white_for_xyz_and_lab() {
+        // the same patch measured for D50 and the 10° observer
+        let both = r#"CGATS.17
+ILLUMINATION_NAME "D50"
+OBSERVER_ANGLE "10"
+BEGIN_DATA_FORMAT
+SAMPLE_ID XYZ_X XYZ_Y XYZ_Z LAB_L LAB_A LAB_B
+END_DATA_FORMAT
+BEGIN_DATA
+1 20.0 15.0 60.0 45.6342 29.9903 -74.4100
+END_DATA
+"#;
+        let xyz = Cgats::parse(both).unwrap();
+        assert!(matches!(xyz.samples[0].measurement, Measurement::Xyz(_)));
+        let lab = Cgats::parse(&both.replace("XYZ_", "NOT_XYZ_")).unwrap();
+        assert!(matches!(lab.samples[0].measurement, Measurement::Lab(_)));
+
+        let color = |cgats: &Cgats| {
+            let palette = cgats
+                .to_palette(
+                    Illuminant::D65,
+                    Observer::Cie1931,
+                    RgbWorkingSpace::SRGB,
+                    ChromaticAdaptationMethod::Bradford,
+                )
+                .unwrap();
+            let rgb = palette.iter().next().unwrap().rgb();
+            rgb
+        };
+        let (xyz, lab) = (color(&xyz), color(&lab));
+        for (xyz, lab) in [(xyz.r(), lab.r()), (xyz.g(), lab.g()), (xyz.b(), lab.b())] {
+            assert!((xyz - lab).abs() < 1e-3, "{} != {}", lab, xyz);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid() {
+        assert!(Cgats::parse("").is_err());
+        assert!(Cgats::parse("BEGIN_DATA_FORMAT\nFOO BAR\nEND_DATA_FORMAT\n").is_err());
+        assert!(Cgats::parse(
+            "BEGIN_DATA_FORMAT\nRGB_R RGB_G RGB_B\nEND_DATA_FORMAT\nBEGIN_DATA\n1 2\nEND_DATA"
+        )
+        .is_err());
+    }
+}
@@ -7,6 +7,13 @@ pub enum ChromaticAdaptationMethod {
     Bradford,
     VonKries,
     XYZScaling,
+    #[serde(rename = "CAT02")]
+    Cat02,
+    #[serde(rename = "CAT16")]
+    Cat16,
+    Sharp,
+    #[serde(rename = "CMCCAT2000")]
+    CmcCat2000,
 }
 
 impl ChromaticAdaptationMethod {
@@ -34,6 +41,34 @@ impl ChromaticAdaptationMethod {
                     [0., 0., 1.],
                 ]
             },
+            ChromaticAdaptationMethod::Cat02 => {
+                [
+                    [ 0.7328, 0.4296, -0.1624],
+                    [-0.7036, 1.6975,  0.0061],
+                    [ 0.003 , 0.0136,  0.9834],
+                ]
+            },
+            ChromaticAdaptationMethod::Cat16 => {
+                [
+                    [ 0.401288, 0.650173, -0.051461],
+                    [-0.250268, 1.204414,  0.045854],
+                    [-0.002079, 0.048952,  0.953127],
+                ]
+            },
+            ChromaticAdaptationMethod::Sharp => {
+                [
+                    [ 1.2694, -0.0988, -0.1706],
+                    [-0.8364,  1.8006,  0.0357],
+                    [ 0.0297, -0.0315,  1.0018],
+                ]
+            },
+            ChromaticAdaptationMethod::CmcCat2000 => {
+                [
+                    [ 0.7982, 0.3389, -0.1371],
+                    [-0.5918, 1.5512,  0.0406],
+                    [ 0.0008, 0.0239,  0.9753],
+                ]
+            },
         }.into()
     }
 }
@@ -44,6 +79,10 @@ impl AsRef<str> for ChromaticAdaptationMethod {
             ChromaticAdaptationMethod::Bradford => "Bradford",
             ChromaticAdaptationMethod::VonKries => "Von Kries",
             ChromaticAdaptationMethod::XYZScaling => "XYZ Scaling",
+            ChromaticAdaptationMethod::Cat02 => "CAT02",
+            ChromaticAdaptationMethod::Cat16 => "CAT16",
+            ChromaticAdaptationMethod::Sharp => "Sharp",
+            ChromaticAdaptationMethod::CmcCat2000 => "CMCCAT2000",
         }
     }
 }
@@ -53,3 +92,41 @@ impl Default for ChromaticAdaptationMethod {
         ChromaticAdaptationMethod::Bradford
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ChromaticAdaptationMethod;
+    use crate::color::{Illuminant, Xyz};
+
+    #[test]
+    fn adapts_white_point() {
+        use ChromaticAdaptationMethod::*;
+        for method in [
+            Bradford, VonKries, XYZScaling, Cat02, Cat16, Sharp, CmcCat2000,
+        ] {
+            let got = Illuminant::D65.xyz().chromatic_adaptation_transform(
+                method,
+                Illuminant::D65,
+                Illuminant::D50,
+            );
+            let want = Illuminant::D50.xyz();
+            assert!((got.x() - want.x()).abs() < 1e-4, "{:?}", method);
+            assert!((got.y() - want.y()).abs() < 1e-4, "{:?}", method);
+            assert!((got.z() - want.z()).abs() < 1e-4, "{:?}", method);
+        }
+    }
+
+    #[test]
+    fn adapts_colors_to_the_destination_white() {
+        // XYZ scaling multiplies every component by the ratio of the destination to the
+        // source white
+        let got = Xyz::new(0.4124564, 0.2126729, 0.0193339).chromatic_adaptation_transform(
+            ChromaticAdaptationMethod::XYZScaling,
+            Illuminant::D65,
+            Illuminant::D50,
+        );
+        assert!((got.x() - 0.4184232).abs() < 1e-5, "{:?}", got);
+        assert!((got.y() - 0.2126729).abs() < 1e-5, "{:?}", got);
+        assert!((got.z() - 0.0146529).abs() < 1e-5, "{:?}", got);
+    }
+}
@@ -2,20 +2,20 @@ use egui::color::{Color32, Hsva, Rgba};
 
 use crate::{
     color::{hsv::Hsv, rgb::Rgb, CIEColor, Color, Hsl, Xyz},
-    math,
+    math::{self, Float},
 };
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Default, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Cmyk {
-    c: f32,
-    m: f32,
-    y: f32,
-    k: f32,
+    c: Float,
+    m: Float,
+    y: Float,
+    k: Float,
 }
 
 impl Cmyk {
-    pub fn new(c: f32, m: f32, y: f32, k: f32) -> Self {
+    pub fn new(c: Float, m: Float, y: Float, k: Float) -> Self {
         let c = if c.is_nan() { 0. } else { c };
         let m = if m.is_nan() { 0. } else { m };
         let y = if y.is_nan() { 0. } else { y };
@@ -25,45 +25,45 @@ impl Cmyk {
 
     #[inline(always)]
     /// Returns Cyan value in the range of 0.0 ..= 1.0
-    pub fn c(&self) -> f32 {
+    pub fn c(&self) -> Float {
         self.c
     }
 
     #[inline(always)]
     /// Returns Magenta value in the range of 0.0 ..= 1.0
-    pub fn m(&self) -> f32 {
+    pub fn m(&self) -> Float {
         self.m
     }
 
     #[inline(always)]
     /// Returns Yellow value in the range of 0.0 ..= 1.0
-    pub fn y(&self) -> f32 {
+    pub fn y(&self) -> Float {
         self.y
     }
 
     #[inline(always)]
     /// Returns Key value in the range of 0.0 ..= 1.0
-    pub fn k(&self) -> f32 {
+    pub fn k(&self) -> Float {
         self.k
     }
 
     /// Returns Cyan value in the range of 0.0 ..= 100.0
-    pub fn c_scaled(&self) -> f32 {
+    pub fn c_scaled(&self) -> Float {
         self.c * 100.
     }
 
     /// Returns Magenta value in the range of 0.0 ..= 100.0
-    pub fn m_scaled(&self) -> f32 {
+    pub fn m_scaled(&self) -> Float {
         self.m * 100.
     }
 
     /// Returns Yellow value in the range of 0.0 ..= 100.0
-    pub fn y_scaled(&self) -> f32 {
+    pub fn y_scaled(&self) -> Float {
         self.y * 100.
     }
 
     /// Returns Key value in the range of 0.0 ..= 100.0
-    pub fn k_scaled(&self) -> f32 {
+    pub fn k_scaled(&self) -> Float {
         self.k * 100.
     }
 }
@@ -119,6 +119,8 @@ impl From<Color> for Cmyk {
             Color::xyY(c, ws) => Xyz::from(c).to_rgb(ws).into(),
             Color::Luv(c, ws) => Xyz::from(c).to_rgb(ws).into(),
             Color::LchUV(c, ws) => Xyz::from(c).to_rgb(ws).into(),
+            Color::Hsluv(c, ws) => c.to_xyz(ws).to_rgb(ws).into(),
+            Color::Hpluv(c, ws) => c.to_xyz(ws).to_rgb(ws).into(),
             Color::Lab(c, ws, illuminant) => c.to_xyz(illuminant).to_rgb(ws).into(),
             Color::LchAB(c, ws, illuminant) => c.to_xyz(illuminant).to_rgb(ws).into(),
             Color::Color32(c) => Rgb::from(c).into(),
@@ -147,13 +149,13 @@ impl From<Hsv> for Cmyk {
 #[allow(clippy::many_single_char_names)]
 impl From<Rgb> for Cmyk {
     fn from(color: Rgb) -> Self {
-        let r: f32 = color.r();
-        let g: f32 = color.g();
-        let b: f32 = color.b();
+        let r: Float = color.r();
+        let g: Float = color.g();
+        let b: Float = color.b();
         let rgb = [r, g, b];
-        let k = 1. - rgb.iter().copied().fold(f32::NAN, f32::max);
+        let k = 1. - rgb.iter().copied().fold(Float::NAN, Float::max);
 
-        if math::eq_f32(k, 1.) {
+        if math::eq_float(k, 1.) {
             return Cmyk::new(0., 0., 0., k);
         }
 
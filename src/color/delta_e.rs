@@ -0,0 +1,156 @@
+use crate::{color::Lab, math::Float};
+
+use serde::{Deserialize, Serialize};
+
+/// Formula used to compute the perceptual difference between two colors in the CIELAB space.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DeltaE {
+    /// Euclidean distance in CIELAB
+    #[serde(rename = "CIE76")]
+    Cie76,
+    /// CIE 1994 with the graphic arts weighting factors
+    #[serde(rename = "CIE94")]
+    Cie94,
+    #[default]
+    #[serde(rename = "CIEDE2000")]
+    Ciede2000,
+}
+
+impl DeltaE {
+    pub fn compute(&self, a: Lab, b: Lab) -> Float {
+        match self {
+            DeltaE::Cie76 => cie76(a, b),
+            DeltaE::Cie94 => cie94(a, b),
+            DeltaE::Ciede2000 => ciede2000(a, b),
+        }
+    }
+}
+
+impl AsRef<str> for DeltaE {
+    fn as_ref(&self) -> &str {
+        match self {
+            DeltaE::Cie76 => "ΔE76",
+            DeltaE::Cie94 => "ΔE94",
+            DeltaE::Ciede2000 => "ΔE2000",
+        }
+    }
+}
+
+fn cie76(a: Lab, b: Lab) -> Float {
+    ((a.l() - b.l()).powi(2) + (a.a() - b.a()).powi(2) + (a.b() - b.b()).powi(2)).sqrt()
+}
+
+fn cie94(a: Lab, b: Lab) -> Float {
+    const K1: Float = 0.045;
+    const K2: Float = 0.015;
+
+    let dl = a.l() - b.l();
+    let c1 = a.a().hypot(a.b());
+    let c2 = b.a().hypot(b.b());
+    let dc = c1 - c2;
+    let da = a.a() - b.a();
+    let db = a.b() - b.b();
+    let dh = (da * da + db * db - dc * dc).max(0.).sqrt();
+
+    let sc = 1. + K1 * c1;
+    let sh = 1. + K2 * c1;
+
+    (dl.powi(2) + (dc / sc).powi(2) + (dh / sh).powi(2)).sqrt()
+}
+
+#[allow(clippy::many_single_char_names)]
+fn ciede2000(a: Lab, b: Lab) -> Float {
+    let pow25_7 = (25. as Float).powi(7);
+
+    let c1 = a.a().hypot(a.b());
+    let c2 = b.a().hypot(b.b());
+    let c_mean = (c1 + c2) / 2.;
+    let g = 0.5 * (1. - (c_mean.powi(7) / (c_mean.powi(7) + pow25_7)).sqrt());
+
+    let a1 = a.a() * (1. + g);
+    let a2 = b.a() * (1. + g);
+    let c1 = a1.hypot(a.b());
+    let c2 = a2.hypot(b.b());
+
+    let hue = |b: Float, a: Float| {
+        if a == 0. && b == 0. {
+            0.
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.)
+        }
+    };
+    let h1 = hue(a.b(), a1);
+    let h2 = hue(b.b(), a2);
+
+    let dl = b.l() - a.l();
+    let dc = c2 - c1;
+    let dh = if c1 * c2 == 0. {
+        0.
+    } else if (h2 - h1).abs() <= 180. {
+        h2 - h1
+    } else if h2 - h1 > 180. {
+        h2 - h1 - 360.
+    } else {
+        h2 - h1 + 360.
+    };
+    let dh = 2. * (c1 * c2).sqrt() * (dh.to_radians() / 2.).sin();
+
+    let l_mean = (a.l() + b.l()) / 2.;
+    let c_mean = (c1 + c2) / 2.;
+    let h_mean = if c1 * c2 == 0. {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180. {
+        (h1 + h2) / 2.
+    } else if h1 + h2 < 360. {
+        (h1 + h2 + 360.) / 2.
+    } else {
+        (h1 + h2 - 360.) / 2.
+    };
+
+    let t = 1. - 0.17 * (h_mean - 30.).to_radians().cos()
+        + 0.24 * (2. * h_mean).to_radians().cos()
+        + 0.32 * (3. * h_mean + 6.).to_radians().cos()
+        - 0.20 * (4. * h_mean - 63.).to_radians().cos();
+    let d_theta = 30. * (-((h_mean - 275.) / 25.).powi(2)).exp();
+    let rc = 2. * (c_mean.powi(7) / (c_mean.powi(7) + pow25_7)).sqrt();
+    let sl = 1. + (0.015 * (l_mean - 50.).powi(2)) / (20. + (l_mean - 50.).powi(2)).sqrt();
+    let sc = 1. + 0.045 * c_mean;
+    let sh = 1. + 0.015 * c_mean * t;
+    let rt = -(2. * d_theta).to_radians().sin() * rc;
+
+    ((dl / sl).powi(2) + (dc / sc).powi(2) + (dh / sh).powi(2) + rt * (dc / sc) * (dh / sh)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_delta_e() {
+        // reference pairs from Sharma, Wu and Dalal, "The CIEDE2000 Color-Difference Formula"
+        let pairs = [
+            ((50., 2.6772, -79.7751), (50., 0., -82.7485), 2.0425),
+            ((50., 0., 0.), (50., -1., 2.), 2.3669),
+            ((50., 2.5, 0.), (73., 25., -18.), 27.1492),
+            (
+                (60.2574, -34.0099, 36.2677),
+                (60.4626, -34.1751, 39.4387),
+                1.2644,
+            ),
+            ((2.0776, 0.0795, -1.135), (0.9033, -0.0636, -0.5514), 0.9082),
+        ];
+        for (a, b, want) in pairs {
+            let a = Lab::new(a.0, a.1, a.2);
+            let b = Lab::new(b.0, b.1, b.2);
+            let got = DeltaE::Ciede2000.compute(a, b);
+            assert!((got - want).abs() < 1e-3, "got {}, want {}", got, want);
+            assert!((DeltaE::Ciede2000.compute(b, a) - want).abs() < 1e-3);
+        }
+
+        let a = Lab::new(50., 2.6772, -79.7751);
+        let b = Lab::new(50., 0., -82.7485);
+        assert!((DeltaE::Cie76.compute(a, b) - 4.0011).abs() < 1e-3);
+        assert!((DeltaE::Cie94.compute(a, b) - 1.3950).abs() < 1e-3);
+        assert_eq!(DeltaE::Ciede2000.compute(a, a), 0.);
+    }
+}
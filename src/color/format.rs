@@ -1,195 +1,1592 @@
-use crate::color::{
-    xyY, CIEColor, Cmyk, Color, Hsl, Hsv, Illuminant, Lab, LchAB, LchUV, Luv, Palette,
-    RgbWorkingSpace, Xyz,
+use crate::{
+    color::{
+        code_value, nearest_name, xyY, CIEColor, Cam16, Cam16Ucs, ChromaticAdaptationMethod, Cmyk,
+        Color, HdrColor, Hpluv, Hsl, Hsluv, Hsv, Hwb, ICtCp, Illuminant, Lab, LchAB, LchUV, Luv,
+        NamedPalette, Rgb, RgbWorkingSpace, TransferFunction, ViewingConditions, Xyz, YCbCr,
+        YCbCrRange, YCbCrStandard, DEFAULT_REFERENCE_WHITE,
+    },
+    math::Float,
 };
 
-use anyhow::{Error, Result};
+use anyhow::{bail, Error, Result};
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while},
-    character::complete::{char, digit1, space0},
-    combinator::{map, map_res, opt},
+    bytes::complete::{tag, take_while, take_while1},
+    character::complete::{char, digit0, digit1, one_of, space0},
+    combinator::{all_consuming, cut, map, map_res, opt, peek, recognize},
     error::{ErrorKind, FromExternalError, ParseError},
-    multi::many0,
-    sequence::{delimited, preceded, tuple},
+    multi::{fold_many0, many0},
+    sequence::{delimited, pair, preceded, terminated, tuple},
     Err, IResult, Parser,
 };
+use once_cell::{sync::Lazy, unsync::OnceCell};
 use serde::{Deserialize, Serialize};
-use std::{fmt::Write, num::ParseIntError};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Write,
+    num::ParseIntError,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
 
+/// Template of a palette made of a prefix, an entry template repeated for every color joined
+/// with a separator, and a suffix.
+///
+/// Besides the color fields of [`CustomColorFormat`] the templates can use `{palette_name}` and
+/// `{count}` anywhere, while the entry template can also use `{index}`, `{number}` (the index
+/// starting at one), `{entry_name}` and conditional blocks like `{if !last},{end}` or
+/// `{if named}{entry_name}{else}{name}{end}` with the conditions `first`, `last` and `named`.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CustomPaletteFormat {
     pub prefix: String,
     pub entry_format: String,
+    /// Text put between the entries
+    #[serde(default)]
+    pub separator: String,
     pub suffix: String,
 }
 
 impl CustomPaletteFormat {
     pub fn format_palette(
         &self,
-        palette: &Palette,
-        ws: RgbWorkingSpace,
-        illuminant: Illuminant,
+        palette: &NamedPalette,
+        options: &FormatOptions,
     ) -> Result<String> {
-        let mut s = self.prefix.clone();
-        let entry_format = CustomColorFormat::parse(&self.entry_format)?;
-        for entry in palette.iter() {
-            s.push_str(&entry_format.format_color(entry, ws, illuminant)?);
+        let template = self.compile()?;
+        let mut s = String::new();
+        let palette_scope = TemplateScope {
+            palette,
+            entry: None,
+        };
+        palette_scope.render(&template.prefix, &mut s)?;
+        for (index, color) in palette.palette.iter().enumerate() {
+            if index > 0 {
+                palette_scope.render(&template.separator, &mut s)?;
+            }
+            let entry = TemplateEntry {
+                index,
+                color,
+                spaces: OnceCell::new(),
+                options,
+            };
+            TemplateScope {
+                palette,
+                entry: Some(&entry),
+            }
+            .render(&template.entry, &mut s)?;
         }
-        s.push_str(&self.suffix);
+        palette_scope.render(&template.suffix, &mut s)?;
         Ok(s)
     }
+
+    /// Checks the templates for errors without formatting a palette
+    pub fn validate(&self) -> Result<(), TemplateError> {
+        self.compile().map(|_| ())
+    }
+
+    fn compile(&self) -> Result<PaletteTemplate<'_>, TemplateError> {
+        Ok(PaletteTemplate {
+            prefix: TemplateParser::new("prefix", &self.prefix, false).parse()?,
+            entry: TemplateParser::new("entry", &self.entry_format, true).parse()?,
+            separator: TemplateParser::new("separator", &self.separator, false).parse()?,
+            suffix: TemplateParser::new("suffix", &self.suffix, false).parse()?,
+        })
+    }
+}
+
+/// Error in one of the templates of a [`CustomPaletteFormat`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TemplateError {
+    /// The template with the error, `prefix`, `entry`, `separator` or `suffix`
+    pub template: &'static str,
+    /// Byte offset of the error in the template
+    pub offset: usize,
+    /// Line of the error starting at 1
+    pub line: usize,
+    /// Column of the error in characters starting at 1
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} template, line {} column {}: {}",
+            self.template, self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+struct PaletteTemplate<'a> {
+    prefix: Vec<TemplateNode<'a>>,
+    entry: Vec<TemplateNode<'a>>,
+    separator: Vec<TemplateNode<'a>>,
+    suffix: Vec<TemplateNode<'a>>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct CustomColorFormat<'a>(Vec<FormatToken<'a>>);
+enum TemplateNode<'a> {
+    Text(&'a str),
+    Field(PaletteField),
+    /// A field or conditional of the color format grammar
+    Color(FormatToken),
+    If {
+        condition: TemplateCondition,
+        negated: bool,
+        then: Vec<TemplateNode<'a>>,
+        otherwise: Vec<TemplateNode<'a>>,
+    },
+}
 
-impl<'a> CustomColorFormat<'a> {
-    pub fn parse(text: &'a str) -> Result<CustomColorFormat<'a>> {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PaletteField {
+    PaletteName,
+    Count,
+    Index,
+    Number,
+    EntryName,
+}
+
+impl PaletteField {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "palette_name" => PaletteField::PaletteName,
+            "count" => PaletteField::Count,
+            "index" => PaletteField::Index,
+            "number" => PaletteField::Number,
+            "entry_name" => PaletteField::EntryName,
+            _ => return None,
+        })
+    }
+
+    fn is_entry_field(&self) -> bool {
+        !matches!(self, PaletteField::PaletteName | PaletteField::Count)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TemplateCondition {
+    First,
+    Last,
+    Named,
+}
+
+/// How a block of a template ended
+enum BlockEnd {
+    Eof,
+    Else(usize),
+    End(usize),
+}
+
+struct TemplateParser<'a> {
+    template: &'static str,
+    text: &'a str,
+    pos: usize,
+    /// Whether the entry fields can be used
+    entry: bool,
+}
+
+impl<'a> TemplateParser<'a> {
+    fn new(template: &'static str, text: &'a str, entry: bool) -> Self {
+        Self {
+            template,
+            text,
+            pos: 0,
+            entry,
+        }
+    }
+
+    fn parse(mut self) -> Result<Vec<TemplateNode<'a>>, TemplateError> {
+        let (nodes, end) = self.block()?;
+        match end {
+            BlockEnd::Eof => Ok(nodes),
+            BlockEnd::Else(offset) => Err(self.error(offset, "`{else}` without an `{if}`")),
+            BlockEnd::End(offset) => Err(self.error(offset, "`{end}` without an `{if}`")),
+        }
+    }
+
+    fn block(&mut self) -> Result<(Vec<TemplateNode<'a>>, BlockEnd), TemplateError> {
+        let mut nodes = vec![];
+        loop {
+            let rest = &self.text[self.pos..];
+            let brace = match rest.find('{') {
+                Some(brace) => brace,
+                None => {
+                    if !rest.is_empty() {
+                        nodes.push(TemplateNode::Text(rest));
+                    }
+                    self.pos = self.text.len();
+                    return Ok((nodes, BlockEnd::Eof));
+                }
+            };
+            if brace > 0 {
+                nodes.push(TemplateNode::Text(&rest[..brace]));
+            }
+            let start = self.pos + brace;
+            if let Ok((rest, token)) = parse_field(&self.text[start..]) {
+                if !self.entry {
+                    return Err(self.error(
+                        start,
+                        format!(
+                            "color field `{}` can only be used in the entry template",
+                            &self.text[start..self.text.len() - rest.len()]
+                        ),
+                    ));
+                }
+                nodes.push(TemplateNode::Color(token));
+                self.pos = self.text.len() - rest.len();
+                continue;
+            }
+            let (tag, end) = match tag_at(self.text, start) {
+                Some(tag) => tag,
+                None => {
+                    // not a field, the brace is part of the text
+                    nodes.push(TemplateNode::Text(&self.text[start..start + 1]));
+                    self.pos = start + 1;
+                    continue;
+                }
+            };
+            self.pos = end;
+
+            match tag {
+                "else" => return Ok((nodes, BlockEnd::Else(start))),
+                "end" => return Ok((nodes, BlockEnd::End(start))),
+                _ => {}
+            }
+            if let Some(condition) = tag.strip_prefix("if ") {
+                nodes.push(self.conditional(start, condition.trim())?);
+            } else if let Some(field) = PaletteField::parse(tag) {
+                if field.is_entry_field() && !self.entry {
+                    return Err(self.error(
+                        start,
+                        format!("`{{{}}}` can only be used in the entry template", tag),
+                    ));
+                }
+                nodes.push(TemplateNode::Field(field));
+            } else {
+                return Err(self.unknown_field(start, tag));
+            }
+        }
+    }
+
+    fn conditional(
+        &mut self,
+        start: usize,
+        condition: &str,
+    ) -> Result<TemplateNode<'a>, TemplateError> {
+        if !self.entry {
+            return Err(self.error(start, "conditions can only be used in the entry template"));
+        }
+        let (negated, name) = match condition.strip_prefix('!') {
+            Some(name) => (true, name.trim_start()),
+            None => (false, condition),
+        };
+        let condition = match name {
+            "first" => TemplateCondition::First,
+            "last" => TemplateCondition::Last,
+            "named" => TemplateCondition::Named,
+            _ => {
+                return Err(self.error(
+                    start,
+                    format!(
+                        "unknown condition `{}`, expected `first`, `last` or `named`",
+                        name
+                    ),
+                ))
+            }
+        };
+
+        let unclosed = |parser: &Self| {
+            parser.error(
+                start,
+                format!("`{{if {}}}` is never closed with `{{end}}`", name),
+            )
+        };
+        let (then, end) = self.block()?;
+        let otherwise = match end {
+            BlockEnd::Eof => return Err(unclosed(self)),
+            BlockEnd::End(_) => vec![],
+            BlockEnd::Else(_) => match self.block()? {
+                (otherwise, BlockEnd::End(_)) => otherwise,
+                (_, BlockEnd::Else(offset)) => {
+                    return Err(self.error(offset, "`{else}` used twice in one `{if}`"))
+                }
+                (_, BlockEnd::Eof) => return Err(unclosed(self)),
+            },
+        };
+        Ok(TemplateNode::If {
+            condition,
+            negated,
+            then,
+            otherwise,
+        })
+    }
+
+    fn unknown_field(&self, start: usize, tag: &str) -> TemplateError {
+        let (name, digit_format) = tag.split_once(':').unwrap_or((tag, ""));
+        if matches!(parse_color_symbol(name), Ok(("", _))) {
+            let offset = start + self.text[start..].find(':').unwrap_or(0) + 1;
+            self.error(
+                offset,
+                format!("invalid digit format `{}` of `{}`", digit_format, name),
+            )
+        } else {
+            self.error(start, format!("unknown field `{{{}}}`", tag))
+        }
+    }
+
+    fn error(&self, offset: usize, message: impl Into<String>) -> TemplateError {
+        let before = &self.text[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        TemplateError {
+            template: self.template,
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+}
+
+/// Returns the trimmed contents of the field starting at `start` and the offset after it. Braces
+/// that aren't followed by a field name, an optional digit format or a condition are text, so
+/// that templates of formats like JSON or CSS don't need escaping.
+fn tag_at(text: &str, start: usize) -> Option<(&str, usize)> {
+    let len = text[start + 1..].find(['{', '}', '\n'])?;
+    let end = start + 1 + len;
+    if text.as_bytes()[end] != b'}' {
+        return None;
+    }
+    let tag = text[start + 1..end].trim();
+
+    let name = match tag.strip_prefix("if ") {
+        Some(condition) => condition.trim_start().trim_start_matches('!').trim_start(),
+        None => tag,
+    };
+    let (name, digit_format) = name.split_once(':').unwrap_or((name, "a"));
+    let is_name = name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    let is_digit_format = !digit_format.is_empty()
+        && digit_format
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.');
+    (is_name && is_digit_format).then_some((tag, end + 1))
+}
+
+/// A palette color being formatted, the color spaces are only computed if a color field is used
+struct TemplateEntry<'c> {
+    index: usize,
+    color: &'c Color,
+    spaces: OnceCell<ColorSpaces<'c>>,
+    options: &'c FormatOptions,
+}
+
+struct TemplateScope<'p> {
+    palette: &'p NamedPalette,
+    entry: Option<&'p TemplateEntry<'p>>,
+}
+
+impl TemplateScope<'_> {
+    fn render(&self, nodes: &[TemplateNode<'_>], s: &mut String) -> Result<()> {
+        for node in nodes {
+            match node {
+                TemplateNode::Text(text) => s.push_str(text),
+                TemplateNode::Field(field) => self.write_field(s, *field)?,
+                TemplateNode::Color(token) => {
+                    if let Some(entry) = self.entry {
+                        entry
+                            .spaces
+                            .get_or_init(|| ColorSpaces::new(entry.color, entry.options))
+                            .write_tokens(s, std::slice::from_ref(token))?;
+                    }
+                }
+                TemplateNode::If {
+                    condition,
+                    negated,
+                    then,
+                    otherwise,
+                } => {
+                    if self.condition(*condition) != *negated {
+                        self.render(then, s)?;
+                    } else {
+                        self.render(otherwise, s)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_field(&self, s: &mut String, field: PaletteField) -> Result<()> {
+        match (field, self.entry) {
+            (PaletteField::PaletteName, _) => s.push_str(&self.palette.name),
+            (PaletteField::Count, _) => write!(s, "{}", self.palette.palette.len())?,
+            (PaletteField::Index, Some(entry)) => write!(s, "{}", entry.index)?,
+            (PaletteField::Number, Some(entry)) => write!(s, "{}", entry.index + 1)?,
+            (PaletteField::EntryName, Some(entry)) => {
+                s.push_str(self.palette.color_name(entry.color).unwrap_or_default())
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn condition(&self, condition: TemplateCondition) -> bool {
+        let entry = match self.entry {
+            Some(entry) => entry,
+            None => return false,
+        };
+        match condition {
+            TemplateCondition::First => entry.index == 0,
+            TemplateCondition::Last => entry.index + 1 == self.palette.palette.len(),
+            TemplateCondition::Named => self.palette.color_name(entry.color).is_some(),
+        }
+    }
+}
+
+/// Settings of the color conversions that the fields of custom formats write and read back
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FormatOptions {
+    pub ws: RgbWorkingSpace,
+    pub illuminant: Illuminant,
+    /// Luminance in cd/m² of the reference white of the ICtCp and HDR fields
+    pub reference_white: Float,
+    /// Adapts the working space white to D65 for the ICtCp and HDR fields
+    pub method: ChromaticAdaptationMethod,
+    /// Viewing conditions of the CAM16 and CAM16-UCS fields
+    pub viewing_conditions: ViewingConditions,
+    /// Exposure in stops applied to the HDR fields
+    pub hdr_exposure: Float,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            ws: RgbWorkingSpace::default(),
+            illuminant: Illuminant::default(),
+            reference_white: DEFAULT_REFERENCE_WHITE,
+            method: ChromaticAdaptationMethod::default(),
+            viewing_conditions: ViewingConditions::default(),
+            hdr_exposure: 0.,
+        }
+    }
+}
+
+/// Number of formats kept by [`CustomColorFormat::cached`], the cache is emptied when it fills
+/// up as editing a format parses every text typed on the way
+const FORMAT_CACHE_SIZE: usize = 64;
+
+static FORMAT_CACHE: Lazy<Mutex<HashMap<String, Arc<CustomColorFormat>>>> =
+    Lazy::new(Default::default);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CustomColorFormat(Vec<FormatToken>);
+
+impl CustomColorFormat {
+    pub fn parse(text: &str) -> Result<CustomColorFormat> {
         match parse_color_format(text).map(|(_, fmt)| fmt) {
             Ok(fmt) => Ok(fmt),
             Err(e) => Err(Error::msg(format!("failed to parse color format - {}", e))),
         }
     }
 
-    pub fn format_color(
-        &self,
-        color: &Color,
-        ws: RgbWorkingSpace,
-        illuminant: Illuminant,
-    ) -> Result<String> {
-        use ColorSymbol::*;
-
-        let rgb = color.rgb();
-        let cmyk = Cmyk::from(rgb);
-        let hsl = Hsl::from(rgb);
-        let hsv = Hsv::from(rgb);
-        let xyz = Xyz::from_rgb(rgb, ws);
-        let xyy = xyY::from(xyz);
-        let lab = Lab::from_xyz(xyz, illuminant);
-        let luv = Luv::from(xyz);
-        let lch_ab = LchAB::from(lab);
-        let lch_uv = LchUV::from(luv);
+    /// Parses the format once and returns the same format for later calls with the same text,
+    /// colors are displayed in custom formats many times every frame
+    pub fn cached(text: &str) -> Result<Arc<CustomColorFormat>> {
+        let mut cache = FORMAT_CACHE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(format) = cache.get(text) {
+            return Ok(format.clone());
+        }
+        let format = Arc::new(Self::parse(text)?);
+        if cache.len() >= FORMAT_CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(text.to_string(), format.clone());
+        Ok(format)
+    }
 
+    pub fn format_color(&self, color: &Color, options: &FormatOptions) -> Result<String> {
+        let spaces = ColorSpaces::new(color, options);
         let mut s = String::new();
+        spaces.write_tokens(&mut s, &self.0)?;
+        Ok(s)
+    }
 
-        for token in &self.0 {
-            match &token {
-                FormatToken::Text(text) => s.push_str(text),
-                FormatToken::Color(ColorField {
-                    symbol,
-                    digit_format,
-                }) => match symbol {
-                    Red | Green | Blue | Cyan | Magenta | Yellow | Key | Cyan100 | Magenta100
-                    | Yellow100 | Key100 | HSLHue | HSLSaturation | HSLLight | HSVHue
-                    | HSVSaturation | HSVValue | LabL | LabA | LabB | LCHabL | LCHabC | LCHabH
-                    | LuvL | LuvU | LuvV | LCHuvL | LCHuvC | LCHuvH | xyYx | xyYy | xyYY | XYZx
-                    | XYZy | XYZz | HSLHue360 | HSLSaturation100 | HSLLight100 | HSVHue360
-                    | HSVSaturation100 | HSVValue100 => {
-                        let num = match symbol {
-                            Red => rgb.r(),
-                            Green => rgb.g(),
-                            Blue => rgb.b(),
-
-                            Cyan => cmyk.c(),
-                            Magenta => cmyk.m(),
-                            Yellow => cmyk.y(),
-                            Key => cmyk.k(),
-
-                            Cyan100 => cmyk.c_scaled(),
-                            Magenta100 => cmyk.m_scaled(),
-                            Yellow100 => cmyk.y_scaled(),
-                            Key100 => cmyk.k_scaled(),
-
-                            HSLHue => hsl.h(),
-                            HSLSaturation => hsl.s(),
-                            HSLLight => hsl.l(),
-
-                            HSLHue360 => hsl.h_scaled(),
-                            HSLSaturation100 => hsl.s_scaled(),
-                            HSLLight100 => hsl.l_scaled(),
-
-                            HSVHue => hsv.h(),
-                            HSVSaturation => hsv.s(),
-                            HSVValue => hsv.v(),
-
-                            HSVHue360 => hsv.h_scaled(),
-                            HSVSaturation100 => hsv.s_scaled(),
-                            HSVValue100 => hsv.v_scaled(),
-
-                            LabL => lab.l(),
-                            LabA => lab.a(),
-                            LabB => lab.b(),
-
-                            LCHabL => lch_ab.l(),
-                            LCHabC => lch_ab.c(),
-                            LCHabH => lch_ab.h(),
-
-                            LuvL => luv.l(),
-                            LuvU => luv.u(),
-                            LuvV => luv.v(),
-
-                            LCHuvL => lch_uv.l(),
-                            LCHuvC => lch_uv.c(),
-                            LCHuvH => lch_uv.h(),
-
-                            xyYx => xyy.x(),
-                            xyYy => xyy.y(),
-                            xyYY => xyy.yy(),
-
-                            XYZx => xyz.x(),
-                            XYZy => xyz.y(),
-                            XYZz => xyz.z(),
-                            _ => unreachable!(),
-                        };
-
-                        match digit_format {
-                            Some(DigitFormat::Decimal) => {
-                                write!(&mut s, "{}", num.abs() as u32)?;
-                            }
-                            Some(DigitFormat::Hex) => {
-                                write!(&mut s, "{:x}", num.abs() as u32)?;
-                            }
-                            Some(DigitFormat::UppercaseHex) => {
-                                write!(&mut s, "{:X}", num.abs() as u32)?;
-                            }
-                            Some(DigitFormat::Octal) => {
-                                write!(&mut s, "{:o}", num.abs() as u32)?;
-                            }
-                            Some(DigitFormat::Float { precision }) => {
-                                write!(&mut s, "{:.*}", *precision as usize, num)?;
-                            }
-                            None => {
-                                write!(&mut s, "{:.1}", num)?;
-                            }
+    /// Checks the format for fields that would be written as text because they can't be
+    /// parsed, like unknown fields or unbalanced parentheses
+    pub fn validate(text: &str) -> Result<(), TemplateError> {
+        let parser = TemplateParser::new("color", text, true);
+        // fields in the branches of conditionals are checked as well
+        for (start, _) in text.match_indices('{') {
+            let rest = &text[start..];
+            if !looks_like_field(rest) {
+                continue;
+            }
+            if let Err(Err::Error(e) | Err::Failure(e)) = parse_field(rest) {
+                return Err(parser.error(text.len() - e.remaining().len(), e.message()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Compiles the format into a matcher that reads colors back from text written in it.
+    /// Fails with the reason when the written text can't be inverted unambiguously.
+    pub fn matcher(&self) -> Result<ColorMatcher> {
+        ColorMatcher::new(&self.0)
+    }
+}
+
+/// Reads colors from text written in a [`CustomColorFormat`]. Text of the format is matched
+/// ignoring ASCII case and whitespace, fields read a number in the format they are written in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorMatcher {
+    segments: Vec<MatchSegment>,
+    space: MatchSpace,
+}
+
+impl ColorMatcher {
+    fn new(tokens: &[FormatToken]) -> Result<Self> {
+        let mut segments: Vec<MatchSegment> = vec![];
+        let mut fields = vec![];
+        let mut reasons = vec![];
+        for token in tokens {
+            match token {
+                FormatToken::Text(text) => match segments.last_mut() {
+                    Some(MatchSegment::Text(prev)) => prev.push_str(text),
+                    Some(MatchSegment::Number(number))
+                        if text.starts_with(|c: char| number.continues_with(c)) =>
+                    {
+                        bail!(
+                            "the text `{}` following a field could be read as part of its number",
+                            text
+                        )
+                    }
+                    _ => segments.push(MatchSegment::Text(text.to_string())),
+                },
+                FormatToken::Color(field) => {
+                    if let Some(MatchSegment::Number(prev)) = segments.last_mut() {
+                        if !prev.zero_pad || prev.width == 0 {
+                            bail!(
+                                "two fields aren't separated by any text so it's unknown where \
+                                 the first number ends, pad it with zeros to a fixed width or \
+                                 add a separator"
+                            );
                         }
+                        prev.fixed_width = true;
                     }
-                    Red255 | Green255 | Blue255 => {
-                        let num = match symbol {
-                            Red255 => rgb.r_scaled(),
-                            Green255 => rgb.g_scaled(),
-                            Blue255 => rgb.b_scaled(),
-                            _ => unreachable!(),
-                        } as u32;
-
-                        match digit_format.unwrap_or_default() {
-                            DigitFormat::Decimal => write!(&mut s, "{}", num)?,
-                            DigitFormat::Hex => write!(&mut s, "{:x}", num)?,
-                            DigitFormat::UppercaseHex => write!(&mut s, "{:X}", num)?,
-                            DigitFormat::Octal => write!(&mut s, "{:o}", num)?,
-                            DigitFormat::Float { precision: _ } => write!(&mut s, "{}", num)?,
+                    let number = NumberMatch::new(field);
+                    let component = match linear(&field.expr) {
+                        Ok((Some(symbol), factor, offset))
+                            if factor != 0. && factor.is_finite() =>
+                        {
+                            match symbol.component() {
+                                Ok(component) => component.map(|(space, index, unit)| {
+                                    (space, index, unit, factor, offset)
+                                }),
+                                Err(reason) => {
+                                    reasons.push(reason);
+                                    None
+                                }
+                            }
+                        }
+                        Ok(_) => None,
+                        Err(reason) => {
+                            reasons.push(reason);
+                            None
                         }
+                    };
+                    fields.push((segments.len(), component));
+                    segments.push(MatchSegment::Number(number));
+                }
+                FormatToken::Name => {
+                    bail!("`{{name}}` can't be read back because names only approximate colors")
+                }
+                FormatToken::Conditional(_) => bail!(
+                    "conditional text can't be read back because either branch could have been \
+                     written"
+                ),
+            }
+        }
+
+        // the first space with every component present, or the most complete one for the error
+        let mut spaces: Vec<(MatchSpace, Vec<usize>)> = vec![];
+        for (space, index, ..) in fields.iter().filter_map(|(_, component)| *component) {
+            match spaces.iter_mut().find(|(s, _)| *s == space) {
+                Some((_, indices)) if !indices.contains(&index) => indices.push(index),
+                Some(_) => {}
+                None => spaces.push((space, vec![index])),
+            }
+        }
+        let space = match spaces
+            .iter()
+            .find(|(space, indices)| indices.len() == space.components().len())
+        {
+            Some((space, _)) => *space,
+            None => {
+                let reasons = if reasons.is_empty() {
+                    String::new()
+                } else {
+                    format!(", {}", reasons.join(", "))
+                };
+                let (space, indices) =
+                    match spaces.iter().rev().max_by_key(|(_, indices)| indices.len()) {
+                        Some(space) => space,
+                        None => bail!(
+                            "the format has no color fields that can be read back{}",
+                            reasons
+                        ),
+                    };
+                let missing: Vec<_> = space
+                    .components()
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !indices.contains(i))
+                    .map(|(_, name)| *name)
+                    .collect();
+                bail!(
+                    "the format is missing the {} of {}{}",
+                    missing.join(" and "),
+                    space.name(),
+                    reasons
+                );
+            }
+        };
+
+        for (i, component) in fields {
+            if let (MatchSegment::Number(number), Some((s, index, unit, factor, offset))) =
+                (&mut segments[i], component)
+            {
+                if s == space {
+                    number.component = Some(MatchComponent {
+                        index,
+                        scale: factor * unit,
+                        offset,
+                    });
+                }
+            }
+        }
+        Ok(Self { segments, space })
+    }
+
+    /// Reads a color from the whole text, returns `None` when it's not written in the format
+    pub fn parse(&self, text: &str, options: &FormatOptions) -> Option<Color> {
+        let mut rest = text.trim();
+        let mut components = [None; 4];
+        for segment in &self.segments {
+            rest = match segment {
+                MatchSegment::Text(pattern) => match_text(pattern, rest)?,
+                MatchSegment::Number(number) => {
+                    let (value, rest) = number.read(rest)?;
+                    if let Some(component) = &number.component {
+                        components[component.index].get_or_insert(component.value(value));
                     }
-                },
+                    rest
+                }
+            };
+        }
+        if !rest.trim().is_empty() {
+            return None;
+        }
+        Some(
+            self.space
+                .color(components.map(|c| c.unwrap_or(0.)), options),
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum MatchSegment {
+    Text(String),
+    Number(NumberMatch),
+}
+
+/// A number read from the text and the color component computed from it
+#[derive(Clone, Debug, PartialEq)]
+struct NumberMatch {
+    digits: DigitFormat,
+    zero_pad: bool,
+    width: usize,
+    /// Whether the number takes exactly its width because a field follows without any text
+    fixed_width: bool,
+    component: Option<MatchComponent>,
+}
+
+impl NumberMatch {
+    fn new(field: &ColorField) -> Self {
+        Self {
+            digits: field.digit_format(),
+            zero_pad: field.format.zero_pad,
+            width: field.format.width,
+            fixed_width: false,
+            component: None,
+        }
+    }
+
+    fn radix(&self) -> u32 {
+        match self.digits {
+            DigitFormat::Hex | DigitFormat::UppercaseHex => 16,
+            DigitFormat::Octal => 8,
+            _ => 10,
+        }
+    }
+
+    /// Whether the character could be read as part of the number, floats are read with the
+    /// fraction after a `.` even when they are written without one
+    fn continues_with(&self, c: char) -> bool {
+        c.is_digit(self.radix())
+            || (self.radix() == 8 && c.is_ascii_digit())
+            || (c == '.' && matches!(self.digits, DigitFormat::Float { .. }))
+    }
+
+    /// Reads the number at the start of the text and returns it with the rest of the text
+    fn read<'t>(&self, text: &'t str) -> Option<(Float, &'t str)> {
+        let (text, len) = if self.fixed_width {
+            (text, self.width.min(text.len()))
+        } else {
+            let text = text.trim_start();
+            (text, text.len())
+        };
+        let bytes = &text.as_bytes()[..len];
+        let mut end = bytes.iter().take_while(|b| **b == b' ').count();
+        let start = end;
+        if matches!(bytes.get(end), Some(b'+' | b'-')) {
+            end += 1;
+        }
+        let digits_start = end;
+        let radix = self.radix();
+        end += bytes[end..]
+            .iter()
+            .take_while(|b| (**b as char).is_digit(radix))
+            .count();
+        let mut has_digits = end > digits_start;
+        if matches!(
+            self.digits,
+            DigitFormat::Float { .. } | DigitFormat::Percent { .. }
+        ) && bytes.get(end) == Some(&b'.')
+        {
+            let fraction = bytes[end + 1..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count();
+            has_digits |= fraction > 0;
+            end += 1 + fraction;
+        }
+        if !has_digits {
+            return None;
+        }
+
+        let number = &text[start..end];
+        let mut value = if radix == 10 {
+            number.parse::<Float>().ok()?
+        } else {
+            i64::from_str_radix(number, radix).ok()? as Float
+        };
+        if let DigitFormat::Percent { .. } = self.digits {
+            if !text[end..].starts_with('%') {
+                return None;
             }
+            end += 1;
+            value /= 100.;
         }
+        Some((value, &text[end..]))
+    }
+}
 
-        Ok(s)
+/// Component of a color space that a field is computed from as `scale * component + offset`
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct MatchComponent {
+    index: usize,
+    scale: Float,
+    offset: Float,
+}
+
+impl MatchComponent {
+    fn value(&self, field: Float) -> Float {
+        (field - self.offset) / self.scale
+    }
+}
+
+/// Color spaces that colors can be read back from, YCbCr code values depend on the standard
+/// and range
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MatchSpace {
+    Rgb,
+    Cmyk,
+    Hsl,
+    Hsv,
+    Hwb,
+    Lab,
+    LchAB,
+    Luv,
+    LchUV,
+    Hsluv,
+    Hpluv,
+    Xyz,
+    XyY,
+    Cam16,
+    Cam16Ucs,
+    YCbCr(YCbCrStandard, YCbCrRange),
+    ICtCp,
+}
+
+impl MatchSpace {
+    fn name(&self) -> &'static str {
+        match self {
+            MatchSpace::Rgb => "RGB",
+            MatchSpace::Cmyk => "CMYK",
+            MatchSpace::Hsl => "HSL",
+            MatchSpace::Hsv => "HSV",
+            MatchSpace::Hwb => "HWB",
+            MatchSpace::Lab => "Lab",
+            MatchSpace::LchAB => "LCH(ab)",
+            MatchSpace::Luv => "Luv",
+            MatchSpace::LchUV => "LCH(uv)",
+            MatchSpace::Hsluv => "HSLuv",
+            MatchSpace::Hpluv => "HPLuv",
+            MatchSpace::Xyz => "XYZ",
+            MatchSpace::XyY => "xyY",
+            MatchSpace::Cam16 => "CAM16",
+            MatchSpace::Cam16Ucs => "CAM16-UCS",
+            MatchSpace::YCbCr(..) => "YCbCr",
+            MatchSpace::ICtCp => "ICtCp",
+        }
+    }
+
+    /// Names of the components in the order they are passed to [`MatchSpace::color`]
+    fn components(&self) -> &'static [&'static str] {
+        match self {
+            MatchSpace::Rgb => &["red", "green", "blue"],
+            MatchSpace::Cmyk => &["cyan", "magenta", "yellow", "key"],
+            MatchSpace::Hsl | MatchSpace::Hsluv | MatchSpace::Hpluv => {
+                &["hue", "saturation", "lightness"]
+            }
+            MatchSpace::Hsv => &["hue", "saturation", "value"],
+            MatchSpace::Hwb => &["hue", "whiteness", "blackness"],
+            MatchSpace::Lab => &["L", "a", "b"],
+            MatchSpace::LchAB | MatchSpace::LchUV => &["lightness", "chroma", "hue"],
+            MatchSpace::Luv => &["L", "u", "v"],
+            MatchSpace::Xyz => &["X", "Y", "Z"],
+            MatchSpace::XyY => &["x", "y", "Y"],
+            MatchSpace::Cam16 => &["J", "C", "h"],
+            MatchSpace::Cam16Ucs => &["J", "a", "b"],
+            MatchSpace::YCbCr(..) => &["Y", "Cb", "Cr"],
+            MatchSpace::ICtCp => &["I", "Ct", "Cp"],
+        }
+    }
+
+    fn color(&self, components: [Float; 4], options: &FormatOptions) -> Color {
+        let FormatOptions {
+            ws,
+            illuminant,
+            reference_white,
+            method,
+            viewing_conditions,
+            ..
+        } = *options;
+        let [a, b, c, d] = components;
+        let xyz = match *self {
+            MatchSpace::Rgb => return Rgb::new(a, b, c).into(),
+            MatchSpace::Cmyk => return Cmyk::new(a, b, c, d).into(),
+            MatchSpace::Hsl => return Hsl::new(a, b, c).into(),
+            MatchSpace::Hsv => return Hsv::new(a, b, c).into(),
+            MatchSpace::Hwb => return Hwb::new(a, b, c).into(),
+            MatchSpace::YCbCr(standard, range) => {
+                return YCbCr::from_code_values(a, b, c, range)
+                    .to_rgb(standard)
+                    .into()
+            }
+            MatchSpace::ICtCp => {
+                return Color::from_ictcp(ICtCp::new(a, b, c), ws, method, reference_white)
+            }
+            MatchSpace::Lab => Lab::new(a, b, c).to_xyz(illuminant),
+            MatchSpace::LchAB => LchAB::new(a, b, c).to_xyz(illuminant),
+            MatchSpace::Luv => Xyz::from(Luv::new(a, b, c)),
+            MatchSpace::LchUV => Xyz::from(LchUV::new(a, b, c)),
+            MatchSpace::Hsluv => Hsluv::new(a, b, c).to_xyz(ws),
+            MatchSpace::Hpluv => Hpluv::new(a, b, c).to_xyz(ws),
+            MatchSpace::Xyz => Xyz::new(a, b, c),
+            MatchSpace::XyY => Xyz::from(xyY::new(a, b, c)),
+            MatchSpace::Cam16 => {
+                let cam16 = Cam16::from_jch(a, b, c, illuminant, &viewing_conditions);
+                return Color::from_cam16(cam16, ws, illuminant, method, &viewing_conditions);
+            }
+            MatchSpace::Cam16Ucs => {
+                let ucs = Cam16Ucs::new(a, b, c);
+                return Color::from_cam16_ucs(ucs, ws, illuminant, method, &viewing_conditions);
+            }
+        };
+        xyz.to_rgb(ws).into()
+    }
+}
+
+/// Returns the symbol of an expression with the factor and offset it's transformed by, or the
+/// reason why the expression can't be inverted
+fn linear(expr: &Expr) -> std::result::Result<(Option<ColorSymbol>, Float, Float), String> {
+    Ok(match expr {
+        Expr::Symbol(symbol) => (Some(*symbol), 1., 0.),
+        Expr::Number(num) => (None, 0., *num),
+        Expr::Negate(expr) => {
+            let (symbol, factor, offset) = linear(expr)?;
+            (symbol, -factor, -offset)
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            let (lhs, rhs) = (linear(lhs)?, linear(rhs)?);
+            let symbol = match (lhs.0, rhs.0) {
+                (Some(_), Some(_)) => {
+                    return Err("fields computed from two values can't be inverted".into())
+                }
+                (symbol, None) | (None, symbol) => symbol,
+            };
+            match op {
+                BinaryOp::Add => (symbol, lhs.1 + rhs.1, lhs.2 + rhs.2),
+                BinaryOp::Sub => (symbol, lhs.1 - rhs.1, lhs.2 - rhs.2),
+                BinaryOp::Mul if lhs.0.is_some() => (symbol, lhs.1 * rhs.2, lhs.2 * rhs.2),
+                BinaryOp::Mul => (symbol, rhs.1 * lhs.2, rhs.2 * lhs.2),
+                BinaryOp::Div if rhs.0.is_some() => {
+                    return Err("fields dividing by a color value can't be inverted".into())
+                }
+                BinaryOp::Div => (symbol, lhs.1 / rhs.2, lhs.2 / rhs.2),
+            }
+        }
+        Expr::Call(function, _) => {
+            return Err(format!("`{}` can't be inverted", function.name()));
+        }
+    })
+}
+
+/// Matches the text of a format at the start of `text` ignoring ASCII case and whitespace,
+/// returns the rest of the text
+fn match_text<'t>(pattern: &str, text: &'t str) -> Option<&'t str> {
+    let mut text = text;
+    for c in pattern.chars().filter(|c| !c.is_whitespace()) {
+        let mut chars = text.trim_start().chars();
+        if !chars.next()?.eq_ignore_ascii_case(&c) {
+            return None;
+        }
+        text = chars.as_str();
+    }
+    Some(text)
+}
+
+/// Formats that can be edited, color formats or the templates of palette formats which can also
+/// use palette fields and blocks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormatKind {
+    Color,
+    Palette,
+}
+
+/// What a part of a format is highlighted as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormatSpanKind {
+    Text,
+    /// Braces of fields and the separators of conditionals
+    Delimiter,
+    Symbol,
+    Function,
+    Number,
+    Operator,
+    DigitFormat,
+    /// Fields and blocks of palette templates
+    Keyword,
+    /// A field that is written as text because it can't be parsed
+    Error,
+}
+
+/// Byte range of a format highlighted as one kind
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatSpan {
+    pub range: Range<usize>,
+    pub kind: FormatSpanKind,
+}
+
+/// A field, function or palette block that can be used in formats with its description
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldHelp {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+const fn help(name: &'static str, description: &'static str) -> FieldHelp {
+    FieldHelp { name, description }
+}
+
+#[rustfmt::skip]
+pub static COLOR_FIELDS: &[FieldHelp] = &[
+    help("r", "Red 0.0 ..= 1.0"),
+    help("g", "Green 0.0 ..= 1.0"),
+    help("b", "Blue 0.0 ..= 1.0"),
+    help("r255", "Red 0 ..= 255"),
+    help("g255", "Green 0 ..= 255"),
+    help("b255", "Blue 0 ..= 255"),
+    help("a", "Alpha, always 1.0 as colors are opaque"),
+    help("c", "CMYK cyan 0.0 ..= 1.0"),
+    help("m", "CMYK magenta 0.0 ..= 1.0"),
+    help("y", "CMYK yellow 0.0 ..= 1.0"),
+    help("k", "CMYK key 0.0 ..= 1.0"),
+    help("c100", "CMYK cyan 0.0 ..= 100.0"),
+    help("m100", "CMYK magenta 0.0 ..= 100.0"),
+    help("y100", "CMYK yellow 0.0 ..= 100.0"),
+    help("k100", "CMYK key 0.0 ..= 100.0"),
+    help("hsl_h", "HSL hue 0.0 ..= 1.0"),
+    help("hsl_s", "HSL saturation 0.0 ..= 1.0"),
+    help("hsl_l", "HSL lightness 0.0 ..= 1.0"),
+    help("hsl_h360", "HSL hue 0.0 ..= 360.0"),
+    help("hsl_s100", "HSL saturation 0.0 ..= 100.0"),
+    help("hsl_l100", "HSL lightness 0.0 ..= 100.0"),
+    help("hsv_h", "HSV hue 0.0 ..= 1.0"),
+    help("hsv_s", "HSV saturation 0.0 ..= 1.0"),
+    help("hsv_v", "HSV value 0.0 ..= 1.0"),
+    help("hsv_h360", "HSV hue 0.0 ..= 360.0"),
+    help("hsv_s100", "HSV saturation 0.0 ..= 100.0"),
+    help("hsv_v100", "HSV value 0.0 ..= 100.0"),
+    help("hwb_h", "HWB hue 0.0 ..= 1.0"),
+    help("hwb_w", "HWB whiteness 0.0 ..= 1.0"),
+    help("hwb_b", "HWB blackness 0.0 ..= 1.0"),
+    help("hwb_h360", "HWB hue 0.0 ..= 360.0"),
+    help("hwb_w100", "HWB whiteness 0.0 ..= 100.0"),
+    help("hwb_b100", "HWB blackness 0.0 ..= 100.0"),
+    help("lab_l", "Lab lightness 0.0 ..= 100.0"),
+    help("lab_a", "Lab a -127.0 ..= 128.0"),
+    help("lab_b", "Lab b -127.0 ..= 128.0"),
+    help("lch_ab_l", "LCH(ab) lightness 0.0 ..= 100.0"),
+    help("lch_ab_c", "LCH(ab) chroma 0.0 ..= 270.0"),
+    help("lch_ab_h", "LCH(ab) hue 0.0 ..= 360.0"),
+    help("luv_l", "Luv lightness 0.0 ..= 100.0"),
+    help("luv_u", "Luv u -134.0 ..= 220.0"),
+    help("luv_v", "Luv v -140.0 ..= 122.0"),
+    help("lch_uv_l", "LCH(uv) lightness 0.0 ..= 100.0"),
+    help("lch_uv_c", "LCH(uv) chroma 0.0 ..= 270.0"),
+    help("lch_uv_h", "LCH(uv) hue 0.0 ..= 360.0"),
+    help("hsluv_h", "HSLuv hue 0.0 ..= 360.0"),
+    help("hsluv_s", "HSLuv saturation 0.0 ..= 100.0"),
+    help("hsluv_l", "HSLuv lightness 0.0 ..= 100.0"),
+    help("hpluv_h", "HPLuv hue 0.0 ..= 360.0"),
+    help("hpluv_p", "HPLuv saturation 0.0 ..= 100.0"),
+    help("hpluv_l", "HPLuv lightness 0.0 ..= 100.0"),
+    help("xyy_x", "xyY chromaticity x"),
+    help("xyy_y", "xyY chromaticity y"),
+    help("xyy_Y", "xyY luminance Y"),
+    help("xyz_x", "XYZ X"),
+    help("xyz_y", "XYZ Y"),
+    help("xyz_z", "XYZ Z"),
+    help("cam16_j", "CAM16 lightness J"),
+    help("cam16_c", "CAM16 chroma C"),
+    help("cam16_h", "CAM16 hue angle h"),
+    help("cam16_m", "CAM16 colorfulness M"),
+    help("cam16_s", "CAM16 saturation s"),
+    help("cam16_q", "CAM16 brightness Q"),
+    help("cam16ucs_j", "CAM16-UCS lightness J'"),
+    help("cam16ucs_a", "CAM16-UCS a'"),
+    help("cam16ucs_b", "CAM16-UCS b'"),
+    help("ycbcr601_y", "BT.601 Y code value, `_full` or `_limited` selects the range"),
+    help("ycbcr601_cb", "BT.601 Cb code value, `_full` or `_limited` selects the range"),
+    help("ycbcr601_cr", "BT.601 Cr code value, `_full` or `_limited` selects the range"),
+    help("ycbcr709_y", "BT.709 Y code value, `_full` or `_limited` selects the range"),
+    help("ycbcr709_cb", "BT.709 Cb code value, `_full` or `_limited` selects the range"),
+    help("ycbcr709_cr", "BT.709 Cr code value, `_full` or `_limited` selects the range"),
+    help("ycbcr2020_y", "BT.2020 Y code value, `_full` or `_limited` selects the range"),
+    help("ycbcr2020_cb", "BT.2020 Cb code value, `_full` or `_limited` selects the range"),
+    help("ycbcr2020_cr", "BT.2020 Cr code value, `_full` or `_limited` selects the range"),
+    help("ictcp_i", "ICtCp intensity I"),
+    help("ictcp_ct", "ICtCp blue-yellow Ct"),
+    help("ictcp_cp", "ICtCp red-green Cp"),
+    help("hdr_nits", "Luminance in cd/m² at the reference white and HDR exposure"),
+    help("pq_r", "PQ signal of red 0.0 ..= 1.0, `pq10_r` for a 10 bit code value"),
+    help("pq_g", "PQ signal of green 0.0 ..= 1.0, `pq10_g` for a 10 bit code value"),
+    help("pq_b", "PQ signal of blue 0.0 ..= 1.0, `pq10_b` for a 10 bit code value"),
+    help("hlg_r", "HLG signal of red 0.0 ..= 1.0, `hlg10_r` for a 10 bit code value"),
+    help("hlg_g", "HLG signal of green 0.0 ..= 1.0, `hlg10_g` for a 10 bit code value"),
+    help("hlg_b", "HLG signal of blue 0.0 ..= 1.0, `hlg10_b` for a 10 bit code value"),
+    help("name", "Nearest color name, can't be used in expressions"),
+];
+
+pub static FUNCTIONS: &[FieldHelp] = &[
+    help("clamp", "clamp(x, min, max) limits x to the range"),
+    help("min", "min(x, y) the smaller value"),
+    help("max", "max(x, y) the larger value"),
+    help("round", "round(x) the nearest whole number"),
+    help("abs", "abs(x) the value without its sign"),
+];
+
+pub static PALETTE_FIELDS: &[FieldHelp] = &[
+    help("palette_name", "Name of the palette"),
+    help("count", "Number of colors in the palette"),
+    help(
+        "index",
+        "Index of the color starting at 0, entry template only",
+    ),
+    help(
+        "number",
+        "Index of the color starting at 1, entry template only",
+    ),
+    help(
+        "entry_name",
+        "Name given to the color in the palette, entry template only",
+    ),
+    help(
+        "if first",
+        "Block used only for the first color, ends with `{end}`",
+    ),
+    help(
+        "if last",
+        "Block used only for the last color, `{if !last}` for all others",
+    ),
+    help(
+        "if named",
+        "Block used only for colors named in the palette",
+    ),
+    help(
+        "else",
+        "Block used when the condition of the `{if}` doesn't hold",
+    ),
+    help("end", "Ends an `{if}` block"),
+];
+
+/// Fields, functions and blocks that can be used in formats of the kind
+pub fn format_fields(kind: FormatKind) -> impl Iterator<Item = &'static FieldHelp> {
+    let palette: &[FieldHelp] = match kind {
+        FormatKind::Color => &[],
+        FormatKind::Palette => PALETTE_FIELDS,
+    };
+    COLOR_FIELDS.iter().chain(FUNCTIONS).chain(palette)
+}
+
+/// Returns the range of the partly typed field name before the cursor, a byte offset, and the
+/// fields starting with it
+pub fn complete_field(
+    text: &str,
+    cursor: usize,
+    kind: FormatKind,
+) -> Option<(Range<usize>, Vec<&'static FieldHelp>)> {
+    let before = text.get(..cursor)?;
+    let brace = before.rfind('{')?;
+    if before[brace..].contains('}') {
+        return None;
+    }
+    let start = before
+        .trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '_')
+        .len();
+    let prefix = &before[start..];
+    if (prefix.is_empty() && start != brace + 1) || prefix.starts_with(|c: char| c.is_ascii_digit())
+    {
+        return None;
     }
+    let fields: Vec<_> = format_fields(kind)
+        .filter(|field| field.name.starts_with(prefix) && field.name != prefix)
+        .collect();
+    (!fields.is_empty()).then_some((start..cursor, fields))
 }
 
-impl<'a> From<Vec<FormatToken<'a>>> for CustomColorFormat<'a> {
-    fn from(vec: Vec<FormatToken<'a>>) -> Self {
+/// Splits a format into highlighted spans covering the whole text
+pub fn highlight_format(text: &str, kind: FormatKind) -> Vec<FormatSpan> {
+    let mut highlighter = Highlighter {
+        text,
+        kind,
+        spans: vec![],
+    };
+    let mut pos = 0;
+    while let Some(brace) = text[pos..].find('{') {
+        let start = pos + brace;
+        highlighter.push(pos, start, FormatSpanKind::Text);
+        pos = highlighter.brace(start);
+    }
+    highlighter.push(pos, text.len(), FormatSpanKind::Text);
+    highlighter.spans
+}
+
+struct Highlighter<'a> {
+    text: &'a str,
+    kind: FormatKind,
+    spans: Vec<FormatSpan>,
+}
+
+impl Highlighter<'_> {
+    fn push(&mut self, start: usize, end: usize, kind: FormatSpanKind) {
+        if start >= end {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some(last) if last.kind == kind && last.range.end == start => last.range.end = end,
+            _ => self.spans.push(FormatSpan {
+                range: start..end,
+                kind,
+            }),
+        }
+    }
+
+    /// Highlights what starts with the brace at `start` and returns the offset after it
+    fn brace(&mut self, start: usize) -> usize {
+        let rest = &self.text[start..];
+        let (result, spans) = record_spans(|| parse_field(rest));
+        let error = match result {
+            Ok((rest, _)) => {
+                let end = self.text.len() - rest.len();
+                let mut pos = start;
+                for (before, after, kind) in spans {
+                    let (span_start, span_end) =
+                        (self.text.len() - before, self.text.len() - after);
+                    self.push(pos, span_start, FormatSpanKind::Text);
+                    self.push(span_start, span_end, kind);
+                    pos = span_end;
+                }
+                self.push(pos, end, FormatSpanKind::Text);
+                return end;
+            }
+            Err(Err::Error(e) | Err::Failure(e)) => e,
+            Err(Err::Incomplete(_)) => ColorParseError::InputEmpty,
+        };
+        if self.kind == FormatKind::Palette {
+            if let Some((tag, end)) = tag_at(self.text, start) {
+                let kind = if is_palette_tag(tag) {
+                    FormatSpanKind::Keyword
+                } else {
+                    FormatSpanKind::Error
+                };
+                self.push(start, end, kind);
+                return end;
+            }
+        } else if looks_like_field(rest) {
+            let offset = self.text.len() - error.remaining().len();
+            let end = match self.text[offset..].find(['}', '\n']) {
+                Some(i) if self.text[offset + i..].starts_with('}') => offset + i + 1,
+                Some(i) => offset + i,
+                None => self.text.len(),
+            };
+            self.push(start, end, FormatSpanKind::Error);
+            return end;
+        }
+        self.push(start, start + 1, FormatSpanKind::Text);
+        start + 1
+    }
+}
+
+fn is_palette_tag(tag: &str) -> bool {
+    let is_condition = |condition: &str| {
+        let name = condition.trim().trim_start_matches('!').trim_start();
+        matches!(name, "first" | "last" | "named")
+    };
+    matches!(tag, "else" | "end")
+        || PaletteField::parse(tag).is_some()
+        || tag.strip_prefix("if ").is_some_and(is_condition)
+}
+
+/// Whether a brace that doesn't start a field was probably meant to, rather than being text
+fn looks_like_field(text: &str) -> bool {
+    text[1..]
+        .trim_start_matches([' ', '\t'])
+        .starts_with(|c: char| c.is_ascii_alphanumeric() || matches!(c, '?' | '(' | '-' | '_'))
+}
+
+/// A color converted to the color spaces that a format refers to. Only RGB is converted up
+/// front, every other space is computed the first time a field reads it.
+struct ColorSpaces<'c> {
+    color: &'c Color,
+    options: &'c FormatOptions,
+    rgb: Rgb,
+    cmyk: OnceCell<Cmyk>,
+    hsl: OnceCell<Hsl>,
+    hsv: OnceCell<Hsv>,
+    hwb: OnceCell<Hwb>,
+    xyz: OnceCell<Xyz>,
+    xyy: OnceCell<xyY>,
+    lab: OnceCell<Lab>,
+    luv: OnceCell<Luv>,
+    lch_ab: OnceCell<LchAB>,
+    lch_uv: OnceCell<LchUV>,
+    hsluv: OnceCell<Hsluv>,
+    hpluv: OnceCell<Hpluv>,
+    cam16: OnceCell<Cam16>,
+    cam16_ucs: OnceCell<Cam16Ucs>,
+    ictcp: OnceCell<ICtCp>,
+    hdr: OnceCell<HdrColor>,
+}
+
+impl<'c> ColorSpaces<'c> {
+    fn new(color: &'c Color, options: &'c FormatOptions) -> Self {
+        Self {
+            color,
+            options,
+            rgb: color.rgb(),
+            cmyk: OnceCell::new(),
+            hsl: OnceCell::new(),
+            hsv: OnceCell::new(),
+            hwb: OnceCell::new(),
+            xyz: OnceCell::new(),
+            xyy: OnceCell::new(),
+            lab: OnceCell::new(),
+            luv: OnceCell::new(),
+            lch_ab: OnceCell::new(),
+            lch_uv: OnceCell::new(),
+            hsluv: OnceCell::new(),
+            hpluv: OnceCell::new(),
+            cam16: OnceCell::new(),
+            cam16_ucs: OnceCell::new(),
+            ictcp: OnceCell::new(),
+            hdr: OnceCell::new(),
+        }
+    }
+
+    fn cmyk(&self) -> Cmyk {
+        *self.cmyk.get_or_init(|| Cmyk::from(self.rgb))
+    }
+
+    fn hsl(&self) -> Hsl {
+        *self.hsl.get_or_init(|| Hsl::from(self.rgb))
+    }
+
+    fn hsv(&self) -> Hsv {
+        *self.hsv.get_or_init(|| Hsv::from(self.rgb))
+    }
+
+    fn hwb(&self) -> Hwb {
+        *self.hwb.get_or_init(|| Hwb::from(self.hsv()))
+    }
+
+    fn xyz(&self) -> Xyz {
+        *self
+            .xyz
+            .get_or_init(|| Xyz::from_rgb(self.rgb, self.options.ws))
+    }
+
+    fn xyy(&self) -> xyY {
+        *self.xyy.get_or_init(|| xyY::from(self.xyz()))
+    }
+
+    fn lab(&self) -> Lab {
+        *self
+            .lab
+            .get_or_init(|| Lab::from_xyz(self.xyz(), self.options.illuminant))
+    }
+
+    fn luv(&self) -> Luv {
+        *self.luv.get_or_init(|| Luv::from(self.xyz()))
+    }
+
+    fn lch_ab(&self) -> LchAB {
+        *self.lch_ab.get_or_init(|| LchAB::from(self.lab()))
+    }
+
+    fn lch_uv(&self) -> LchUV {
+        *self.lch_uv.get_or_init(|| LchUV::from(self.luv()))
+    }
+
+    fn hsluv(&self) -> Hsluv {
+        *self
+            .hsluv
+            .get_or_init(|| Hsluv::from_lch_uv(self.lch_uv(), self.options.ws))
+    }
+
+    fn hpluv(&self) -> Hpluv {
+        *self
+            .hpluv
+            .get_or_init(|| Hpluv::from_lch_uv(self.lch_uv(), self.options.ws))
+    }
+
+    fn cam16(&self) -> Cam16 {
+        *self.cam16.get_or_init(|| {
+            let FormatOptions {
+                ws,
+                illuminant,
+                method,
+                ..
+            } = *self.options;
+            let xyz = if illuminant != ws.reference_illuminant() {
+                self.xyz().chromatic_adaptation_transform(
+                    method,
+                    ws.reference_illuminant(),
+                    illuminant,
+                )
+            } else {
+                self.xyz()
+            };
+            Cam16::from_xyz(xyz, illuminant, &self.options.viewing_conditions)
+        })
+    }
+
+    fn cam16_ucs(&self) -> Cam16Ucs {
+        *self.cam16_ucs.get_or_init(|| Cam16Ucs::from(self.cam16()))
+    }
+
+    fn ictcp(&self) -> ICtCp {
+        *self.ictcp.get_or_init(|| {
+            let options = self.options;
+            self.color
+                .ictcp(options.ws, options.method, options.reference_white)
+        })
+    }
+
+    fn hdr(&self) -> HdrColor {
+        *self.hdr.get_or_init(|| {
+            let options = self.options;
+            self.color
+                .hdr(options.ws, options.method, options.reference_white)
+                .exposed(options.hdr_exposure)
+        })
+    }
+
+    fn write_tokens(&self, s: &mut String, tokens: &[FormatToken]) -> Result<()> {
+        for token in tokens {
+            match token {
+                FormatToken::Text(text) => s.push_str(text),
+                FormatToken::Color(field) => self.write_field(s, field)?,
+                FormatToken::Name => {
+                    if let Some((name, _)) = nearest_name(self.color) {
+                        s.push_str(&name);
+                    }
+                }
+                FormatToken::Conditional(conditional) => {
+                    if conditional.condition.holds(self) {
+                        self.write_tokens(s, &conditional.then)?;
+                    } else {
+                        self.write_tokens(s, &conditional.otherwise)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_field(&self, s: &mut String, field: &ColorField) -> Result<()> {
+        let num = field.expr.eval(self);
+        field.format.write(s, num, field.digit_format())
+    }
+
+    fn symbol_value(&self, symbol: ColorSymbol) -> Float {
+        use ColorSymbol::*;
+
+        match symbol {
+            Red255 => self.rgb.r_scaled() as u32 as Float,
+            Green255 => self.rgb.g_scaled() as u32 as Float,
+            Blue255 => self.rgb.b_scaled() as u32 as Float,
+            // colors are opaque
+            Alpha => 1.,
+
+            Red => self.rgb.r(),
+            Green => self.rgb.g(),
+            Blue => self.rgb.b(),
+
+            Cyan => self.cmyk().c(),
+            Magenta => self.cmyk().m(),
+            Yellow => self.cmyk().y(),
+            Key => self.cmyk().k(),
+
+            Cyan100 => self.cmyk().c_scaled(),
+            Magenta100 => self.cmyk().m_scaled(),
+            Yellow100 => self.cmyk().y_scaled(),
+            Key100 => self.cmyk().k_scaled(),
+
+            HSLHue => self.hsl().h(),
+            HSLSaturation => self.hsl().s(),
+            HSLLight => self.hsl().l(),
+
+            HSLHue360 => self.hsl().h_scaled(),
+            HSLSaturation100 => self.hsl().s_scaled(),
+            HSLLight100 => self.hsl().l_scaled(),
+
+            HSVHue => self.hsv().h(),
+            HSVSaturation => self.hsv().s(),
+            HSVValue => self.hsv().v(),
+
+            HWBHue => self.hwb().h(),
+            HWBWhiteness => self.hwb().w(),
+            HWBBlackness => self.hwb().b(),
+
+            HWBHue360 => self.hwb().h_scaled(),
+            HWBWhiteness100 => self.hwb().w_scaled(),
+            HWBBlackness100 => self.hwb().b_scaled(),
+
+            HSVHue360 => self.hsv().h_scaled(),
+            HSVSaturation100 => self.hsv().s_scaled(),
+            HSVValue100 => self.hsv().v_scaled(),
+
+            LabL => self.lab().l(),
+            LabA => self.lab().a(),
+            LabB => self.lab().b(),
+
+            LCHabL => self.lch_ab().l(),
+            LCHabC => self.lch_ab().c(),
+            LCHabH => self.lch_ab().h(),
+
+            LuvL => self.luv().l(),
+            LuvU => self.luv().u(),
+            LuvV => self.luv().v(),
+
+            LCHuvL => self.lch_uv().l(),
+            LCHuvC => self.lch_uv().c(),
+            LCHuvH => self.lch_uv().h(),
+
+            HsluvH => self.hsluv().h(),
+            HsluvS => self.hsluv().s(),
+            HsluvL => self.hsluv().l(),
+
+            HpluvH => self.hpluv().h(),
+            HpluvP => self.hpluv().p(),
+            HpluvL => self.hpluv().l(),
+
+            xyYx => self.xyy().x(),
+            xyYy => self.xyy().y(),
+            xyYY => self.xyy().yy(),
+
+            XYZx => self.xyz().x(),
+            XYZy => self.xyz().y(),
+            XYZz => self.xyz().z(),
+
+            Cam16J => self.cam16().j(),
+            Cam16C => self.cam16().c(),
+            Cam16H => self.cam16().h(),
+            Cam16M => self.cam16().m(),
+            Cam16S => self.cam16().s(),
+            Cam16Q => self.cam16().q(),
+
+            Cam16UcsJ => self.cam16_ucs().j(),
+            Cam16UcsA => self.cam16_ucs().a(),
+            Cam16UcsB => self.cam16_ucs().b(),
+
+            YCbCrCode {
+                standard,
+                component,
+                range,
+            } => {
+                let ycbcr = YCbCr::from_rgb(self.rgb, standard);
+                let code = match component {
+                    YCbCrComponent::Y => ycbcr.y_code(range),
+                    YCbCrComponent::Cb => ycbcr.cb_code(range),
+                    YCbCrComponent::Cr => ycbcr.cr_code(range),
+                };
+                code.round()
+            }
+
+            ICtCpI => self.ictcp().i(),
+            ICtCpCt => self.ictcp().ct(),
+            ICtCpCp => self.ictcp().cp(),
+
+            HdrNits => self.hdr().luminance(),
+            HdrSignal {
+                transfer,
+                channel,
+                code,
+            } => {
+                let signal = self.hdr().encode(transfer)[channel as usize];
+                match code {
+                    Some((bit_depth, range)) => code_value(signal, bit_depth, range),
+                    None => signal,
+                }
+            }
+
+            Name => 0.,
+        }
+    }
+}
+
+impl From<Vec<FormatToken>> for CustomColorFormat {
+    fn from(vec: Vec<FormatToken>) -> Self {
         Self(vec)
     }
 }
@@ -197,36 +1594,334 @@ impl<'a> From<Vec<FormatToken<'a>>> for CustomColorFormat<'a> {
 #[derive(Debug, PartialEq)]
 enum ColorParseError<I> {
     InputEmpty,
-    InvalidPrecision,
     Nom(I, ErrorKind),
+    /// What was expected at the input
+    Expected(I, String),
+    /// The reason why the input is invalid
+    Invalid(I, String),
 }
 
-impl<I> ParseError<I> for ColorParseError<I> {
-    fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+impl<'a> ColorParseError<&'a str> {
+    /// The input left at the error
+    fn remaining(&self) -> &'a str {
+        match self {
+            Self::InputEmpty => "",
+            Self::Nom(i, _) | Self::Expected(i, _) | Self::Invalid(i, _) => i,
+        }
+    }
+
+    fn message(&self) -> String {
+        let found = self.remaining().chars().next();
+        match (self, found) {
+            (Self::Invalid(_, message), _) => message.clone(),
+            (Self::Expected(_, expected), Some(c)) => {
+                format!("expected {} but found `{}`", expected, c)
+            }
+            (Self::Expected(_, expected), None) => {
+                format!("expected {} but the format ends", expected)
+            }
+            (_, Some(c)) => format!("unexpected `{}`", c),
+            (_, None) => "unexpected end of the format".into(),
+        }
+    }
+}
+
+impl<'a> ParseError<&'a str> for ColorParseError<&'a str> {
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
         ColorParseError::Nom(input, kind)
     }
 
-    fn append(_: I, _: ErrorKind, other: Self) -> Self {
+    fn append(_: &'a str, _: ErrorKind, other: Self) -> Self {
         other
     }
+
+    /// Keeps the error of the alternative that got the furthest, errors with a reason take
+    /// precedence over the errors of nom
+    fn or(self, other: Self) -> Self {
+        let is_nom = |e: &Self| matches!(e, Self::InputEmpty | Self::Nom(..));
+        match (is_nom(&self), is_nom(&other)) {
+            (true, false) => other,
+            (false, true) => self,
+            _ if other.remaining().len() < self.remaining().len() => other,
+            _ => self,
+        }
+    }
 }
 
-impl FromExternalError<&str, ParseIntError> for ColorParseError<&str> {
-    fn from_external_error(_: &str, _: ErrorKind, _: ParseIntError) -> Self {
-        Self::InvalidPrecision
+impl<'a> FromExternalError<&'a str, ParseIntError> for ColorParseError<&'a str> {
+    fn from_external_error(input: &'a str, kind: ErrorKind, _: ParseIntError) -> Self {
+        Self::Nom(input, kind)
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum FormatToken<'a> {
+/// Spans highlighted while parsing, with the length of the input left before and after each
+type RecordedSpans = Vec<(usize, usize, FormatSpanKind)>;
+
+thread_local! {
+    /// Only recorded by [`highlight_format`], parsing doesn't keep any spans
+    static SPANS: RefCell<Option<RecordedSpans>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` recording the spans of what it parses
+fn record_spans<T>(f: impl FnOnce() -> T) -> (T, RecordedSpans) {
+    SPANS.with(|spans| *spans.borrow_mut() = Some(vec![]));
+    let result = f();
+    let spans = SPANS.with(|spans| spans.borrow_mut().take());
+    (result, spans.unwrap_or_default())
+}
+
+fn record_span(before: &str, after: &str, kind: FormatSpanKind) {
+    SPANS.with(|spans| {
+        if let Some(spans) = spans.borrow_mut().as_mut() {
+            spans.push((before.len(), after.len(), kind));
+        }
+    });
+}
+
+/// Highlights what `parser` parses as `kind`
+fn spanned<'a, O>(
+    kind: FormatSpanKind,
+    mut parser: impl Parser<&'a str, O, ColorParseError<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O, ColorParseError<&'a str>> {
+    move |i| {
+        let (rest, output) = parser.parse(i)?;
+        record_span(i, rest, kind);
+        Ok((rest, output))
+    }
+}
+
+/// Drops the spans recorded by `parser` when it fails
+fn checkpoint<'a, O>(
+    mut parser: impl Parser<&'a str, O, ColorParseError<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O, ColorParseError<&'a str>> {
+    move |i| {
+        let len = SPANS.with(|spans| spans.borrow().as_ref().map(Vec::len));
+        let result = parser.parse(i);
+        if let (Err(_), Some(len)) = (&result, len) {
+            SPANS.with(|spans| spans.borrow_mut().as_mut().map(|spans| spans.truncate(len)));
+        }
+        result
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum FormatToken {
     Color(ColorField),
-    Text(&'a str),
+    /// Nearest color name
+    Name,
+    Text(String),
+    Conditional(Box<Conditional>),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// A number computed from the color and the way it is written
+#[derive(Clone, Debug, PartialEq)]
 pub struct ColorField {
-    symbol: ColorSymbol,
-    digit_format: Option<DigitFormat>,
+    expr: Expr,
+    format: NumberFormat,
+}
+
+impl ColorField {
+    fn digit_format(&self) -> DigitFormat {
+        match self.format.digits {
+            Some(digit_format) => digit_format,
+            None if self.expr.is_integer() => DigitFormat::Decimal,
+            None => DigitFormat::Float { precision: 1 },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Symbol(ColorSymbol),
+    Number(Float),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, spaces: &ColorSpaces<'_>) -> Float {
+        match self {
+            Expr::Symbol(symbol) => spaces.symbol_value(*symbol),
+            Expr::Number(num) => *num,
+            Expr::Negate(expr) => -expr.eval(spaces),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(spaces), rhs.eval(spaces));
+                match op {
+                    BinaryOp::Add => lhs + rhs,
+                    BinaryOp::Sub => lhs - rhs,
+                    BinaryOp::Mul => lhs * rhs,
+                    BinaryOp::Div => lhs / rhs,
+                }
+            }
+            Expr::Call(function, args) => {
+                let args: Vec<_> = args.iter().map(|arg| arg.eval(spaces)).collect();
+                match function {
+                    Function::Clamp => args[0].max(args[1]).min(args[2]),
+                    Function::Min => args[0].min(args[1]),
+                    Function::Max => args[0].max(args[1]),
+                    Function::Round => args[0].round(),
+                    Function::Abs => args[0].abs(),
+                }
+            }
+        }
+    }
+
+    /// Whether the expression is a symbol with whole number values that are written without
+    /// decimals by default
+    fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            Expr::Symbol(ColorSymbol::Red255 | ColorSymbol::Green255 | ColorSymbol::Blue255)
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Function {
+    Clamp,
+    Min,
+    Max,
+    Round,
+    Abs,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "clamp" => Function::Clamp,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "round" => Function::Round,
+            "abs" => Function::Abs,
+            _ => return None,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Function::Clamp => "clamp",
+            Function::Min => "min",
+            Function::Max => "max",
+            Function::Round => "round",
+            Function::Abs => "abs",
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Function::Clamp => 3,
+            Function::Min | Function::Max => 2,
+            Function::Round | Function::Abs => 1,
+        }
+    }
+}
+
+/// Text written only when the condition holds, or the alternative text otherwise
+#[derive(Clone, Debug, PartialEq)]
+struct Conditional {
+    condition: Condition,
+    then: Vec<FormatToken>,
+    otherwise: Vec<FormatToken>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Condition {
+    lhs: Expr,
+    comparison: Comparison,
+    rhs: Expr,
+}
+
+impl Condition {
+    fn holds(&self, spaces: &ColorSpaces<'_>) -> bool {
+        let (lhs, rhs) = (self.lhs.eval(spaces), self.rhs.eval(spaces));
+        match self.comparison {
+            Comparison::Less => lhs < rhs,
+            Comparison::LessEqual => lhs <= rhs,
+            Comparison::Greater => lhs > rhs,
+            Comparison::GreaterEqual => lhs >= rhs,
+            Comparison::Equal => lhs == rhs,
+            Comparison::NotEqual => lhs != rhs,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Sign {
+    /// Only negative numbers have a sign
+    #[default]
+    Negative,
+    /// Positive numbers have a `+` sign
+    Always,
+    /// Positive numbers start with a space to line up with negative ones
+    Space,
+}
+
+/// Sign, width and digits of a written number, like `+08.3` or `03d`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct NumberFormat {
+    sign: Sign,
+    /// Pad to the width with zeros after the sign rather than with spaces before it
+    zero_pad: bool,
+    width: usize,
+    digits: Option<DigitFormat>,
+}
+
+impl NumberFormat {
+    fn write(&self, s: &mut String, num: Float, digits: DigitFormat) -> Result<()> {
+        let magnitude = num.abs();
+        let text = match digits {
+            DigitFormat::Decimal => format!("{}", magnitude as u64),
+            DigitFormat::Hex => format!("{:x}", magnitude as u64),
+            DigitFormat::UppercaseHex => format!("{:X}", magnitude as u64),
+            DigitFormat::Octal => format!("{:o}", magnitude as u64),
+            DigitFormat::Float { precision } => format!("{:.*}", precision as usize, magnitude),
+            DigitFormat::Percent { precision } => {
+                format!("{:.*}%", precision as usize, magnitude * 100.)
+            }
+        };
+        // integers truncated to zero are written without a sign
+        let is_integer = !matches!(
+            digits,
+            DigitFormat::Float { .. } | DigitFormat::Percent { .. }
+        );
+        let negative = num < 0. && !(is_integer && magnitude < 1.);
+        let sign = match (negative, self.sign) {
+            (true, _) => "-",
+            (false, Sign::Always) => "+",
+            (false, Sign::Space) => " ",
+            (false, Sign::Negative) => "",
+        };
+
+        let padding = self.width.saturating_sub(sign.len() + text.chars().count());
+        if self.zero_pad {
+            s.push_str(sign);
+            s.extend(std::iter::repeat_n('0', padding));
+        } else {
+            s.extend(std::iter::repeat_n(' ', padding));
+            s.push_str(sign);
+        }
+        s.push_str(&text);
+        Ok(())
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -235,13 +1930,13 @@ enum DigitFormat {
     UppercaseHex,
     Octal,
     Decimal,
-    Float { precision: u8 },
-}
-
-impl Default for DigitFormat {
-    fn default() -> Self {
-        Self::Decimal
-    }
+    Float {
+        precision: u8,
+    },
+    /// The number multiplied by 100 followed by `%`
+    Percent {
+        precision: u8,
+    },
 }
 
 #[rustfmt::skip]
@@ -256,6 +1951,8 @@ enum ColorSymbol {
     Green255,
     Blue255,
 
+    Alpha,
+
     Cyan,
     Magenta,
     Yellow,
@@ -282,6 +1979,14 @@ enum ColorSymbol {
     HSVSaturation100,
     HSVValue100,
 
+    HWBHue,
+    HWBWhiteness,
+    HWBBlackness,
+
+    HWBHue360,
+    HWBWhiteness100,
+    HWBBlackness100,
+
     LabL,
     LabA,
     LabB,
@@ -297,6 +2002,14 @@ enum ColorSymbol {
     LCHuvL,
     LCHuvC,
     LCHuvH,
+
+    HsluvH,
+    HsluvS,
+    HsluvL,
+
+    HpluvH,
+    HpluvP,
+    HpluvL,
     
     xyYx,
     xyYy,
@@ -305,6 +2018,149 @@ enum ColorSymbol {
     XYZx,
     XYZy,
     XYZz,
+
+    Cam16J,
+    Cam16C,
+    Cam16H,
+    Cam16M,
+    Cam16S,
+    Cam16Q,
+
+    Cam16UcsJ,
+    Cam16UcsA,
+    Cam16UcsB,
+
+    /// 8 bit code value of a YCbCr component
+    YCbCrCode {
+        standard: YCbCrStandard,
+        component: YCbCrComponent,
+        range: YCbCrRange,
+    },
+
+    ICtCpI,
+    ICtCpCt,
+    ICtCpCp,
+
+    /// Luminance in cd/m² of the color shown at the reference white
+    HdrNits,
+    /// HDR signal value of a channel, quantized to a code value with the given bit depth and
+    /// range if present
+    HdrSignal {
+        transfer: TransferFunction,
+        channel: HdrChannel,
+        code: Option<(u8, YCbCrRange)>,
+    },
+
+    /// Nearest color name
+    Name,
+}
+
+impl ColorSymbol {
+    /// Returns the color space component the symbol is read back as with the unit its value
+    /// is scaled by, `None` for redundant values or the reason why it can't be read back
+    fn component(&self) -> std::result::Result<Option<(MatchSpace, usize, Float)>, String> {
+        use ColorSymbol::*;
+        use MatchSpace as S;
+
+        Ok(Some(match *self {
+            Red => (S::Rgb, 0, 1.),
+            Green => (S::Rgb, 1, 1.),
+            Blue => (S::Rgb, 2, 1.),
+            Red255 => (S::Rgb, 0, 255.),
+            Green255 => (S::Rgb, 1, 255.),
+            Blue255 => (S::Rgb, 2, 255.),
+
+            Cyan => (S::Cmyk, 0, 1.),
+            Magenta => (S::Cmyk, 1, 1.),
+            Yellow => (S::Cmyk, 2, 1.),
+            Key => (S::Cmyk, 3, 1.),
+            Cyan100 => (S::Cmyk, 0, 100.),
+            Magenta100 => (S::Cmyk, 1, 100.),
+            Yellow100 => (S::Cmyk, 2, 100.),
+            Key100 => (S::Cmyk, 3, 100.),
+
+            HSLHue => (S::Hsl, 0, 1.),
+            HSLSaturation => (S::Hsl, 1, 1.),
+            HSLLight => (S::Hsl, 2, 1.),
+            HSLHue360 => (S::Hsl, 0, 360.),
+            HSLSaturation100 => (S::Hsl, 1, 100.),
+            HSLLight100 => (S::Hsl, 2, 100.),
+
+            HSVHue => (S::Hsv, 0, 1.),
+            HSVSaturation => (S::Hsv, 1, 1.),
+            HSVValue => (S::Hsv, 2, 1.),
+            HSVHue360 => (S::Hsv, 0, 360.),
+            HSVSaturation100 => (S::Hsv, 1, 100.),
+            HSVValue100 => (S::Hsv, 2, 100.),
+
+            HWBHue => (S::Hwb, 0, 1.),
+            HWBWhiteness => (S::Hwb, 1, 1.),
+            HWBBlackness => (S::Hwb, 2, 1.),
+            HWBHue360 => (S::Hwb, 0, 360.),
+            HWBWhiteness100 => (S::Hwb, 1, 100.),
+            HWBBlackness100 => (S::Hwb, 2, 100.),
+
+            LabL => (S::Lab, 0, 1.),
+            LabA => (S::Lab, 1, 1.),
+            LabB => (S::Lab, 2, 1.),
+            LCHabL => (S::LchAB, 0, 1.),
+            LCHabC => (S::LchAB, 1, 1.),
+            LCHabH => (S::LchAB, 2, 1.),
+            LuvL => (S::Luv, 0, 1.),
+            LuvU => (S::Luv, 1, 1.),
+            LuvV => (S::Luv, 2, 1.),
+            LCHuvL => (S::LchUV, 0, 1.),
+            LCHuvC => (S::LchUV, 1, 1.),
+            LCHuvH => (S::LchUV, 2, 1.),
+            HsluvH => (S::Hsluv, 0, 1.),
+            HsluvS => (S::Hsluv, 1, 1.),
+            HsluvL => (S::Hsluv, 2, 1.),
+            HpluvH => (S::Hpluv, 0, 1.),
+            HpluvP => (S::Hpluv, 1, 1.),
+            HpluvL => (S::Hpluv, 2, 1.),
+            xyYx => (S::XyY, 0, 1.),
+            xyYy => (S::XyY, 1, 1.),
+            xyYY => (S::XyY, 2, 1.),
+            XYZx => (S::Xyz, 0, 1.),
+            XYZy => (S::Xyz, 1, 1.),
+            XYZz => (S::Xyz, 2, 1.),
+
+            Cam16J => (S::Cam16, 0, 1.),
+            Cam16C => (S::Cam16, 1, 1.),
+            Cam16H => (S::Cam16, 2, 1.),
+            Cam16UcsJ => (S::Cam16Ucs, 0, 1.),
+            Cam16UcsA => (S::Cam16Ucs, 1, 1.),
+            Cam16UcsB => (S::Cam16Ucs, 2, 1.),
+
+            YCbCrCode {
+                standard,
+                component,
+                range,
+            } => (S::YCbCr(standard, range), component as usize, 1.),
+
+            ICtCpI => (S::ICtCp, 0, 1.),
+            ICtCpCt => (S::ICtCp, 1, 1.),
+            ICtCpCp => (S::ICtCp, 2, 1.),
+
+            // colors are opaque and the other CAM16 correlates follow from J, C and h
+            Alpha | Cam16M | Cam16S | Cam16Q | Name => return Ok(None),
+            HdrNits | HdrSignal { .. } => return Err("HDR values can't be read back".to_string()),
+        }))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum YCbCrComponent {
+    Y,
+    Cb,
+    Cr,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum HdrChannel {
+    R = 0,
+    G = 1,
+    B = 2,
 }
 
 fn parse_rgb_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>> {
@@ -315,6 +2171,7 @@ fn parse_rgb_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>
         char('r').map(|_| ColorSymbol::Red),
         char('g').map(|_| ColorSymbol::Green),
         char('b').map(|_| ColorSymbol::Blue),
+        char('a').map(|_| ColorSymbol::Alpha),
     ))(i)
 }
 
@@ -353,6 +2210,17 @@ fn parse_hsv_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>
     ))(i)
 }
 
+fn parse_hwb_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>> {
+    alt((
+        tag("hwb_h360").map(|_| ColorSymbol::HWBHue360),
+        tag("hwb_w100").map(|_| ColorSymbol::HWBWhiteness100),
+        tag("hwb_b100").map(|_| ColorSymbol::HWBBlackness100),
+        tag("hwb_h").map(|_| ColorSymbol::HWBHue),
+        tag("hwb_w").map(|_| ColorSymbol::HWBWhiteness),
+        tag("hwb_b").map(|_| ColorSymbol::HWBBlackness),
+    ))(i)
+}
+
 fn parse_lab_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>> {
     alt((
         tag("lab_l").map(|_| ColorSymbol::LabL),
@@ -385,6 +2253,17 @@ fn parse_lch_uv_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&s
     ))(i)
 }
 
+fn parse_hsluv_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>> {
+    alt((
+        tag("hsluv_h").map(|_| ColorSymbol::HsluvH),
+        tag("hsluv_s").map(|_| ColorSymbol::HsluvS),
+        tag("hsluv_l").map(|_| ColorSymbol::HsluvL),
+        tag("hpluv_h").map(|_| ColorSymbol::HpluvH),
+        tag("hpluv_p").map(|_| ColorSymbol::HpluvP),
+        tag("hpluv_l").map(|_| ColorSymbol::HpluvL),
+    ))(i)
+}
+
 fn parse_xyy_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>> {
     alt((
         tag("xyy_x").map(|_| ColorSymbol::xyYx),
@@ -401,74 +2280,446 @@ fn parse_xyz_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>
     ))(i)
 }
 
+fn parse_cam16_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>> {
+    alt((
+        tag("cam16ucs_j").map(|_| ColorSymbol::Cam16UcsJ),
+        tag("cam16ucs_a").map(|_| ColorSymbol::Cam16UcsA),
+        tag("cam16ucs_b").map(|_| ColorSymbol::Cam16UcsB),
+        tag("cam16_j").map(|_| ColorSymbol::Cam16J),
+        tag("cam16_c").map(|_| ColorSymbol::Cam16C),
+        tag("cam16_h").map(|_| ColorSymbol::Cam16H),
+        tag("cam16_m").map(|_| ColorSymbol::Cam16M),
+        tag("cam16_s").map(|_| ColorSymbol::Cam16S),
+        tag("cam16_q").map(|_| ColorSymbol::Cam16Q),
+    ))(i)
+}
+
+/// Parses symbols like `ycbcr709_cb` with an optional `_full` or `_limited` range suffix,
+/// full range is used when the suffix is missing.
+fn parse_ycbcr_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>> {
+    map(
+        tuple((
+            preceded(
+                tag("ycbcr"),
+                alt((
+                    tag("601").map(|_| YCbCrStandard::Bt601),
+                    tag("709").map(|_| YCbCrStandard::Bt709),
+                    tag("2020").map(|_| YCbCrStandard::Bt2020),
+                )),
+            ),
+            preceded(
+                char('_'),
+                alt((
+                    tag("cb").map(|_| YCbCrComponent::Cb),
+                    tag("cr").map(|_| YCbCrComponent::Cr),
+                    char('y').map(|_| YCbCrComponent::Y),
+                )),
+            ),
+            opt(preceded(
+                char('_'),
+                alt((
+                    tag("full").map(|_| YCbCrRange::Full),
+                    tag("limited").map(|_| YCbCrRange::Limited),
+                )),
+            )),
+        )),
+        |(standard, component, range)| ColorSymbol::YCbCrCode {
+            standard,
+            component,
+            range: range.unwrap_or_default(),
+        },
+    )(i)
+}
+
+fn parse_ictcp_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>> {
+    alt((
+        tag("ictcp_i").map(|_| ColorSymbol::ICtCpI),
+        tag("ictcp_ct").map(|_| ColorSymbol::ICtCpCt),
+        tag("ictcp_cp").map(|_| ColorSymbol::ICtCpCp),
+    ))(i)
+}
+
+/// Parses symbols like `pq_r` for signal values or `hlg10_g_limited` for code values with a bit
+/// depth of 8, 10, 12 or 16 and an optional `_full` or `_limited` range suffix.
+fn parse_hdr_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>> {
+    alt((
+        tag("hdr_nits").map(|_| ColorSymbol::HdrNits),
+        map(
+            tuple((
+                alt((
+                    tag("pq").map(|_| TransferFunction::Pq),
+                    tag("hlg").map(|_| TransferFunction::Hlg),
+                )),
+                opt(alt((
+                    tag("8").map(|_| 8),
+                    tag("10").map(|_| 10),
+                    tag("12").map(|_| 12),
+                    tag("16").map(|_| 16),
+                ))),
+                preceded(
+                    char('_'),
+                    alt((
+                        char('r').map(|_| HdrChannel::R),
+                        char('g').map(|_| HdrChannel::G),
+                        char('b').map(|_| HdrChannel::B),
+                    )),
+                ),
+                opt(preceded(
+                    char('_'),
+                    alt((
+                        tag("full").map(|_| YCbCrRange::Full),
+                        tag("limited").map(|_| YCbCrRange::Limited),
+                    )),
+                )),
+            )),
+            |(transfer, bit_depth, channel, range)| ColorSymbol::HdrSignal {
+                transfer,
+                channel,
+                code: bit_depth.map(|bits| (bits, range.unwrap_or_default())),
+            },
+        ),
+    ))(i)
+}
+
 fn parse_color_symbol(i: &str) -> IResult<&str, ColorSymbol, ColorParseError<&str>> {
     alt((
+        // must go before cmyk symbols as `c` and `y` would match the start of `cam16` and `ycbcr`
+        parse_cam16_symbol,
+        parse_ycbcr_symbol,
         parse_rgb_symbol,
         parse_cmyk_symbol,
         parse_hsl_symbol,
         parse_hsv_symbol,
+        parse_hwb_symbol,
         parse_lab_symbol,
         parse_lch_ab_symbol,
         parse_luv_symbol,
         parse_lch_uv_symbol,
+        parse_hsluv_symbol,
         parse_xyy_symbol,
         parse_xyz_symbol,
+        parse_ictcp_symbol,
+        parse_hdr_symbol,
+        tag("name").map(|_| ColorSymbol::Name),
     ))(i)
 }
 
-fn parse_decimal_format(i: &str) -> IResult<&str, DigitFormat, ColorParseError<&str>> {
-    map(char('d'), |_| DigitFormat::Decimal)(i)
+fn parse_number(i: &str) -> IResult<&str, Float, ColorParseError<&str>> {
+    map(
+        recognize(pair(digit1, opt(pair(char('.'), digit0)))),
+        |s: &str| s.parse().unwrap_or_default(),
+    )(i)
 }
 
-fn parse_hex_format(i: &str) -> IResult<&str, DigitFormat, ColorParseError<&str>> {
-    map(char('x'), |_| DigitFormat::Hex)(i)
+fn parse_identifier(i: &str) -> IResult<&str, &str, ColorParseError<&str>> {
+    take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_')(i)
 }
 
-fn parse_hex_uppercase_format(i: &str) -> IResult<&str, DigitFormat, ColorParseError<&str>> {
-    map(char('X'), |_| DigitFormat::UppercaseHex)(i)
+/// Parses `c` highlighted as `kind`, failing with `c` as what was expected
+fn expect<'a>(
+    c: char,
+    kind: FormatSpanKind,
+) -> impl FnMut(&'a str) -> IResult<&'a str, char, ColorParseError<&'a str>> {
+    move |i| {
+        spanned(kind, char(c))(i)
+            .map_err(|_| Err::Error(ColorParseError::Expected(i, format!("`{}`", c))))
+    }
 }
 
-fn parse_octal_format(i: &str) -> IResult<&str, DigitFormat, ColorParseError<&str>> {
-    map(char('o'), |_| DigitFormat::Octal)(i)
+/// Parses a call of `function` whose name starts the input
+fn parse_call(i: &str, function: Function) -> IResult<&str, Expr, ColorParseError<&str>> {
+    let (rest, _) = spanned(FormatSpanKind::Function, parse_identifier)(i)?;
+    let (rest, _) = space0(rest)?;
+    let (mut rest, _) = spanned(FormatSpanKind::Operator, char('('))(rest).map_err(|_| {
+        Err::Failure(ColorParseError::Expected(
+            rest,
+            format!("`(` after `{}`", function.name()),
+        ))
+    })?;
+    let mut args = vec![];
+    loop {
+        let (after, arg) = cut(parse_expr)(rest)?;
+        args.push(arg);
+        let (after, _) = space0(after)?;
+        let (after, separator) = spanned(FormatSpanKind::Operator, one_of(",)"))(after)
+            .map_err(|_| Err::Failure(ColorParseError::Expected(after, "`,` or `)`".into())))?;
+        rest = after;
+        if separator == ')' {
+            break;
+        }
+    }
+    if args.len() != function.arity() {
+        return Err(Err::Failure(ColorParseError::Invalid(
+            i,
+            format!(
+                "`{}` takes {} arguments but {} were given",
+                function.name(),
+                function.arity(),
+                args.len()
+            ),
+        )));
+    }
+    Ok((rest, Expr::Call(function, args)))
 }
 
-fn parse_float_format(i: &str) -> IResult<&str, DigitFormat, ColorParseError<&str>> {
-    map(
-        preceded(char('.'), map_res(digit1, |s: &str| s.parse::<u8>())),
-        |precision| DigitFormat::Float { precision },
-    )(i)
+fn parse_factor(i: &str) -> IResult<&str, Expr, ColorParseError<&str>> {
+    let (i, _) = space0(i)?;
+    match i.chars().next() {
+        Some('-') => map(
+            preceded(
+                spanned(FormatSpanKind::Operator, char('-')),
+                cut(parse_factor),
+            ),
+            |expr| Expr::Negate(Box::new(expr)),
+        )(i),
+        Some('(') => delimited(
+            spanned(FormatSpanKind::Operator, char('(')),
+            cut(parse_expr),
+            cut(preceded(space0, expect(')', FormatSpanKind::Operator))),
+        )(i),
+        Some(c) if c.is_ascii_digit() => {
+            map(spanned(FormatSpanKind::Number, parse_number), Expr::Number)(i)
+        }
+        Some(c) if c.is_ascii_alphanumeric() || c == '_' => {
+            let (rest, identifier) = parse_identifier(i)?;
+            if let Some(function) = Function::parse(identifier) {
+                return parse_call(i, function);
+            }
+            let message = match all_consuming(parse_color_symbol)(identifier) {
+                Ok((_, ColorSymbol::Name)) => {
+                    "the color name can't be used in an expression".to_string()
+                }
+                Ok((_, symbol)) => {
+                    record_span(i, rest, FormatSpanKind::Symbol);
+                    return Ok((rest, Expr::Symbol(symbol)));
+                }
+                Err(_) => format!("unknown field `{}`", identifier),
+            };
+            Err(Err::Error(ColorParseError::Invalid(i, message)))
+        }
+        _ => Err(Err::Error(ColorParseError::Expected(
+            i,
+            "a field, a number or `(`".into(),
+        ))),
+    }
 }
 
-fn parse_digit_format(i: &str) -> IResult<&str, DigitFormat, ColorParseError<&str>> {
-    preceded(
+fn parse_binary<'a>(
+    operand: fn(&'a str) -> IResult<&'a str, Expr, ColorParseError<&'a str>>,
+    operators: &'static str,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Expr, ColorParseError<&'a str>> {
+    move |i| {
+        let (i, first) = operand(i)?;
+        fold_many0(
+            pair(
+                preceded(space0, spanned(FormatSpanKind::Operator, one_of(operators))),
+                // an operator must be followed by an operand
+                cut(operand),
+            ),
+            move || first.clone(),
+            |lhs, (op, rhs)| {
+                let op = match op {
+                    '+' => BinaryOp::Add,
+                    '-' => BinaryOp::Sub,
+                    '*' => BinaryOp::Mul,
+                    _ => BinaryOp::Div,
+                };
+                Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+            },
+        )(i)
+    }
+}
+
+fn parse_term(i: &str) -> IResult<&str, Expr, ColorParseError<&str>> {
+    parse_binary(parse_factor, "*/")(i)
+}
+
+fn parse_expr(i: &str) -> IResult<&str, Expr, ColorParseError<&str>> {
+    parse_binary(parse_term, "+-")(i)
+}
+
+fn parse_sign(i: &str) -> IResult<&str, Sign, ColorParseError<&str>> {
+    alt((
+        map(char('+'), |_| Sign::Always),
+        map(char(' '), |_| Sign::Space),
+        map(char('-'), |_| Sign::Negative),
+    ))(i)
+}
+
+fn parse_number_format(i: &str) -> IResult<&str, NumberFormat, ColorParseError<&str>> {
+    let (rest, (sign, zero_pad, width, precision, digits)) = preceded(
         char(':'),
-        alt((
-            parse_hex_format,
-            parse_hex_uppercase_format,
-            parse_octal_format,
-            parse_decimal_format,
-            parse_float_format,
+        tuple((
+            opt(parse_sign),
+            opt(char('0')),
+            opt(map_res(digit1, |s: &str| s.parse::<usize>())),
+            opt(preceded(
+                char('.'),
+                map_res(digit1, |s: &str| s.parse::<u8>()),
+            )),
+            opt(one_of("dxXo%")),
         )),
+    )(i)?;
+    if rest.len() + 1 == i.len() {
+        // nothing follows the colon
+        return Err(Err::Error(ColorParseError::Nom(rest, ErrorKind::Verify)));
+    }
+    let digits = match (digits, precision) {
+        (Some('d'), _) => Some(DigitFormat::Decimal),
+        (Some('x'), _) => Some(DigitFormat::Hex),
+        (Some('X'), _) => Some(DigitFormat::UppercaseHex),
+        (Some('o'), _) => Some(DigitFormat::Octal),
+        (Some(_), precision) => Some(DigitFormat::Percent {
+            precision: precision.unwrap_or_default(),
+        }),
+        (None, Some(precision)) => Some(DigitFormat::Float { precision }),
+        (None, None) => None,
+    };
+    Ok((
+        rest,
+        NumberFormat {
+            sign: sign.unwrap_or_default(),
+            zero_pad: zero_pad.is_some(),
+            width: width.unwrap_or_default(),
+            digits,
+        },
+    ))
+}
+
+/// Parses the optional digit format after the expression of a field, which has to be followed
+/// by the closing brace of the field
+fn parse_field_format(i: &str) -> IResult<&str, NumberFormat, ColorParseError<&str>> {
+    let (i, _) = space0(i)?;
+    if !i.starts_with(':') {
+        return Ok((i, NumberFormat::default()));
+    }
+    spanned(
+        FormatSpanKind::DigitFormat,
+        terminated(parse_number_format, peek(preceded(space0, char('}')))),
     )(i)
+    .map_err(|_| {
+        let format = &i[1..];
+        let end = format.find('}').unwrap_or(format.len());
+        Err::Failure(ColorParseError::Invalid(
+            format,
+            format!(
+                "invalid digit format `{}`, expected a sign, a width, a precision like `.2` and \
+                 one of `d`, `x`, `X`, `o` or `%`",
+                &format[..end]
+            ),
+        ))
+    })
 }
 
 fn parse_color_field(i: &str) -> IResult<&str, ColorField, ColorParseError<&str>> {
-    delimited(
-        char('{'),
-        preceded(
-            space0,
-            map(
-                tuple((parse_color_symbol, opt(parse_digit_format))),
-                |(symbol, digit_format)| ColorField {
-                    symbol,
-                    digit_format,
-                },
-            ),
+    let (rest, _) = spanned(FormatSpanKind::Delimiter, char('{'))(i)?;
+    let (rest, expr) = parse_expr(rest)?;
+    let (rest, format) = parse_field_format(rest)?;
+    let (rest, _) = cut(preceded(space0, expect('}', FormatSpanKind::Delimiter)))(rest)?;
+    Ok((rest, ColorField { expr, format }))
+}
+
+fn parse_name_field(i: &str) -> IResult<&str, FormatToken, ColorParseError<&str>> {
+    map(
+        delimited(
+            pair(spanned(FormatSpanKind::Delimiter, char('{')), space0),
+            spanned(FormatSpanKind::Symbol, tag("name")),
+            pair(space0, spanned(FormatSpanKind::Delimiter, char('}'))),
         ),
-        preceded(space0, char('}')),
+        |_| FormatToken::Name,
     )(i)
 }
 
+fn parse_comparison(i: &str) -> IResult<&str, Comparison, ColorParseError<&str>> {
+    spanned(
+        FormatSpanKind::Operator,
+        alt((
+            map(tag("<="), |_| Comparison::LessEqual),
+            map(tag(">="), |_| Comparison::GreaterEqual),
+            map(tag("=="), |_| Comparison::Equal),
+            map(tag("!="), |_| Comparison::NotEqual),
+            map(char('<'), |_| Comparison::Less),
+            map(char('>'), |_| Comparison::Greater),
+        )),
+    )(i)
+    .map_err(|_| {
+        Err::Error(ColorParseError::Expected(
+            i,
+            "a comparison like `<`, `>=` or `==`".into(),
+        ))
+    })
+}
+
+/// Parses a conditional like `{?a<1:, {a:.2}}` or `{?lab_l>50:dark|light}`
+fn parse_conditional(i: &str) -> IResult<&str, Conditional, ColorParseError<&str>> {
+    let (rest, _) = spanned(
+        FormatSpanKind::Delimiter,
+        tuple((char('{'), space0, char('?'))),
+    )(i)?;
+    let (rest, (lhs, comparison, rhs, _, then, otherwise)) = cut(tuple((
+        parse_expr,
+        preceded(space0, parse_comparison),
+        parse_expr,
+        preceded(space0, expect(':', FormatSpanKind::Delimiter)),
+        many0(parse_conditional_token),
+        opt(preceded(
+            spanned(FormatSpanKind::Delimiter, char('|')),
+            many0(parse_conditional_token),
+        )),
+    )))(rest)?;
+    let (rest, _) = spanned(FormatSpanKind::Delimiter, char('}'))(rest).map_err(|_| {
+        Err::Failure(match rest.chars().next() {
+            Some('|') => {
+                ColorParseError::Invalid(rest, "`|` can only be used once in a conditional".into())
+            }
+            _ => ColorParseError::Invalid(i, "the conditional is never closed with `}`".into()),
+        })
+    })?;
+    let conditional = Conditional {
+        condition: Condition {
+            lhs,
+            comparison,
+            rhs,
+        },
+        then,
+        otherwise: otherwise.unwrap_or_default(),
+    };
+    Ok((rest, conditional))
+}
+
+/// Parses a field or a conditional. Fields that don't parse always fail with an error rather
+/// than a failure, so that their brace is parsed as text instead.
+fn parse_field(i: &str) -> IResult<&str, FormatToken, ColorParseError<&str>> {
+    alt((
+        checkpoint(map(parse_conditional, |conditional| {
+            FormatToken::Conditional(Box::new(conditional))
+        })),
+        checkpoint(parse_name_field),
+        checkpoint(map(parse_color_field, FormatToken::Color)),
+    ))(i)
+    .map_err(|e| match e {
+        Err::Failure(e) => Err::Error(e),
+        e => e,
+    })
+}
+
+/// Tokens of the branches of a conditional, which end at `|` or `}`. Fields in the branches
+/// that don't parse are an error of the conditional rather than text.
+fn parse_conditional_token(i: &str) -> IResult<&str, FormatToken, ColorParseError<&str>> {
+    alt((
+        |i| match parse_field(i) {
+            Err(Err::Error(e)) if i.starts_with('{') && looks_like_field(i) => Err(Err::Failure(e)),
+            result => result,
+        },
+        parse_brace,
+        map(take_while1(|c| !matches!(c, '{' | '}' | '|')), text_token),
+    ))(i)
+}
+
+fn text_token(text: &str) -> FormatToken {
+    FormatToken::Text(text.to_string())
+}
+
 #[inline]
 fn is_not_variable_start(chr: char) -> bool {
     chr != '{'
@@ -483,15 +2734,11 @@ fn parse_text(i: &str) -> IResult<&str, &str, ColorParseError<&str>> {
 }
 
 fn parse_brace(i: &str) -> IResult<&str, FormatToken, ColorParseError<&str>> {
-    map(tag("{"), FormatToken::Text)(i)
+    map(tag("{"), text_token)(i)
 }
 
 fn parse_format_token(i: &str) -> IResult<&str, FormatToken, ColorParseError<&str>> {
-    alt((
-        map(parse_color_field, FormatToken::Color),
-        parse_brace,
-        map(parse_text, FormatToken::Text),
-    ))(i)
+    alt((parse_field, parse_brace, map(parse_text, text_token)))(i)
 }
 
 fn parse_color_format(i: &str) -> IResult<&str, CustomColorFormat, ColorParseError<&str>> {
@@ -500,27 +2747,41 @@ fn parse_color_format(i: &str) -> IResult<&str, CustomColorFormat, ColorParseErr
 
 #[cfg(test)]
 mod tests {
+    use nom::combinator::all_consuming;
+    use std::sync::Arc;
+
     use crate::color::{
-        format::{ColorField, ColorSymbol, CustomColorFormat, DigitFormat, FormatToken},
-        Color, Illuminant, Rgb, RgbWorkingSpace,
+        format::{
+            complete_field, highlight_format, parse_color_symbol, ColorField, ColorSpaces,
+            ColorSymbol, CustomColorFormat, DigitFormat, Expr, FormatKind, FormatOptions,
+            FormatSpanKind, FormatToken, Function, NumberFormat, COLOR_FIELDS, FUNCTIONS,
+        },
+        ChromaticAdaptationMethod, Color, CustomPaletteFormat, NamedPalette, Palette, Rgb,
+        RgbWorkingSpace, Surround, ViewingConditions,
     };
     macro_rules! field {
         ($sym:tt) => {
             FormatToken::Color(ColorField {
-                symbol: ColorSymbol::$sym,
-                digit_format: None,
+                expr: Expr::Symbol(ColorSymbol::$sym),
+                format: NumberFormat::default(),
             })
         };
         ($sym:tt, $fmt:tt) => {
             FormatToken::Color(ColorField {
-                symbol: ColorSymbol::$sym,
-                digit_format: Some(DigitFormat::$fmt),
+                expr: Expr::Symbol(ColorSymbol::$sym),
+                format: NumberFormat {
+                    digits: Some(DigitFormat::$fmt),
+                    ..NumberFormat::default()
+                },
             })
         };
         ($sym:tt, $fmt:expr) => {
             FormatToken::Color(ColorField {
-                symbol: ColorSymbol::$sym,
-                digit_format: Some($fmt),
+                expr: Expr::Symbol(ColorSymbol::$sym),
+                format: NumberFormat {
+                    digits: Some($fmt),
+                    ..NumberFormat::default()
+                },
             })
         };
     }
@@ -538,7 +2799,7 @@ mod tests {
                 let color_format = CustomColorFormat::parse($fmt).unwrap();
                 let color = $color;
                 let formatted = color_format
-                    .format_color(&color, RgbWorkingSpace::SRGB, Illuminant::D65)
+                    .format_color(&color, &FormatOptions::default())
                     .unwrap();
                 assert_eq!(formatted, $want);
             };
@@ -563,6 +2824,75 @@ mod tests {
             "{hsv_h360:d} {hsv_s100:X} {hsv_v100:x}" => "326 4B 2f",
             Color::Rgb(Rgb::new_scaled(120, 30, 80))
         );
+        test_case!(
+            "{cam16_j:.0} {cam16_h:.0} {cam16ucs_j:.0}" => "100 209 100",
+            Color::Rgb(Rgb::new(1., 1., 1.))
+        );
+        test_case!(
+            "{hsluv_h:.0} {hsluv_s:.0} {hsluv_l:.0} {hpluv_p:.0}" => "12 100 53 427",
+            Color::Rgb(Rgb::new(1., 0., 0.))
+        );
+        test_case!(
+            "hwb({hwb_h360:.0} {hwb_w100:.0}% {hwb_b100:.0}%)" => "hwb(120 50% 20%)",
+            Color::Rgb(Rgb::new(0.5, 0.8, 0.5))
+        );
+        test_case!(
+            "{ycbcr709_y:d} {ycbcr709_cb:d} {ycbcr709_cr:d} {ycbcr709_y_limited:d} {ycbcr601_cr_full:d}" => "255 128 128 235 128",
+            Color::Rgb(Rgb::new(1., 1., 1.))
+        );
+        test_case!(
+            "{ycbcr709_y_limited:.0} {ycbcr709_cb_limited:.0} {ycbcr2020_cr_limited:.0}" => "63 102 240",
+            Color::Rgb(Rgb::new(1., 0., 0.))
+        );
+        test_case!(
+            "{ictcp_i:.2} {ictcp_ct:.2} {ictcp_cp:.2}" => "0.58 -0.00 0.00",
+            Color::Rgb(Rgb::new(1., 1., 1.))
+        );
+        test_case!(
+            "{hdr_nits:.0} {pq_r:.2} {pq10_g:d} {hlg10_b_limited:d} {pq12_r_limited:d}" => "203 0.58 594 721 2291",
+            Color::Rgb(Rgb::new(1., 1., 1.))
+        );
+        test_case!(
+            "{name}: {r255} {g255} {b255}" => "teal: 1 127 130",
+            Color::Rgb(Rgb::new_scaled(1, 127, 130))
+        );
+    }
+
+    #[test]
+    fn formats_expressions_and_conditionals() {
+        macro_rules! test_case {
+            ($fmt:literal => $want:literal, $color:expr) => {
+                let color_format = CustomColorFormat::parse($fmt).unwrap();
+                let formatted = color_format
+                    .format_color(&$color.into(), &FormatOptions::default())
+                    .unwrap();
+                assert_eq!(formatted, $want, "{}", $fmt);
+            };
+        }
+        let teal = Rgb::new_scaled(1, 127, 130);
+        test_case!("{r255:03d} {g255:5} {b255:<}" => "001   127 {b255:<}", teal);
+        test_case!("{lab_a:+.1} {lab_l:+.0} {lab_l: .0} {lab_a: .0}" => "-27.6 +48  48 -28", teal);
+        test_case!("{lab_a:08.2} {lab_a:8.2}|{lab_b:d}" => "-0027.64   -27.64|-10", teal);
+        test_case!("{g:%} {g:.1%} {a:%}" => "50% 49.8% 100%", teal);
+        test_case!("{r*100:.0} {g255 / 255 * 100:.1} {(r255 + 1) * 2}" => "0 49.8 4.0", teal);
+        test_case!("{-lab_a:.0} {lab_l - -2:.0} {2 * 3 + 4 * 5:d}" => "28 50 26", teal);
+        test_case!(
+            "{clamp(lab_a * 10, -100, 100):.0} {min(r255, g255)} {max(g, b):.2} {round(lab_l):.1} {abs(lab_b):.1}"
+                => "-100 1.0 0.51 48.0 10.0",
+            teal
+        );
+        test_case!("rgb{?a<1:a}({r255}, {g255}, {b255}{?a<1:, {a:.2}})" => "rgb(1, 127, 130)", teal);
+        test_case!(
+            "{?lab_l>50:light|dark} {?r255==1:one} {?g255 != 127:x|{g255:x}} {?b>=1:{?b<2:nested}}"
+                => "dark one 7f ",
+            teal
+        );
+        test_case!(
+            "{?lab_l <= 50 : {name} }" => " teal ",
+            teal
+        );
+        // invalid fields are written as text
+        test_case!("{r*} {clamp(r)} {name*2} {r:}" => "{r*} {clamp(r)} {name*2} {r:}", teal);
     }
 
     #[test]
@@ -571,9 +2901,9 @@ mod tests {
             "{r} {g} {b}",
             vec![
                 field!(Red),
-                FormatToken::Text(" "),
+                FormatToken::Text(" ".into()),
                 field!(Green),
-                FormatToken::Text(" "),
+                FormatToken::Text(" ".into()),
                 field!(Blue),
             ]
             .into()
@@ -583,11 +2913,11 @@ mod tests {
             vec![
                 field!(Cyan),
                 field!(Magenta),
-                FormatToken::Text(" "),
+                FormatToken::Text(" ".into()),
                 field!(Yellow),
-                FormatToken::Text("   "),
+                FormatToken::Text("   ".into()),
                 field!(Key),
-                FormatToken::Text("%"),
+                FormatToken::Text("%".into()),
             ]
             .into()
         );
@@ -595,12 +2925,12 @@ mod tests {
             "{hsv_h} {{ {hsv_s} }} {hsv_v}",
             vec![
                 field!(HSVHue),
-                FormatToken::Text(" "),
-                FormatToken::Text("{"),
-                FormatToken::Text("{"),
-                FormatToken::Text(" "),
+                FormatToken::Text(" ".into()),
+                FormatToken::Text("{".into()),
+                FormatToken::Text("{".into()),
+                FormatToken::Text(" ".into()),
                 field!(HSVSaturation),
-                FormatToken::Text(" }} "),
+                FormatToken::Text(" }} ".into()),
                 field!(HSVValue),
             ]
             .into()
@@ -612,16 +2942,572 @@ mod tests {
         test_case!(
             "L:{ lch_ab_l:x } C:{lch_ab_c:X} H:{lch_ab_h:o} r:{r:.4}",
             vec![
-                FormatToken::Text("L:"),
+                FormatToken::Text("L:".into()),
                 field!(LCHabL, Hex),
-                FormatToken::Text(" C:"),
+                FormatToken::Text(" C:".into()),
                 field!(LCHabC, UppercaseHex),
-                FormatToken::Text(" H:"),
+                FormatToken::Text(" H:".into()),
                 field!(LCHabH, Octal),
-                FormatToken::Text(" r:"),
+                FormatToken::Text(" r:".into()),
                 field!(Red, DigitFormat::Float { precision: 4 }),
             ]
             .into()
         );
+        test_case!(
+            "{cam16_j} {cam16_c:.2} {cam16ucs_b} {c}",
+            vec![
+                field!(Cam16J),
+                FormatToken::Text(" ".into()),
+                field!(Cam16C, DigitFormat::Float { precision: 2 }),
+                FormatToken::Text(" ".into()),
+                field!(Cam16UcsB),
+                FormatToken::Text(" ".into()),
+                field!(Cyan),
+            ]
+            .into()
+        );
+    }
+
+    fn palette_format(
+        prefix: &str,
+        entry: &str,
+        separator: &str,
+        suffix: &str,
+    ) -> CustomPaletteFormat {
+        CustomPaletteFormat {
+            prefix: prefix.into(),
+            entry_format: entry.into(),
+            separator: separator.into(),
+            suffix: suffix.into(),
+        }
+    }
+
+    fn format_palette(format: &CustomPaletteFormat) -> String {
+        let mut palette = NamedPalette::new(
+            "brand",
+            Palette::from_iter([
+                Rgb::new_scaled(255, 0, 0).into(),
+                Rgb::new_scaled(0, 255, 0).into(),
+                Rgb::new_scaled(0, 0, 255).into(),
+            ]),
+        );
+        palette.set_color_name(&Rgb::new_scaled(0, 255, 0).into(), "lime");
+        format
+            .format_palette(&palette, &FormatOptions::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn formats_palette_templates() {
+        let format = palette_format("[", "{r255}", "", "]\n");
+        assert_eq!(format_palette(&format), "[25500]\n");
+
+        let format = palette_format(
+            "{ \"{palette_name}\": [",
+            "\"#{r255:x}{g255:x}{b255:x}\"",
+            ", ",
+            "] }",
+        );
+        assert_eq!(
+            format_palette(&format),
+            r##"{ "brand": ["#ff00", "#0ff0", "#00ff"] }"##
+        );
+
+        let format = palette_format("", "{number}{?g255>0:={g255:x}}", " ", "");
+        assert_eq!(format_palette(&format), "1 2=ff 3");
+
+        let format = palette_format(
+            "{count} colors\n",
+            "{number}/{count} {if named}{entry_name}{else}{name}{end}{if !last},{end}\n",
+            "",
+            "",
+        );
+        assert_eq!(
+            format_palette(&format),
+            "3 colors\n1/3 red,\n2/3 lime,\n3/3 blue\n"
+        );
+
+        let format = palette_format(
+            ":root {\n",
+            "  --color-{index}: rgb({r255} {g255} {b255});{if first} /* primary */{end}",
+            "\n",
+            "\n}",
+        );
+        assert_eq!(
+            format_palette(&format),
+            r#":root {
+  --color-0: rgb(255 0 0); /* primary */
+  --color-1: rgb(0 255 0);
+  --color-2: rgb(0 0 255);
+}"#
+        );
+    }
+
+    #[test]
+    fn reports_palette_template_errors() {
+        macro_rules! error {
+            ($format:expr => $template:literal, $line:literal, $column:literal, $message:literal) => {
+                let error = $format.validate().unwrap_err();
+                assert_eq!(error.template, $template);
+                assert_eq!((error.line, error.column), ($line, $column));
+                assert_eq!(error.message, $message);
+            };
+        }
+        error!(
+            palette_format("", "{r} {foo}", "", "") => "entry", 1, 5, "unknown field `{foo}`"
+        );
+        error!(
+            palette_format("", "{r}\n  {g:q}", "", "") => "entry", 2, 6, "invalid digit format `q` of `g`"
+        );
+        error!(
+            palette_format("", "{if !last},", "", "") => "entry", 1, 1, "`{if last}` is never closed with `{end}`"
+        );
+        error!(
+            palette_format("", "{if middle}{end}", "", "") => "entry", 1, 1, "unknown condition `middle`, expected `first`, `last` or `named`"
+        );
+        error!(
+            palette_format("", "{r}{end}", "", "") => "entry", 1, 4, "`{end}` without an `{if}`"
+        );
+        error!(
+            palette_format("", "{if first}a{else}b{else}c{end}", "", "") => "entry", 1, 19, "`{else}` used twice in one `{if}`"
+        );
+        error!(
+            palette_format("{palette_name} {index}", "", "", "") => "prefix", 1, 16, "`{index}` can only be used in the entry template"
+        );
+        error!(
+            palette_format("", "", "", "{r}") => "suffix", 1, 1, "color field `{r}` can only be used in the entry template"
+        );
+        error!(
+            palette_format("", "", "{if first}", "") => "separator", 1, 1, "conditions can only be used in the entry template"
+        );
+        // braces that don't start a field are text
+        assert!(palette_format("a { color: red }", "{ \"x\": 1 }", "{", "}")
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn parses_expressions() {
+        use crate::color::format::{BinaryOp, Sign};
+
+        let parsed = CustomColorFormat::parse("{r * 100 + g255 / 2:+04.1}").unwrap();
+        let field = match &parsed.0[..] {
+            [FormatToken::Color(field)] => field,
+            tokens => panic!("unexpected tokens {:?}", tokens),
+        };
+        assert_eq!(
+            field.expr,
+            Expr::Binary(
+                BinaryOp::Add,
+                Box::new(Expr::Binary(
+                    BinaryOp::Mul,
+                    Box::new(Expr::Symbol(ColorSymbol::Red)),
+                    Box::new(Expr::Number(100.))
+                )),
+                Box::new(Expr::Binary(
+                    BinaryOp::Div,
+                    Box::new(Expr::Symbol(ColorSymbol::Green255)),
+                    Box::new(Expr::Number(2.))
+                )),
+            )
+        );
+        assert_eq!(
+            field.format,
+            NumberFormat {
+                sign: Sign::Always,
+                zero_pad: true,
+                width: 4,
+                digits: Some(DigitFormat::Float { precision: 1 }),
+            }
+        );
+    }
+
+    #[test]
+    fn reads_colors_back() {
+        let parse = |fmt: &str, text: &str| {
+            CustomColorFormat::parse(fmt)
+                .unwrap()
+                .matcher()
+                .unwrap()
+                .parse(text, &FormatOptions::default())
+                .map(|color| color.as_hex())
+        };
+        let green = Some("#0cc821".to_string());
+        assert_eq!(parse("{r255} {g255} {b255}", "12 200 33"), green);
+        assert_eq!(
+            parse("rgb({r255}, {g255}, {b255})", " RGB( 12,200 ,  33 ) "),
+            green
+        );
+        assert_eq!(parse("#{r255:02x}{g255:02X}{b255:02x}", "#0cC821"), green);
+        assert_eq!(parse("{r255:03d}{g255:03d}{b255:03d}", "012200033"), green);
+        assert_eq!(parse("{r255}.{g255}.{b255}", "12.200.33"), green);
+        assert_eq!(
+            parse(
+                "{g255 + 1}/{b255 * 2:.1}/{-(r255 - 2) / 2}",
+                "201/66.0/-5.0"
+            ),
+            green
+        );
+        assert_eq!(parse("{r255} {g255} {b255} {a:.1}", "12 200 33 1.0"), green);
+        assert_eq!(parse("{r255} {g255} {b255}", "12 200"), None);
+        assert_eq!(parse("{r255} {g255} {b255}", "12 200 33 4"), None);
+        assert_eq!(parse("{r:.0%} {g255} {b255}", "5 200 33"), None);
+
+        let teal: Color = Rgb::new_scaled(1, 127, 130).into();
+        for (fmt, tolerance) in [
+            ("{r:.5} {g:.5} {b:.5}", 1e-4),
+            ("{c100:.3} {m:.5} {y:.5} {k100:.3}", 1e-4),
+            ("hsl({hsl_h360:.2}°, {hsl_s:.3%}, {hsl_l100:.3}%)", 1e-4),
+            ("{hsv_h:.5} {hsv_s100:.3} {hsv_v:.5}", 1e-4),
+            ("{hwb_h360:.2} {hwb_w100:.3} {hwb_b:.5}", 1e-4),
+            ("{lab_l:.4} {lab_a:+.4} {lab_b: .4}", 1e-3),
+            ("{lch_ab_l:.4} {lch_ab_c:.4} {lch_ab_h:.4}", 1e-3),
+            ("{luv_l:.4} {luv_u:.4} {luv_v:.4}", 1e-3),
+            ("{lch_uv_l:.4} {lch_uv_c:.4} {lch_uv_h:.4}", 1e-3),
+            ("{hsluv_h:.4} {hsluv_s:.4} {hsluv_l:.4}", 1e-3),
+            ("{hpluv_h:.4} {hpluv_p:.4} {hpluv_l:.4}", 1e-3),
+            ("{xyz_x:.5} {xyz_y:.5} {xyz_z:.5}", 1e-3),
+            ("{xyy_x:.5} {xyy_y:.5} {xyy_Y:.5}", 1e-3),
+            ("{cam16_j:.4} {cam16_c:.4} {cam16_h:.4} {cam16_m:.1}", 1e-3),
+            ("{cam16ucs_j:.4} {cam16ucs_a:.4} {cam16ucs_b:.4}", 1e-3),
+            ("{ictcp_i:.5} {ictcp_ct:.5} {ictcp_cp:.5}", 1e-3),
+            ("{ycbcr709_y} {ycbcr709_cb} {ycbcr709_cr}", 1e-2),
+        ] {
+            let format = CustomColorFormat::parse(fmt).unwrap();
+            let text = format
+                .format_color(&teal, &FormatOptions::default())
+                .unwrap();
+            let color = format
+                .matcher()
+                .unwrap_or_else(|e| panic!("{}: {}", fmt, e))
+                .parse(&text, &FormatOptions::default())
+                .unwrap_or_else(|| panic!("{} can't read `{}`", fmt, text));
+            let (want, got) = (teal.rgb(), color.rgb());
+            for (want, got) in [
+                (want.r(), got.r()),
+                (want.g(), got.g()),
+                (want.b(), got.b()),
+            ] {
+                assert!(
+                    (want - got).abs() < tolerance,
+                    "{}: {} != {}",
+                    fmt,
+                    got,
+                    want
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn uses_the_viewing_conditions_of_the_options() {
+        let teal: Color = Rgb::new_scaled(1, 127, 130).into();
+        let dark = FormatOptions {
+            viewing_conditions: ViewingConditions {
+                adapting_luminance: 200.,
+                surround: Surround::Dark,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let format = CustomColorFormat::parse("{cam16_j:.4} {cam16_c:.4} {cam16_h:.4}").unwrap();
+        let text = format.format_color(&teal, &dark).unwrap();
+        assert_ne!(
+            text,
+            format
+                .format_color(&teal, &FormatOptions::default())
+                .unwrap()
+        );
+
+        let matcher = format.matcher().unwrap();
+        let got = matcher.parse(&text, &dark).unwrap().rgb();
+        let want = teal.rgb();
+        for (want, got) in [
+            (want.r(), got.r()),
+            (want.g(), got.g()),
+            (want.b(), got.b()),
+        ] {
+            assert!((want - got).abs() < 1e-3, "{} != {}", got, want);
+        }
+        assert_ne!(
+            matcher
+                .parse(&text, &FormatOptions::default())
+                .unwrap()
+                .as_hex(),
+            teal.as_hex()
+        );
+    }
+
+    #[test]
+    fn uses_the_hdr_options() {
+        let white: Color = Rgb::new(1., 1., 1.).into();
+        let exposed = FormatOptions {
+            hdr_exposure: 1.,
+            ..Default::default()
+        };
+        let format = CustomColorFormat::parse("{hdr_nits:.0} {pq10_g:d}").unwrap();
+        assert_eq!(
+            format
+                .format_color(&white, &FormatOptions::default())
+                .unwrap(),
+            "203 594"
+        );
+        assert_eq!(format.format_color(&white, &exposed).unwrap(), "406 669");
+
+        let teal: Color = Rgb::new_scaled(1, 127, 130).into();
+        let options = |method| FormatOptions {
+            ws: RgbWorkingSpace::ProPhoto,
+            method,
+            ..Default::default()
+        };
+        let bradford = options(ChromaticAdaptationMethod::Bradford);
+        let scaling = options(ChromaticAdaptationMethod::XYZScaling);
+        let format = CustomColorFormat::parse("{ictcp_i:.5} {ictcp_ct:.5} {ictcp_cp:.5}").unwrap();
+        let text = format.format_color(&teal, &scaling).unwrap();
+        assert_ne!(text, format.format_color(&teal, &bradford).unwrap());
+        let got = format.matcher().unwrap().parse(&text, &scaling).unwrap();
+        assert_eq!(got.as_hex(), teal.as_hex());
+    }
+
+    #[test]
+    fn reports_formats_that_cant_be_read_back() {
+        let error = |fmt: &str| {
+            CustomColorFormat::parse(fmt)
+                .unwrap()
+                .matcher()
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            error("{r255} {g255}"),
+            "the format is missing the blue of RGB"
+        );
+        assert_eq!(
+            error("{hsl_h} {round(hsl_s * 100)} {hdr_nits}"),
+            "the format is missing the saturation and lightness of HSL, `round` can't be \
+             inverted, HDR values can't be read back"
+        );
+        assert_eq!(
+            error("{r255 * g255} {a}"),
+            "the format has no color fields that can be read back, fields computed from two \
+             values can't be inverted"
+        );
+        assert!(error("{r255}{g255:02x}{b255:02x}").contains("aren't separated by any text"));
+        assert!(error("{r255:x}a {g255} {b255}").contains("could be read as part of its number"));
+        assert!(error("{r255:.0}.{g255:.0}.{b255:.0}").contains("`.` following a field"));
+        assert!(error("{hsl_h:.2}.{hsl_s:.0%}.{hsl_l:.0%}").contains("`.` following a field"));
+        assert!(error("{name}: {r255} {g255} {b255}").contains("`{name}`"));
+        assert!(error("{r255} {g255} {b255}{?a<1: {a}}").contains("conditional"));
+    }
+
+    #[test]
+    fn lists_fields_that_parse() {
+        for field in COLOR_FIELDS {
+            assert!(
+                all_consuming(parse_color_symbol)(field.name).is_ok(),
+                "{}",
+                field.name
+            );
+        }
+        for function in FUNCTIONS {
+            assert!(
+                Function::parse(function.name).is_some(),
+                "{}",
+                function.name
+            );
+        }
+    }
+
+    #[test]
+    fn reports_errors_at_their_column() {
+        let error = |fmt: &str| {
+            let e = CustomColorFormat::validate(fmt).unwrap_err();
+            (e.line, e.column, e.message)
+        };
+        assert_eq!(
+            CustomColorFormat::validate("#{r255:02x} {?a<1:{a:.2}}"),
+            Ok(())
+        );
+        assert_eq!(
+            error("rgb({r255}, {g255 +}, {b255})"),
+            (
+                1,
+                20,
+                "expected a field, a number or `(` but found `}`".to_string()
+            )
+        );
+        assert_eq!(
+            error("{r:.2} {lab_x}"),
+            (1, 9, "unknown field `lab_x`".to_string())
+        );
+        assert_eq!(
+            error("{clamp(r, 0)}"),
+            (
+                1,
+                2,
+                "`clamp` takes 3 arguments but 2 were given".to_string()
+            )
+        );
+        assert_eq!(
+            error("{?r>0.5:{foo}|x}"),
+            (1, 10, "unknown field `foo`".to_string())
+        );
+        let (line, column, message) = error("a\n {r255:q}");
+        assert_eq!((line, column), (2, 8));
+        assert!(message.starts_with("invalid digit format `q`"));
+        assert_eq!(
+            error("{?r>0.5:a|b|c}"),
+            (
+                1,
+                12,
+                "`|` can only be used once in a conditional".to_string()
+            )
+        );
+        assert_eq!(
+            error("x {?r 0.5:a}"),
+            (
+                1,
+                7,
+                "expected a comparison like `<`, `>=` or `==` but found `0`".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn highlights_formats() {
+        let spans = |fmt: &'static str, kind| {
+            highlight_format(fmt, kind)
+                .into_iter()
+                .map(|span| (&fmt[span.range], span.kind))
+                .collect::<Vec<_>>()
+        };
+        use FormatSpanKind::*;
+        assert_eq!(
+            spans("#{r255:02x} {clamp(lab_l * 2, 0, 1)}", FormatKind::Color),
+            vec![
+                ("#", Text),
+                ("{", Delimiter),
+                ("r255", Symbol),
+                (":02x", DigitFormat),
+                ("}", Delimiter),
+                (" ", Text),
+                ("{", Delimiter),
+                ("clamp", Function),
+                ("(", Operator),
+                ("lab_l", Symbol),
+                (" ", Text),
+                ("*", Operator),
+                (" ", Text),
+                ("2", Number),
+                (",", Operator),
+                (" ", Text),
+                ("0", Number),
+                (",", Operator),
+                (" ", Text),
+                ("1", Number),
+                (")", Operator),
+                ("}", Delimiter),
+            ]
+        );
+        assert_eq!(spans("{oops}", FormatKind::Color), vec![("{oops}", Error)]);
+        assert_eq!(
+            spans("{ ?a < 1:, {a:.2}|.}", FormatKind::Color),
+            vec![
+                ("{ ?", Delimiter),
+                ("a", Symbol),
+                (" ", Text),
+                ("<", Operator),
+                (" ", Text),
+                ("1", Number),
+                (":", Delimiter),
+                (", ", Text),
+                ("{", Delimiter),
+                ("a", Symbol),
+                (":.2", DigitFormat),
+                ("}|", Delimiter),
+                (".", Text),
+                ("}", Delimiter),
+            ]
+        );
+        assert_eq!(
+            spans("{?a<1:{foo}} {r}", FormatKind::Color),
+            vec![
+                ("{?a<1:{foo}", Error),
+                ("} ", Text),
+                ("{", Delimiter),
+                ("r", Symbol),
+                ("}", Delimiter),
+            ]
+        );
+        assert_eq!(
+            spans("{if !last}, {end}{palette_name}", FormatKind::Palette),
+            vec![
+                ("{if !last}", Keyword),
+                (", ", Text),
+                ("{end}{palette_name}", Keyword),
+            ]
+        );
+    }
+
+    #[test]
+    fn completes_partly_typed_fields() {
+        let names = |text: &str, kind| {
+            complete_field(text, text.len(), kind).map(|(range, fields)| {
+                let names: Vec<_> = fields.iter().map(|field| field.name).collect();
+                (range, names)
+            })
+        };
+        assert_eq!(
+            names("x {lab_", FormatKind::Color),
+            Some((3..7, vec!["lab_l", "lab_a", "lab_b"]))
+        );
+        assert_eq!(
+            names("{r * cl", FormatKind::Color),
+            Some((5..7, vec!["clamp"]))
+        );
+        assert_eq!(names("{r} pal", FormatKind::Palette), None);
+        assert_eq!(names("{pal", FormatKind::Color), None);
+        assert_eq!(
+            names("{if !last}{pal", FormatKind::Palette),
+            Some((11..14, vec!["palette_name"]))
+        );
+    }
+
+    #[test]
+    fn caches_parsed_formats() {
+        let format = CustomColorFormat::cached("{r255} {g255} {b255}").unwrap();
+        assert!(Arc::ptr_eq(
+            &format,
+            &CustomColorFormat::cached("{r255} {g255} {b255}").unwrap()
+        ));
+        assert_eq!(
+            *format,
+            CustomColorFormat::parse("{r255} {g255} {b255}").unwrap()
+        );
+    }
+
+    #[test]
+    fn converts_only_the_spaces_a_format_uses() {
+        let color: Color = Rgb::new_scaled(12, 200, 33).into();
+        let options = FormatOptions::default();
+        let spaces = ColorSpaces::new(&color, &options);
+        let write = |fmt: &str| {
+            let mut s = String::new();
+            spaces
+                .write_tokens(&mut s, &CustomColorFormat::parse(fmt).unwrap().0)
+                .unwrap();
+            s
+        };
+        assert_eq!(write("{r255} {g255} {b255} {hsl_h360:.0}"), "12 200 33 127");
+        assert!(spaces.hsl.get().is_some());
+        assert!(spaces.xyz.get().is_none());
+        assert!(spaces.cam16.get().is_none());
+
+        assert_eq!(write("{?lch_ab_l>50:light|dark}"), "light");
+        assert!(spaces.lch_ab.get().is_some());
+        assert!(spaces.lab.get().is_some());
+        assert!(spaces.xyz.get().is_some());
+        assert!(spaces.luv.get().is_none());
+        assert!(spaces.cam16.get().is_none());
     }
 }
@@ -0,0 +1,273 @@
+use crate::{
+    color::{Xyz, YCbCrRange},
+    math::{Float, Matrix1x3, Matrix3},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Luminance in cd/m² that a diffuse white with Y = 1 is mapped to, as recommended by
+/// ITU-R BT.2408 for SDR content shown in HDR.
+pub const DEFAULT_REFERENCE_WHITE: Float = 203.;
+
+/// Peak luminance in cd/m² of the PQ transfer function
+pub const PQ_PEAK_NITS: Float = 10000.;
+
+/// Nominal peak luminance in cd/m² of the display used for the HLG system gamma
+pub const HLG_PEAK_NITS: Float = 1000.;
+
+const PQ_M1: Float = 2610. / 16384.;
+const PQ_M2: Float = 2523. / 4096. * 128.;
+const PQ_C1: Float = 3424. / 4096.;
+const PQ_C2: Float = 2413. / 4096. * 32.;
+const PQ_C3: Float = 2392. / 4096. * 32.;
+
+const HLG_A: Float = 0.178_832_77;
+const HLG_B: Float = 1. - 4. * HLG_A;
+const HLG_C: Float = 0.559_910_7;
+/// System gamma for the nominal peak luminance of 1000 cd/m²
+const HLG_GAMMA: Float = 1.2;
+
+/// SMPTE ST 2084 inverse EOTF, maps luminance relative to 10000 cd/m² to a signal value
+pub fn pq_encode(luminance: Float) -> Float {
+    let p = luminance.max(0.).powf(PQ_M1);
+    ((PQ_C1 + PQ_C2 * p) / (1. + PQ_C3 * p)).powf(PQ_M2)
+}
+
+/// SMPTE ST 2084 EOTF, maps a signal value to luminance relative to 10000 cd/m²
+pub fn pq_decode(signal: Float) -> Float {
+    let p = signal.max(0.).powf(1. / PQ_M2);
+    ((p - PQ_C1).max(0.) / (PQ_C2 - PQ_C3 * p)).powf(1. / PQ_M1)
+}
+
+/// ITU-R BT.2100 HLG OETF, maps relative scene light in 0.0 ..= 1.0 to a signal value
+pub fn hlg_encode(light: Float) -> Float {
+    let light = light.max(0.);
+    if light <= 1. / 12. {
+        (3. * light).sqrt()
+    } else {
+        HLG_A * (12. * light - HLG_B).ln() + HLG_C
+    }
+}
+
+/// ITU-R BT.2100 HLG inverse OETF, maps a signal value to relative scene light
+pub fn hlg_decode(signal: Float) -> Float {
+    let signal = signal.max(0.);
+    if signal <= 0.5 {
+        signal * signal / 3.
+    } else {
+        (((signal - HLG_C) / HLG_A).exp() + HLG_B) / 12.
+    }
+}
+
+/// Transfer function used to encode HDR light into a signal
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TransferFunction {
+    /// Perceptual quantizer of SMPTE ST 2084
+    #[default]
+    Pq,
+    /// Hybrid log-gamma of ARIB STD-B67
+    Hlg,
+}
+
+impl AsRef<str> for TransferFunction {
+    fn as_ref(&self) -> &str {
+        match self {
+            TransferFunction::Pq => "PQ",
+            TransferFunction::Hlg => "HLG",
+        }
+    }
+}
+
+#[rustfmt::skip]
+pub(crate) fn xyz_to_bt2020_matrix() -> Matrix3 {
+    Matrix3::from([
+        [ 1.716_651_2, -0.355_670_8, -0.253_366_3],
+        [-0.666_684_4,  1.616_481_2,  0.015_768_5],
+        [ 0.017_639_9, -0.042_770_6,  0.942_103_1],
+    ])
+}
+
+/// Luminance coefficients of BT.2020 primaries
+const BT2020_LUMINANCE: [Float; 3] = [0.2627, 0.6780, 0.0593];
+
+/// Display light in cd/m² with ITU-R BT.2020 primaries and a D65 white as used by BT.2100.
+/// Channels are linear and not limited to the SDR range.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct HdrColor {
+    r: Float,
+    g: Float,
+    b: Float,
+}
+
+impl HdrColor {
+    pub fn new(r: Float, g: Float, b: Float) -> Self {
+        let r = if r.is_nan() { 0. } else { r };
+        let g = if g.is_nan() { 0. } else { g };
+        let b = if b.is_nan() { 0. } else { b };
+
+        Self { r, g, b }
+    }
+
+    #[inline(always)]
+    /// Returns Red in cd/m²
+    pub fn r(&self) -> Float {
+        self.r
+    }
+
+    #[inline(always)]
+    /// Returns Green in cd/m²
+    pub fn g(&self) -> Float {
+        self.g
+    }
+
+    #[inline(always)]
+    /// Returns Blue in cd/m²
+    pub fn b(&self) -> Float {
+        self.b
+    }
+
+    /// Returns the channels in the order red, green, blue
+    pub fn channels(&self) -> [Float; 3] {
+        [self.r, self.g, self.b]
+    }
+
+    /// Returns luminance in cd/m²
+    pub fn luminance(&self) -> Float {
+        BT2020_LUMINANCE[0] * self.r + BT2020_LUMINANCE[1] * self.g + BT2020_LUMINANCE[2] * self.b
+    }
+
+    /// Takes in a color relative to the D65 white where Y = 1 is shown at `reference_white` cd/m².
+    /// Values above 1 are brighter than the reference white.
+    pub fn from_xyz(color: Xyz, reference_white: Float) -> Self {
+        let rgb = xyz_to_bt2020_matrix() * Matrix1x3::from(color);
+        HdrColor::new(
+            rgb[0] * reference_white,
+            rgb[1] * reference_white,
+            rgb[2] * reference_white,
+        )
+    }
+
+    pub fn to_xyz(self, reference_white: Float) -> Xyz {
+        let rgb = Matrix1x3::from([
+            self.r / reference_white,
+            self.g / reference_white,
+            self.b / reference_white,
+        ]);
+        let xyz = xyz_to_bt2020_matrix().inverse().expect("inverse matrix") * rgb;
+        Xyz::new(xyz[0], xyz[1], xyz[2])
+    }
+
+    /// Returns non-linear signal values in the range 0.0 ..= 1.0
+    pub fn encode(&self, transfer: TransferFunction) -> [Float; 3] {
+        match transfer {
+            TransferFunction::Pq => self.channels().map(|c| pq_encode(c / PQ_PEAK_NITS)),
+            TransferFunction::Hlg => {
+                // inverse of the OOTF, which applies the system gamma to the luminance only
+                let luminance = (self.luminance() / HLG_PEAK_NITS).max(0.);
+                let scene_luminance = luminance.powf(1. / HLG_GAMMA);
+                let scale = if luminance > 0. {
+                    scene_luminance / luminance / HLG_PEAK_NITS
+                } else {
+                    0.
+                };
+                self.channels().map(|c| hlg_encode((c * scale).min(1.)))
+            }
+        }
+    }
+
+    /// Creates the color from non-linear signal values in the range 0.0 ..= 1.0
+    pub fn decode(signal: [Float; 3], transfer: TransferFunction) -> Self {
+        let [r, g, b] = match transfer {
+            TransferFunction::Pq => signal.map(|s| pq_decode(s) * PQ_PEAK_NITS),
+            TransferFunction::Hlg => {
+                let scene = signal.map(hlg_decode);
+                let scene_luminance = BT2020_LUMINANCE
+                    .iter()
+                    .zip(scene)
+                    .map(|(k, c)| k * c)
+                    .sum::<Float>();
+                let scale = HLG_PEAK_NITS * scene_luminance.powf(HLG_GAMMA - 1.);
+                scene.map(|c| c * scale)
+            }
+        };
+        HdrColor::new(r, g, b)
+    }
+
+    /// Returns the color multiplied by `2^stops`
+    pub fn exposed(self, stops: Float) -> Self {
+        let factor = stops.exp2();
+        HdrColor::new(self.r * factor, self.g * factor, self.b * factor)
+    }
+
+    /// Maps the color to the SDR range of a working space for previews. Colors up to 80% of the
+    /// reference white are kept as they are, brighter ones are compressed towards white while
+    /// keeping the ratios of the channels.
+    pub fn tone_map(&self, reference_white: Float) -> Xyz {
+        const KNEE: Float = 0.8;
+        let xyz = self.to_xyz(reference_white);
+        let rgb = xyz_to_bt2020_matrix() * Matrix1x3::from(xyz);
+        let max = rgb[0].max(rgb[1]).max(rgb[2]);
+        if max <= KNEE {
+            return xyz;
+        }
+        let excess = (max - KNEE) / (1. - KNEE);
+        let mapped = KNEE + (1. - KNEE) * excess / (1. + excess);
+        let scale = mapped / max;
+        Xyz::new(xyz.x() * scale, xyz.y() * scale, xyz.z() * scale)
+    }
+}
+
+/// Quantizes a signal value to an integer code value with `bit_depth` bits. Limited range uses
+/// the BT.2100 narrow range, 64 ..= 940 for 10 bits.
+pub fn code_value(signal: Float, bit_depth: u8, range: YCbCrRange) -> Float {
+    let max = ((1u32 << bit_depth) - 1) as Float;
+    let value = match range {
+        YCbCrRange::Full => signal * max,
+        YCbCrRange::Limited => (219. * signal + 16.) * (1u32 << (bit_depth - 8)) as Float,
+    };
+    value.round().clamp(0., max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transfer_functions() {
+        // reference white and peak levels from ITU-R BT.2408
+        let white = HdrColor::new(203., 203., 203.);
+        let pq = white.encode(TransferFunction::Pq);
+        assert!((pq[0] - 0.58).abs() < 1e-3, "{:?}", pq);
+        assert_eq!(code_value(pq[0], 10, YCbCrRange::Full), 594.);
+        let hlg = white.encode(TransferFunction::Hlg);
+        assert!((hlg[0] - 0.75).abs() < 1e-3, "{:?}", hlg);
+        assert_eq!(code_value(hlg[1], 10, YCbCrRange::Limited), 721.);
+
+        assert!((pq_encode(1.) - 1.).abs() < 1e-5);
+        assert!((hlg_encode(1.) - 1.).abs() < 1e-5);
+        assert!((pq_decode(pq_encode(0.3)) - 0.3).abs() < 1e-4);
+        assert!((hlg_decode(hlg_encode(0.3)) - 0.3).abs() < 1e-5);
+        assert!((hlg_decode(hlg_encode(0.05)) - 0.05).abs() < 1e-5);
+
+        for transfer in [TransferFunction::Pq, TransferFunction::Hlg] {
+            let color = HdrColor::new(600., 150., 40.);
+            let back = HdrColor::decode(color.encode(transfer), transfer);
+            for (got, want) in back.channels().iter().zip(color.channels()) {
+                assert!((got - want).abs() / want < 1e-3, "{:?} {:?}", back, color);
+            }
+        }
+    }
+
+    #[test]
+    fn tone_maps_bright_colors() {
+        let d65 = Xyz::new(0.95047, 1., 1.08883);
+        let gray = HdrColor::from_xyz(Xyz::new(0.95047 * 0.5, 0.5, 1.08883 * 0.5), 203.);
+        assert!((gray.luminance() - 101.5).abs() < 0.1);
+        assert!((gray.tone_map(203.).y() - 0.5).abs() < 1e-4);
+
+        let bright = HdrColor::from_xyz(Xyz::new(0.95047 * 4., 4., 1.08883 * 4.), 203.);
+        let mapped = bright.tone_map(203.);
+        assert!(mapped.y() > 0.9 && mapped.y() < 1., "{:?}", mapped);
+        assert!((mapped.x() / mapped.y() - d65.x()).abs() < 1e-3);
+    }
+}
@@ -1,17 +1,20 @@
-use crate::color::{hsv::Hsv, rgb::Rgb, CIEColor, Cmyk, Color, Xyz};
+use crate::{
+    color::{hsv::Hsv, rgb::Rgb, CIEColor, Cmyk, Color, Xyz},
+    math::Float,
+};
 use egui::color::{Color32, Hsva, Rgba};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Hsl {
-    h: f32,
-    s: f32,
-    l: f32,
+    h: Float,
+    s: Float,
+    l: Float,
 }
 
 impl Hsl {
     /// Takes in values in the range 0.0 ..= 1.0 and returns an HSL color
-    pub fn new(hue: f32, saturation: f32, light: f32) -> Self {
+    pub fn new(hue: Float, saturation: Float, light: Float) -> Self {
         let hue = if hue.is_nan() { 0. } else { hue };
         let saturation = if saturation.is_nan() { 0. } else { saturation };
         let light = if light.is_nan() { 0. } else { light };
@@ -24,34 +27,34 @@ impl Hsl {
 
     #[inline(always)]
     /// Returns Hue in the range of 0.0 ..= 1.0
-    pub fn h(&self) -> f32 {
+    pub fn h(&self) -> Float {
         self.h
     }
 
     #[inline(always)]
     /// Returns Saturation in the range of 0.0 ..= 1.0
-    pub fn s(&self) -> f32 {
+    pub fn s(&self) -> Float {
         self.s
     }
 
     #[inline(always)]
     /// Returns Light in the range of 0.0 ..= 1.0
-    pub fn l(&self) -> f32 {
+    pub fn l(&self) -> Float {
         self.l
     }
 
     /// Returns Hue in the range of 0.0 ..= 360.0
-    pub fn h_scaled(&self) -> f32 {
+    pub fn h_scaled(&self) -> Float {
         self.h * 360.
     }
 
     /// Returns Saturation in the range of 0.0 ..= 100.0
-    pub fn s_scaled(&self) -> f32 {
+    pub fn s_scaled(&self) -> Float {
         self.s * 100.
     }
 
     /// Returns Light in the range of 0.0 ..= 100.0
-    pub fn l_scaled(&self) -> f32 {
+    pub fn l_scaled(&self) -> Float {
         self.l * 100.
     }
 }
@@ -107,6 +110,8 @@ impl From<Color> for Hsl {
             Color::xyY(c, ws) => Xyz::from(c).to_rgb(ws).into(),
             Color::Luv(c, ws) => Xyz::from(c).to_rgb(ws).into(),
             Color::LchUV(c, ws) => Xyz::from(c).to_rgb(ws).into(),
+            Color::Hsluv(c, ws) => c.to_xyz(ws).to_rgb(ws).into(),
+            Color::Hpluv(c, ws) => c.to_xyz(ws).to_rgb(ws).into(),
             Color::Lab(c, ws, illuminant) => c.to_xyz(illuminant).to_rgb(ws).into(),
             Color::LchAB(c, ws, illuminant) => c.to_xyz(illuminant).to_rgb(ws).into(),
             Color::Color32(c) => Rgb::from(c).into(),
@@ -164,7 +169,14 @@ mod tests {
                 let expected = Hsl::new($h, $s, $l);
                 let hsv = Hsv::new($hh, $ss, $v);
                 let got = Hsl::from(hsv);
-                assert_eq!(got, expected);
+                assert!(
+                    (got.h() - expected.h()).abs() < 1e-6
+                        && (got.s() - expected.s()).abs() < 1e-6
+                        && (got.l() - expected.l()).abs() < 1e-6,
+                    "{:?} != {:?}",
+                    got,
+                    expected
+                );
             };
         }
 
@@ -0,0 +1,246 @@
+use crate::{
+    color::{LchUV, Luv, RgbWorkingSpace, Xyz, CIE_E, CIE_K},
+    math::Float,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Lines in the chroma/hue plane of LCh(uv) with a constant lightness where a channel of the
+/// working space reaches 0 or 1. Returned as `(slope, intercept)` pairs.
+fn gamut_bounds(l: Float, ws: RgbWorkingSpace) -> [(Float, Float); 6] {
+    let m = ws.inverse_rgb_matrix();
+    let sub1 = (l + 16.).powi(3) / 1_560_896.;
+    let sub2 = if sub1 > CIE_E { sub1 } else { l / CIE_K };
+
+    let mut bounds = [(0., 0.); 6];
+    for c in 0..3 {
+        let [m1, m2, m3] = m[c];
+        for t in 0..2 {
+            let t = t as Float;
+            let top1 = (284_517. * m1 - 94_839. * m3) * sub2;
+            let top2 =
+                (838_422. * m3 + 769_860. * m2 + 731_718. * m1) * l * sub2 - 769_860. * t * l;
+            let bottom = (632_260. * m3 - 126_452. * m2) * sub2 + 126_452. * t;
+            bounds[c * 2 + t as usize] = (top1 / bottom, top2 / bottom);
+        }
+    }
+    bounds
+}
+
+/// Maximum chroma that stays inside of the working space for the given lightness and hue.
+fn max_chroma_for_lh(l: Float, h: Float, ws: RgbWorkingSpace) -> Float {
+    let h = h.to_radians();
+    gamut_bounds(l, ws)
+        .iter()
+        .map(|(slope, intercept)| intercept / (h.sin() - slope * h.cos()))
+        .filter(|length| *length >= 0.)
+        .fold(Float::MAX, Float::min)
+}
+
+/// Maximum chroma that stays inside of the working space for the given lightness at any hue.
+fn max_safe_chroma_for_l(l: Float, ws: RgbWorkingSpace) -> Float {
+    gamut_bounds(l, ws)
+        .iter()
+        .map(|(slope, intercept)| intercept.abs() / (slope.powi(2) + 1.).sqrt())
+        .fold(Float::MAX, Float::min)
+}
+
+/// Returns true if the lightness is so close to black or white that the chroma is always 0.
+fn is_achromatic(l: Float) -> bool {
+    !(0.00001..=99.99999).contains(&l)
+}
+
+/// HSLuv, a human friendly alternative to HSL built on LCh(uv). Saturation is the chroma
+/// expressed as a percentage of the maximum chroma available for the lightness and hue in the
+/// working space.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Hsluv {
+    h: Float,
+    s: Float,
+    l: Float,
+}
+
+impl Hsluv {
+    /// Takes in hue in the range 0.0 ..= 360.0 and saturation and lightness in the range
+    /// 0.0 ..= 100.0
+    pub fn new(h: Float, s: Float, l: Float) -> Self {
+        let h = if h.is_nan() { 0. } else { h };
+        let s = if s.is_nan() { 0. } else { s };
+        let l = if l.is_nan() { 0. } else { l };
+
+        Self { h, s, l }
+    }
+
+    #[inline(always)]
+    /// Returns Hue in the range of 0.0 ..= 360.0
+    pub fn h(&self) -> Float {
+        self.h
+    }
+
+    #[inline(always)]
+    /// Returns Saturation in the range of 0.0 ..= 100.0
+    pub fn s(&self) -> Float {
+        self.s
+    }
+
+    #[inline(always)]
+    /// Returns Light in the range of 0.0 ..= 100.0
+    pub fn l(&self) -> Float {
+        self.l
+    }
+
+    pub fn from_lch_uv(color: LchUV, ws: RgbWorkingSpace) -> Self {
+        let (l, c, h) = (color.l(), color.c(), color.h());
+        let s = if is_achromatic(l) {
+            0.
+        } else {
+            c / max_chroma_for_lh(l, h, ws) * 100.
+        };
+        Hsluv::new(h, s, l)
+    }
+
+    pub fn to_lch_uv(self, ws: RgbWorkingSpace) -> LchUV {
+        let c = if is_achromatic(self.l) {
+            0.
+        } else {
+            max_chroma_for_lh(self.l, self.h, ws) / 100. * self.s
+        };
+        LchUV::new(self.l, c, self.h)
+    }
+
+    pub fn from_xyz(color: Xyz, ws: RgbWorkingSpace) -> Self {
+        Self::from_lch_uv(LchUV::from(color), ws)
+    }
+
+    pub fn to_xyz(self, ws: RgbWorkingSpace) -> Xyz {
+        Xyz::from(Luv::from(self.to_lch_uv(ws)))
+    }
+}
+
+/// HPLuv, a variant of [`Hsluv`] that only covers pastel colors. Its saturation is relative to
+/// the largest chroma available at a lightness for every hue, so equal saturation means equal
+/// chroma across hues. Values above 100 are outside of the pastel range.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Hpluv {
+    h: Float,
+    p: Float,
+    l: Float,
+}
+
+impl Hpluv {
+    /// Takes in hue in the range 0.0 ..= 360.0 and saturation and lightness in the range
+    /// 0.0 ..= 100.0
+    pub fn new(h: Float, p: Float, l: Float) -> Self {
+        let h = if h.is_nan() { 0. } else { h };
+        let p = if p.is_nan() { 0. } else { p };
+        let l = if l.is_nan() { 0. } else { l };
+
+        Self { h, p, l }
+    }
+
+    #[inline(always)]
+    /// Returns Hue in the range of 0.0 ..= 360.0
+    pub fn h(&self) -> Float {
+        self.h
+    }
+
+    #[inline(always)]
+    /// Returns Saturation, 0.0 ..= 100.0 for pastel colors
+    pub fn p(&self) -> Float {
+        self.p
+    }
+
+    #[inline(always)]
+    /// Returns Light in the range of 0.0 ..= 100.0
+    pub fn l(&self) -> Float {
+        self.l
+    }
+
+    pub fn from_lch_uv(color: LchUV, ws: RgbWorkingSpace) -> Self {
+        let (l, c, h) = (color.l(), color.c(), color.h());
+        let p = if is_achromatic(l) {
+            0.
+        } else {
+            c / max_safe_chroma_for_l(l, ws) * 100.
+        };
+        Hpluv::new(h, p, l)
+    }
+
+    pub fn to_lch_uv(self, ws: RgbWorkingSpace) -> LchUV {
+        let c = if is_achromatic(self.l) {
+            0.
+        } else {
+            max_safe_chroma_for_l(self.l, ws) / 100. * self.p
+        };
+        LchUV::new(self.l, c, self.h)
+    }
+
+    pub fn from_xyz(color: Xyz, ws: RgbWorkingSpace) -> Self {
+        Self::from_lch_uv(LchUV::from(color), ws)
+    }
+
+    pub fn to_xyz(self, ws: RgbWorkingSpace) -> Xyz {
+        Xyz::from(Luv::from(self.to_lch_uv(ws)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{CIEColor, Rgb};
+
+    fn assert_close(got: (Float, Float, Float), want: (Float, Float, Float)) {
+        assert!(
+            (got.0 - want.0).abs() < 0.1
+                && (got.1 - want.1).abs() < 0.1
+                && (got.2 - want.2).abs() < 0.1,
+            "got {:?}, want {:?}",
+            got,
+            want
+        );
+    }
+
+    #[test]
+    fn rgb_to_hsluv() {
+        let ws = RgbWorkingSpace::SRGB;
+        // reference values computed with the reference HSLuv algorithm
+        for (rgb, hsluv, hpluv) in [
+            (
+                (1., 0., 0.),
+                (12.177, 100., 53.237),
+                (12.177, 426.747, 53.237),
+            ),
+            (
+                (0., 0., 1.),
+                (265.874, 100., 32.301),
+                (265.874, 513.412, 32.301),
+            ),
+            (
+                (0.2, 0.6, 0.4),
+                (143.899, 85.249, 56.53),
+                (143.899, 116.959, 56.53),
+            ),
+        ] {
+            let xyz = Xyz::from_rgb(Rgb::new(rgb.0, rgb.1, rgb.2), ws);
+            let got = Hsluv::from_xyz(xyz, ws);
+            assert_close((got.h(), got.s(), got.l()), hsluv);
+            let got = Hpluv::from_xyz(xyz, ws);
+            assert_close((got.h(), got.p(), got.l()), hpluv);
+        }
+    }
+
+    #[test]
+    fn hsluv_roundtrip() {
+        let ws = RgbWorkingSpace::SRGB;
+        let rgb = Rgb::new(0.3, 0.7, 0.9);
+        let xyz = Xyz::from_rgb(rgb, ws);
+
+        let got = Hsluv::from_xyz(xyz, ws).to_xyz(ws).to_rgb(ws);
+        assert_close((got.r(), got.g(), got.b()), (rgb.r(), rgb.g(), rgb.b()));
+        let got = Hpluv::from_xyz(xyz, ws).to_xyz(ws).to_rgb(ws);
+        assert_close((got.r(), got.g(), got.b()), (rgb.r(), rgb.g(), rgb.b()));
+
+        let white = Hsluv::from_xyz(Xyz::from_rgb(Rgb::new(1., 1., 1.), ws), ws);
+        assert_eq!(white.s(), 0.);
+    }
+}
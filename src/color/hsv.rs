@@ -1,21 +1,21 @@
 #![allow(clippy::many_single_char_names)]
 use crate::{
     color::{rgb::Rgb, CIEColor, Cmyk, Color, Hsl, Xyz},
-    math,
+    math::{self, Float},
 };
 use egui::color::{Color32, Hsva, Rgba};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Hsv {
-    h: f32,
-    s: f32,
-    v: f32,
+    h: Float,
+    s: Float,
+    v: Float,
 }
 
 impl Hsv {
     /// Takes in values in the range of 0.0 ..= 1.0 and returns a HSV color.
-    pub fn new(hue: f32, saturation: f32, value: f32) -> Self {
+    pub fn new(hue: Float, saturation: Float, value: Float) -> Self {
         let hue = if hue.is_nan() { 0. } else { hue };
         let saturation = if saturation.is_nan() { 0. } else { saturation };
         let value = if value.is_nan() { 0. } else { value };
@@ -28,43 +28,43 @@ impl Hsv {
 
     #[inline(always)]
     /// Returns Hue in the range of 0.0 ..= 1.0
-    pub fn h(&self) -> f32 {
+    pub fn h(&self) -> Float {
         self.h
     }
 
     #[inline(always)]
     /// Returns Saturation in the range of 0.0 ..= 1.0
-    pub fn s(&self) -> f32 {
+    pub fn s(&self) -> Float {
         self.s
     }
 
     #[inline(always)]
     /// Returns Value in the range of 0.0 ..= 1.0
-    pub fn v(&self) -> f32 {
+    pub fn v(&self) -> Float {
         self.v
     }
 
     /// Returns Hue in the range of 0.0 ..= 360.0
-    pub fn h_scaled(&self) -> f32 {
+    pub fn h_scaled(&self) -> Float {
         self.h * 360.
     }
 
     /// Returns Saturation in the range of 0.0 ..= 100.0
-    pub fn s_scaled(&self) -> f32 {
+    pub fn s_scaled(&self) -> Float {
         self.s * 100.
     }
 
     /// Returns Value in the range of 0.0 ..= 100.0
-    pub fn v_scaled(&self) -> f32 {
+    pub fn v_scaled(&self) -> Float {
         self.v * 100.
     }
 
-    pub fn offset_hue(&mut self, offset: f32) {
-        self.h = math::wrap_f32(self.h + offset);
+    pub fn offset_hue(&mut self, offset: Float) {
+        self.h = math::wrap_float(self.h + offset);
     }
 
-    pub fn offset_saturation(&mut self, offset: f32) {
-        self.s = math::wrap_f32(self.s + offset);
+    pub fn offset_saturation(&mut self, offset: Float) {
+        self.s = math::wrap_float(self.s + offset);
     }
 }
 
@@ -85,9 +85,9 @@ impl From<Color32> for Hsv {
 impl From<Hsv> for Hsva {
     fn from(hsv: Hsv) -> Self {
         Hsva {
-            h: hsv.h(),
-            s: hsv.s(),
-            v: hsv.v(),
+            h: math::to_f32(hsv.h()),
+            s: math::to_f32(hsv.s()),
+            v: math::to_f32(hsv.v()),
             a: 1.,
         }
     }
@@ -95,7 +95,7 @@ impl From<Hsv> for Hsva {
 
 impl From<Hsva> for Hsv {
     fn from(hsv: Hsva) -> Self {
-        Self::new(hsv.h, hsv.s, hsv.v)
+        Self::new(hsv.h as Float, hsv.s as Float, hsv.v as Float)
     }
 }
 
@@ -124,6 +124,8 @@ impl From<Color> for Hsv {
             Color::xyY(c, ws) => Xyz::from(c).to_rgb(ws).into(),
             Color::Luv(c, ws) => Xyz::from(c).to_rgb(ws).into(),
             Color::LchUV(c, ws) => Xyz::from(c).to_rgb(ws).into(),
+            Color::Hsluv(c, ws) => c.to_xyz(ws).to_rgb(ws).into(),
+            Color::Hpluv(c, ws) => c.to_xyz(ws).to_rgb(ws).into(),
             Color::Lab(c, ws, illuminant) => c.to_xyz(illuminant).to_rgb(ws).into(),
             Color::LchAB(c, ws, illuminant) => c.to_xyz(illuminant).to_rgb(ws).into(),
             Color::Color32(c) => Rgb::from(c).into(),
@@ -173,15 +175,15 @@ impl From<Rgb> for Hsv {
 
         let h = if delta == 0. {
             0.
-        } else if math::eq_f32(max, r) {
+        } else if math::eq_float(max, r) {
             (g - b) / (delta * 6.)
-        } else if math::eq_f32(max, g) {
+        } else if math::eq_float(max, g) {
             1. / 3. + (b - r) / (delta * 6.)
         } else {
             2. / 3. + (r - g) / (delta * 6.)
         };
 
-        let h = if h < 0. { math::wrap_f32(h) } else { h }; // wrap
+        let h = if h < 0. { math::wrap_float(h) } else { h }; // wrap
 
         let v = max;
         let s = if v == 0. { 0. } else { 1. - min / max };
@@ -222,7 +224,14 @@ mod tests {
                 let expected = Hsv::new($h, $s, $v);
                 let hsl = Hsl::new($hh, $ss, $l);
                 let got = Hsv::from(hsl);
-                assert_eq!(got, expected);
+                assert!(
+                    (got.h() - expected.h()).abs() < 1e-6
+                        && (got.s() - expected.s()).abs() < 1e-6
+                        && (got.v() - expected.v()).abs() < 1e-6,
+                    "{:?} != {:?}",
+                    got,
+                    expected
+                );
             };
         }
 
@@ -0,0 +1,142 @@
+use crate::{
+    color::{hsv::Hsv, rgb::Rgb, Color},
+    math::Float,
+};
+use serde::{Deserialize, Serialize};
+
+/// HWB as used by the CSS `hwb()` function, a hue with an amount of white and black mixed in.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Hwb {
+    h: Float,
+    w: Float,
+    b: Float,
+}
+
+impl Hwb {
+    /// Takes in values in the range of 0.0 ..= 1.0 and returns a HWB color.
+    pub fn new(hue: Float, whiteness: Float, blackness: Float) -> Self {
+        let hue = if hue.is_nan() { 0. } else { hue };
+        let whiteness = if whiteness.is_nan() { 0. } else { whiteness };
+        let blackness = if blackness.is_nan() { 0. } else { blackness };
+        Self {
+            h: hue,
+            w: whiteness,
+            b: blackness,
+        }
+    }
+
+    #[inline(always)]
+    /// Returns Hue in the range of 0.0 ..= 1.0
+    pub fn h(&self) -> Float {
+        self.h
+    }
+
+    #[inline(always)]
+    /// Returns Whiteness in the range of 0.0 ..= 1.0
+    pub fn w(&self) -> Float {
+        self.w
+    }
+
+    #[inline(always)]
+    /// Returns Blackness in the range of 0.0 ..= 1.0
+    pub fn b(&self) -> Float {
+        self.b
+    }
+
+    /// Returns Hue in the range of 0.0 ..= 360.0
+    pub fn h_scaled(&self) -> Float {
+        self.h * 360.
+    }
+
+    /// Returns Whiteness in the range of 0.0 ..= 100.0
+    pub fn w_scaled(&self) -> Float {
+        self.w * 100.
+    }
+
+    /// Returns Blackness in the range of 0.0 ..= 100.0
+    pub fn b_scaled(&self) -> Float {
+        self.b * 100.
+    }
+}
+
+//####################################################################################################
+
+impl From<Color> for Hwb {
+    fn from(c: Color) -> Hwb {
+        Hsv::from(c).into()
+    }
+}
+
+impl From<&Color> for Hwb {
+    fn from(c: &Color) -> Self {
+        (*c).into()
+    }
+}
+
+impl From<Hwb> for Color {
+    fn from(c: Hwb) -> Color {
+        Color::Hsv(c.into())
+    }
+}
+
+impl From<Hsv> for Hwb {
+    fn from(color: Hsv) -> Self {
+        Hwb::new(color.h(), (1. - color.s()) * color.v(), 1. - color.v())
+    }
+}
+
+impl From<Hwb> for Hsv {
+    fn from(color: Hwb) -> Self {
+        let mut w = color.w();
+        let mut b = color.b();
+        // as in CSS, whiteness and blackness adding up to more than 100% result in a gray
+        if w + b > 1. {
+            let sum = w + b;
+            w /= sum;
+            b /= sum;
+        }
+
+        let v = 1. - b;
+        let s = if v == 0. { 0. } else { 1. - w / v };
+
+        Hsv::new(color.h(), s, v)
+    }
+}
+
+impl From<Rgb> for Hwb {
+    fn from(rgb: Rgb) -> Self {
+        Hsv::from(rgb).into()
+    }
+}
+
+//####################################################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::{Hsv, Hwb, Rgb};
+    #[test]
+    fn rgb_to_hwb() {
+        macro_rules! test_case {
+            (Rgb: $r:expr, $g:expr, $b:expr ;Hwb: $h:expr, $w:expr, $bb:expr) => {
+                let expected = Hwb::new($h, $w, $bb);
+                let rgb = Rgb::new($r, $g, $b);
+                let got = Hwb::from(rgb);
+                assert_eq!(got, expected);
+                assert_eq!(Rgb::from(Hsv::from(got)), rgb);
+            };
+        }
+
+        test_case!(Rgb: 0., 0., 0.; Hwb: 0., 0., 1.);
+        test_case!(Rgb: 1., 1., 1.; Hwb: 0., 1., 0.);
+        test_case!(Rgb: 1., 0., 0.; Hwb: 0., 0., 0.);
+        test_case!(Rgb: 0.5, 1., 0.5; Hwb: 1./3., 0.5, 0.);
+        test_case!(Rgb: 0., 0., 0.5; Hwb: 2./3., 0., 0.5);
+    }
+
+    #[test]
+    fn normalizes_grays() {
+        let hsv = Hsv::from(Hwb::new(0.5, 0.6, 0.6));
+        assert_eq!(hsv.s(), 0.);
+        assert_eq!(hsv.v(), 0.5);
+    }
+}
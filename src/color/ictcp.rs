@@ -0,0 +1,120 @@
+use crate::{
+    color::{
+        hdr::{pq_decode, pq_encode, xyz_to_bt2020_matrix, PQ_PEAK_NITS},
+        Xyz,
+    },
+    math::{Float, Matrix1x3, Matrix3},
+};
+
+use serde::{Deserialize, Serialize};
+
+#[rustfmt::skip]
+fn xyz_to_lms_matrix() -> Matrix3 {
+    let lms = Matrix3::from([
+        [1688. / 4096., 2146. / 4096.,  262. / 4096.],
+        [ 683. / 4096., 2951. / 4096.,  462. / 4096.],
+        [  99. / 4096.,  309. / 4096., 3688. / 4096.],
+    ]);
+    lms * xyz_to_bt2020_matrix()
+}
+
+#[rustfmt::skip]
+fn lms_to_ictcp_matrix() -> Matrix3 {
+    Matrix3::from([
+        [ 2048. / 4096.,   2048. / 4096.,    0. / 4096.],
+        [ 6610. / 4096., -13613. / 4096., 7003. / 4096.],
+        [17933. / 4096., -17390. / 4096., -543. / 4096.],
+    ])
+}
+
+/// ICtCp as defined by ITU-R BT.2100 using the PQ transfer function. Intensity is in the range
+/// of 0.0 ..= 1.0 where 1.0 is 10000 cd/m², the chroma components are roughly in -0.5 ..= 0.5.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ICtCp {
+    i: Float,
+    ct: Float,
+    cp: Float,
+}
+
+impl ICtCp {
+    pub fn new(i: Float, ct: Float, cp: Float) -> Self {
+        let i = if i.is_nan() { 0. } else { i };
+        let ct = if ct.is_nan() { 0. } else { ct };
+        let cp = if cp.is_nan() { 0. } else { cp };
+
+        Self { i, ct, cp }
+    }
+
+    #[inline(always)]
+    /// Returns Intensity
+    pub fn i(&self) -> Float {
+        self.i
+    }
+
+    #[inline(always)]
+    /// Returns the blue-yellow Chroma component
+    pub fn ct(&self) -> Float {
+        self.ct
+    }
+
+    #[inline(always)]
+    /// Returns the red-green Chroma component
+    pub fn cp(&self) -> Float {
+        self.cp
+    }
+
+    /// Takes in a color relative to the D65 white and the luminance in cd/m² that Y = 1 maps to.
+    pub fn from_xyz(color: Xyz, reference_white: Float) -> Self {
+        let lms = xyz_to_lms_matrix() * Matrix1x3::from(color);
+        let scale = reference_white / PQ_PEAK_NITS;
+        let lms = Matrix1x3::from([
+            pq_encode(lms[0] * scale),
+            pq_encode(lms[1] * scale),
+            pq_encode(lms[2] * scale),
+        ]);
+        let ictcp = lms_to_ictcp_matrix() * lms;
+
+        ICtCp::new(ictcp[0], ictcp[1], ictcp[2])
+    }
+
+    /// Returns a color relative to the D65 white where Y = 1 is `reference_white` cd/m².
+    pub fn to_xyz(self, reference_white: Float) -> Xyz {
+        let lms = lms_to_ictcp_matrix().inverse().expect("inverse matrix")
+            * Matrix1x3::from([self.i, self.ct, self.cp]);
+        let scale = PQ_PEAK_NITS / reference_white;
+        let lms = Matrix1x3::from([
+            pq_decode(lms[0]) * scale,
+            pq_decode(lms[1]) * scale,
+            pq_decode(lms[2]) * scale,
+        ]);
+        let xyz = xyz_to_lms_matrix().inverse().expect("inverse matrix") * lms;
+
+        Xyz::new(xyz[0], xyz[1], xyz[2])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::hdr::DEFAULT_REFERENCE_WHITE;
+
+    #[test]
+    fn xyz_to_ictcp() {
+        let white = Xyz::new(0.95047, 1., 1.08883);
+        // a diffuse white of 100 cd/m² is at about half of the PQ signal range
+        let got = ICtCp::from_xyz(white, 100.);
+        assert!((got.i() - 0.5081).abs() < 1e-3, "{:?}", got);
+        assert!(got.ct().abs() < 1e-3 && got.cp().abs() < 1e-3, "{:?}", got);
+
+        let red = Xyz::new(0.4124564, 0.2126729, 0.0193339);
+        let got = ICtCp::from_xyz(red, DEFAULT_REFERENCE_WHITE);
+        assert!((got.i() - 0.4279).abs() < 1e-3, "{:?}", got);
+        assert!((got.ct() + 0.1157).abs() < 1e-3, "{:?}", got);
+        assert!((got.cp() - 0.2787).abs() < 1e-3, "{:?}", got);
+
+        let back = got.to_xyz(DEFAULT_REFERENCE_WHITE);
+        assert!((back.x() - red.x()).abs() < 1e-3);
+        assert!((back.y() - red.y()).abs() < 1e-3);
+        assert!((back.z() - red.z()).abs() < 1e-3);
+    }
+}
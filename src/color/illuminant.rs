@@ -1,4 +1,4 @@
-use crate::color::Xyz;
+use crate::{color::Xyz, math::Float};
 
 use serde::{Deserialize, Serialize};
 
@@ -34,10 +34,10 @@ impl Illuminant {
             Illuminant::F11 => Xyz::new(1.00962, 1., 0.64350),
         }
     }
-    pub fn reference_u(&self) -> f32 {
+    pub fn reference_u(&self) -> Float {
         self.xyz().u()
     }
-    pub fn reference_v(&self) -> f32 {
+    pub fn reference_v(&self) -> Float {
         self.xyz().v()
     }
 }
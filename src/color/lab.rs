@@ -1,16 +1,19 @@
-use crate::color::{illuminant::Illuminant, lch_ab::LchAB, Xyz, CIE_E, CIE_K};
+use crate::{
+    color::{illuminant::Illuminant, lch_ab::LchAB, Xyz, CIE_E, CIE_K},
+    math::Float,
+};
 
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Lab {
-    l: f32,
-    a: f32,
-    b: f32,
+    l: Float,
+    a: Float,
+    b: Float,
 }
 
 impl Lab {
-    pub fn new(l: f32, a: f32, b: f32) -> Self {
+    pub fn new(l: Float, a: Float, b: Float) -> Self {
         let l = if l.is_nan() { 0. } else { l };
         let a = if a.is_nan() { 0. } else { a };
         let b = if b.is_nan() { 0. } else { b };
@@ -20,19 +23,19 @@ impl Lab {
 
     #[inline(always)]
     /// Returns Light
-    pub fn l(&self) -> f32 {
+    pub fn l(&self) -> Float {
         self.l
     }
 
     #[inline(always)]
     /// Returns A coordinate
-    pub fn a(&self) -> f32 {
+    pub fn a(&self) -> Float {
         self.a
     }
 
     #[inline(always)]
     /// Returns B coordinate
-    pub fn b(&self) -> f32 {
+    pub fn b(&self) -> Float {
         self.b
     }
 
@@ -43,7 +46,7 @@ impl Lab {
         let y = color.y() / ref_xyz.y();
         let z = color.z() / ref_xyz.z();
 
-        fn f(num: f32) -> f32 {
+        fn f(num: Float) -> Float {
             if num > CIE_E {
                 num.cbrt()
             } else {
@@ -63,8 +66,12 @@ impl Lab {
     }
 
     pub fn to_xyz(self, reference_white: Illuminant) -> Xyz {
-        let ref_xyz = reference_white.xyz();
+        self.to_xyz_relative_to(reference_white.xyz())
+    }
 
+    /// Same as [`Lab::to_xyz`] but relative to an arbitrary white point, for example one computed
+    /// from spectral data.
+    pub fn to_xyz_relative_to(self, ref_xyz: Xyz) -> Xyz {
         let fy = (self.l + 16.) / 116.;
         let fz = fy - (self.b / 200.);
         let fx = fy + (self.a / 500.);
@@ -112,6 +119,8 @@ mod tests {
         let lch_ab = LchAB::from(inp);
         let got = Lab::from(lch_ab);
 
-        assert_eq!(got, inp);
+        assert!((got.l() - inp.l()).abs() < 1e-4);
+        assert!((got.a() - inp.a()).abs() < 1e-4);
+        assert!((got.b() - inp.b()).abs() < 1e-4);
     }
 }
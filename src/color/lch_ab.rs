@@ -1,16 +1,19 @@
-use crate::color::{Illuminant, Lab, Xyz};
+use crate::{
+    color::{Illuminant, Lab, Xyz},
+    math::Float,
+};
 
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub struct LchAB {
-    l: f32,
-    c: f32,
-    h: f32,
+    l: Float,
+    c: Float,
+    h: Float,
 }
 
 impl LchAB {
-    pub fn new(l: f32, c: f32, h: f32) -> Self {
+    pub fn new(l: Float, c: Float, h: Float) -> Self {
         let l = if l.is_nan() { 0. } else { l };
         let c = if c.is_nan() { 0. } else { c };
         let h = if h.is_nan() { 0. } else { h };
@@ -20,19 +23,19 @@ impl LchAB {
 
     #[inline(always)]
     /// Returns Light
-    pub fn l(&self) -> f32 {
+    pub fn l(&self) -> Float {
         self.l
     }
 
     #[inline(always)]
     /// Returns Chroma
-    pub fn c(&self) -> f32 {
+    pub fn c(&self) -> Float {
         self.c
     }
 
     #[inline(always)]
     /// Returns Hue in the range of 0.0 ..= 360.0
-    pub fn h(&self) -> f32 {
+    pub fn h(&self) -> Float {
         self.h
     }
 
@@ -49,7 +52,7 @@ impl LchAB {
 
 impl From<Lab> for LchAB {
     fn from(color: Lab) -> Self {
-        let arctan_ba = f32::atan2(color.b(), color.a()).to_degrees();
+        let arctan_ba = Float::atan2(color.b(), color.a()).to_degrees();
         let l = color.l();
         let c = (color.a().powi(2) + color.b().powi(2)).sqrt();
         let h = if arctan_ba >= 0. {
@@ -1,16 +1,19 @@
-use crate::color::{Luv, Xyz};
+use crate::{
+    color::{Luv, Xyz},
+    math::Float,
+};
 
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub struct LchUV {
-    l: f32,
-    c: f32,
-    h: f32,
+    l: Float,
+    c: Float,
+    h: Float,
 }
 
 impl LchUV {
-    pub fn new(l: f32, c: f32, h: f32) -> Self {
+    pub fn new(l: Float, c: Float, h: Float) -> Self {
         let l = if l.is_nan() { 0. } else { l };
         let c = if c.is_nan() { 0. } else { c };
         let h = if h.is_nan() { 0. } else { h };
@@ -20,19 +23,19 @@ impl LchUV {
 
     #[inline(always)]
     /// Returns Light
-    pub fn l(&self) -> f32 {
+    pub fn l(&self) -> Float {
         self.l
     }
 
     #[inline(always)]
     /// Returns Chroma
-    pub fn c(&self) -> f32 {
+    pub fn c(&self) -> Float {
         self.c
     }
 
     #[inline(always)]
     /// Returns Hue in the range of 0.0 ..= 360.0
-    pub fn h(&self) -> f32 {
+    pub fn h(&self) -> Float {
         self.h
     }
 }
@@ -45,7 +48,7 @@ impl From<Luv> for LchUV {
         let u = color.u();
         let v = color.v();
         let c = (u.powi(2) + v.powi(2)).sqrt();
-        let vu_atan = f32::atan2(v, u).to_degrees();
+        let vu_atan = Float::atan2(v, u).to_degrees();
         let h = if vu_atan >= 0. {
             vu_atan
         } else {
@@ -1,16 +1,19 @@
-use crate::color::{illuminant::Illuminant, LchUV, Xyz, CIE_E, CIE_K};
+use crate::{
+    color::{illuminant::Illuminant, LchUV, Xyz, CIE_E, CIE_K},
+    math::Float,
+};
 
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Luv {
-    l: f32,
-    u: f32,
-    v: f32,
+    l: Float,
+    u: Float,
+    v: Float,
 }
 
 impl Luv {
-    pub fn new(l: f32, u: f32, v: f32) -> Self {
+    pub fn new(l: Float, u: Float, v: Float) -> Self {
         let l = if l.is_nan() { 0. } else { l };
         let u = if u.is_nan() { 0. } else { u };
         let v = if v.is_nan() { 0. } else { v };
@@ -20,19 +23,19 @@ impl Luv {
 
     #[inline(always)]
     /// Returns Light
-    pub fn l(&self) -> f32 {
+    pub fn l(&self) -> Float {
         self.l
     }
 
     #[inline(always)]
     /// Returns U coordinate
-    pub fn u(&self) -> f32 {
+    pub fn u(&self) -> Float {
         self.u
     }
 
     #[inline(always)]
     /// Returns V coordinate
-    pub fn v(&self) -> f32 {
+    pub fn v(&self) -> Float {
         self.v
     }
 }
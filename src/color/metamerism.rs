@@ -0,0 +1,167 @@
+use crate::{
+    color::{
+        spectral::{Observer, Spectrum, WAVELENGTH_MIN},
+        CIEColor, ChromaticAdaptationMethod, Color, DeltaE, Illuminant, Lab, RgbWorkingSpace, Xyz,
+    },
+    math::{Float, Matrix1x3, Matrix3},
+};
+
+const BASIS_INTERVAL: Float = 10.;
+const BASIS_LEN: usize = 41;
+
+/// Smooth basis used to reconstruct reflectances of colors without spectral data: a constant,
+/// a linear slope and a bump in the middle of the visible spectrum. Including the constant
+/// makes neutral colors reconstruct to flat reflectances that never shift with the illuminant.
+fn basis() -> [Spectrum; 3] {
+    let wavelength = |i: usize| WAVELENGTH_MIN + i as Float * BASIS_INTERVAL;
+    let spectrum = |f: &dyn Fn(Float) -> Float| {
+        Spectrum::new(
+            WAVELENGTH_MIN,
+            BASIS_INTERVAL,
+            (0..BASIS_LEN).map(|i| f(wavelength(i))).collect(),
+        )
+    };
+    [
+        spectrum(&|_| 1.),
+        spectrum(&|l| (l - 580.) / 200.),
+        spectrum(&|l| (-((l - 540.) / 60.).powi(2)).exp()),
+    ]
+}
+
+/// Reconstructs a plausible reflectance of a surface that has the tristimulus values `xyz`
+/// when lit by `illuminant`.
+pub fn reconstruct_reflectance(xyz: Xyz, illuminant: &Spectrum, observer: Observer) -> Spectrum {
+    let basis = basis();
+    let responses: Vec<_> = basis
+        .iter()
+        .map(|b| b.to_xyz(illuminant, observer))
+        .collect();
+    let m = Matrix3::from([
+        [responses[0].x(), responses[1].x(), responses[2].x()],
+        [responses[0].y(), responses[1].y(), responses[2].y()],
+        [responses[0].z(), responses[1].z(), responses[2].z()],
+    ]);
+    let weights = match m.inverse() {
+        Some(inverse) => inverse * Matrix1x3::from(xyz),
+        None => Matrix1x3::from([xyz.y(), 0., 0.]),
+    };
+
+    let values = (0..BASIS_LEN)
+        .map(|i| {
+            basis
+                .iter()
+                .enumerate()
+                .map(|(j, b)| weights[j] * b.values()[i])
+                .sum()
+        })
+        .collect();
+    Spectrum::new(WAVELENGTH_MIN, BASIS_INTERVAL, values)
+}
+
+/// A color as seen under the source and the target illuminant of an [`IlluminantChange`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorShift {
+    pub source: Color,
+    pub target: Color,
+    pub delta_e: Float,
+}
+
+/// Simulates how surface colors change when the lighting changes from `source` to `target`.
+///
+/// The reflectance of every color is reconstructed under the source illuminant and lit by the
+/// target one. The result is adapted back to the white of the working space, so it shows what
+/// an observer adapted to the target lighting sees. A perfect color constant surface results in
+/// the same color with a ΔE of 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IlluminantChange {
+    pub source: Illuminant,
+    pub target: Illuminant,
+    pub observer: Observer,
+    pub method: ChromaticAdaptationMethod,
+    pub metric: DeltaE,
+}
+
+impl IlluminantChange {
+    /// Returns `None` if spectral data is missing for either of the illuminants.
+    pub fn apply(&self, color: &Color, ws: RgbWorkingSpace) -> Option<ColorShift> {
+        let source_spd = self.source.spd()?;
+        let target_spd = self.target.spd()?;
+        let source_white = source_spd.white_point(self.observer);
+        let target_white = target_spd.white_point(self.observer);
+        let ws_illuminant = ws.reference_illuminant();
+        let ws_white = ws_illuminant.xyz();
+
+        let xyz = color.xyz(ws);
+        let reflectance = reconstruct_reflectance(
+            xyz.adapt(self.method, ws_white, source_white),
+            &source_spd,
+            self.observer,
+        );
+        let shifted = reflectance.to_xyz(&target_spd, self.observer).adapt(
+            self.method,
+            target_white,
+            ws_white,
+        );
+
+        Some(ColorShift {
+            source: *color,
+            target: Color::Rgb(shifted.to_rgb(ws)),
+            delta_e: self.metric.compute(
+                Lab::from_xyz(xyz, ws_illuminant),
+                Lab::from_xyz(shifted, ws_illuminant),
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Rgb;
+
+    fn change(source: Illuminant, target: Illuminant) -> IlluminantChange {
+        IlluminantChange {
+            source,
+            target,
+            observer: Observer::Cie1931,
+            method: ChromaticAdaptationMethod::Bradford,
+            metric: DeltaE::Ciede2000,
+        }
+    }
+
+    #[test]
+    fn reconstructs_reflectance() {
+        let d65 = Illuminant::D65.spd().unwrap();
+        let xyz = Xyz::new(0.3, 0.25, 0.1);
+        let got =
+            reconstruct_reflectance(xyz, &d65, Observer::Cie1931).to_xyz(&d65, Observer::Cie1931);
+        assert!((got.x() - xyz.x()).abs() < 1e-4);
+        assert!((got.y() - xyz.y()).abs() < 1e-4);
+        assert!((got.z() - xyz.z()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn shifts_colors() {
+        let ws = RgbWorkingSpace::SRGB;
+        let gray = Color::Rgb(Rgb::new(0.5, 0.5, 0.5));
+        let brand = Color::Rgb(Rgb::new(0.8, 0.2, 0.6));
+
+        let same = change(Illuminant::D65, Illuminant::D65)
+            .apply(&brand, ws)
+            .unwrap();
+        assert!(same.delta_e < 0.1, "{}", same.delta_e);
+
+        for target in [Illuminant::A, Illuminant::F11] {
+            let shift = change(Illuminant::D65, target).apply(&gray, ws).unwrap();
+            assert!(shift.delta_e < 0.1, "{}", shift.delta_e);
+
+            let shift = change(Illuminant::D65, target).apply(&brand, ws).unwrap();
+            assert!(shift.delta_e > 1., "{}", shift.delta_e);
+            assert_eq!(shift.source, brand);
+        }
+
+        assert!(change(Illuminant::D65, Illuminant::C)
+            .apply(&gray, ws)
+            .is_none());
+    }
+}
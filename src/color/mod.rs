@@ -1,48 +1,86 @@
+mod cam16;
+mod cgats;
 mod chromatic_adaptation;
 mod cmyk;
+mod delta_e;
 mod format;
 mod gradient;
+mod hdr;
 mod hsl;
+mod hsluv;
 mod hsv;
+mod hwb;
+mod ictcp;
 mod illuminant;
 mod lab;
 mod lch_ab;
 mod lch_uv;
 mod luv;
+mod metamerism;
+mod names;
 mod palette;
+mod palette_dir;
 mod palettes;
+mod platform;
+#[cfg(test)]
+mod reference_tests;
 mod rgb;
+mod sheet;
+mod spectral;
+mod swatches;
+mod tokens;
 mod working_space;
 mod xyy;
 mod xyz;
+mod ycbcr;
 
-pub use format::CustomPaletteFormat;
+pub use format::{
+    complete_field, format_fields, highlight_format, ColorMatcher, CustomColorFormat,
+    CustomPaletteFormat, FieldHelp, FormatKind, FormatOptions, FormatSpanKind, TemplateError,
+    FUNCTIONS,
+};
 pub use gradient::Gradient;
-pub use palette::{NamedPalette, Palette, PaletteFormat};
-pub use palettes::Palettes;
-
+pub use hdr::{code_value, HdrColor, TransferFunction, DEFAULT_REFERENCE_WHITE, PQ_PEAK_NITS};
+pub use palette::{NamedPalette, Palette, PaletteFilter, PaletteFormat, PaletteMeta, PaletteSort};
+pub use palette_dir::{PaletteDir, PaletteFileFormat};
+pub use palettes::{PaletteBackup, Palettes};
+pub use platform::{PlatformExport, PlatformFormat, XcodeColorSpace};
+
+pub use cam16::{Cam16, Cam16Ucs, Surround, ViewingConditions};
+pub use cgats::Cgats;
 pub use chromatic_adaptation::ChromaticAdaptationMethod;
 pub use cmyk::Cmyk;
+pub use delta_e::DeltaE;
 pub use hsl::Hsl;
+pub use hsluv::{Hpluv, Hsluv};
 pub use hsv::Hsv;
+pub use hwb::Hwb;
+pub use ictcp::ICtCp;
 pub use illuminant::Illuminant;
 pub use lab::Lab;
 pub use lch_ab::LchAB;
 pub use lch_uv::LchUV;
 pub use luv::Luv;
+pub use metamerism::IlluminantChange;
+pub use names::{nearest_name, parse_color, ColorNames, COLOR_NAMES};
 pub use rgb::Rgb;
+pub use sheet::{SheetFormat, SheetLayout, SwatchSheet};
+pub use spectral::Observer;
+pub use swatches::{import_extensions, import_palette_file, SwatchFormat};
+pub use tokens::{import_tokens, TokenCase, TokenExport, TokenFormat, TokenNameSource, TokenValue};
 pub use working_space::RgbWorkingSpace;
 pub use xyy::xyY;
 pub use xyz::Xyz;
+pub use ycbcr::{YCbCr, YCbCrRange, YCbCrStandard};
 
-use crate::color::format::CustomColorFormat;
+use crate::math::Float;
 use egui::color::{Color32, Hsva, HsvaGamma, Rgba};
 use serde::{Deserialize, Serialize};
 
-pub const CIE_E: f32 = 216. / 24389.;
-pub const CIE_K: f32 = 24389. / 27.;
-pub const U8_MAX: f32 = u8::MAX as f32;
-pub const U8_MIN: f32 = u8::MIN as f32;
+pub const CIE_E: Float = 216. / 24389.;
+pub const CIE_K: Float = 24389. / 27.;
+pub const U8_MAX: Float = u8::MAX as Float;
+pub const U8_MIN: Float = u8::MIN as Float;
 
 //################################################################################
 
@@ -158,6 +196,8 @@ pub enum Color {
     xyY(xyY, RgbWorkingSpace),
     Luv(Luv, RgbWorkingSpace),
     LchUV(LchUV, RgbWorkingSpace),
+    Hsluv(Hsluv, RgbWorkingSpace),
+    Hpluv(Hpluv, RgbWorkingSpace),
     Lab(Lab, RgbWorkingSpace, Illuminant),
     LchAB(LchAB, RgbWorkingSpace, Illuminant),
     Color32(Color32),
@@ -172,7 +212,7 @@ impl Color {
         Self::Rgb(Rgb::new(1., 1., 1.))
     }
 
-    pub fn intensity(&self) -> f32 {
+    pub fn intensity(&self) -> Float {
         let rgb = self.rgb();
         0.215 * rgb.r() + 0.7 * rgb.g() + 0.085 * rgb.b()
     }
@@ -222,20 +262,15 @@ impl Color {
         )
     }
 
-    pub fn display(
-        &self,
-        format: ColorFormat,
-        ws: RgbWorkingSpace,
-        illuminant: Illuminant,
-    ) -> String {
+    pub fn display(&self, format: ColorFormat, options: &FormatOptions) -> String {
         match format {
             ColorFormat::Hex => self.as_hex(),
             ColorFormat::HexUpercase => self.as_hex().to_uppercase(),
             ColorFormat::CssRgb => self.as_css_rgb(),
             ColorFormat::CssHsl { degree_symbol } => self.as_css_hsl(degree_symbol),
             ColorFormat::Custom(fmt) => {
-                if let Ok(fmt) = CustomColorFormat::parse(fmt) {
-                    fmt.format_color(self, ws, illuminant).unwrap_or_default()
+                if let Ok(fmt) = CustomColorFormat::cached(fmt) {
+                    fmt.format_color(self, options).unwrap_or_default()
                 } else {
                     self.as_hex()
                 }
@@ -247,13 +282,13 @@ impl Color {
         parse_hex(hex).map(|(r, g, b)| Rgb::new_scaled(r, g, b).into())
     }
 
-    pub fn as_hue_offset(&self, offset: f32) -> Color {
+    pub fn as_hue_offset(&self, offset: Float) -> Color {
         let mut hsv = self.hsv();
         hsv.offset_hue(offset);
         Self::Hsv(hsv)
     }
 
-    pub fn as_saturation_offset(&self, offset: f32) -> Color {
+    pub fn as_saturation_offset(&self, offset: Float) -> Color {
         let mut hsv = self.hsv();
         hsv.offset_saturation(offset);
         Self::Hsv(hsv)
@@ -268,7 +303,7 @@ impl Color {
         )
     }
 
-    pub fn as_rgb_triplet(&self) -> (f32, f32, f32) {
+    pub fn as_rgb_triplet(&self) -> (Float, Float, Float) {
         let color = self.rgb();
         (color.r(), color.g(), color.b())
     }
@@ -297,6 +332,87 @@ impl Color {
         self.into()
     }
 
+    pub fn hwb(&self) -> Hwb {
+        self.into()
+    }
+
+    pub fn ycbcr(&self, standard: YCbCrStandard) -> YCbCr {
+        YCbCr::from_rgb(self.rgb(), standard)
+    }
+
+    /// Returns ICtCp with the white of the working space shown at `reference_white` cd/m².
+    pub fn ictcp(
+        &self,
+        ws: RgbWorkingSpace,
+        method: ChromaticAdaptationMethod,
+        reference_white: Float,
+    ) -> ICtCp {
+        ICtCp::from_xyz(self.xyz_d65(ws, method), reference_white)
+    }
+
+    /// Creates a color from ICtCp with the white of the working space shown at
+    /// `reference_white` cd/m².
+    pub fn from_ictcp(
+        color: ICtCp,
+        ws: RgbWorkingSpace,
+        method: ChromaticAdaptationMethod,
+        reference_white: Float,
+    ) -> Color {
+        Color::Rgb(Color::xyz_from_d65(color.to_xyz(reference_white), ws, method).to_rgb(ws))
+    }
+
+    /// Returns the light emitted by a display showing the white of the working space at
+    /// `reference_white` cd/m².
+    pub fn hdr(
+        &self,
+        ws: RgbWorkingSpace,
+        method: ChromaticAdaptationMethod,
+        reference_white: Float,
+    ) -> HdrColor {
+        HdrColor::from_xyz(self.xyz_d65(ws, method), reference_white)
+    }
+
+    /// Creates a color for previewing HDR light on an SDR display by compressing highlights
+    /// brighter than `reference_white`.
+    pub fn from_hdr_tone_mapped(
+        color: HdrColor,
+        ws: RgbWorkingSpace,
+        method: ChromaticAdaptationMethod,
+        reference_white: Float,
+    ) -> Color {
+        let xyz = Color::xyz_from_d65(color.tone_map(reference_white), ws, method);
+        Color::Rgb(xyz.to_rgb(ws))
+    }
+
+    /// Returns linear RGB of the working space where 1.0 is `reference_white`. Channels of
+    /// colors brighter than the reference white are larger than 1.0.
+    pub fn linear_rgb_from_hdr(
+        color: HdrColor,
+        ws: RgbWorkingSpace,
+        method: ChromaticAdaptationMethod,
+        reference_white: Float,
+    ) -> Rgb {
+        Color::xyz_from_d65(color.to_xyz(reference_white), ws, method).to_linear_rgb(ws)
+    }
+
+    fn xyz_from_d65(xyz: Xyz, ws: RgbWorkingSpace, method: ChromaticAdaptationMethod) -> Xyz {
+        if ws.reference_illuminant() != Illuminant::D65 {
+            xyz.chromatic_adaptation_transform(method, Illuminant::D65, ws.reference_illuminant())
+        } else {
+            xyz
+        }
+    }
+
+    /// Returns XYZ adapted to the D65 white if the working space uses a different one.
+    fn xyz_d65(&self, ws: RgbWorkingSpace, method: ChromaticAdaptationMethod) -> Xyz {
+        let xyz = self.xyz(ws);
+        if ws.reference_illuminant() != Illuminant::D65 {
+            xyz.chromatic_adaptation_transform(method, ws.reference_illuminant(), Illuminant::D65)
+        } else {
+            xyz
+        }
+    }
+
     pub fn lab(
         &self,
         ws: RgbWorkingSpace,
@@ -329,6 +445,78 @@ impl Color {
         Luv::from(Xyz::from_rgb(self.rgb(), ws)).into()
     }
 
+    pub fn hsluv(&self, ws: RgbWorkingSpace) -> Hsluv {
+        // hue and saturation are lost for grays so return the stored value if possible
+        match self {
+            Color::Hsluv(c, color_ws) if *color_ws == ws => *c,
+            _ => Hsluv::from_xyz(self.xyz(ws), ws),
+        }
+    }
+
+    pub fn hpluv(&self, ws: RgbWorkingSpace) -> Hpluv {
+        match self {
+            Color::Hpluv(c, color_ws) if *color_ws == ws => *c,
+            _ => Hpluv::from_xyz(self.xyz(ws), ws),
+        }
+    }
+
+    /// Returns CAM16 with `white` as the adopted white, the color is adapted to it from the white
+    /// of the working space like [`Color::lab`] does.
+    pub fn cam16(
+        &self,
+        ws: RgbWorkingSpace,
+        white: Illuminant,
+        method: ChromaticAdaptationMethod,
+        viewing_conditions: &ViewingConditions,
+    ) -> Cam16 {
+        let xyz = self.xyz(ws);
+        let xyz = if white != ws.reference_illuminant() {
+            xyz.chromatic_adaptation_transform(method, ws.reference_illuminant(), white)
+        } else {
+            xyz
+        };
+        Cam16::from_xyz(xyz, white, viewing_conditions)
+    }
+
+    pub fn cam16_ucs(
+        &self,
+        ws: RgbWorkingSpace,
+        white: Illuminant,
+        method: ChromaticAdaptationMethod,
+        viewing_conditions: &ViewingConditions,
+    ) -> Cam16Ucs {
+        self.cam16(ws, white, method, viewing_conditions).into()
+    }
+
+    /// Creates a color from CAM16 with `white` as the adopted white, the inverse of
+    /// [`Color::cam16`].
+    pub fn from_cam16(
+        color: Cam16,
+        ws: RgbWorkingSpace,
+        white: Illuminant,
+        method: ChromaticAdaptationMethod,
+        viewing_conditions: &ViewingConditions,
+    ) -> Color {
+        let xyz = color.to_xyz(white, viewing_conditions);
+        let xyz = if white != ws.reference_illuminant() {
+            xyz.chromatic_adaptation_transform(method, white, ws.reference_illuminant())
+        } else {
+            xyz
+        };
+        Color::Rgb(xyz.to_rgb(ws))
+    }
+
+    pub fn from_cam16_ucs(
+        color: Cam16Ucs,
+        ws: RgbWorkingSpace,
+        white: Illuminant,
+        method: ChromaticAdaptationMethod,
+        viewing_conditions: &ViewingConditions,
+    ) -> Color {
+        let cam16 = color.to_cam16(white, viewing_conditions);
+        Color::from_cam16(cam16, ws, white, method, viewing_conditions)
+    }
+
     pub fn rgb(&self) -> Rgb {
         self.into()
     }
@@ -345,7 +533,7 @@ impl Color {
         if total == 0 {
             return vec![*self];
         }
-        let mut step_total = total.saturating_sub(1) as f32;
+        let mut step_total = total.saturating_sub(1) as Float;
         if step_total == 0. {
             step_total = 1.;
         }
@@ -354,9 +542,9 @@ impl Color {
         let mut base_r = rgb.r_scaled() as u8;
         let mut base_g = rgb.g_scaled() as u8;
         let mut base_b = rgb.b_scaled() as u8;
-        let step_r = (base_r as f32 / step_total).ceil() as u8;
-        let step_g = (base_g as f32 / step_total).ceil() as u8;
-        let step_b = (base_b as f32 / step_total).ceil() as u8;
+        let step_r = (base_r as Float / step_total).ceil() as u8;
+        let step_g = (base_g as Float / step_total).ceil() as u8;
+        let step_b = (base_b as Float / step_total).ceil() as u8;
 
         (0..total)
             .into_iter()
@@ -374,7 +562,7 @@ impl Color {
         if total == 0 {
             return vec![*self];
         }
-        let mut step_total = total.saturating_sub(1) as f32;
+        let mut step_total = total.saturating_sub(1) as Float;
         if step_total == 0. {
             step_total = 1.;
         }
@@ -382,9 +570,9 @@ impl Color {
         let mut base_r = rgb.r_scaled() as u8;
         let mut base_g = rgb.g_scaled() as u8;
         let mut base_b = rgb.b_scaled() as u8;
-        let step_r = ((U8_MAX - base_r as f32) / step_total).ceil() as u8;
-        let step_g = ((U8_MAX - base_g as f32) / step_total).ceil() as u8;
-        let step_b = ((U8_MAX - base_b as f32) / step_total).ceil() as u8;
+        let step_r = ((U8_MAX - base_r as Float) / step_total).ceil() as u8;
+        let step_g = ((U8_MAX - base_g as Float) / step_total).ceil() as u8;
+        let step_b = ((U8_MAX - base_b as Float) / step_total).ceil() as u8;
 
         (0..total)
             .into_iter()
@@ -398,18 +586,18 @@ impl Color {
             .collect()
     }
 
-    pub fn hues(&self, total: u8, step: f32) -> Vec<Color> {
+    pub fn hues(&self, total: u8, step: Float) -> Vec<Color> {
         let mut colors = Vec::new();
         let hsv = self.hsv();
         for i in (0..=total).rev() {
             let mut _h = hsv;
-            _h.offset_hue(-1. * step * i as f32);
+            _h.offset_hue(-1. * step * i as Float);
             colors.push(_h.into());
         }
 
         for i in 1..=total {
             let mut _h = hsv;
-            _h.offset_hue(1. * step * i as f32);
+            _h.offset_hue(1. * step * i as Float);
             colors.push(_h.into());
         }
 
@@ -497,6 +685,8 @@ impl From<Color> for Color32 {
             Color::xyY(c, ws) => Xyz::from(c).to_rgb(ws).into(),
             Color::Luv(c, ws) => Xyz::from(c).to_rgb(ws).into(),
             Color::LchUV(c, ws) => Xyz::from(c).to_rgb(ws).into(),
+            Color::Hsluv(c, ws) => c.to_xyz(ws).to_rgb(ws).into(),
+            Color::Hpluv(c, ws) => c.to_xyz(ws).to_rgb(ws).into(),
             Color::Lab(c, ws, illuminant) => c.to_xyz(illuminant).to_rgb(ws).into(),
             Color::LchAB(c, ws, illuminant) => c.to_xyz(illuminant).to_rgb(ws).into(),
             Color::Color32(c) => c,
@@ -533,6 +723,8 @@ macro_rules! to_epaint_color {
             Color::xyY(c, ws) => Xyz::from(c).to_rgb(ws).into(),
             Color::Luv(c, ws) => Xyz::from(c).to_rgb(ws).into(),
             Color::LchUV(c, ws) => Xyz::from(c).to_rgb(ws).into(),
+            Color::Hsluv(c, ws) => c.to_xyz(ws).to_rgb(ws).into(),
+            Color::Hpluv(c, ws) => c.to_xyz(ws).to_rgb(ws).into(),
             Color::Lab(c, ws, illuminant) => c.to_xyz(illuminant).to_rgb(ws).into(),
             Color::LchAB(c, ws, illuminant) => c.to_xyz(illuminant).to_rgb(ws).into(),
             Color::Color32(c) => c.into(),
@@ -588,7 +780,7 @@ impl From<Rgb> for Color {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_hex;
+    use super::*;
     #[test]
     fn parses_hex() {
         macro_rules! test_case {
@@ -614,4 +806,42 @@ mod tests {
         test_case!("12abf", None);
         test_case!("12abfff", None);
     }
+
+    #[test]
+    fn adapts_to_the_cam16_white() {
+        let white = Color::white();
+        // the white is only achromatic when fully adapted to
+        let vc = ViewingConditions {
+            discounting: true,
+            ..Default::default()
+        };
+        for method in [
+            ChromaticAdaptationMethod::Bradford,
+            ChromaticAdaptationMethod::VonKries,
+            ChromaticAdaptationMethod::XYZScaling,
+        ] {
+            let ucs = white.cam16_ucs(RgbWorkingSpace::SRGB, Illuminant::D50, method, &vc);
+            assert!(ucs.a().abs() < 1e-2, "a = {} with {:?}", ucs.a(), method);
+            assert!(ucs.b().abs() < 1e-2, "b = {} with {:?}", ucs.b(), method);
+
+            let teal = Color::Rgb(Rgb::new(0.1, 0.5, 0.5));
+            let ucs = teal.cam16_ucs(RgbWorkingSpace::SRGB, Illuminant::D50, method, &vc);
+            let back =
+                Color::from_cam16_ucs(ucs, RgbWorkingSpace::SRGB, Illuminant::D50, method, &vc);
+            let (want, got) = (teal.rgb(), back.rgb());
+            for (want, got) in [
+                (want.r(), got.r()),
+                (want.g(), got.g()),
+                (want.b(), got.b()),
+            ] {
+                assert!(
+                    (want - got).abs() < 1e-3,
+                    "{} != {} with {:?}",
+                    got,
+                    want,
+                    method
+                );
+            }
+        }
+    }
 }
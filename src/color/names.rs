@@ -0,0 +1,484 @@
+use crate::{
+    color::{CIEColor, Color, DeltaE, Illuminant, Lab, Rgb, RgbWorkingSpace, Xyz},
+    math::Float,
+};
+
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::{cmp::Ordering, collections::BTreeMap, fs, path::Path, sync::RwLock};
+
+/// Names used by the tooltips and the `{name}` format symbol. Starts with the built-in names,
+/// additional lists are loaded from the configuration directory on startup.
+pub static COLOR_NAMES: Lazy<RwLock<ColorNames>> = Lazy::new(|| RwLock::new(ColorNames::builtin()));
+
+/// Number of colors closest in Euclidean Lab distance that are compared with the selected
+/// metric. CIE94 and CIEDE2000 are not distances in Lab so the tree can't be searched with them
+/// directly, but for colors this close their ordering rarely differs from the Euclidean one.
+const CANDIDATES: usize = 8;
+
+/// Named colors of CSS Color Module Level 4, these are the X11 colors as adopted by browsers.
+#[rustfmt::skip]
+const CSS_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xF0F8FF), ("antiquewhite", 0xFAEBD7), ("aqua", 0x00FFFF),
+    ("aquamarine", 0x7FFFD4), ("azure", 0xF0FFFF), ("beige", 0xF5F5DC), ("bisque", 0xFFE4C4),
+    ("black", 0x000000), ("blanchedalmond", 0xFFEBCD), ("blue", 0x0000FF),
+    ("blueviolet", 0x8A2BE2), ("brown", 0xA52A2A), ("burlywood", 0xDEB887),
+    ("cadetblue", 0x5F9EA0), ("chartreuse", 0x7FFF00), ("chocolate", 0xD2691E),
+    ("coral", 0xFF7F50), ("cornflowerblue", 0x6495ED), ("cornsilk", 0xFFF8DC),
+    ("crimson", 0xDC143C), ("cyan", 0x00FFFF), ("darkblue", 0x00008B), ("darkcyan", 0x008B8B),
+    ("darkgoldenrod", 0xB8860B), ("darkgray", 0xA9A9A9), ("darkgreen", 0x006400),
+    ("darkgrey", 0xA9A9A9), ("darkkhaki", 0xBDB76B), ("darkmagenta", 0x8B008B),
+    ("darkolivegreen", 0x556B2F), ("darkorange", 0xFF8C00), ("darkorchid", 0x9932CC),
+    ("darkred", 0x8B0000), ("darksalmon", 0xE9967A), ("darkseagreen", 0x8FBC8F),
+    ("darkslateblue", 0x483D8B), ("darkslategray", 0x2F4F4F), ("darkslategrey", 0x2F4F4F),
+    ("darkturquoise", 0x00CED1), ("darkviolet", 0x9400D3), ("deeppink", 0xFF1493),
+    ("deepskyblue", 0x00BFFF), ("dimgray", 0x696969), ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1E90FF), ("firebrick", 0xB22222), ("floralwhite", 0xFFFAF0),
+    ("forestgreen", 0x228B22), ("fuchsia", 0xFF00FF), ("gainsboro", 0xDCDCDC),
+    ("ghostwhite", 0xF8F8FF), ("gold", 0xFFD700), ("goldenrod", 0xDAA520), ("gray", 0x808080),
+    ("green", 0x008000), ("greenyellow", 0xADFF2F), ("grey", 0x808080), ("honeydew", 0xF0FFF0),
+    ("hotpink", 0xFF69B4), ("indianred", 0xCD5C5C), ("indigo", 0x4B0082), ("ivory", 0xFFFFF0),
+    ("khaki", 0xF0E68C), ("lavender", 0xE6E6FA), ("lavenderblush", 0xFFF0F5),
+    ("lawngreen", 0x7CFC00), ("lemonchiffon", 0xFFFACD), ("lightblue", 0xADD8E6),
+    ("lightcoral", 0xF08080), ("lightcyan", 0xE0FFFF), ("lightgoldenrodyellow", 0xFAFAD2),
+    ("lightgray", 0xD3D3D3), ("lightgreen", 0x90EE90), ("lightgrey", 0xD3D3D3),
+    ("lightpink", 0xFFB6C1), ("lightsalmon", 0xFFA07A), ("lightseagreen", 0x20B2AA),
+    ("lightskyblue", 0x87CEFA), ("lightslategray", 0x778899), ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xB0C4DE), ("lightyellow", 0xFFFFE0), ("lime", 0x00FF00),
+    ("limegreen", 0x32CD32), ("linen", 0xFAF0E6), ("magenta", 0xFF00FF), ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66CDAA), ("mediumblue", 0x0000CD), ("mediumorchid", 0xBA55D3),
+    ("mediumpurple", 0x9370DB), ("mediumseagreen", 0x3CB371), ("mediumslateblue", 0x7B68EE),
+    ("mediumspringgreen", 0x00FA9A), ("mediumturquoise", 0x48D1CC),
+    ("mediumvioletred", 0xC71585), ("midnightblue", 0x191970), ("mintcream", 0xF5FFFA),
+    ("mistyrose", 0xFFE4E1), ("moccasin", 0xFFE4B5), ("navajowhite", 0xFFDEAD),
+    ("navy", 0x000080), ("oldlace", 0xFDF5E6), ("olive", 0x808000), ("olivedrab", 0x6B8E23),
+    ("orange", 0xFFA500), ("orangered", 0xFF4500), ("orchid", 0xDA70D6),
+    ("palegoldenrod", 0xEEE8AA), ("palegreen", 0x98FB98), ("paleturquoise", 0xAFEEEE),
+    ("palevioletred", 0xDB7093), ("papayawhip", 0xFFEFD5), ("peachpuff", 0xFFDAB9),
+    ("peru", 0xCD853F), ("pink", 0xFFC0CB), ("plum", 0xDDA0DD), ("powderblue", 0xB0E0E6),
+    ("purple", 0x800080), ("rebeccapurple", 0x663399), ("red", 0xFF0000),
+    ("rosybrown", 0xBC8F8F), ("royalblue", 0x4169E1), ("saddlebrown", 0x8B4513),
+    ("salmon", 0xFA8072), ("sandybrown", 0xF4A460), ("seagreen", 0x2E8B57),
+    ("seashell", 0xFFF5EE), ("sienna", 0xA0522D), ("silver", 0xC0C0C0), ("skyblue", 0x87CEEB),
+    ("slateblue", 0x6A5ACD), ("slategray", 0x708090), ("slategrey", 0x708090),
+    ("snow", 0xFFFAFA), ("springgreen", 0x00FF7F), ("steelblue", 0x4682B4), ("tan", 0xD2B48C),
+    ("teal", 0x008080), ("thistle", 0xD8BFD8), ("tomato", 0xFF6347), ("turquoise", 0x40E0D0),
+    ("violet", 0xEE82EE), ("wheat", 0xF5DEB3), ("white", 0xFFFFFF), ("whitesmoke", 0xF5F5F5),
+    ("yellow", 0xFFFF00), ("yellowgreen", 0x9ACD32),
+];
+
+/// X11 colors whose values differ from the CSS colors of the same name
+#[rustfmt::skip]
+const X11_COLORS: &[(&str, u32)] = &[
+    ("x11gray", 0xBEBEBE), ("x11green", 0x00FF00), ("x11maroon", 0xB03060),
+    ("x11purple", 0xA020F0),
+];
+
+/// Names are matched in CIELAB of sRGB with a D65 white, the space hex codes of name lists are
+/// defined in.
+fn lab(color: &Color) -> Lab {
+    Lab::from_xyz(
+        Xyz::from_rgb(color.rgb(), RgbWorkingSpace::SRGB),
+        Illuminant::D65,
+    )
+}
+
+/// Parses a hex code with an optional leading `#`
+fn parse_hex_code(hex: &str) -> Option<Color> {
+    let hex = hex.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Color::from_hex(hex)
+}
+
+#[derive(Clone, Debug)]
+struct NamedColor {
+    name: String,
+    color: Color,
+    coords: [Float; 3],
+    /// Position in the order the names were added, breaks ties between equal colors
+    order: usize,
+}
+
+/// Result of a nearest name lookup
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorName<'a> {
+    pub name: &'a str,
+    pub color: Color,
+    /// Difference between the named color and the looked up one
+    pub delta_e: Float,
+}
+
+#[derive(Debug, Deserialize)]
+struct NameEntry {
+    name: String,
+    #[serde(alias = "color")]
+    hex: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum NameList {
+    Map(BTreeMap<String, String>),
+    List(Vec<NameEntry>),
+}
+
+/// Database of named colors stored as a k-d tree over CIELAB for nearest name lookups.
+#[derive(Clone, Debug)]
+pub struct ColorNames {
+    /// Implicit k-d tree, the median of every slice is the node splitting its halves
+    colors: Vec<NamedColor>,
+    metric: DeltaE,
+}
+
+impl Default for ColorNames {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl ColorNames {
+    /// Directory inside of the configuration directory with additional name lists
+    pub const DIR_NAME: &'static str = "color_names";
+
+    /// Returns the database with CSS and X11 named colors
+    pub fn builtin() -> Self {
+        let mut names = Self::empty();
+        names.extend(CSS_COLORS.iter().chain(X11_COLORS).map(|(name, hex)| {
+            let [_, r, g, b] = hex.to_be_bytes();
+            (name.to_string(), Rgb::new_scaled(r, g, b).into())
+        }));
+        names
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            colors: vec![],
+            metric: DeltaE::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn metric(&self) -> DeltaE {
+        self.metric
+    }
+
+    pub fn set_metric(&mut self, metric: DeltaE) {
+        self.metric = metric;
+    }
+
+    /// Adds named colors to the database and rebuilds the tree
+    pub fn extend(&mut self, colors: impl IntoIterator<Item = (String, Color)>) {
+        let start = self.colors.len();
+        self.colors
+            .extend(colors.into_iter().enumerate().map(|(i, (name, color))| {
+                let lab = lab(&color);
+                NamedColor {
+                    name,
+                    color,
+                    coords: [lab.l(), lab.a(), lab.b()],
+                    order: start + i,
+                }
+            }));
+        build_tree(&mut self.colors, 0);
+    }
+
+    /// Loads every `.csv` and `.json` name list in `dir`. Lists are added in the order of their
+    /// file names, a file that fails to load is reported after the other lists are added.
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        let mut paths = fs::read_dir(dir)
+            .with_context(|| format!("failed to read directory {}", dir.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                matches!(
+                    path.extension().and_then(|ext| ext.to_str()),
+                    Some("csv" | "json")
+                )
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        let mut error = None;
+        for path in paths {
+            match Self::load_file(&path) {
+                Ok(colors) => self.extend(colors),
+                Err(e) => error = Some(e),
+            }
+        }
+        error.map_or(Ok(()), Err)
+    }
+
+    /// Reads a name list from a CSV or JSON file depending on the extension
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Vec<(String, Color)>> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("failed to read color names from {}", path.display()))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::parse_json(&data)
+                .with_context(|| format!("invalid color names in {}", path.display())),
+            _ => Ok(Self::parse_csv(&data)),
+        }
+    }
+
+    /// Parses lines of `name,hex`. Lines without a valid hex code, like a header, are skipped.
+    pub fn parse_csv(data: &str) -> Vec<(String, Color)> {
+        data.lines()
+            .filter_map(|line| {
+                let (name, hex) = line.rsplit_once(',')?;
+                let name = name.trim().trim_matches('"');
+                if name.is_empty() {
+                    return None;
+                }
+                parse_hex_code(hex.trim().trim_matches('"')).map(|color| (name.to_string(), color))
+            })
+            .collect()
+    }
+
+    /// Parses either an object mapping names to hex codes or a list of objects with `name` and
+    /// `hex` fields.
+    pub fn parse_json(data: &str) -> Result<Vec<(String, Color)>> {
+        let entries = match serde_json::from_str(data)? {
+            NameList::Map(map) => map.into_iter().collect::<Vec<_>>(),
+            NameList::List(list) => list.into_iter().map(|it| (it.name, it.hex)).collect(),
+        };
+        entries
+            .into_iter()
+            .map(|(name, hex)| {
+                parse_hex_code(&hex)
+                    .map(|color| (name.clone(), color))
+                    .ok_or_else(|| anyhow!("invalid hex code `{}` of color `{}`", hex, name))
+            })
+            .collect()
+    }
+
+    /// Returns the color with the given name ignoring case, spaces and dashes
+    pub fn find(&self, name: &str) -> Option<Color> {
+        let normalize = |name: &str| {
+            name.chars()
+                .filter(|c| !matches!(c, ' ' | '-' | '_'))
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        };
+        let name = normalize(name);
+        self.colors
+            .iter()
+            .filter(|named| normalize(&named.name) == name)
+            .min_by_key(|named| named.order)
+            .map(|named| named.color)
+    }
+
+    /// Returns the named color closest to `color` according to the selected metric
+    pub fn nearest(&self, color: &Color) -> Option<ColorName<'_>> {
+        let target = lab(color);
+        let k = if self.metric == DeltaE::Cie76 {
+            1
+        } else {
+            CANDIDATES
+        };
+        let mut candidates = Vec::with_capacity(k + 1);
+        nearest_in_tree(
+            &self.colors,
+            0,
+            [target.l(), target.a(), target.b()],
+            k,
+            &mut candidates,
+        );
+
+        candidates
+            .into_iter()
+            .map(|(_, named)| {
+                let [l, a, b] = named.coords;
+                (self.metric.compute(target, Lab::new(l, a, b)), named)
+            })
+            .min_by(|(a, a_named), (b, b_named)| {
+                a.partial_cmp(b)
+                    .unwrap_or(Ordering::Equal)
+                    .then(a_named.order.cmp(&b_named.order))
+            })
+            .map(|(delta_e, named)| ColorName {
+                name: &named.name,
+                color: named.color,
+                delta_e,
+            })
+    }
+}
+
+fn compare_axis(a: &NamedColor, b: &NamedColor, axis: usize) -> Ordering {
+    a.coords[axis]
+        .partial_cmp(&b.coords[axis])
+        .unwrap_or(Ordering::Equal)
+}
+
+fn build_tree(colors: &mut [NamedColor], depth: usize) {
+    if colors.len() <= 1 {
+        return;
+    }
+    let axis = depth % 3;
+    let mid = colors.len() / 2;
+    colors.select_nth_unstable_by(mid, |a, b| compare_axis(a, b, axis));
+    let (left, right) = colors.split_at_mut(mid);
+    build_tree(left, depth + 1);
+    build_tree(&mut right[1..], depth + 1);
+}
+
+/// Collects the `k` colors with the smallest squared Euclidean distance to `target` sorted in
+/// ascending order.
+fn nearest_in_tree<'a>(
+    colors: &'a [NamedColor],
+    depth: usize,
+    target: [Float; 3],
+    k: usize,
+    best: &mut Vec<(Float, &'a NamedColor)>,
+) {
+    if colors.is_empty() {
+        return;
+    }
+    let axis = depth % 3;
+    let mid = colors.len() / 2;
+    let node = &colors[mid];
+
+    let distance = node
+        .coords
+        .iter()
+        .zip(target)
+        .map(|(c, t)| (c - t) * (c - t))
+        .sum::<Float>();
+    if best.len() < k || distance < best[best.len() - 1].0 {
+        let idx = best.partition_point(|(d, _)| *d <= distance);
+        best.insert(idx, (distance, node));
+        best.truncate(k);
+    }
+
+    let diff = target[axis] - node.coords[axis];
+    let (near, far) = if diff < 0. {
+        (&colors[..mid], &colors[mid + 1..])
+    } else {
+        (&colors[mid + 1..], &colors[..mid])
+    };
+    nearest_in_tree(near, depth + 1, target, k, best);
+    if best.len() < k || diff * diff <= best[best.len() - 1].0 {
+        nearest_in_tree(far, depth + 1, target, k, best);
+    }
+}
+
+/// Looks up the nearest name in the global database
+pub fn nearest_name(color: &Color) -> Option<(String, Float)> {
+    COLOR_NAMES.read().ok().and_then(|names| {
+        names
+            .nearest(color)
+            .map(|name| (name.name.to_string(), name.delta_e))
+    })
+}
+
+/// Parses a hex code with an optional leading `#` or a name from the global database
+pub fn parse_color(text: &str) -> Option<Color> {
+    let text = text.trim();
+    parse_hex_code(text).or_else(|| COLOR_NAMES.read().ok()?.find(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_exact_and_nearest_names() {
+        let names = ColorNames::builtin();
+        let teal = names.nearest(&Color::from_hex("008080").unwrap()).unwrap();
+        assert_eq!(teal.name, "teal");
+        assert!(teal.delta_e < 1e-3);
+
+        // equal colors resolve to the name added first
+        let cyan = names.nearest(&Color::from_hex("00ffff").unwrap()).unwrap();
+        assert_eq!(cyan.name, "aqua");
+
+        let near = names.nearest(&Color::from_hex("fe0102").unwrap()).unwrap();
+        assert_eq!(near.name, "red");
+        assert!(near.delta_e > 0. && near.delta_e < 2.);
+
+        assert!(ColorNames::empty()
+            .nearest(&Color::from_hex("fe0102").unwrap())
+            .is_none());
+
+        assert_eq!(names.find("Dark Slate-Gray"), Color::from_hex("2f4f4f"));
+        assert_eq!(names.find("not a color"), None);
+        assert_eq!(parse_color(" #008080 "), Color::from_hex("008080"));
+        assert_eq!(parse_color("orchid"), Color::from_hex("da70d6"));
+        assert_eq!(parse_color("#12345"), None);
+    }
+
+    fn linear_nearest(names: &ColorNames, color: &Color) -> Float {
+        let target = lab(color);
+        names
+            .colors
+            .iter()
+            .map(|named| {
+                let [l, a, b] = named.coords;
+                names.metric.compute(target, Lab::new(l, a, b))
+            })
+            .fold(Float::MAX, Float::min)
+    }
+
+    #[test]
+    fn tree_matches_linear_search() {
+        let mut names = ColorNames::builtin();
+        names.set_metric(DeltaE::Cie76);
+        for i in 0..125 {
+            let level = |n: usize| n as Float / 4.;
+            let color = Color::Rgb(Rgb::new(level(i / 25), level(i / 5 % 5), level(i % 5)));
+            let got = names.nearest(&color).unwrap();
+            let want = linear_nearest(&names, &color);
+            assert!((got.delta_e - want).abs() < 1e-3, "{:?}", color);
+        }
+
+        // other metrics are exact for colors close to a named one
+        for metric in [DeltaE::Cie76, DeltaE::Cie94, DeltaE::Ciede2000] {
+            names.set_metric(metric);
+            for (_, hex) in CSS_COLORS {
+                let [_, r, g, b] = hex.to_be_bytes();
+                let color = Rgb::new_scaled(r.saturating_add(3), g.saturating_sub(2), b).into();
+                let got = names.nearest(&color).unwrap();
+                let want = linear_nearest(&names, &color);
+                assert!(
+                    (got.delta_e - want).abs() < 1e-3,
+                    "{:?} {:?}: got {} ({}), want {}",
+                    metric,
+                    color,
+                    got.name,
+                    got.delta_e,
+                    want
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parses_name_lists() {
+        let csv = "name,hex\nMy Teal,#008081\n\"Ocean, deep\",003366\nbroken,#12345\n";
+        let parsed = ColorNames::parse_csv(csv);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].0, "My Teal");
+        assert_eq!(parsed[0].1.as_rgb_triplet_scaled(), (0, 128, 129));
+        assert_eq!(parsed[1].0, "Ocean, deep");
+
+        let json = r##"{"brand red": "#d01c1f", "brand ink": "1a1a2e"}"##;
+        let parsed = ColorNames::parse_json(json).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].0, "brand red");
+
+        let json = r##"[{"name": "brand red", "hex": "#d01c1f"}]"##;
+        assert_eq!(ColorNames::parse_json(json).unwrap().len(), 1);
+        assert!(ColorNames::parse_json(r##"{"bad": "#zzzzzz"}"##).is_err());
+
+        let mut names = ColorNames::builtin();
+        names.extend(ColorNames::parse_csv(csv));
+        let got = names.nearest(&Color::from_hex("008081").unwrap()).unwrap();
+        assert_eq!(got.name, "My Teal");
+    }
+}
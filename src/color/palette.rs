@@ -1,12 +1,29 @@
-use crate::color::{Color, CustomPaletteFormat, Illuminant, RgbWorkingSpace};
+use crate::{
+    color::{
+        ChromaticAdaptationMethod, Color, CustomPaletteFormat, DeltaE, FormatOptions, Illuminant,
+        Lab, LchAB, PlatformExport, PlatformFormat, Rgb, RgbWorkingSpace, SheetFormat,
+        SwatchFormat, SwatchSheet, TokenExport, TokenFormat,
+    },
+    math::Float,
+};
 
+use anyhow::{anyhow, bail, Result};
 use serde::{Deserialize, Serialize};
-use std::fmt::Write as _;
+use std::{cmp::Ordering, collections::BTreeMap, fmt::Write as _, path::PathBuf};
+
+/// Chroma below which a color is treated as a gray without a meaningful hue
+const ACHROMATIC_CHROMA: Float = 5.;
+/// Chroma separating saturated from muted colors
+const SATURATED_CHROMA: Float = 30.;
+/// Lightness separating light from dark colors
+const LIGHT_LIGHTNESS: Float = 50.;
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct NamedPalette {
     pub name: String,
     pub palette: Palette,
+    #[serde(default, skip_serializing_if = "PaletteMeta::is_empty")]
+    pub meta: PaletteMeta,
 }
 
 impl Default for NamedPalette {
@@ -14,25 +31,302 @@ impl Default for NamedPalette {
         Self {
             name: "palette".into(),
             palette: Palette::default(),
+            meta: PaletteMeta::default(),
+        }
+    }
+}
+
+/// Information about a palette that doesn't affect its colors
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PaletteMeta {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Path of the folder containing the palette with segments separated by `/`, empty for
+    /// palettes outside of any folder.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub folder: String,
+    /// Where the palette comes from, like the file it was imported from
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub source: String,
+    /// Unix timestamp of the creation of the palette, 0 if unknown
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub created: u64,
+    /// Unix timestamp of the last change of the palette, 0 if unknown
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub modified: u64,
+    /// Names of the colors of the palette keyed by their hex code, like the token names of an
+    /// imported design system
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub color_names: BTreeMap<String, String>,
+    /// Other names of the colors keyed by their hex code, like imported tokens that have the
+    /// same value as another token. They are exported as tokens of their own.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub color_aliases: BTreeMap<String, Vec<String>>,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+impl PaletteMeta {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Adds `tag` unless it is blank or already present. Returns true if the tag was added.
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        let tag = tag.trim();
+        if tag.is_empty() || self.has_tag(tag) {
+            return false;
+        }
+        self.tags.push(tag.to_string());
+        true
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) {
+        self.tags.retain(|t| t != tag);
+    }
+
+    /// Returns the folder of the palette with empty segments and whitespace around segments
+    /// removed.
+    pub fn folder_path(&self) -> String {
+        self.folder
+            .split('/')
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Checks whether the palette is in `folder` or in any of its subfolders
+    pub fn is_in_folder(&self, folder: &str) -> bool {
+        let path = self.folder_path();
+        folder.is_empty()
+            || path
+                .strip_prefix(folder)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+}
+
+/// Prefix of the codes of [`NamedPalette::to_code`]
+const CODE_PREFIX: &str = "epick:";
+/// Version of the binary layout of palette codes
+const CODE_VERSION: u8 = 1;
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encodes `data` as unpadded base64 with the URL and file name safe alphabet
+fn base64url_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity((data.len() * 4).div_ceil(3));
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            encoded.push(BASE64URL[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    encoded
+}
+
+fn base64url_decode(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.trim_end_matches('=').as_bytes();
+    if encoded.len() % 4 == 1 {
+        return None;
+    }
+    let mut data = Vec::with_capacity(encoded.len() * 3 / 4);
+    for chunk in encoded.chunks(4) {
+        let mut bits = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = BASE64URL.iter().position(|it| it == c)? as u32;
+            bits |= value << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            data.push((bits >> (16 - 8 * i)) as u8);
         }
     }
+    Some(data)
 }
 
 impl NamedPalette {
-    pub fn display(
-        &self,
-        format: &PaletteFormat,
-        ws: RgbWorkingSpace,
-        illuminant: Illuminant,
-    ) -> String {
+    /// Creates a palette with the creation time set to now
+    pub fn new(name: impl Into<String>, palette: Palette) -> Self {
+        let now = crate::get_timestamp();
+        Self {
+            name: name.into(),
+            palette,
+            meta: PaletteMeta {
+                created: now,
+                modified: now,
+                ..PaletteMeta::default()
+            },
+        }
+    }
+
+    /// Marks the palette as modified now
+    pub fn touch(&mut self) {
+        self.meta.modified = crate::get_timestamp();
+    }
+
+    /// Returns a palette made from the colors of this one, placed in the same folder with the
+    /// same tags.
+    pub fn derived(&self, name: impl Into<String>, palette: Palette) -> NamedPalette {
+        let mut derived = NamedPalette::new(name, palette);
+        derived.meta.tags = self.meta.tags.clone();
+        derived.meta.folder = self.meta.folder.clone();
+        derived.meta.source = self.name.clone();
+        derived.meta.color_names = self.meta.color_names.clone();
+        derived.meta.color_aliases = self.meta.color_aliases.clone();
+        derived
+    }
+
+    /// Returns the name given to a color of the palette
+    pub fn color_name(&self, color: &Color) -> Option<&str> {
+        self.meta
+            .color_names
+            .get(&color.as_hex())
+            .map(String::as_str)
+    }
+
+    /// Names a color of the palette, an empty name removes it
+    pub fn set_color_name(&mut self, color: &Color, name: impl Into<String>) {
+        let name = name.into();
+        if name.is_empty() {
+            self.meta.color_names.remove(&color.as_hex());
+        } else {
+            self.meta.color_names.insert(color.as_hex(), name);
+        }
+    }
+
+    /// Returns the other names of a color of the palette
+    pub fn color_aliases(&self, color: &Color) -> &[String] {
+        self.meta
+            .color_aliases
+            .get(&color.as_hex())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Adds another name to a color unless the color already has that name
+    pub fn add_color_alias(&mut self, color: &Color, alias: impl Into<String>) {
+        let alias = alias.into();
+        if self.color_name(color) == Some(alias.as_str())
+            || self.color_aliases(color).contains(&alias)
+        {
+            return;
+        }
+        self.meta
+            .color_aliases
+            .entry(color.as_hex())
+            .or_default()
+            .push(alias);
+    }
+
+    /// Checks whether the text looks like a palette code rather than some other palette data
+    pub fn is_code(text: &str) -> bool {
+        text.trim().trim_start_matches('#').starts_with(CODE_PREFIX)
+    }
+
+    /// Encodes the name and colors of the palette as a short text that can be shared and
+    /// imported with [`NamedPalette::from_code`]. Colors are stored as 8 bit sRGB.
+    pub fn to_code(&self) -> String {
+        let mut name = self.name.as_str();
+        while name.len() > u8::MAX as usize {
+            let mut end = name.len() - 1;
+            while !name.is_char_boundary(end) {
+                end -= 1;
+            }
+            name = &name[..end];
+        }
+
+        let mut data = Vec::with_capacity(2 + name.len() + self.palette.len() * 3);
+        data.push(CODE_VERSION);
+        data.push(name.len() as u8);
+        data.extend_from_slice(name.as_bytes());
+        for color in self.palette.iter() {
+            let (r, g, b) = color.as_rgb_triplet_scaled();
+            data.extend_from_slice(&[r, g, b]);
+        }
+        format!("{}{}", CODE_PREFIX, base64url_encode(&data))
+    }
+
+    /// Decodes a palette from a code created by [`NamedPalette::to_code`]. Whitespace around the
+    /// code and a leading `#` of an URL hash are ignored.
+    pub fn from_code(code: &str) -> Result<NamedPalette> {
+        let code = code.trim().trim_start_matches('#');
+        let code = code
+            .strip_prefix(CODE_PREFIX)
+            .ok_or_else(|| anyhow!("palette codes start with `{}`", CODE_PREFIX))?;
+        let data = base64url_decode(code).ok_or_else(|| anyhow!("invalid palette code"))?;
+        match data.first() {
+            Some(&CODE_VERSION) => {}
+            Some(version) => bail!("unsupported palette code version {}", version),
+            None => bail!("empty palette code"),
+        }
+        let name_len = *data
+            .get(1)
+            .ok_or_else(|| anyhow!("truncated palette code"))? as usize;
+        let name = data
+            .get(2..2 + name_len)
+            .ok_or_else(|| anyhow!("truncated palette code"))?;
+        let name = std::str::from_utf8(name).map_err(|_| anyhow!("invalid palette name"))?;
+        let colors = &data[2 + name_len..];
+        if colors.len() % 3 != 0 {
+            bail!("truncated palette code");
+        }
+
+        Ok(NamedPalette::new(
+            name,
+            colors
+                .chunks_exact(3)
+                .map(|rgb| Rgb::new_scaled(rgb[0], rgb[1], rgb[2]).into())
+                .collect(),
+        ))
+    }
+
+    pub fn display(&self, format: &PaletteFormat, options: &FormatOptions) -> String {
         match format {
             PaletteFormat::Gimp => self.palette.as_gimp_palette(&self.name),
             PaletteFormat::HexList => self.palette.as_hex_list(),
-            PaletteFormat::Custom(_, fmt) => fmt
-                .format_palette(&self.palette, ws, illuminant)
+            PaletteFormat::Custom(_, fmt) => fmt.format_palette(self, options).unwrap_or_default(),
+            PaletteFormat::Tokens(tokens) => tokens.export(self, options),
+            PaletteFormat::Platform(platform) => platform.export(self),
+            PaletteFormat::Swatches(swatches) if swatches.is_text() => swatches
+                .export(self)
+                .ok()
+                .and_then(|text| String::from_utf8(text).ok())
                 .unwrap_or_default(),
+            PaletteFormat::Sheet(sheet) if sheet.format == SheetFormat::Svg => {
+                sheet.svg(self, options).unwrap_or_default()
+            }
+            // binary files can't be displayed
+            PaletteFormat::Swatches(_) | PaletteFormat::Sheet(_) => String::new(),
         }
     }
+
+    /// Returns the files of an export of the palette with paths relative to the export directory
+    pub fn export_files(
+        &self,
+        format: &PaletteFormat,
+        options: &FormatOptions,
+    ) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+        let file_name = PathBuf::from(format!("{}.{}", self.name, format.extension()));
+        Ok(match format {
+            PaletteFormat::Platform(platform) => platform
+                .files(self)
+                .into_iter()
+                .map(|(path, contents)| (path, contents.into_bytes()))
+                .collect(),
+            PaletteFormat::Swatches(swatches) => vec![(file_name, swatches.export(self)?)],
+            PaletteFormat::Sheet(sheet) => vec![(file_name, sheet.export(self, options)?)],
+            _ => vec![(file_name, self.display(format, options).into_bytes())],
+        })
+    }
 }
 
 #[derive(Clone, Default, Debug, Deserialize, Serialize, PartialEq)]
@@ -116,6 +410,342 @@ impl Palette {
     }
 }
 
+impl Palette {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Sorts the colors in ascending order of `by`
+    pub fn sort(
+        &mut self,
+        by: PaletteSort,
+        ws: RgbWorkingSpace,
+        illuminant: Illuminant,
+        method: ChromaticAdaptationMethod,
+    ) {
+        let mut keyed: Vec<_> = self
+            .0
+            .iter()
+            .map(|color| {
+                let lch = color.lch_ab(ws, illuminant, method);
+                let key = match by {
+                    // grays go after all hues ordered by their lightness
+                    PaletteSort::Hue if lch.c() < ACHROMATIC_CHROMA => 360. + lch.l(),
+                    PaletteSort::Hue => lch.h(),
+                    PaletteSort::Lightness => lch.l(),
+                    PaletteSort::Chroma => lch.c(),
+                    PaletteSort::Luminance => color.xyz(ws).y(),
+                };
+                (key, *color)
+            })
+            .collect();
+        keyed.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        self.0 = keyed.into_iter().map(|(_, color)| color).collect();
+    }
+
+    /// Orders the colors so that each one is followed by the most similar of the remaining ones
+    /// starting from the darkest color.
+    pub fn sort_smooth(
+        &mut self,
+        metric: DeltaE,
+        ws: RgbWorkingSpace,
+        illuminant: Illuminant,
+        method: ChromaticAdaptationMethod,
+    ) {
+        let mut remaining: Vec<(Lab, Color)> = self
+            .0
+            .iter()
+            .map(|color| (color.lab(ws, illuminant, method), *color))
+            .collect();
+        let darkest = remaining
+            .iter()
+            .enumerate()
+            .min_by(|(_, (a, _)), (_, (b, _))| a.l().partial_cmp(&b.l()).unwrap_or(Ordering::Equal))
+            .map(|(i, _)| i);
+        let mut current = match darkest {
+            Some(i) => remaining.swap_remove(i),
+            None => return,
+        };
+
+        let mut sorted = Vec::with_capacity(self.0.len());
+        sorted.push(current.1);
+        while !remaining.is_empty() {
+            let (next, _) = remaining
+                .iter()
+                .enumerate()
+                .map(|(i, (lab, _))| (i, metric.compute(current.0, *lab)))
+                .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+                .expect("remaining colors");
+            current = remaining.remove(next);
+            sorted.push(current.1);
+        }
+        self.0 = sorted;
+    }
+
+    pub fn reverse(&mut self) {
+        self.0.reverse()
+    }
+
+    /// Removes colors that differ by at most `tolerance` from a color earlier in the palette.
+    /// Returns the number of removed colors.
+    pub fn dedupe(
+        &mut self,
+        tolerance: Float,
+        metric: DeltaE,
+        ws: RgbWorkingSpace,
+        illuminant: Illuminant,
+        method: ChromaticAdaptationMethod,
+    ) -> usize {
+        let len = self.0.len();
+        let mut kept: Vec<(Lab, Color)> = Vec::with_capacity(len);
+        for color in &self.0 {
+            let lab = color.lab(ws, illuminant, method);
+            if !kept
+                .iter()
+                .any(|(other, _)| metric.compute(*other, lab) <= tolerance)
+            {
+                kept.push((lab, *color));
+            }
+        }
+        self.0 = kept.into_iter().map(|(_, color)| color).collect();
+        len - self.0.len()
+    }
+
+    /// Returns a new palette with the colors that match `filter`
+    pub fn filtered(
+        &self,
+        filter: PaletteFilter,
+        ws: RgbWorkingSpace,
+        illuminant: Illuminant,
+        method: ChromaticAdaptationMethod,
+    ) -> Palette {
+        self.0
+            .iter()
+            .filter(|color| filter.matches(color.lch_ab(ws, illuminant, method)))
+            .copied()
+            .collect()
+    }
+
+    /// Returns the colors of this palette followed by the colors of `other` that differ by more
+    /// than `tolerance` from all of them.
+    pub fn union(
+        &self,
+        other: &Palette,
+        tolerance: Float,
+        metric: DeltaE,
+        ws: RgbWorkingSpace,
+        illuminant: Illuminant,
+        method: ChromaticAdaptationMethod,
+    ) -> Palette {
+        let mut labs: Vec<_> = self
+            .0
+            .iter()
+            .map(|color| color.lab(ws, illuminant, method))
+            .collect();
+        let mut colors = self.0.clone();
+        for color in &other.0 {
+            let lab = color.lab(ws, illuminant, method);
+            if !labs
+                .iter()
+                .any(|other| metric.compute(*other, lab) <= tolerance)
+            {
+                labs.push(lab);
+                colors.push(*color);
+            }
+        }
+        Palette(colors)
+    }
+
+    /// Returns the colors of this palette that are within `tolerance` of a color of `other`
+    pub fn intersection(
+        &self,
+        other: &Palette,
+        tolerance: Float,
+        metric: DeltaE,
+        ws: RgbWorkingSpace,
+        illuminant: Illuminant,
+        method: ChromaticAdaptationMethod,
+    ) -> Palette {
+        let labs = other.labs(ws, illuminant, method);
+        self.0
+            .iter()
+            .filter(|color| {
+                let lab = color.lab(ws, illuminant, method);
+                labs.iter()
+                    .any(|other| metric.compute(*other, lab) <= tolerance)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Returns the colors of this palette that differ by more than `tolerance` from all colors
+    /// of `other`
+    pub fn difference(
+        &self,
+        other: &Palette,
+        tolerance: Float,
+        metric: DeltaE,
+        ws: RgbWorkingSpace,
+        illuminant: Illuminant,
+        method: ChromaticAdaptationMethod,
+    ) -> Palette {
+        let labs = other.labs(ws, illuminant, method);
+        self.0
+            .iter()
+            .filter(|color| {
+                let lab = color.lab(ws, illuminant, method);
+                !labs
+                    .iter()
+                    .any(|other| metric.compute(*other, lab) <= tolerance)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Groups the colors by their hue family keeping their order. Families without colors are
+    /// left out.
+    pub fn split_by_hue(
+        &self,
+        ws: RgbWorkingSpace,
+        illuminant: Illuminant,
+        method: ChromaticAdaptationMethod,
+    ) -> Vec<(HueFamily, Palette)> {
+        let mut families: Vec<(HueFamily, Palette)> = vec![];
+        for color in &self.0 {
+            let family = HueFamily::of(color.lch_ab(ws, illuminant, method));
+            match families.iter_mut().find(|(it, _)| *it == family) {
+                Some((_, palette)) => palette.0.push(*color),
+                None => families.push((family, Palette(vec![*color]))),
+            }
+        }
+        families.sort_by_key(|(family, _)| *family);
+        families
+    }
+
+    fn labs(
+        &self,
+        ws: RgbWorkingSpace,
+        illuminant: Illuminant,
+        method: ChromaticAdaptationMethod,
+    ) -> Vec<Lab> {
+        self.0
+            .iter()
+            .map(|color| color.lab(ws, illuminant, method))
+            .collect()
+    }
+}
+
+/// Named range of CIELAB hue angles
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HueFamily {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Cyan,
+    Blue,
+    Purple,
+    Pink,
+    /// Grays without a meaningful hue
+    Neutral,
+}
+
+impl HueFamily {
+    pub fn of(color: LchAB) -> Self {
+        if color.c() < ACHROMATIC_CHROMA {
+            return HueFamily::Neutral;
+        }
+        match color.h() {
+            h if h < 20. => HueFamily::Pink,
+            h if h < 55. => HueFamily::Red,
+            h if h < 85. => HueFamily::Orange,
+            h if h < 115. => HueFamily::Yellow,
+            h if h < 175. => HueFamily::Green,
+            h if h < 235. => HueFamily::Cyan,
+            h if h < 315. => HueFamily::Blue,
+            h if h < 345. => HueFamily::Purple,
+            _ => HueFamily::Pink,
+        }
+    }
+}
+
+impl AsRef<str> for HueFamily {
+    fn as_ref(&self) -> &str {
+        match self {
+            HueFamily::Red => "red",
+            HueFamily::Orange => "orange",
+            HueFamily::Yellow => "yellow",
+            HueFamily::Green => "green",
+            HueFamily::Cyan => "cyan",
+            HueFamily::Blue => "blue",
+            HueFamily::Purple => "purple",
+            HueFamily::Pink => "pink",
+            HueFamily::Neutral => "neutral",
+        }
+    }
+}
+
+/// Property of colors that a palette can be sorted by
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum PaletteSort {
+    /// CIELAB hue angle, grays are placed after all other colors
+    #[default]
+    Hue,
+    /// CIELAB lightness
+    Lightness,
+    /// CIELAB chroma
+    Chroma,
+    /// Relative luminance Y in the working space
+    Luminance,
+}
+
+impl AsRef<str> for PaletteSort {
+    fn as_ref(&self) -> &str {
+        match self {
+            PaletteSort::Hue => "hue",
+            PaletteSort::Lightness => "lightness",
+            PaletteSort::Chroma => "chroma",
+            PaletteSort::Luminance => "luminance",
+        }
+    }
+}
+
+/// Kind of colors kept by [`Palette::filtered`]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum PaletteFilter {
+    /// Colors with a CIELAB chroma of at least 30
+    #[default]
+    Saturated,
+    /// Colors with a CIELAB chroma below 30
+    Muted,
+    /// Colors with a CIELAB lightness of at least 50
+    Light,
+    /// Colors with a CIELAB lightness below 50
+    Dark,
+}
+
+impl PaletteFilter {
+    fn matches(&self, color: LchAB) -> bool {
+        match self {
+            PaletteFilter::Saturated => color.c() >= SATURATED_CHROMA,
+            PaletteFilter::Muted => color.c() < SATURATED_CHROMA,
+            PaletteFilter::Light => color.l() >= LIGHT_LIGHTNESS,
+            PaletteFilter::Dark => color.l() < LIGHT_LIGHTNESS,
+        }
+    }
+}
+
+impl AsRef<str> for PaletteFilter {
+    fn as_ref(&self) -> &str {
+        match self {
+            PaletteFilter::Saturated => "saturated",
+            PaletteFilter::Muted => "muted",
+            PaletteFilter::Light => "light",
+            PaletteFilter::Dark => "dark",
+        }
+    }
+}
+
 impl std::iter::FromIterator<Color> for Palette {
     fn from_iter<T: IntoIterator<Item = Color>>(iter: T) -> Self {
         Self(iter.into_iter().collect())
@@ -127,6 +757,10 @@ pub enum PaletteFormat {
     Gimp,
     HexList,
     Custom(String, CustomPaletteFormat),
+    Tokens(TokenExport),
+    Platform(PlatformExport),
+    Swatches(SwatchFormat),
+    Sheet(SwatchSheet),
 }
 
 impl Default for PaletteFormat {
@@ -141,19 +775,74 @@ impl AsRef<str> for PaletteFormat {
             PaletteFormat::Gimp => "GIMP (gpl)",
             PaletteFormat::HexList => "Hex list",
             PaletteFormat::Custom(name, _) => name,
+            PaletteFormat::Tokens(tokens) => tokens.format.as_ref(),
+            PaletteFormat::Platform(platform) => platform.format.as_ref(),
+            PaletteFormat::Swatches(swatches) => swatches.as_ref(),
+            PaletteFormat::Sheet(sheet) => sheet.format.as_ref(),
         }
     }
 }
 
 impl PaletteFormat {
+    /// Returns every built-in format, with default options for the formats that have them
+    pub fn builtin() -> Vec<PaletteFormat> {
+        let mut formats = vec![PaletteFormat::Gimp, PaletteFormat::HexList];
+        formats.extend(SwatchFormat::ALL.map(PaletteFormat::Swatches));
+        formats.extend(TokenFormat::ALL.map(|format| {
+            PaletteFormat::Tokens(TokenExport {
+                format,
+                ..TokenExport::default()
+            })
+        }));
+        formats.extend(PlatformFormat::ALL.map(|format| {
+            PaletteFormat::Platform(PlatformExport {
+                format,
+                ..PlatformExport::default()
+            })
+        }));
+        formats.extend(SheetFormat::ALL.map(|format| {
+            PaletteFormat::Sheet(SwatchSheet {
+                format,
+                ..SwatchSheet::default()
+            })
+        }));
+        formats
+    }
+
     pub fn extension(&self) -> &str {
         match self {
             PaletteFormat::Gimp => "gpl",
+            PaletteFormat::Tokens(tokens) => tokens.format.extension(),
+            PaletteFormat::Platform(platform) => platform.format.extension(),
+            PaletteFormat::Swatches(swatches) => swatches.extension(),
+            PaletteFormat::Sheet(sheet) => sheet.format.extension(),
             _ => "txt",
         }
     }
 }
 
+/// A palette of 8 bit RGB colors for tests, with names given to some of its colors
+#[cfg(test)]
+pub(crate) fn test_palette(
+    name: &str,
+    colors: &[(u8, u8, u8)],
+    color_names: &[(usize, &str)],
+) -> NamedPalette {
+    let colors: Vec<Color> = colors
+        .iter()
+        .map(|&(r, g, b)| crate::color::Rgb::new_scaled(r, g, b).into())
+        .collect();
+    let mut palette = NamedPalette {
+        name: name.into(),
+        palette: Palette::from_iter(colors.iter().copied()),
+        ..NamedPalette::default()
+    };
+    for &(i, color_name) in color_names {
+        palette.set_color_name(&colors[i], color_name);
+    }
+    palette
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -186,4 +875,186 @@ Columns: 1
 
         assert_eq!(colors.as_gimp_palette("colors"), want);
     }
+
+    fn hex(palette: &Palette) -> Vec<String> {
+        palette.iter().map(|color| color.as_hex()).collect()
+    }
+
+    #[test]
+    fn sorts_palette() {
+        let ws = RgbWorkingSpace::SRGB;
+        let illuminant = Illuminant::D65;
+        let method = ChromaticAdaptationMethod::default();
+        let mut colors = Palette::from_iter(
+            ["0000ff", "808080", "ff0000", "ffff00", "000000", "00ff00"]
+                .into_iter()
+                .map(|hex| Color::from_hex(hex).unwrap()),
+        );
+
+        colors.sort(PaletteSort::Hue, ws, illuminant, method);
+        assert_eq!(
+            hex(&colors),
+            ["#ff0000", "#ffff00", "#00ff00", "#0000ff", "#000000", "#808080"]
+        );
+        colors.sort(PaletteSort::Lightness, ws, illuminant, method);
+        assert_eq!(
+            hex(&colors),
+            ["#000000", "#0000ff", "#ff0000", "#808080", "#00ff00", "#ffff00"]
+        );
+        colors.sort(PaletteSort::Luminance, ws, illuminant, method);
+        assert_eq!(hex(&colors)[..3], ["#000000", "#0000ff", "#ff0000"]);
+        colors.sort(PaletteSort::Chroma, ws, illuminant, method);
+        assert_eq!(hex(&colors)[..2], ["#000000", "#808080"]);
+        colors.reverse();
+        assert_eq!(hex(&colors)[4..], ["#808080", "#000000"]);
+
+        let mut colors = Palette::from_iter(
+            ["ffffff", "000000", "f0f0f0", "101010", "808080"]
+                .into_iter()
+                .map(|hex| Color::from_hex(hex).unwrap()),
+        );
+        colors.sort_smooth(DeltaE::Ciede2000, ws, illuminant, method);
+        assert_eq!(
+            hex(&colors),
+            ["#000000", "#101010", "#808080", "#f0f0f0", "#ffffff"]
+        );
+    }
+
+    #[test]
+    fn dedupes_and_filters_palette() {
+        let ws = RgbWorkingSpace::SRGB;
+        let illuminant = Illuminant::D65;
+        let method = ChromaticAdaptationMethod::default();
+        let mut colors = Palette::from_iter(
+            ["ff0000", "fe0101", "00ff00", "f00000", "202020", "e0e0e0"]
+                .into_iter()
+                .map(|hex| Color::from_hex(hex).unwrap()),
+        );
+
+        let mut exact = colors.clone();
+        assert_eq!(exact.dedupe(0., DeltaE::Cie76, ws, illuminant, method), 0);
+        assert_eq!(
+            colors.dedupe(2., DeltaE::Ciede2000, ws, illuminant, method),
+            1
+        );
+        assert_eq!(
+            hex(&colors),
+            ["#ff0000", "#00ff00", "#f00000", "#202020", "#e0e0e0"]
+        );
+
+        let filtered = |filter| hex(&colors.filtered(filter, ws, illuminant, method));
+        assert_eq!(
+            filtered(PaletteFilter::Saturated),
+            ["#ff0000", "#00ff00", "#f00000"]
+        );
+        assert_eq!(filtered(PaletteFilter::Muted), ["#202020", "#e0e0e0"]);
+        assert_eq!(
+            filtered(PaletteFilter::Light),
+            ["#ff0000", "#00ff00", "#f00000", "#e0e0e0"]
+        );
+        assert_eq!(filtered(PaletteFilter::Dark), ["#202020"]);
+    }
+
+    #[test]
+    fn combines_palettes() {
+        let ws = RgbWorkingSpace::SRGB;
+        let illuminant = Illuminant::D65;
+        let method = ChromaticAdaptationMethod::default();
+        let metric = DeltaE::Ciede2000;
+        let palette =
+            |hex: &[&str]| Palette::from_iter(hex.iter().map(|hex| Color::from_hex(hex).unwrap()));
+        let a = palette(&["ff0000", "00ff00", "0000ff"]);
+        let b = palette(&["fe0101", "ffff00", "0000ff"]);
+
+        let union = |tolerance| hex(&a.union(&b, tolerance, metric, ws, illuminant, method));
+        assert_eq!(
+            union(0.),
+            ["#ff0000", "#00ff00", "#0000ff", "#fe0101", "#ffff00"]
+        );
+        assert_eq!(union(1.), ["#ff0000", "#00ff00", "#0000ff", "#ffff00"]);
+
+        let intersection = a.intersection(&b, 1., metric, ws, illuminant, method);
+        assert_eq!(hex(&intersection), ["#ff0000", "#0000ff"]);
+        let intersection = a.intersection(&b, 0., metric, ws, illuminant, method);
+        assert_eq!(hex(&intersection), ["#0000ff"]);
+        let difference = a.difference(&b, 1., metric, ws, illuminant, method);
+        assert_eq!(hex(&difference), ["#00ff00"]);
+    }
+
+    #[test]
+    fn splits_by_hue() {
+        let colors = Palette::from_iter(
+            [
+                "0000ff", "ff0000", "808080", "ffa500", "ffc0cb", "00ffff", "dc143c", "800080",
+                "ffff00", "008000",
+            ]
+            .into_iter()
+            .map(|hex| Color::from_hex(hex).unwrap()),
+        );
+        let families: Vec<_> = colors
+            .split_by_hue(
+                RgbWorkingSpace::SRGB,
+                Illuminant::D65,
+                ChromaticAdaptationMethod::default(),
+            )
+            .into_iter()
+            .map(|(family, palette)| (family.as_ref().to_string(), hex(&palette)))
+            .collect();
+        let want = [
+            ("red", vec!["#ff0000", "#dc143c"]),
+            ("orange", vec!["#ffa500"]),
+            ("yellow", vec!["#ffff00"]),
+            ("green", vec!["#008000"]),
+            ("cyan", vec!["#00ffff"]),
+            ("blue", vec!["#0000ff"]),
+            ("purple", vec!["#800080"]),
+            ("pink", vec!["#ffc0cb"]),
+            ("neutral", vec!["#808080"]),
+        ];
+        assert_eq!(families.len(), want.len());
+        for ((family, colors), (want_family, want_colors)) in families.iter().zip(want) {
+            assert_eq!(family, want_family);
+            assert_eq!(colors, &want_colors);
+        }
+    }
+
+    #[test]
+    fn encodes_palette_codes() {
+        for (data, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg"),
+            (b"fo", "Zm8"),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg"),
+            (&[0xfb, 0xff], "-_8"),
+        ] {
+            assert_eq!(base64url_encode(data), encoded);
+            assert_eq!(base64url_decode(encoded).as_deref(), Some(data));
+        }
+        assert_eq!(base64url_decode("Zm9vY"), None);
+        assert_eq!(base64url_decode("Zm9+"), None);
+
+        let palette = NamedPalette::new(
+            "sunset ☀",
+            Palette::from_iter([
+                Rgb::new_scaled(255, 94, 77).into(),
+                Rgb::new_scaled(0, 0, 0).into(),
+                Rgb::new_scaled(18, 52, 86).into(),
+            ]),
+        );
+        let code = palette.to_code();
+        assert!(code.starts_with("epick:"));
+        let decoded = NamedPalette::from_code(&format!("  #{}\n", code)).unwrap();
+        assert_eq!(decoded.name, palette.name);
+        assert_eq!(decoded.palette.as_hex_list(), palette.palette.as_hex_list());
+
+        let long = NamedPalette::new("ä".repeat(200), Palette::default());
+        let decoded = NamedPalette::from_code(&long.to_code()).unwrap();
+        assert_eq!(decoded.name, "ä".repeat(127));
+
+        assert!(NamedPalette::from_code("Zm9v").is_err());
+        assert!(NamedPalette::from_code("epick:Ag").is_err());
+        // colors cut off in the middle
+        assert!(NamedPalette::from_code(&code[..code.len() - 2]).is_err());
+    }
 }
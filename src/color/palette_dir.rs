@@ -0,0 +1,861 @@
+//! Storage of palettes as one file per palette in a directory, like a git repository, kept in
+//! sync with changes made to the files by other programs.
+
+use crate::color::{palettes::TrashedPalette, NamedPalette, Palettes};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PaletteFileFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+impl PaletteFileFormat {
+    pub fn extension(&self) -> &str {
+        match self {
+            PaletteFileFormat::Yaml => "yaml",
+            PaletteFileFormat::Json => "json",
+        }
+    }
+
+    /// Returns the format of a palette file based on its extension
+    fn of(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "yaml" | "yml" => Some(PaletteFileFormat::Yaml),
+            "json" => Some(PaletteFileFormat::Json),
+            _ => None,
+        }
+    }
+
+    fn serialize(&self, palette: &NamedPalette) -> Result<String> {
+        match self {
+            PaletteFileFormat::Yaml => {
+                serde_yaml::to_string(palette).context("failed to serialize palette as YAML")
+            }
+            PaletteFileFormat::Json => serde_json::to_string_pretty(palette)
+                .map(|json| json + "\n")
+                .context("failed to serialize palette as JSON"),
+        }
+    }
+
+    fn deserialize(&self, data: &str) -> Result<NamedPalette> {
+        match self {
+            PaletteFileFormat::Yaml => {
+                serde_yaml::from_str(data).context("failed to deserialize palette from YAML")
+            }
+            PaletteFileFormat::Json => {
+                serde_json::from_str(data).context("failed to deserialize palette from JSON")
+            }
+        }
+    }
+}
+
+impl AsRef<str> for PaletteFileFormat {
+    fn as_ref(&self) -> &str {
+        match self {
+            PaletteFileFormat::Yaml => "YAML",
+            PaletteFileFormat::Json => "JSON",
+        }
+    }
+}
+
+/// Modification time and size of a file, used to skip reading files that didn't change
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileStamp {
+    /// Files modified this recently could still change without changing the stamp, as some
+    /// file systems only store the modification time in seconds, so they have no stamp and are
+    /// compared by their contents.
+    const RACY: Duration = Duration::from_secs(2);
+
+    fn of(path: &Path) -> Option<Self> {
+        let meta = fs::metadata(path).ok()?;
+        let modified = meta.modified().ok()?;
+        let age = SystemTime::now().duration_since(modified).ok()?;
+        (age >= Self::RACY).then_some(FileStamp {
+            modified,
+            len: meta.len(),
+        })
+    }
+}
+
+/// A palette file as last read or written by the app
+#[derive(Clone, Debug)]
+struct SyncedFile {
+    palette: NamedPalette,
+    /// The palette of the app is written to the file even if it didn't change
+    outdated: bool,
+    /// Hash of the contents of the file
+    hash: u64,
+    stamp: Option<FileStamp>,
+}
+
+impl SyncedFile {
+    /// Checks whether the file still has the contents it was synchronized with, the file is only
+    /// read if its stamp changed
+    fn is_unchanged(&mut self, path: &Path) -> bool {
+        let stamp = FileStamp::of(path);
+        if stamp.is_some() && stamp == self.stamp {
+            return true;
+        }
+        let unchanged = file_hash(path) == Some(self.hash);
+        if unchanged {
+            self.stamp = stamp;
+        }
+        unchanged
+    }
+}
+
+/// Watches the palettes directory for changes made by other programs
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone)]
+struct DirWatcher {
+    _watcher: Arc<RecommendedWatcher>,
+    changed: Arc<AtomicBool>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::fmt::Debug for DirWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DirWatcher")
+            .field("changed", &self.changed)
+            .finish()
+    }
+}
+
+/// A palette changed both in the app and in its file since they were last synchronized
+#[derive(Clone, Debug)]
+pub struct PaletteConflict {
+    pub path: PathBuf,
+    /// Name of the palette in the app
+    pub name: String,
+    /// Palette stored in the file, `None` if the file was deleted
+    pub theirs: Option<NamedPalette>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PaletteDir {
+    dir: PathBuf,
+    /// Format of newly created palette files, existing files keep their format
+    format: PaletteFileFormat,
+    files: HashMap<PathBuf, SyncedFile>,
+    /// Files that failed to load with their modification time so they are only retried once
+    /// changed again
+    failed: HashMap<PathBuf, Option<SystemTime>>,
+    conflicts: Vec<PaletteConflict>,
+    /// Hashes of the order and trash files as last written
+    written: HashMap<PathBuf, (Option<FileStamp>, u64)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    watcher: Option<DirWatcher>,
+}
+
+impl PaletteDir {
+    /// File listing the names of palette files in the order of the palettes
+    pub const ORDER_FILE: &'static str = "order.txt";
+    /// Deleted palettes, in a subdirectory so that the file isn't read as a palette
+    pub const TRASH_FILE: &'static str = ".trash/trash.json";
+
+    pub fn new(dir: impl Into<PathBuf>, format: PaletteFileFormat) -> Self {
+        Self {
+            dir: dir.into(),
+            format,
+            files: HashMap::new(),
+            failed: HashMap::new(),
+            conflicts: vec![],
+            written: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            watcher: None,
+        }
+    }
+
+    /// Starts watching the directory for changes so that [`PaletteDir::has_changes`] only
+    /// reports the directory as changed when files were modified.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn watch(&mut self) -> Result<()> {
+        let changed = Arc::new(AtomicBool::new(true));
+        let flag = Arc::clone(&changed);
+        // errors of the watcher are reported as changes so that the directory is checked anyway
+        let mut watcher = notify::recommended_watcher(move |_: notify::Result<notify::Event>| {
+            flag.store(true, Ordering::Relaxed);
+        })
+        .context("failed to create a watcher of the palettes directory")?;
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create `{}`", self.dir.display()))?;
+        watcher
+            .watch(&self.dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("failed to watch `{}`", self.dir.display()))?;
+        self.watcher = Some(DirWatcher {
+            _watcher: Arc::new(watcher),
+            changed,
+        });
+        Ok(())
+    }
+
+    /// Checks whether files of the directory changed since the last call. Always `true` if the
+    /// directory isn't watched, then the files are compared on every synchronization.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn has_changes(&self) -> bool {
+        match &self.watcher {
+            Some(watcher) => watcher.changed.swap(false, Ordering::Relaxed),
+            None => true,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn format(&self) -> PaletteFileFormat {
+        self.format
+    }
+
+    pub fn set_format(&mut self, format: PaletteFileFormat) {
+        self.format = format;
+    }
+
+    pub fn conflicts(&self) -> &[PaletteConflict] {
+        &self.conflicts
+    }
+
+    /// Checks whether the directory contains any palette files
+    pub fn has_palettes(&self) -> bool {
+        self.palette_files().is_ok_and(|files| !files.is_empty())
+    }
+
+    /// Reads all palettes of the directory ordered as listed in the order file, with unlisted
+    /// palettes last, and the palettes in the trash of the directory.
+    pub fn load(&mut self) -> Result<Palettes> {
+        self.files.clear();
+        self.failed.clear();
+        self.conflicts.clear();
+        self.written.clear();
+
+        let mut palettes: Option<Palettes> = None;
+        for path in self.palette_files()? {
+            let (palette, file) = read_file(&path)?;
+            let name = match palettes.as_mut() {
+                Some(palettes) => palettes.add_unique(palette),
+                None => {
+                    let name = palette.name.clone();
+                    palettes = Some(Palettes::new(palette));
+                    name
+                }
+            };
+            self.files.insert(
+                path,
+                SyncedFile {
+                    palette: NamedPalette {
+                        name,
+                        ..file.palette
+                    },
+                    ..file
+                },
+            );
+        }
+        let mut palettes = palettes.unwrap_or_default();
+        palettes.set_trash(self.read_trash()?);
+        Ok(palettes)
+    }
+
+    /// Writes the palettes changed in the app since the last synchronization and removes the
+    /// files of palettes deleted in the app. Files changed by other programs in the meantime are
+    /// left for [`PaletteDir::sync`] to resolve.
+    pub fn save(&mut self, palettes: &Palettes) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create `{}`", self.dir.display()))?;
+
+        let mut used = HashSet::new();
+        let mut order = String::new();
+        for palette in palettes.iter() {
+            let path = self.path_of(&palette.name, &used);
+            used.insert(path.clone());
+            if let Some(name) = path.file_name() {
+                order.push_str(&name.to_string_lossy());
+                order.push('\n');
+            }
+            if self.is_conflicted(&path) {
+                continue;
+            }
+            let skip = match self.files.get_mut(&path) {
+                Some(file) if !file.outdated && &file.palette == palette => true,
+                // changed by another program, resolved by the next synchronization
+                Some(file) => !file.is_unchanged(&path),
+                None if path.exists() => {
+                    // created by another program before the palette was first saved
+                    self.conflicts.push(PaletteConflict {
+                        path: path.clone(),
+                        name: palette.name.clone(),
+                        theirs: read_file(&path).ok().map(|(palette, _)| palette),
+                    });
+                    true
+                }
+                None => false,
+            };
+            if skip {
+                continue;
+            }
+            let format = PaletteFileFormat::of(&path).unwrap_or(self.format);
+            let data = format.serialize(palette)?;
+            fs::write(&path, &data)
+                .with_context(|| format!("failed to write `{}`", path.display()))?;
+            self.files.insert(
+                path.clone(),
+                SyncedFile {
+                    palette: palette.clone(),
+                    outdated: false,
+                    hash: hash(&data),
+                    stamp: FileStamp::of(&path),
+                },
+            );
+        }
+
+        let stale: Vec<_> = self
+            .files
+            .keys()
+            .filter(|path| !used.contains(*path) && !self.is_conflicted(path))
+            .cloned()
+            .collect();
+        for path in stale {
+            if path.exists() {
+                // changed by another program after the palette was removed or renamed
+                if !self
+                    .files
+                    .get_mut(&path)
+                    .is_some_and(|file| file.is_unchanged(&path))
+                {
+                    continue;
+                }
+                fs::remove_file(&path)
+                    .with_context(|| format!("failed to remove `{}`", path.display()))?;
+            }
+            self.files.remove(&path);
+        }
+
+        self.write_if_changed(self.dir.join(Self::ORDER_FILE), &order)?;
+        self.write_trash(palettes.trash())
+    }
+
+    /// Writes `data` to `path` unless the file already contains it, files written before are
+    /// only read again if their stamp changed
+    fn write_if_changed(&mut self, path: PathBuf, data: &str) -> Result<()> {
+        let stamp = FileStamp::of(&path);
+        let data_hash = hash(data);
+        match self.written.get(&path) {
+            Some(&(written, written_hash))
+                if stamp.is_some() && written == stamp && written_hash == data_hash =>
+            {
+                return Ok(())
+            }
+            _ => {}
+        }
+        if file_hash(&path) != Some(data_hash) {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .with_context(|| format!("failed to create `{}`", dir.display()))?;
+            }
+            fs::write(&path, data)
+                .with_context(|| format!("failed to write `{}`", path.display()))?;
+        }
+        self.written
+            .insert(path.clone(), (FileStamp::of(&path), data_hash));
+        Ok(())
+    }
+
+    fn read_trash(&self) -> Result<Vec<TrashedPalette>> {
+        let path = self.dir.join(Self::TRASH_FILE);
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let data = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read `{}`", path.display()))?;
+        serde_json::from_str(&data)
+            .with_context(|| format!("failed to deserialize trash from `{}`", path.display()))
+    }
+
+    /// Writes the trash if it changed, the file is removed once the trash is empty
+    fn write_trash(&mut self, trash: &[TrashedPalette]) -> Result<()> {
+        let path = self.dir.join(Self::TRASH_FILE);
+        if trash.is_empty() {
+            if path.exists() {
+                fs::remove_file(&path)
+                    .with_context(|| format!("failed to remove `{}`", path.display()))?;
+            }
+            self.written.remove(&path);
+            return Ok(());
+        }
+        let data = serde_json::to_string_pretty(trash).context("failed to serialize trash")? + "\n";
+        self.write_if_changed(path, &data)
+    }
+
+    /// Applies the changes made to the palette files by other programs. Palettes changed both in
+    /// the app and in their file are recorded as conflicts and left untouched until resolved.
+    pub fn sync(&mut self, palettes: &mut Palettes) -> Result<()> {
+        let mut tracked: Vec<_> = self.files.keys().cloned().collect();
+        tracked.sort();
+        for path in tracked {
+            if self.is_conflicted(&path) {
+                continue;
+            }
+            let stamp = FileStamp::of(&path);
+            let file = self.files[&path].clone();
+            if stamp.is_some() && stamp == file.stamp {
+                continue;
+            }
+            let theirs = match fs::read_to_string(&path) {
+                Ok(data) if hash(&data) == file.hash => {
+                    if let Some(file) = self.files.get_mut(&path) {
+                        file.stamp = stamp;
+                    }
+                    continue;
+                }
+                Ok(_) => match read_file(&path) {
+                    Ok(it) => Some(it),
+                    // most likely still being written, retried on the next synchronization
+                    Err(_) => continue,
+                },
+                Err(_) if path.exists() => continue,
+                Err(_) => None,
+            };
+
+            let name = file.palette.name.clone();
+            let mine = palettes.iter().find(|p| p.name == name);
+            if mine != Some(&file.palette) {
+                self.conflicts.push(PaletteConflict {
+                    path,
+                    name,
+                    theirs: theirs.map(|(palette, _)| palette),
+                });
+                continue;
+            }
+            match theirs {
+                Some((palette, synced)) => {
+                    let name = palettes.replace(&name, palette).unwrap_or_default();
+                    self.track(path, name, synced);
+                }
+                None => {
+                    if let Some(idx) = palettes.position(&name) {
+                        palettes.remove_pos(idx);
+                    }
+                    self.files.remove(&path);
+                }
+            }
+        }
+
+        let mut errors = vec![];
+        for path in self.palette_files()? {
+            if self.files.contains_key(&path) || self.is_conflicted(&path) {
+                continue;
+            }
+            let time = modified(&path);
+            if self.failed.get(&path).is_some_and(|failed| *failed == time) {
+                continue;
+            }
+            match read_file(&path) {
+                Ok((palette, synced)) => {
+                    self.failed.remove(&path);
+                    let name = palettes.add_unique(palette);
+                    self.track(path, name, synced);
+                }
+                Err(e) => {
+                    errors.push(format!("{}: {:?}", path.display(), e));
+                    self.failed.insert(path, time);
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(errors.join("\n")))
+        }
+    }
+
+    /// Resolves the conflict at `idx` in favor of the palette in the app, the file is overwritten
+    /// on the next save.
+    pub fn keep_mine(&mut self, idx: usize) {
+        if idx >= self.conflicts.len() {
+            return;
+        }
+        let conflict = self.conflicts.remove(idx);
+        match read_file(&conflict.path) {
+            Ok((_, mut synced)) => {
+                synced.outdated = true;
+                self.track(conflict.path, conflict.name, synced);
+            }
+            Err(_) => {
+                self.files.remove(&conflict.path);
+            }
+        }
+    }
+
+    /// Resolves the conflict at `idx` in favor of the file, replacing the palette in the app
+    pub fn use_theirs(&mut self, idx: usize, palettes: &mut Palettes) {
+        if idx >= self.conflicts.len() {
+            return;
+        }
+        let conflict = self.conflicts.remove(idx);
+        match (conflict.theirs, read_file(&conflict.path)) {
+            (Some(palette), Ok((_, synced))) => {
+                let name = match palettes.position(&conflict.name) {
+                    Some(_) => palettes.replace(&conflict.name, palette),
+                    None => Some(palettes.add_unique(palette)),
+                };
+                self.track(conflict.path, name.unwrap_or_default(), synced);
+            }
+            _ => {
+                if let Some(idx) = palettes.position(&conflict.name) {
+                    palettes.remove_pos(idx);
+                }
+                self.files.remove(&conflict.path);
+            }
+        }
+    }
+
+    fn track(&mut self, path: PathBuf, name: String, mut synced: SyncedFile) {
+        synced.palette.name = name;
+        self.files.insert(path, synced);
+    }
+
+    fn is_conflicted(&self, path: &Path) -> bool {
+        self.conflicts.iter().any(|c| c.path == path)
+    }
+
+    /// Returns the file of the palette named `name`. Palettes without a file yet get a new one
+    /// named after the palette that isn't in `used`.
+    fn path_of(&self, name: &str, used: &HashSet<PathBuf>) -> PathBuf {
+        if let Some((path, _)) = self
+            .files
+            .iter()
+            .find(|(path, file)| file.palette.name == name && !used.contains(*path))
+        {
+            return path.clone();
+        }
+        let stem = file_stem(name);
+        let is_free = |path: &PathBuf| !used.contains(path) && !self.files.contains_key(path);
+        let ext = self.format.extension();
+        let path = self.dir.join(format!("{}.{}", stem, ext));
+        if is_free(&path) {
+            return path;
+        }
+        (1..)
+            .map(|i| self.dir.join(format!("{} ({}).{}", stem, i, ext)))
+            .find(is_free)
+            .unwrap_or(path)
+    }
+
+    /// Returns the palette files of the directory in the order of the order file
+    fn palette_files(&self) -> Result<Vec<PathBuf>> {
+        if !self.dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut files = vec![];
+        for entry in fs::read_dir(&self.dir)
+            .with_context(|| format!("failed to read `{}`", self.dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_file() && PaletteFileFormat::of(&path).is_some() {
+                files.push(path);
+            }
+        }
+        files.sort();
+
+        let order = fs::read_to_string(self.dir.join(Self::ORDER_FILE)).unwrap_or_default();
+        let position = |path: &PathBuf| {
+            let name = path.file_name().map(|name| name.to_string_lossy());
+            order
+                .lines()
+                .position(|line| Some(line.trim()) == name.as_deref())
+                .unwrap_or(usize::MAX)
+        };
+        // stable so that unlisted files stay sorted by name
+        files.sort_by_key(position);
+        Ok(files)
+    }
+}
+
+/// Reads a palette file returning the palette and the state of the file
+fn read_file(path: &Path) -> Result<(NamedPalette, SyncedFile)> {
+    let format = PaletteFileFormat::of(path)
+        .ok_or_else(|| anyhow!("`{}` is not a palette file", path.display()))?;
+    // taken before reading so that changes made while reading change the stamp
+    let stamp = FileStamp::of(path);
+    let data =
+        fs::read_to_string(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+    let palette = format.deserialize(&data)?;
+    let file = SyncedFile {
+        palette: palette.clone(),
+        outdated: false,
+        hash: hash(&data),
+        stamp,
+    };
+    Ok((palette, file))
+}
+
+fn file_hash(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok().map(|data| hash(&data))
+}
+
+fn hash(data: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Returns a file name for a palette with characters that aren't allowed in file names on some
+/// systems replaced.
+fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    if stem.is_empty() || stem.starts_with('.') {
+        format!("palette{}", stem)
+    } else {
+        stem
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::{Color, Palette, Rgb},
+        math::Float,
+    };
+
+    fn palette(name: &str, colors: &[(Float, Float, Float)]) -> NamedPalette {
+        NamedPalette {
+            name: name.into(),
+            palette: Palette::from_iter(
+                colors
+                    .iter()
+                    .map(|&(r, g, b)| Color::Rgb(Rgb::new(r, g, b))),
+            ),
+            meta: Default::default(),
+        }
+    }
+
+    fn names(palettes: &Palettes) -> Vec<&str> {
+        palettes.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn saves_and_loads_palette_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut palettes = Palettes::new(palette("warm", &[(1., 0., 0.)]));
+        palettes.add(palette("a/b", &[(0., 0., 1.)]));
+        palettes.add(palette("cold", &[(0., 1., 1.)]));
+
+        let mut store = PaletteDir::new(dir.path(), PaletteFileFormat::Yaml);
+        store.save(&palettes).unwrap();
+        assert!(dir.path().join("warm.yaml").exists());
+        assert!(dir.path().join("a_b.yaml").exists());
+
+        store.set_format(PaletteFileFormat::Json);
+        palettes.swap(0, 2);
+        palettes.add(palette("new", &[]));
+        store.save(&palettes).unwrap();
+        // existing files keep their format
+        assert!(dir.path().join("warm.yaml").exists());
+        assert!(dir.path().join("new.json").exists());
+
+        let mut store = PaletteDir::new(dir.path(), PaletteFileFormat::Yaml);
+        let loaded = store.load().unwrap();
+        assert_eq!(names(&loaded), ["cold", "a/b", "warm", "new"]);
+        assert_eq!(loaded[2], palettes[2]);
+
+        palettes.remove_pos(1);
+        store.save(&palettes).unwrap();
+        assert!(!dir.path().join("a_b.yaml").exists());
+    }
+
+    #[test]
+    fn keeps_the_trash_in_the_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut palettes = Palettes::new(palette("one", &[(1., 0., 0.)]));
+        palettes.add(palette("two", &[(0., 1., 0.)]));
+        let mut store = PaletteDir::new(dir.path(), PaletteFileFormat::Yaml);
+        store.save(&palettes).unwrap();
+        assert!(!dir.path().join(PaletteDir::TRASH_FILE).exists());
+
+        palettes.remove_pos(1);
+        store.save(&palettes).unwrap();
+        assert!(dir.path().join(PaletteDir::TRASH_FILE).exists());
+
+        let mut store = PaletteDir::new(dir.path(), PaletteFileFormat::Yaml);
+        let mut loaded = store.load().unwrap();
+        assert_eq!(names(&loaded), ["one"]);
+        assert_eq!(loaded.trash(), palettes.trash());
+        assert_eq!(loaded.trash()[0].palette, palette("two", &[(0., 1., 0.)]));
+
+        assert_eq!(loaded.restore(0).as_deref(), Some("two"));
+        store.save(&loaded).unwrap();
+        assert!(!dir.path().join(PaletteDir::TRASH_FILE).exists());
+        let loaded = store.load().unwrap();
+        assert_eq!(names(&loaded), ["one", "two"]);
+        assert!(loaded.trash().is_empty());
+    }
+
+    #[test]
+    fn syncs_external_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut palettes = Palettes::new(palette("one", &[(1., 0., 0.)]));
+        palettes.add(palette("two", &[(0., 1., 0.)]));
+        let mut store = PaletteDir::new(dir.path(), PaletteFileFormat::Json);
+        store.save(&palettes).unwrap();
+
+        let changed = palette("one", &[(0., 0., 1.)]);
+        fs::write(
+            dir.path().join("one.json"),
+            serde_json::to_string(&changed).unwrap(),
+        )
+        .unwrap();
+        fs::remove_file(dir.path().join("two.json")).unwrap();
+        fs::write(
+            dir.path().join("three.yaml"),
+            serde_yaml::to_string(&palette("three", &[])).unwrap(),
+        )
+        .unwrap();
+        fs::write(dir.path().join("broken.yaml"), "name: [").unwrap();
+
+        assert!(store.sync(&mut palettes).is_err());
+        assert_eq!(names(&palettes), ["one", "three"]);
+        assert_eq!(palettes[0], changed);
+        assert!(store.conflicts().is_empty());
+        // broken files are reported only once
+        store.sync(&mut palettes).unwrap();
+
+        // unchanged palettes aren't written back
+        store.save(&palettes).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("one.json")).unwrap(),
+            serde_json::to_string(&changed).unwrap()
+        );
+    }
+
+    #[test]
+    fn only_reads_files_with_a_new_stamp() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut palettes = Palettes::new(palette("one", &[(1., 0., 0.)]));
+        let mut store = PaletteDir::new(dir.path(), PaletteFileFormat::Json);
+        store.save(&palettes).unwrap();
+
+        let path = dir.path().join("one.json");
+        let past = SystemTime::now() - FileStamp::RACY * 2;
+        let set_modified = |time| {
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(time)
+                .unwrap()
+        };
+        set_modified(past);
+        store.sync(&mut palettes).unwrap();
+
+        // same size and modification time, so the file isn't read again
+        let len = fs::metadata(&path).unwrap().len() as usize;
+        fs::write(&path, "x".repeat(len)).unwrap();
+        set_modified(past);
+        store.sync(&mut palettes).unwrap();
+        assert_eq!(palettes[0], palette("one", &[(1., 0., 0.)]));
+
+        let changed = palette("one", &[(0., 0., 1.)]);
+        fs::write(&path, serde_json::to_string(&changed).unwrap()).unwrap();
+        set_modified(past + Duration::from_secs(1));
+        store.sync(&mut palettes).unwrap();
+        assert_eq!(palettes[0], changed);
+    }
+
+    #[test]
+    fn watches_the_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = PaletteDir::new(dir.path(), PaletteFileFormat::Yaml);
+        store.watch().unwrap();
+        assert!(store.has_changes());
+        assert!(!store.has_changes());
+
+        fs::write(
+            dir.path().join("new.yaml"),
+            serde_yaml::to_string(&palette("new", &[])).unwrap(),
+        )
+        .unwrap();
+        let changed = (0..100).any(|_| {
+            std::thread::sleep(Duration::from_millis(20));
+            store.has_changes()
+        });
+        assert!(changed);
+    }
+
+    #[test]
+    fn detects_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut palettes = Palettes::new(palette("one", &[(1., 0., 0.)]));
+        palettes.add(palette("two", &[(0., 1., 0.)]));
+        let mut store = PaletteDir::new(dir.path(), PaletteFileFormat::Yaml);
+        store.save(&palettes).unwrap();
+
+        let theirs = palette("one", &[(0., 0., 1.)]);
+        let two = palette("two", &[(1., 1., 1.)]);
+        fs::write(
+            dir.path().join("one.yaml"),
+            serde_yaml::to_string(&theirs).unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("two.yaml"),
+            serde_yaml::to_string(&two).unwrap(),
+        )
+        .unwrap();
+        palettes.current_mut().palette.clear();
+        palettes.move_to_name("two");
+        palettes.current_mut().palette.clear();
+
+        // the changes in the app are not written over the changed files
+        store.save(&palettes).unwrap();
+        store.sync(&mut palettes).unwrap();
+        assert_eq!(store.conflicts().len(), 2);
+        assert_eq!(store.conflicts()[0].theirs.as_ref(), Some(&theirs));
+        assert!(palettes[0].palette.is_empty());
+
+        store.use_theirs(0, &mut palettes);
+        assert_eq!(palettes[0], theirs);
+        store.keep_mine(0);
+        assert!(store.conflicts().is_empty());
+        store.save(&palettes).unwrap();
+        let saved: NamedPalette =
+            serde_yaml::from_str(&fs::read_to_string(dir.path().join("two.yaml")).unwrap())
+                .unwrap();
+        assert!(saved.palette.is_empty());
+    }
+}
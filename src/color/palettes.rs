@@ -1,16 +1,58 @@
-use crate::color::NamedPalette;
+use crate::{
+    color::{
+        ChromaticAdaptationMethod, Color, DeltaE, Illuminant, NamedPalette, Palette,
+        RgbWorkingSpace,
+    },
+    math::Float,
+};
 
 use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp::Ordering,
+    collections::BTreeSet,
     fs,
     path::{Path, PathBuf},
 };
 
+/// A color of a saved palette found by [`Palettes::find_similar`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimilarColor {
+    /// Name of the palette containing the color
+    pub palette: String,
+    /// Position of the color in the palette
+    pub index: usize,
+    pub color: Color,
+    /// Difference from the searched color
+    pub delta_e: Float,
+}
+
+/// A deleted palette that can still be restored
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct TrashedPalette {
+    pub palette: NamedPalette,
+    /// Unix timestamp of the deletion
+    pub deleted: u64,
+    /// Position of the palette before it was deleted
+    pub position: usize,
+}
+
+/// A copy of the palettes written by [`Palettes::backup`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaletteBackup {
+    pub path: PathBuf,
+    /// Unix timestamp of the backup
+    pub timestamp: u64,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Palettes {
     palettes: Vec<NamedPalette>,
     current_idx: usize,
+    /// Deleted palettes, the most recently deleted last
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    trash: Vec<TrashedPalette>,
 }
 
 impl Default for Palettes {
@@ -22,11 +64,20 @@ impl Default for Palettes {
 impl Palettes {
     pub const STORAGE_KEY: &'static str = "epick.saved.palettes";
     pub const FILE_NAME: &'static str = "palettes.json";
+    /// Directory with the backups of the palettes, relative to the cache directory
+    pub const BACKUP_DIR: &'static str = "backups";
+    /// Number of backups kept, older backups are removed
+    pub const MAX_BACKUPS: usize = 10;
+    /// Seconds after a backup before the next one is written
+    pub const BACKUP_INTERVAL: u64 = 600;
+    /// Number of palettes kept in the trash, the oldest are removed first
+    pub const MAX_TRASH: usize = 50;
 
     pub fn new(palette: NamedPalette) -> Self {
         Self {
             palettes: vec![palette],
             current_idx: 0,
+            trash: vec![],
         }
     }
 
@@ -83,17 +134,92 @@ impl Palettes {
         }
     }
 
+    pub fn position(&self, name: impl AsRef<str>) -> Option<usize> {
+        let name = name.as_ref();
+        self.palettes.iter().position(|p| p.name == name)
+    }
+
+    /// Replaces the palette named `name` keeping its position. The new palette gets a numeric
+    /// suffix appended to its name if it is taken by another palette. Returns the name the
+    /// palette was stored with.
+    pub fn replace(&mut self, name: &str, mut palette: NamedPalette) -> Option<String> {
+        let idx = self.position(name)?;
+        if palette.name != name {
+            palette.name = self.unique_name(palette.name);
+        }
+        let new_name = palette.name.clone();
+        self.palettes[idx] = palette;
+        Some(new_name)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &NamedPalette> {
         self.palettes.iter()
     }
 
+    /// Returns the colors of all palettes that differ from `color` by at most `threshold`, the
+    /// most similar first.
+    pub fn find_similar(
+        &self,
+        color: &Color,
+        threshold: Float,
+        metric: DeltaE,
+        ws: RgbWorkingSpace,
+        illuminant: Illuminant,
+        method: ChromaticAdaptationMethod,
+    ) -> Vec<SimilarColor> {
+        let lab = color.lab(ws, illuminant, method);
+        let mut found = vec![];
+        for palette in &self.palettes {
+            for (index, it) in palette.palette.iter().enumerate() {
+                let delta_e = metric.compute(lab, it.lab(ws, illuminant, method));
+                if delta_e <= threshold {
+                    found.push(SimilarColor {
+                        palette: palette.name.clone(),
+                        index,
+                        color: *it,
+                        delta_e,
+                    });
+                }
+            }
+        }
+        // stable so equal differences keep the order of the palettes
+        found.sort_by(|a, b| a.delta_e.partial_cmp(&b.delta_e).unwrap_or(Ordering::Equal));
+        found
+    }
+
     pub fn append_empty(&mut self) {
         use std::fmt::Write as _;
-        let mut palette = NamedPalette::default();
+        let mut palette = NamedPalette::new("palette", Palette::default());
         let _ = write!(palette.name, "{}", self.len() - 1);
         self.add(palette);
     }
 
+    /// Returns the paths of all folders containing palettes, including their parent folders,
+    /// sorted so that every folder directly precedes its subfolders.
+    pub fn folders(&self) -> Vec<String> {
+        let mut folders = BTreeSet::new();
+        for palette in &self.palettes {
+            let path = palette.meta.folder_path();
+            let mut end = 0;
+            for segment in path.split('/').filter(|s| !s.is_empty()) {
+                end += segment.len();
+                folders.insert(path[..end].to_string());
+                end += 1;
+            }
+        }
+        folders.into_iter().collect()
+    }
+
+    /// Returns all tags used by the palettes sorted alphabetically
+    pub fn tags(&self) -> Vec<String> {
+        self.palettes
+            .iter()
+            .flat_map(|p| p.meta.tags.iter().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
     pub fn add(&mut self, palette: NamedPalette) -> bool {
         if !self.palettes.iter().any(|p| p.name == palette.name) {
             self.palettes.push(palette);
@@ -102,6 +228,42 @@ impl Palettes {
         false
     }
 
+    /// Returns `name` if no palette is named like that yet, otherwise appends the first free
+    /// numeric suffix to it.
+    pub fn unique_name(&self, name: impl Into<String>) -> String {
+        let name = name.into();
+        let is_taken = |name: &str| self.palettes.iter().any(|p| p.name == name);
+        if !is_taken(&name) {
+            return name;
+        }
+        (1..)
+            .map(|i| format!("{} ({})", name, i))
+            .find(|candidate| !is_taken(candidate))
+            .unwrap_or(name)
+    }
+
+    /// Adds `palette` with a numeric suffix appended to its name if it is already taken. Returns
+    /// the name the palette was added with.
+    pub fn add_unique(&mut self, mut palette: NamedPalette) -> String {
+        palette.name = self.unique_name(palette.name);
+        let name = palette.name.clone();
+        self.palettes.push(palette);
+        name
+    }
+
+    /// Adds a copy of the palette at `idx` right after it and returns the name of the copy
+    pub fn duplicate(&mut self, idx: usize) -> Option<String> {
+        let original = self.palettes.get(idx)?;
+        let mut copy = original.derived(
+            self.unique_name(format!("{} copy", original.name)),
+            original.palette.clone(),
+        );
+        copy.meta.description = original.meta.description.clone();
+        let name = copy.name.clone();
+        self.insert(idx + 1, copy);
+        Some(name)
+    }
+
     pub fn insert(&mut self, i: usize, palette: NamedPalette) {
         if !self.palettes.iter().any(|p| p.name == palette.name) {
             self.palettes.insert(i, palette);
@@ -111,9 +273,20 @@ impl Palettes {
         }
     }
 
+    /// Removes the palette at position `i` and moves it to the trash unless it has no colors
     pub fn remove_pos(&mut self, i: usize) -> Option<NamedPalette> {
         if i < self.palettes.len() {
             let removed = self.palettes.remove(i);
+            if !removed.palette.is_empty() {
+                if self.trash.len() >= Self::MAX_TRASH {
+                    self.trash.remove(0);
+                }
+                self.trash.push(TrashedPalette {
+                    palette: removed.clone(),
+                    deleted: crate::get_timestamp(),
+                    position: i,
+                });
+            }
             if self.palettes.is_empty() {
                 self.palettes.push(NamedPalette::default());
                 self.current_idx = 0;
@@ -142,6 +315,91 @@ impl Palettes {
         self.palettes.swap(a, b)
     }
 
+    pub fn trash(&self) -> &[TrashedPalette] {
+        &self.trash
+    }
+
+    /// Replaces the trash, keeping only the most recently deleted palettes if it is too long
+    pub fn set_trash(&mut self, mut trash: Vec<TrashedPalette>) {
+        trash.drain(..trash.len().saturating_sub(Self::MAX_TRASH));
+        self.trash = trash;
+    }
+
+    /// Moves the palette at position `idx` of the trash back to where it was deleted from.
+    /// Returns the name of the restored palette, with a numeric suffix if the name got taken.
+    pub fn restore(&mut self, idx: usize) -> Option<String> {
+        if idx >= self.trash.len() {
+            return None;
+        }
+        let TrashedPalette {
+            mut palette,
+            position,
+            ..
+        } = self.trash.remove(idx);
+        palette.name = self.unique_name(palette.name);
+        let name = palette.name.clone();
+        self.insert(position.min(self.len()), palette);
+        Some(name)
+    }
+
+    /// Permanently deletes the palette at position `idx` of the trash
+    pub fn delete_from_trash(&mut self, idx: usize) {
+        if idx < self.trash.len() {
+            self.trash.remove(idx);
+        }
+    }
+
+    pub fn empty_trash(&mut self) {
+        self.trash.clear();
+    }
+
+    /// Writes a copy of the palettes named after `timestamp` to `dir`, unless the newest backup
+    /// is more recent than [`Palettes::BACKUP_INTERVAL`] or has the same contents. Only the
+    /// newest [`Palettes::MAX_BACKUPS`] backups are kept. Returns true if a backup was written.
+    pub fn backup(&self, dir: impl AsRef<Path>, timestamp: u64) -> Result<bool> {
+        let dir = dir.as_ref();
+        let backups = Self::backups(dir)?;
+        let data = serde_json::to_vec(self).context("failed to serialize palettes")?;
+        if let Some(newest) = backups.first() {
+            if timestamp < newest.timestamp + Self::BACKUP_INTERVAL
+                || fs::read(&newest.path).is_ok_and(|saved| saved == data)
+            {
+                return Ok(false);
+            }
+        }
+
+        fs::create_dir_all(dir).context("failed to create backup directory")?;
+        fs::write(dir.join(format!("palettes-{}.json", timestamp)), &data)
+            .context("failed to write palettes backup")?;
+        for old in backups.iter().skip(Self::MAX_BACKUPS - 1) {
+            fs::remove_file(&old.path).context("failed to remove old palettes backup")?;
+        }
+        Ok(true)
+    }
+
+    /// Returns the backups found in `dir`, the newest first
+    pub fn backups(dir: impl AsRef<Path>) -> Result<Vec<PaletteBackup>> {
+        let dir = dir.as_ref();
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut backups = vec![];
+        for entry in fs::read_dir(dir).context("failed to read backup directory")? {
+            let path = entry?.path();
+            let timestamp = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("palettes-"))
+                .and_then(|name| name.strip_suffix(".json"))
+                .and_then(|timestamp| timestamp.parse().ok());
+            if let Some(timestamp) = timestamp {
+                backups.push(PaletteBackup { path, timestamp });
+            }
+        }
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.timestamp));
+        Ok(backups)
+    }
+
     /// Loads the saved colors from the specified file located at `path`. The file is expected to
     /// be a valid json file.
     pub fn load(path: impl AsRef<Path>) -> Result<Self> {
@@ -208,7 +466,7 @@ impl std::ops::Index<usize> for Palettes {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::color::{Color, Palette, Rgb};
+    use crate::color::{Color, Palette, PaletteMeta, Rgb};
     const C1: crate::color::Color = Color::Rgb(Rgb::new_unchecked(0., 0., 0.));
     const C2: crate::color::Color = Color::Rgb(Rgb::new_unchecked(0., 1., 0.));
     const C3: crate::color::Color = Color::Rgb(Rgb::new_unchecked(1., 0., 1.));
@@ -217,18 +475,22 @@ mod tests {
         let p1 = NamedPalette {
             palette: Palette::from_iter([C1]),
             name: "p1".into(),
+            meta: PaletteMeta::default(),
         };
         let p2 = NamedPalette {
             palette: Palette::from_iter([C1, C2]),
             name: "p2".into(),
+            meta: PaletteMeta::default(),
         };
         let p3 = NamedPalette {
             palette: Palette::from_iter([C1, C2, C3]),
             name: "p3".into(),
+            meta: PaletteMeta::default(),
         };
         let p4 = NamedPalette {
             palette: Palette::from_iter([C3]),
             name: "p4".into(),
+            meta: PaletteMeta::default(),
         };
         (p1, p2, p3, p4)
     }
@@ -275,6 +537,57 @@ mod tests {
         assert_eq!(palettes.current(), &p1);
     }
 
+    #[test]
+    fn finds_similar_colors() {
+        let (p1, p2, p3, p4) = test_palettes();
+        let mut palettes = Palettes::new(p1);
+        palettes.add(p2);
+        palettes.add(p3);
+        palettes.add(p4);
+
+        let search = |color: Color, threshold: Float| {
+            palettes.find_similar(
+                &color,
+                threshold,
+                DeltaE::Ciede2000,
+                RgbWorkingSpace::SRGB,
+                Illuminant::D65,
+                ChromaticAdaptationMethod::default(),
+            )
+        };
+
+        let found = search(Color::Rgb(Rgb::new(0.02, 0., 0.)), 5.);
+        let names: Vec<_> = found.iter().map(|it| it.palette.as_str()).collect();
+        assert_eq!(names, ["p1", "p2", "p3"]);
+        assert!(found.iter().all(|it| it.index == 0 && it.delta_e > 0.));
+
+        let found = search(Color::Rgb(Rgb::new(0.9, 0.1, 0.9)), 50.);
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].palette.as_str(), found[0].index), ("p3", 2));
+        assert_eq!((found[1].palette.as_str(), found[1].index), ("p4", 0));
+        assert!(found.windows(2).all(|it| it[0].delta_e <= it[1].delta_e));
+
+        assert!(search(Color::Rgb(Rgb::new(0.5, 0.5, 0.5)), 1.).is_empty());
+    }
+
+    #[test]
+    fn duplicates_with_unique_names() {
+        let (p1, p2, _, _) = test_palettes();
+        let mut palettes = Palettes::new(p1.clone());
+        palettes.add(p2);
+
+        assert_eq!(palettes.duplicate(0).as_deref(), Some("p1 copy"));
+        assert_eq!(palettes.duplicate(0).as_deref(), Some("p1 copy (1)"));
+        assert_eq!(palettes.duplicate(5), None);
+        let names: Vec<_> = palettes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["p1", "p1 copy (1)", "p1 copy", "p2"]);
+        assert_eq!(palettes[1].palette, p1.palette);
+
+        assert_eq!(palettes.add_unique(p1.clone()), "p1 (1)");
+        assert_eq!(palettes.add_unique(p1), "p1 (2)");
+        assert_eq!(palettes.len(), 6);
+    }
+
     #[test]
     fn append() {
         let (p1, _, _, _) = test_palettes();
@@ -283,11 +596,11 @@ mod tests {
         palettes.append_empty();
         assert_eq!(palettes.len(), 2);
         palettes.move_to_last();
-        let p = NamedPalette {
-            name: "palette0".into(),
-            palette: Palette::default(),
-        };
-        assert_eq!(palettes.current(), &p);
+        let p = palettes.current();
+        assert_eq!(p.name, "palette0");
+        assert_eq!(p.palette, Palette::default());
+        assert!(p.meta.created > 0);
+        assert_eq!(p.meta.modified, p.meta.created);
     }
 
     #[test]
@@ -309,4 +622,129 @@ mod tests {
         palettes.add(p3);
         palettes.add(p4);
     }
+
+    #[test]
+    fn unique_name() {
+        let (p1, p2, _, _) = test_palettes();
+        let mut palettes = Palettes::new(p1);
+        assert_eq!(palettes.unique_name("new"), "new");
+        assert_eq!(palettes.unique_name("p1"), "p1 (1)");
+        palettes.add(NamedPalette {
+            name: "p1 (1)".into(),
+            ..p2
+        });
+        assert_eq!(palettes.unique_name("p1"), "p1 (2)");
+    }
+
+    #[test]
+    fn groups_by_folders_and_tags() {
+        let (mut p1, mut p2, mut p3, p4) = test_palettes();
+        p1.meta.folder = "brand/ web/".into();
+        p1.meta.add_tag("dark");
+        p2.meta.folder = "brand".into();
+        p2.meta.add_tag("web");
+        p2.meta.add_tag("dark");
+        assert!(!p2.meta.add_tag(" dark "));
+        p3.meta.folder = "print/cmyk/coated".into();
+        let mut palettes = Palettes::new(p1);
+        palettes.add(p2);
+        palettes.add(p3);
+        palettes.add(p4);
+
+        assert_eq!(
+            palettes.folders(),
+            [
+                "brand",
+                "brand/web",
+                "print",
+                "print/cmyk",
+                "print/cmyk/coated"
+            ]
+        );
+        assert_eq!(palettes.tags(), ["dark", "web"]);
+        assert_eq!(palettes[0].meta.folder_path(), "brand/web");
+    }
+
+    #[test]
+    fn loads_palettes_without_metadata() {
+        let json = r#"{"palettes":[{"name":"old","palette":[{"Rgb":{"r":0.0,"g":1.0,"b":0.0}}]}],"current_idx":0}"#;
+        let palettes = Palettes::from_json_str(json).unwrap();
+        assert_eq!(palettes.current().name, "old");
+        assert_eq!(palettes.current().palette, Palette::from_iter([C2]));
+        assert!(palettes.current().meta.is_empty());
+
+        // palettes without metadata are saved the same way
+        let saved = palettes.as_json_str().unwrap();
+        assert!(!saved.contains("meta"));
+
+        let mut palettes = palettes;
+        palettes.current_mut().meta.add_tag("legacy");
+        let loaded = Palettes::from_json_str(&palettes.as_json_str().unwrap()).unwrap();
+        assert_eq!(loaded.current().meta.tags, ["legacy"]);
+    }
+
+    #[test]
+    fn keeps_removed_palettes_in_trash() {
+        let (p1, p2, p3, _) = test_palettes();
+        let mut palettes = Palettes::new(p1);
+        palettes.add(p2.clone());
+        palettes.add(p3.clone());
+        palettes.append_empty();
+
+        palettes.remove(&p2);
+        palettes.remove_pos(2);
+        palettes.move_to_last();
+        palettes.remove_current();
+        assert_eq!(palettes.len(), 1);
+        // empty palettes are not kept
+        assert_eq!(palettes.trash().len(), 2);
+        assert_eq!(palettes.trash()[0].palette, p2);
+        assert_eq!(palettes.trash()[0].position, 1);
+
+        palettes.add(NamedPalette {
+            palette: Palette::default(),
+            ..p2.clone()
+        });
+        assert_eq!(palettes.restore(0).as_deref(), Some("p2 (1)"));
+        assert_eq!(palettes.restore(0).as_deref(), Some("p3"));
+        assert_eq!(palettes.restore(0), None);
+        let names: Vec<_> = palettes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["p1", "p3", "p2 (1)", "p2"]);
+
+        palettes.remove_pos(0);
+        let restored = Palettes::from_json_str(&palettes.as_json_str().unwrap()).unwrap();
+        assert_eq!(restored.trash(), palettes.trash());
+        palettes.empty_trash();
+        assert!(palettes.trash().is_empty());
+    }
+
+    #[test]
+    fn rotates_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let (p1, p2, _, _) = test_palettes();
+        let mut palettes = Palettes::new(p1);
+
+        assert!(palettes.backup(dir.path(), 1000).unwrap());
+        // too soon after the previous backup
+        palettes.add(p2);
+        assert!(!palettes.backup(dir.path(), 1001).unwrap());
+        let next = 1000 + Palettes::BACKUP_INTERVAL;
+        assert!(palettes.backup(dir.path(), next).unwrap());
+        // nothing changed
+        assert!(!palettes
+            .backup(dir.path(), next + Palettes::BACKUP_INTERVAL)
+            .unwrap());
+
+        for i in 0..Palettes::MAX_BACKUPS as u64 {
+            palettes.append_empty();
+            palettes
+                .backup(dir.path(), next + (i + 1) * Palettes::BACKUP_INTERVAL)
+                .unwrap();
+        }
+        let backups = Palettes::backups(dir.path()).unwrap();
+        assert_eq!(backups.len(), Palettes::MAX_BACKUPS);
+        assert!(backups.windows(2).all(|b| b[0].timestamp > b[1].timestamp));
+        let newest = Palettes::load(&backups[0].path).unwrap();
+        assert_eq!(newest.len(), palettes.len());
+    }
 }
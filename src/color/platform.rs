@@ -0,0 +1,358 @@
+use crate::{
+    color::{
+        tokens::{entry_names, words},
+        Color, NamedPalette, Rgb, TokenCase, TokenNameSource,
+    },
+    math::Float,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{fmt::Write as _, path::PathBuf};
+
+/// Mobile platform source files a palette can be exported to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PlatformFormat {
+    /// Android `res/values` color resources
+    #[default]
+    Android,
+    /// Xcode asset catalog with a color set per color
+    Xcode,
+    /// Flutter class with `Color` constants
+    Flutter,
+    /// Jetpack Compose object with `Color` constants
+    Compose,
+}
+
+impl PlatformFormat {
+    pub const ALL: [PlatformFormat; 4] = [
+        PlatformFormat::Android,
+        PlatformFormat::Xcode,
+        PlatformFormat::Flutter,
+        PlatformFormat::Compose,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PlatformFormat::Android => "xml",
+            PlatformFormat::Xcode => "xcassets",
+            PlatformFormat::Flutter => "dart",
+            PlatformFormat::Compose => "kt",
+        }
+    }
+
+    /// Whether the export is a single file rather than a directory
+    pub fn is_single_file(&self) -> bool {
+        *self != PlatformFormat::Xcode
+    }
+}
+
+impl AsRef<str> for PlatformFormat {
+    fn as_ref(&self) -> &str {
+        match self {
+            PlatformFormat::Android => "Android colors.xml",
+            PlatformFormat::Xcode => "Xcode asset catalog",
+            PlatformFormat::Flutter => "Flutter (Dart)",
+            PlatformFormat::Compose => "Jetpack Compose (Kotlin)",
+        }
+    }
+}
+
+/// Color space of the colors in an Xcode asset catalog
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum XcodeColorSpace {
+    #[default]
+    Srgb,
+    DisplayP3,
+}
+
+impl AsRef<str> for XcodeColorSpace {
+    fn as_ref(&self) -> &str {
+        match self {
+            XcodeColorSpace::Srgb => "sRGB",
+            XcodeColorSpace::DisplayP3 => "Display P3",
+        }
+    }
+}
+
+/// Exports a palette as colors of a mobile platform with a named constant per color
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PlatformExport {
+    pub format: PlatformFormat,
+    #[serde(default)]
+    pub names: TokenNameSource,
+    #[serde(default)]
+    pub color_space: XcodeColorSpace,
+}
+
+impl PlatformExport {
+    /// Returns the exported files with paths relative to the export directory
+    pub fn files(&self, palette: &NamedPalette) -> Vec<(PathBuf, String)> {
+        match self.format {
+            PlatformFormat::Android => vec![(
+                format!("{}.xml", type_name(palette, TokenCase::Snake)).into(),
+                self.android(palette),
+            )],
+            PlatformFormat::Xcode => self.xcode(palette),
+            PlatformFormat::Flutter => vec![(
+                format!("{}.dart", type_name(palette, TokenCase::Snake)).into(),
+                self.flutter(palette),
+            )],
+            PlatformFormat::Compose => vec![(
+                format!("{}.kt", type_name(palette, TokenCase::Pascal)).into(),
+                self.compose(palette),
+            )],
+        }
+    }
+
+    /// Returns the export as text, the files of an asset catalog follow their paths
+    pub fn export(&self, palette: &NamedPalette) -> String {
+        match self.format {
+            PlatformFormat::Android => self.android(palette),
+            PlatformFormat::Flutter => self.flutter(palette),
+            PlatformFormat::Compose => self.compose(palette),
+            PlatformFormat::Xcode => {
+                self.xcode(palette)
+                    .into_iter()
+                    .fold(String::new(), |mut text, (path, contents)| {
+                        let _ = writeln!(text, "{}\n{}", path.display(), contents);
+                        text
+                    })
+            }
+        }
+    }
+
+    fn android(&self, palette: &NamedPalette) -> String {
+        let prefix = type_name(palette, TokenCase::Snake);
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<resources>\n");
+        for (name, color) in self.entries(palette, TokenCase::Snake) {
+            let _ = writeln!(
+                xml,
+                "    <color name=\"{}_{}\">#{}</color>",
+                prefix,
+                name,
+                argb(color)
+            );
+        }
+        xml.push_str("</resources>\n");
+        xml
+    }
+
+    fn flutter(&self, palette: &NamedPalette) -> String {
+        let class = type_name(palette, TokenCase::Pascal);
+        let mut dart = format!(
+            "import 'package:flutter/painting.dart';\n\nclass {class} {{\n  {class}._();\n\n"
+        );
+        for (name, color) in self.entries(palette, TokenCase::Camel) {
+            let _ = writeln!(
+                dart,
+                "  static const Color {} = Color(0x{});",
+                name,
+                argb(color)
+            );
+        }
+        dart.push_str("}\n");
+        dart
+    }
+
+    fn compose(&self, palette: &NamedPalette) -> String {
+        let mut kotlin = format!(
+            "import androidx.compose.ui.graphics.Color\n\nobject {} {{\n",
+            type_name(palette, TokenCase::Pascal)
+        );
+        for (name, color) in self.entries(palette, TokenCase::Pascal) {
+            let _ = writeln!(kotlin, "    val {} = Color(0x{})", name, argb(color));
+        }
+        kotlin.push_str("}\n");
+        kotlin
+    }
+
+    fn xcode(&self, palette: &NamedPalette) -> Vec<(PathBuf, String)> {
+        let catalog = PathBuf::from(format!(
+            "{}.xcassets",
+            type_name(palette, TokenCase::Pascal)
+        ));
+        let info = json!({ "author": "xcode", "version": 1 });
+        let mut files = vec![(
+            catalog.join("Contents.json"),
+            pretty_json(&json!({ "info": info })),
+        )];
+        for (name, color) in self.entries(palette, TokenCase::Camel) {
+            let (space, [r, g, b]) = match self.color_space {
+                XcodeColorSpace::Srgb => {
+                    let (r, g, b) = color.as_rgb_triplet_scaled();
+                    (
+                        "srgb",
+                        [r, g, b].map(|channel| format!("0x{:02X}", channel)),
+                    )
+                }
+                XcodeColorSpace::DisplayP3 => (
+                    "display-p3",
+                    display_p3(color).map(|channel| format!("{:.3}", channel)),
+                ),
+            };
+            let colorset = json!({
+                "colors": [{
+                    "color": {
+                        "color-space": space,
+                        "components": {
+                            "alpha": "1.000",
+                            "blue": b,
+                            "green": g,
+                            "red": r,
+                        },
+                    },
+                    "idiom": "universal",
+                }],
+                "info": info,
+            });
+            files.push((
+                catalog
+                    .join(format!("{}.colorset", name))
+                    .join("Contents.json"),
+                pretty_json(&colorset),
+            ));
+        }
+        files
+    }
+
+    fn entries<'p>(&self, palette: &'p NamedPalette, case: TokenCase) -> Vec<(String, &'p Color)> {
+        entry_names(palette, self.names, case, true)
+            .into_iter()
+            .zip(palette.palette.iter())
+            .collect()
+    }
+}
+
+/// Name of the palette usable as a class or file name
+fn type_name(palette: &NamedPalette, case: TokenCase) -> String {
+    let mut name_words = words(&palette.name);
+    if name_words
+        .first()
+        .is_none_or(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+    {
+        name_words.insert(0, "palette".into());
+    }
+    case.join(&name_words)
+}
+
+fn argb(color: &Color) -> String {
+    let (r, g, b) = color.as_rgb_triplet_scaled();
+    format!("FF{:02X}{:02X}{:02X}", r, g, b)
+}
+
+fn pretty_json(json: &serde_json::Value) -> String {
+    let mut text = serde_json::to_string_pretty(json).unwrap_or_default();
+    text.push('\n');
+    text
+}
+
+/// Converts the color to Display P3, which has wider primaries than sRGB but the same white
+/// point and transfer function.
+fn display_p3(color: &Color) -> [Float; 3] {
+    let rgb = color.rgb().inverse_srgb_compand();
+    let (r, g, b) = (rgb.r(), rgb.g(), rgb.b());
+    let p3 = Rgb::new(
+        0.822_462 * r + 0.177_538 * g,
+        0.033_194 * r + 0.966_806 * g,
+        0.017_083 * r + 0.072_397 * g + 0.910_520 * b,
+    )
+    .srgb_compand();
+    [p3.r(), p3.g(), p3.b()].map(|channel| channel.clamp(0., 1.))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::palette::test_palette;
+
+    fn palette() -> NamedPalette {
+        test_palette(
+            "brand colors",
+            &[(255, 0, 0), (0, 128, 255)],
+            &[(1, "sky blue")],
+        )
+    }
+
+    #[test]
+    fn exports_platform_colors() {
+        let mut export = PlatformExport {
+            names: TokenNameSource::Hex,
+            ..PlatformExport::default()
+        };
+        let files = export.files(&palette());
+        assert_eq!(files[0].0, PathBuf::from("brand_colors.xml"));
+        assert_eq!(
+            files[0].1,
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<resources>
+    <color name="brand_colors_ff0000">#FFFF0000</color>
+    <color name="brand_colors_sky_blue">#FF0080FF</color>
+</resources>
+"#
+        );
+
+        export.format = PlatformFormat::Flutter;
+        assert_eq!(
+            export.export(&palette()),
+            r#"import 'package:flutter/painting.dart';
+
+class BrandColors {
+  BrandColors._();
+
+  static const Color ff0000 = Color(0xFFFF0000);
+  static const Color skyBlue = Color(0xFF0080FF);
+}
+"#
+        );
+
+        export.format = PlatformFormat::Compose;
+        export.names = TokenNameSource::Index;
+        let files = export.files(&palette());
+        assert_eq!(files[0].0, PathBuf::from("BrandColors.kt"));
+        assert_eq!(
+            files[0].1,
+            r#"import androidx.compose.ui.graphics.Color
+
+object BrandColors {
+    val Color1 = Color(0xFFFF0000)
+    val SkyBlue = Color(0xFF0080FF)
+}
+"#
+        );
+    }
+
+    #[test]
+    fn exports_xcode_asset_catalog() {
+        let mut export = PlatformExport {
+            format: PlatformFormat::Xcode,
+            ..PlatformExport::default()
+        };
+        let files = export.files(&palette());
+        let paths: Vec<_> = files.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(
+            paths,
+            [
+                "BrandColors.xcassets/Contents.json",
+                "BrandColors.xcassets/color1.colorset/Contents.json",
+                "BrandColors.xcassets/skyBlue.colorset/Contents.json",
+            ]
+            .map(PathBuf::from)
+        );
+        let colorset: serde_json::Value = serde_json::from_str(&files[2].1).unwrap();
+        let color = &colorset["colors"][0]["color"];
+        assert_eq!(color["color-space"], "srgb");
+        assert_eq!(color["components"]["red"], "0x00");
+        assert_eq!(color["components"]["green"], "0x80");
+        assert_eq!(color["components"]["blue"], "0xFF");
+
+        export.color_space = XcodeColorSpace::DisplayP3;
+        let files = export.files(&palette());
+        let colorset: serde_json::Value = serde_json::from_str(&files[1].1).unwrap();
+        let color = &colorset["colors"][0]["color"];
+        assert_eq!(color["color-space"], "display-p3");
+        assert_eq!(color["components"]["red"], "0.917");
+        assert_eq!(color["components"]["green"], "0.200");
+        assert_eq!(color["components"]["blue"], "0.139");
+    }
+}
@@ -0,0 +1,341 @@
+//! Conversions of every color space checked against published reference values and by
+//! converting a grid of colors to the space and back.
+//!
+//! sRGB values are the ones published by Bruce Lindbloom and EasyRGB for the IEC 61966-2-1
+//! primaries with a D65 white of (0.95047, 1.0, 1.08883).
+
+use crate::{
+    color::{
+        hdr::{hlg_decode, hlg_encode, pq_decode, pq_encode},
+        xyY, CIEColor, Cam16, Cam16Ucs, Cmyk, HdrColor, Hpluv, Hsl, Hsluv, Hsv, Hwb, ICtCp,
+        Illuminant, Lab, LchAB, LchUV, Luv, Rgb, RgbWorkingSpace, TransferFunction,
+        ViewingConditions, Xyz, YCbCr, YCbCrStandard, DEFAULT_REFERENCE_WHITE,
+    },
+    math::Float,
+};
+
+/// Largest difference allowed between a color and the same color converted to a space and back
+#[cfg(not(feature = "f64"))]
+const ROUNDTRIP_EPSILON: Float = 1e-3;
+#[cfg(feature = "f64")]
+const ROUNDTRIP_EPSILON: Float = 1e-9;
+
+/// Largest difference allowed from reference values published with 4 decimals
+const REFERENCE_EPSILON: Float = 1e-3;
+
+fn assert_close<const N: usize>(got: [Float; N], want: [Float; N], epsilon: Float, what: &str) {
+    for (g, w) in got.iter().zip(want) {
+        assert!(
+            (g - w).abs() <= epsilon * w.abs().max(1.),
+            "{}: got {:?}, want {:?}",
+            what,
+            got,
+            want
+        );
+    }
+}
+
+fn rgb_grid() -> impl Iterator<Item = Rgb> {
+    const STEPS: usize = 6;
+    let level = |i: usize| i as Float / (STEPS - 1) as Float;
+    (0..STEPS).flat_map(move |r| {
+        (0..STEPS)
+            .flat_map(move |g| (0..STEPS).map(move |b| Rgb::new(level(r), level(g), level(b))))
+    })
+}
+
+fn channels(rgb: Rgb) -> [Float; 3] {
+    [rgb.r(), rgb.g(), rgb.b()]
+}
+
+fn xyz_channels(xyz: Xyz) -> [Float; 3] {
+    [xyz.x(), xyz.y(), xyz.z()]
+}
+
+#[test]
+fn srgb_reference_values() {
+    let ws = RgbWorkingSpace::SRGB;
+    let white = Illuminant::D65;
+    #[rustfmt::skip]
+    let reference = [
+        // rgb, xyz, xy, lab, lch(ab), luv, lch(uv)
+        (
+            [1., 0., 0.],
+            [0.412456, 0.212673, 0.019334], [0.64, 0.33],
+            [53.2408, 80.0925, 67.2032], [53.2408, 104.5518, 39.9990],
+            [53.2408, 175.0151, 37.7564], [53.2408, 179.0414, 12.1740],
+        ),
+        (
+            [0., 1., 0.],
+            [0.357576, 0.715152, 0.119192], [0.30, 0.60],
+            [87.7347, -86.1827, 83.1793], [87.7347, 119.7759, 136.016],
+            [87.7347, -83.0776, 107.3985], [87.7347, 135.7804, 127.7236],
+        ),
+        (
+            [0., 0., 1.],
+            [0.180437, 0.072175, 0.950304], [0.15, 0.06],
+            [32.2970, 79.1875, -107.8602], [32.2970, 133.8076, 306.2849],
+            [32.2970, -9.4054, -130.3423], [32.2970, 130.6812, 265.8727],
+        ),
+        (
+            [0.2, 0.4, 0.6],
+            [0.118643, 0.125053, 0.319193], [0.210775, 0.222162],
+            [42.0081, -0.1517, -32.8460], [42.0081, 32.8464, 269.7354],
+            [42.0081, -20.2486, -47.5548], [42.0081, 51.6862, 246.9359],
+        ),
+    ];
+
+    for (rgb, xyz, xy, lab, lch_ab, luv, lch_uv) in reference {
+        let what = format!("{:?}", rgb);
+        let rgb = Rgb::new(rgb[0], rgb[1], rgb[2]);
+
+        let got = Xyz::from_rgb(rgb, ws);
+        assert_close(xyz_channels(got), xyz, REFERENCE_EPSILON, &what);
+        let got_xyy = xyY::from(got);
+        assert_close([got_xyy.x(), got_xyy.y()], xy, REFERENCE_EPSILON, &what);
+
+        let got_lab = Lab::from_xyz(got, white);
+        assert_close(
+            [got_lab.l(), got_lab.a(), got_lab.b()],
+            lab,
+            REFERENCE_EPSILON,
+            &what,
+        );
+        let got_lch = LchAB::from(got_lab);
+        assert_close(
+            [got_lch.l(), got_lch.c(), got_lch.h()],
+            lch_ab,
+            REFERENCE_EPSILON,
+            &what,
+        );
+
+        let got_luv = Luv::from(got);
+        assert_close(
+            [got_luv.l(), got_luv.u(), got_luv.v()],
+            luv,
+            REFERENCE_EPSILON,
+            &what,
+        );
+        let got_lch = LchUV::from(got_luv);
+        assert_close(
+            [got_lch.l(), got_lch.c(), got_lch.h()],
+            lch_uv,
+            REFERENCE_EPSILON,
+            &what,
+        );
+    }
+}
+
+#[test]
+fn lab_xyz_rgb_roundtrip() {
+    let ws = RgbWorkingSpace::SRGB;
+    let white = Illuminant::D65;
+    for rgb in rgb_grid() {
+        let lab = Lab::from_xyz(Xyz::from_rgb(rgb, ws), white);
+        let back = Lab::from_xyz(Xyz::from_rgb(lab.to_xyz(white).to_rgb(ws), ws), white);
+        // lightness is in the range of 0 ..= 100 so the error is scaled accordingly
+        assert_close(
+            [back.l(), back.a(), back.b()],
+            [lab.l(), lab.a(), lab.b()],
+            ROUNDTRIP_EPSILON * 100.,
+            &format!("{:?}", rgb),
+        );
+    }
+}
+
+#[test]
+fn rgb_spaces_roundtrip() {
+    for rgb in rgb_grid() {
+        let want = channels(rgb);
+        let what = format!("{:?}", rgb);
+        assert_close(
+            channels(Rgb::from(Cmyk::from(rgb))),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+        assert_close(
+            channels(Rgb::from(Hsv::from(rgb))),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+        assert_close(
+            channels(Rgb::from(Hsl::from(rgb))),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+        assert_close(
+            channels(Rgb::from(Hsv::from(Hwb::from(rgb)))),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+        for standard in [
+            YCbCrStandard::Bt601,
+            YCbCrStandard::Bt709,
+            YCbCrStandard::Bt2020,
+        ] {
+            assert_close(
+                channels(YCbCr::from_rgb(rgb, standard).to_rgb(standard)),
+                want,
+                ROUNDTRIP_EPSILON,
+                &what,
+            );
+        }
+    }
+}
+
+#[test]
+fn cie_spaces_roundtrip() {
+    let ws = RgbWorkingSpace::SRGB;
+    let white = Illuminant::D65;
+    for rgb in rgb_grid() {
+        let xyz = Xyz::from_rgb(rgb, ws);
+        let want = xyz_channels(xyz);
+        let what = format!("{:?}", rgb);
+
+        assert_close(
+            channels(xyz.to_rgb(ws)),
+            channels(rgb),
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+        if xyz.y() > 0. {
+            // chromaticity of black is undefined
+            assert_close(
+                xyz_channels(Xyz::from(xyY::from(xyz))),
+                want,
+                ROUNDTRIP_EPSILON,
+                &what,
+            );
+        }
+        assert_close(
+            xyz_channels(Lab::from_xyz(xyz, white).to_xyz(white)),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+        assert_close(
+            xyz_channels(LchAB::from_xyz(xyz, white).to_xyz(white)),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+        assert_close(
+            xyz_channels(Xyz::from(Luv::from(xyz))),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+        assert_close(
+            xyz_channels(Xyz::from(LchUV::from(xyz))),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+        assert_close(
+            xyz_channels(Hsluv::from_xyz(xyz, ws).to_xyz(ws)),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+        assert_close(
+            xyz_channels(Hpluv::from_xyz(xyz, ws).to_xyz(ws)),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+    }
+}
+
+#[test]
+fn appearance_models_roundtrip() {
+    let ws = RgbWorkingSpace::SRGB;
+    let white = Illuminant::D65;
+    let vc = ViewingConditions::default();
+    for rgb in rgb_grid() {
+        let xyz = Xyz::from_rgb(rgb, ws);
+        let want = xyz_channels(xyz);
+        let what = format!("{:?}", rgb);
+
+        assert_close(
+            xyz_channels(Cam16::from_xyz(xyz, white, &vc).to_xyz(white, &vc)),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+        assert_close(
+            xyz_channels(Cam16Ucs::from_xyz(xyz, white, &vc).to_xyz(white, &vc)),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+    }
+}
+
+#[test]
+fn hdr_spaces_roundtrip() {
+    let ws = RgbWorkingSpace::SRGB;
+    for rgb in rgb_grid() {
+        let xyz = Xyz::from_rgb(rgb, ws);
+        let want = xyz_channels(xyz);
+        let what = format!("{:?}", rgb);
+
+        assert_close(
+            xyz_channels(
+                ICtCp::from_xyz(xyz, DEFAULT_REFERENCE_WHITE).to_xyz(DEFAULT_REFERENCE_WHITE),
+            ),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+
+        let hdr = HdrColor::from_xyz(xyz, DEFAULT_REFERENCE_WHITE);
+        assert_close(
+            xyz_channels(hdr.to_xyz(DEFAULT_REFERENCE_WHITE)),
+            want,
+            ROUNDTRIP_EPSILON,
+            &what,
+        );
+        for transfer in [TransferFunction::Pq, TransferFunction::Hlg] {
+            let back = HdrColor::decode(hdr.encode(transfer), transfer);
+            // channels are in cd/m²
+            assert_close(
+                back.channels(),
+                hdr.channels(),
+                ROUNDTRIP_EPSILON * DEFAULT_REFERENCE_WHITE,
+                &what,
+            );
+        }
+    }
+}
+
+#[test]
+fn transfer_function_reference_values() {
+    // signal levels of ITU-R BT.2100 table 4 and ITU-R BT.2408
+    assert_close([pq_encode(0.)], [0.], REFERENCE_EPSILON, "PQ black");
+    assert_close(
+        [pq_encode(0.01)],
+        [0.5081],
+        REFERENCE_EPSILON,
+        "PQ 100 nits",
+    );
+    assert_close(
+        [pq_encode(0.0203)],
+        [0.5806],
+        REFERENCE_EPSILON,
+        "PQ 203 nits",
+    );
+    assert_close(
+        [pq_encode(0.1)],
+        [0.7518],
+        REFERENCE_EPSILON,
+        "PQ 1000 nits",
+    );
+    assert_close([pq_decode(1.)], [1.], REFERENCE_EPSILON, "PQ peak");
+    assert_close([hlg_encode(1. / 12.)], [0.5], REFERENCE_EPSILON, "HLG knee");
+    assert_close([hlg_encode(1.)], [1.], REFERENCE_EPSILON, "HLG peak");
+    assert_close([hlg_decode(0.5)], [1. / 12.], REFERENCE_EPSILON, "HLG knee");
+}
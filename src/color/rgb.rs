@@ -1,7 +1,7 @@
 #![allow(clippy::many_single_char_names)]
 use crate::{
     color::{hsv::Hsv, CIEColor, Cmyk, Color, Hsl, Xyz, CIE_E, CIE_K, U8_MAX},
-    math::Matrix1x3,
+    math::{Float, Matrix1x3},
 };
 use egui::{
     color::{Hsva, HsvaGamma},
@@ -11,14 +11,14 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Rgb {
-    r: f32,
-    g: f32,
-    b: f32,
+    r: Float,
+    g: Float,
+    b: Float,
 }
 
 impl Rgb {
     /// Takes in values in the range 0.0 ..= 1.0 and returns an RGB color.
-    pub fn new(red: f32, green: f32, blue: f32) -> Self {
+    pub fn new(red: Float, green: Float, blue: Float) -> Self {
         let red = if red.is_nan() || red.is_sign_negative() {
             0.
         } else if red > 1. {
@@ -48,7 +48,7 @@ impl Rgb {
         }
     }
 
-    pub const fn new_unchecked(red: f32, green: f32, blue: f32) -> Self {
+    pub const fn new_unchecked(red: Float, green: Float, blue: Float) -> Self {
         Self {
             r: red,
             g: green,
@@ -58,56 +58,56 @@ impl Rgb {
 
     pub fn new_scaled(red: u8, green: u8, blue: u8) -> Self {
         Self::new(
-            red as f32 / U8_MAX,
-            green as f32 / U8_MAX,
-            blue as f32 / U8_MAX,
+            red as Float / U8_MAX,
+            green as Float / U8_MAX,
+            blue as Float / U8_MAX,
         )
     }
 
     #[inline(always)]
     /// Returns Red value in the range 0.0 ..= 1.0
-    pub fn r(&self) -> f32 {
+    pub fn r(&self) -> Float {
         self.r
     }
 
     #[inline(always)]
     /// Returns Green value in the range 0.0 ..= 1.0
-    pub fn g(&self) -> f32 {
+    pub fn g(&self) -> Float {
         self.g
     }
 
     #[inline(always)]
     /// Returns Blue value in the range 0.0 ..= 1.0
-    pub fn b(&self) -> f32 {
+    pub fn b(&self) -> Float {
         self.b
     }
 
     #[inline(always)]
     /// Returns Red value in the range 0.0 ..= 255.0
-    pub fn r_scaled(&self) -> f32 {
+    pub fn r_scaled(&self) -> Float {
         self.r * U8_MAX
     }
 
     #[inline(always)]
     /// Returns Green value in the range 0.0 ..= 255.0
-    pub fn g_scaled(&self) -> f32 {
+    pub fn g_scaled(&self) -> Float {
         self.g * U8_MAX
     }
 
     #[inline(always)]
     /// Returns Blue value in the range 0.0 ..= 255.0
-    pub fn b_scaled(&self) -> f32 {
+    pub fn b_scaled(&self) -> Float {
         self.b * U8_MAX
     }
 
-    pub fn gamma_compand(mut self, gamma: f32) -> Rgb {
+    pub fn gamma_compand(mut self, gamma: Float) -> Rgb {
         self.r = self.r.powf(1. / gamma);
         self.g = self.g.powf(1. / gamma);
         self.b = self.b.powf(1. / gamma);
         self
     }
 
-    pub fn inverse_gamma_compand(mut self, gamma: f32) -> Rgb {
+    pub fn inverse_gamma_compand(mut self, gamma: Float) -> Rgb {
         self.r = self.r.powf(gamma);
         self.g = self.g.powf(gamma);
         self.b = self.b.powf(gamma);
@@ -115,7 +115,7 @@ impl Rgb {
     }
 
     pub fn srgb_compand(mut self) -> Rgb {
-        fn compand(num: f32) -> f32 {
+        fn compand(num: Float) -> Float {
             if num <= 0.0031308 {
                 num * 12.92
             } else {
@@ -130,7 +130,7 @@ impl Rgb {
     }
 
     pub fn inverse_srgb_compand(mut self) -> Rgb {
-        fn inverse_compand(num: f32) -> f32 {
+        fn inverse_compand(num: Float) -> Float {
             if num <= 0.04045 {
                 num / 12.92
             } else {
@@ -144,7 +144,7 @@ impl Rgb {
     }
 
     pub fn l_compand(mut self) -> Rgb {
-        fn compand(num: f32) -> f32 {
+        fn compand(num: Float) -> Float {
             if num <= CIE_E {
                 num * CIE_K
             } else {
@@ -159,7 +159,7 @@ impl Rgb {
     }
 
     pub fn inverse_l_compand(mut self) -> Rgb {
-        fn inverse_compand(num: f32) -> f32 {
+        fn inverse_compand(num: Float) -> Float {
             if num <= 0.08 {
                 100. * num / CIE_K
             } else {
@@ -189,9 +189,9 @@ impl From<Rgb> for Color32 {
 impl From<Color32> for Rgb {
     fn from(color: Color32) -> Self {
         Self::new(
-            color.r() as f32 / U8_MAX,
-            color.g() as f32 / U8_MAX,
-            color.b() as f32 / U8_MAX,
+            color.r() as Float / U8_MAX,
+            color.g() as Float / U8_MAX,
+            color.b() as Float / U8_MAX,
         )
     }
 }
@@ -239,6 +239,8 @@ impl From<Color> for Rgb {
             Color::xyY(c, ws) => Xyz::from(c).to_rgb(ws),
             Color::Luv(c, ws) => Xyz::from(c).to_rgb(ws),
             Color::LchUV(c, ws) => Xyz::from(c).to_rgb(ws),
+            Color::Hsluv(c, ws) => c.to_xyz(ws).to_rgb(ws),
+            Color::Hpluv(c, ws) => c.to_xyz(ws).to_rgb(ws),
             Color::Lab(c, ws, illuminant) => c.to_xyz(illuminant).to_rgb(ws),
             Color::LchAB(c, ws, illuminant) => c.to_xyz(illuminant).to_rgb(ws),
             Color::Color32(c) => c.into(),
@@ -310,15 +312,15 @@ impl From<Rgb> for Matrix1x3 {
 mod tests {
     use crate::{
         color::{Cmyk, Hsv, Rgb},
-        math::eq_f32,
+        math::eq_float,
     };
 
     #[test]
     fn rgb_scaled_init() {
         let rgb = Rgb::new_scaled(255, 85, 0);
-        assert!(eq_f32(rgb.r(), 1.));
-        assert!(eq_f32(rgb.g(), 1. / 3.));
-        assert!(eq_f32(rgb.b(), 0.));
+        assert!(eq_float(rgb.r(), 1.));
+        assert!(eq_float(rgb.g(), 1. / 3.));
+        assert!(eq_float(rgb.b(), 0.));
         assert_eq!(rgb.r_scaled() as u32, 255);
         assert_eq!(rgb.g_scaled() as u32, 85);
         assert_eq!(rgb.b_scaled() as u32, 0);
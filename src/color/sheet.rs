@@ -0,0 +1,481 @@
+//! Swatch sheets, pictures of a palette with a swatch per color.
+
+use crate::color::{swatches::xml_escape, Color, FormatOptions, NamedPalette, TokenValue};
+
+use ab_glyph::{point, Font, FontArc, PxScale, PxScaleFont, ScaleFont};
+use anyhow::{bail, Result};
+use image::{codecs::png::PngEncoder, ColorType, ImageEncoder, Rgba, RgbaImage};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+/// Font of the labels, the proportional font that egui ships with
+static FONT: Lazy<Option<FontArc>> = Lazy::new(|| {
+    let fonts = epaint::text::FontDefinitions::default();
+    let data = fonts.font_data.get("Ubuntu-Light")?;
+    FontArc::try_from_vec(data.font.to_vec()).ok()
+});
+const FONT_FAMILY: &str = "Ubuntu, sans-serif";
+
+const CARDS_BACKGROUND: [u8; 3] = [0xe8, 0xe8, 0xe8];
+
+/// Image format of a swatch sheet
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SheetFormat {
+    #[default]
+    Png,
+    Svg,
+}
+
+impl SheetFormat {
+    pub const ALL: [SheetFormat; 2] = [SheetFormat::Png, SheetFormat::Svg];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SheetFormat::Png => "png",
+            SheetFormat::Svg => "svg",
+        }
+    }
+}
+
+impl AsRef<str> for SheetFormat {
+    fn as_ref(&self) -> &str {
+        match self {
+            SheetFormat::Png => "Swatch sheet (png)",
+            SheetFormat::Svg => "Swatch sheet (svg)",
+        }
+    }
+}
+
+/// Arrangement of the swatches on a sheet
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SheetLayout {
+    /// Square swatches in rows with the labels on the swatches
+    #[default]
+    Grid,
+    /// A single row of adjoining swatches with the labels on the swatches
+    Strip,
+    /// Swatches on cards with the labels below them
+    Cards,
+}
+
+impl SheetLayout {
+    pub const ALL: [SheetLayout; 3] = [SheetLayout::Grid, SheetLayout::Strip, SheetLayout::Cards];
+}
+
+impl AsRef<str> for SheetLayout {
+    fn as_ref(&self) -> &str {
+        match self {
+            SheetLayout::Grid => "grid",
+            SheetLayout::Strip => "strip",
+            SheetLayout::Cards => "cards",
+        }
+    }
+}
+
+/// Renders a palette as a PNG or SVG picture with a swatch per color
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SwatchSheet {
+    pub format: SheetFormat,
+    #[serde(default)]
+    pub layout: SheetLayout,
+    /// Swatches per row of the grid and cards layouts
+    #[serde(default = "default_columns")]
+    pub columns: u32,
+    /// Width of a swatch in pixels
+    #[serde(default = "default_swatch_size")]
+    pub swatch_size: u32,
+    /// Label the swatches with the names of the colors in the palette
+    #[serde(default)]
+    pub names: bool,
+    /// Label the swatches with their color values
+    #[serde(default)]
+    pub values: bool,
+    #[serde(default)]
+    pub value: TokenValue,
+}
+
+fn default_columns() -> u32 {
+    6
+}
+
+fn default_swatch_size() -> u32 {
+    120
+}
+
+impl Default for SwatchSheet {
+    fn default() -> Self {
+        Self {
+            format: SheetFormat::default(),
+            layout: SheetLayout::default(),
+            columns: default_columns(),
+            swatch_size: default_swatch_size(),
+            names: true,
+            values: true,
+            value: TokenValue::default(),
+        }
+    }
+}
+
+/// A rectangle filled with a color
+struct Fill {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    color: [u8; 3],
+}
+
+struct Label {
+    x: u32,
+    baseline: u32,
+    text: String,
+    color: [u8; 3],
+}
+
+/// Positions of everything drawn on a sheet, shared by the PNG and SVG renderers
+struct Sheet {
+    width: u32,
+    height: u32,
+    font_size: f32,
+    fills: Vec<Fill>,
+    labels: Vec<Label>,
+}
+
+impl SwatchSheet {
+    pub fn export(&self, palette: &NamedPalette, options: &FormatOptions) -> Result<Vec<u8>> {
+        match self.format {
+            SheetFormat::Png => self.png(palette, options),
+            SheetFormat::Svg => Ok(self.svg(palette, options)?.into_bytes()),
+        }
+    }
+
+    pub fn png(&self, palette: &NamedPalette, options: &FormatOptions) -> Result<Vec<u8>> {
+        let sheet = self.layout(palette, options)?;
+        let mut image = RgbaImage::new(sheet.width, sheet.height);
+        for fill in &sheet.fills {
+            let [r, g, b] = fill.color;
+            for y in fill.y..fill.y + fill.height {
+                for x in fill.x..fill.x + fill.width {
+                    image.put_pixel(x, y, Rgba([r, g, b, 255]));
+                }
+            }
+        }
+        if let Some(font) = &*FONT {
+            let font = font.as_scaled(PxScale::from(sheet.font_size));
+            for label in &sheet.labels {
+                draw_text(&mut image, &font, label);
+            }
+        }
+
+        let mut png = vec![];
+        PngEncoder::new(&mut png).write_image(
+            image.as_raw(),
+            sheet.width,
+            sheet.height,
+            ColorType::Rgba8,
+        )?;
+        Ok(png)
+    }
+
+    pub fn svg(&self, palette: &NamedPalette, options: &FormatOptions) -> Result<String> {
+        let sheet = self.layout(palette, options)?;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = sheet.width,
+            h = sheet.height
+        );
+        let _ = writeln!(svg, "  <title>{}</title>", xml_escape(&palette.name));
+        for fill in &sheet.fills {
+            let _ = writeln!(
+                svg,
+                "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                fill.x,
+                fill.y,
+                fill.width,
+                fill.height,
+                hex(fill.color)
+            );
+        }
+        for label in &sheet.labels {
+            let _ = writeln!(
+                svg,
+                "  <text x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\" fill=\"{}\">{}</text>",
+                label.x,
+                label.baseline,
+                FONT_FAMILY,
+                sheet.font_size,
+                hex(label.color),
+                xml_escape(&label.text)
+            );
+        }
+        svg.push_str("</svg>\n");
+        Ok(svg)
+    }
+
+    fn layout(&self, palette: &NamedPalette, options: &FormatOptions) -> Result<Sheet> {
+        let count = palette.palette.len() as u32;
+        if count == 0 {
+            bail!("palette `{}` has no colors", palette.name);
+        }
+
+        let size = self.swatch_size.clamp(16, 1024);
+        let font_size = (size / 10).max(9) as f32;
+        let line_height = (font_size * 1.3).round() as u32;
+        let padding = (size / 16).max(2);
+        let lines = self.names as u32 + self.values as u32;
+        let label_block = lines * line_height;
+
+        let (columns, gap, swatch_height, card_height) = match self.layout {
+            SheetLayout::Grid => (self.columns, padding, size, 0),
+            SheetLayout::Strip => (count, 0, size * 3 / 2, 0),
+            SheetLayout::Cards => (self.columns, padding * 2, size, label_block + padding * 2),
+        };
+        let columns = columns.clamp(1, count);
+        let rows = count.div_ceil(columns);
+        let margin = if self.layout == SheetLayout::Cards {
+            gap
+        } else {
+            0
+        };
+        let cell_height = swatch_height + card_height;
+
+        let mut sheet = Sheet {
+            width: margin * 2 + columns * size + (columns - 1) * gap,
+            height: margin * 2 + rows * cell_height + (rows - 1) * gap,
+            font_size,
+            fills: vec![],
+            labels: vec![],
+        };
+        if self.layout == SheetLayout::Cards {
+            sheet.fills.push(Fill {
+                x: 0,
+                y: 0,
+                width: sheet.width,
+                height: sheet.height,
+                color: CARDS_BACKGROUND,
+            });
+        }
+
+        let descent = (font_size * 0.3).round() as u32;
+        for (i, color) in palette.palette.iter().enumerate() {
+            let i = i as u32;
+            let x = margin + (i % columns) * (size + gap);
+            let y = margin + (i / columns) * (cell_height + gap);
+            sheet.fills.push(Fill {
+                x,
+                y,
+                width: size,
+                height: swatch_height,
+                color: rgb(color),
+            });
+
+            let text_color = if self.layout == SheetLayout::Cards {
+                let card = Color::white();
+                sheet.fills.push(Fill {
+                    x,
+                    y: y + swatch_height,
+                    width: size,
+                    height: card_height,
+                    color: rgb(&card),
+                });
+                rgb(&card.contrast())
+            } else {
+                rgb(&color.contrast())
+            };
+
+            let mut texts = vec![];
+            if self.names {
+                texts.push(palette.color_name(color).unwrap_or_default().to_string());
+            }
+            if self.values {
+                texts.push(color.display(self.value.color_format(), options));
+            }
+            let bottom = y + cell_height - padding - descent;
+            for (line, text) in texts.into_iter().enumerate() {
+                let offset = (lines - 1 - line as u32) * line_height;
+                // the lines that don't fit on small swatches are left out starting from the top
+                if text.is_empty() || offset + font_size as u32 > bottom - y {
+                    continue;
+                }
+                sheet.labels.push(Label {
+                    x: x + padding,
+                    baseline: bottom - offset,
+                    text: truncate(&text, font_size, (size - padding * 2) as f32),
+                    color: text_color,
+                });
+            }
+        }
+        Ok(sheet)
+    }
+}
+
+fn rgb(color: &Color) -> [u8; 3] {
+    let (r, g, b) = color.as_rgb_triplet_scaled();
+    [r, g, b]
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn text_width(text: &str, font_size: f32) -> f32 {
+    let font = match &*FONT {
+        Some(font) => font.as_scaled(PxScale::from(font_size)),
+        None => return 0.,
+    };
+    let mut width = 0.;
+    let mut previous = None;
+    for c in text.chars() {
+        let glyph = font.glyph_id(c);
+        if let Some(previous) = previous {
+            width += font.kern(previous, glyph);
+        }
+        width += font.h_advance(glyph);
+        previous = Some(glyph);
+    }
+    width
+}
+
+/// Shortens the text with an ellipsis so that it fits in `max_width`
+fn truncate(text: &str, font_size: f32, max_width: f32) -> String {
+    if text_width(text, font_size) <= max_width {
+        return text.to_string();
+    }
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let truncated = format!("{}…", chars.iter().collect::<String>().trim_end());
+        if text_width(&truncated, font_size) <= max_width {
+            return truncated;
+        }
+    }
+    String::new()
+}
+
+fn draw_text(image: &mut RgbaImage, font: &PxScaleFont<&FontArc>, label: &Label) {
+    let mut x = label.x as f32;
+    let mut previous = None;
+    for c in label.text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            x += font.kern(previous, id);
+        }
+        let glyph = id.with_scale_and_position(font.scale(), point(x, label.baseline as f32));
+        x += font.h_advance(id);
+        previous = Some(id);
+
+        let outline = match font.outline_glyph(glyph) {
+            Some(outline) => outline,
+            None => continue,
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|gx, gy, coverage| {
+            let px = bounds.min.x as i64 + gx as i64;
+            let py = bounds.min.y as i64 + gy as i64;
+            if px < 0 || py < 0 || px >= image.width() as i64 || py >= image.height() as i64 {
+                return;
+            }
+            let pixel = image.get_pixel_mut(px as u32, py as u32);
+            let coverage = coverage.clamp(0., 1.);
+            for (channel, text) in pixel.0.iter_mut().zip(label.color) {
+                *channel =
+                    (*channel as f32 * (1. - coverage) + text as f32 * coverage).round() as u8;
+            }
+            pixel.0[3] = pixel.0[3].max((coverage * 255.).round() as u8);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::palette::test_palette;
+
+    fn palette() -> NamedPalette {
+        test_palette(
+            "brand <colors>",
+            &[(0, 0, 0), (255, 255, 255), (255, 0, 0)],
+            &[(0, "ink")],
+        )
+    }
+
+    fn export(sheet: &SwatchSheet) -> Vec<u8> {
+        sheet.export(&palette(), &FormatOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn renders_png_swatch_sheets() {
+        let mut sheet = SwatchSheet {
+            columns: 2,
+            swatch_size: 100,
+            ..SwatchSheet::default()
+        };
+        let image = image::load_from_memory(&export(&sheet)).unwrap().to_rgba8();
+        // two rows of two swatches with a gap of 6 pixels between them
+        assert_eq!(image.dimensions(), (206, 206));
+        assert_eq!(image.get_pixel(10, 10).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(120, 10).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(10, 120).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(150, 150).0[3], 0);
+        // the labels contrast with the swatches
+        let label = |x: std::ops::Range<u32>, y: std::ops::Range<u32>| {
+            x.flat_map(|x| y.clone().map(move |y| (x, y)))
+                .any(|(x, y)| image.get_pixel(x, y).0[0] > 200)
+        };
+        assert!(label(0..100, 60..100));
+        assert!(!label(0..100, 0..50));
+
+        sheet.layout = SheetLayout::Strip;
+        let image = image::load_from_memory(&export(&sheet)).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (300, 150));
+
+        sheet.layout = SheetLayout::Cards;
+        let image = image::load_from_memory(&export(&sheet)).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (236, 312));
+        assert_eq!(image.get_pixel(0, 0).0, [0xe8, 0xe8, 0xe8, 255]);
+        assert_eq!(image.get_pixel(20, 20).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn fits_labels_on_the_smallest_swatches() {
+        for (layout, labels) in [
+            (SheetLayout::Grid, 3),
+            (SheetLayout::Strip, 3),
+            (SheetLayout::Cards, 4),
+        ] {
+            let sheet = SwatchSheet {
+                layout,
+                swatch_size: 16,
+                ..SwatchSheet::default()
+            };
+            let laid_out = sheet.layout(&palette(), &FormatOptions::default()).unwrap();
+            assert_eq!(laid_out.labels.len(), labels, "{:?}", layout);
+            for label in &laid_out.labels {
+                assert!(label.baseline >= laid_out.font_size as u32, "{:?}", layout);
+                assert!(label.baseline <= laid_out.height, "{:?}", layout);
+            }
+            export(&sheet);
+        }
+    }
+
+    #[test]
+    fn renders_svg_swatch_sheets() {
+        let sheet = SwatchSheet {
+            format: SheetFormat::Svg,
+            layout: SheetLayout::Strip,
+            swatch_size: 100,
+            ..SwatchSheet::default()
+        };
+        let svg = String::from_utf8(export(&sheet)).unwrap();
+        assert!(svg
+            .starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"300\" height=\"150\""));
+        assert!(svg.contains("<title>brand &lt;colors&gt;</title>"));
+        assert!(
+            svg.contains("<rect x=\"100\" y=\"0\" width=\"100\" height=\"150\" fill=\"#ffffff\"/>")
+        );
+        assert!(svg.contains("fill=\"#ffffff\">ink</text>"));
+        assert!(svg.contains("fill=\"#000000\">#ffffff</text>"));
+        assert!(svg.contains("fill=\"#ffffff\">#ff0000</text>"));
+    }
+}
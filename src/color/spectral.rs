@@ -0,0 +1,425 @@
+use crate::{
+    color::{Illuminant, Xyz},
+    math::Float,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// First wavelength in nanometers covered by the observer tables.
+pub const WAVELENGTH_MIN: Float = 380.;
+/// Last wavelength in nanometers covered by the observer tables.
+pub const WAVELENGTH_MAX: Float = 780.;
+/// Interval in nanometers between entries of the observer and daylight tables.
+const TABLE_INTERVAL: Float = 10.;
+/// Interval in nanometers used when integrating spectra. It is finer than the tables so that
+/// narrow emission lines of fluorescent illuminants are not skipped.
+const INTEGRATION_INTERVAL: Float = 5.;
+
+/// CIE standard colorimetric observer.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Observer {
+    /// CIE 1931 2° standard observer
+    #[default]
+    Cie1931,
+    /// CIE 1964 10° supplementary standard observer
+    Cie1964,
+}
+
+impl Observer {
+    fn table(&self) -> &'static [[Float; 3]; 41] {
+        match self {
+            Observer::Cie1931 => &CIE_1931_2,
+            Observer::Cie1964 => &CIE_1964_10,
+        }
+    }
+
+    /// Returns the color matching functions `[x̄, ȳ, z̄]` at the given wavelength, linearly
+    /// interpolating between table entries. Outside of the 380-780nm range all functions are 0.
+    pub fn cmf(&self, wavelength: Float) -> [Float; 3] {
+        if !(WAVELENGTH_MIN..=WAVELENGTH_MAX).contains(&wavelength) {
+            return [0.; 3];
+        }
+        let table = self.table();
+        let pos = (wavelength - WAVELENGTH_MIN) / TABLE_INTERVAL;
+        let i = (pos.floor() as usize).min(table.len() - 1);
+        let j = (i + 1).min(table.len() - 1);
+        let t = pos - i as Float;
+
+        let mut cmf = [0.; 3];
+        for (k, v) in cmf.iter_mut().enumerate() {
+            *v = table[i][k] + (table[j][k] - table[i][k]) * t;
+        }
+        cmf
+    }
+}
+
+impl AsRef<str> for Observer {
+    fn as_ref(&self) -> &str {
+        match self {
+            Observer::Cie1931 => "CIE 1931 2°",
+            Observer::Cie1964 => "CIE 1964 10°",
+        }
+    }
+}
+
+/// Spectral data sampled at a constant interval, either a reflectance factor (0.0 ..= 1.0)
+/// or a relative spectral power distribution of an illuminant.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Spectrum {
+    start: Float,
+    interval: Float,
+    values: Vec<Float>,
+}
+
+impl Spectrum {
+    /// Creates a spectrum where `values[i]` is the value at `start + i * interval` nanometers.
+    pub fn new(start: Float, interval: Float, values: Vec<Float>) -> Self {
+        Self {
+            start,
+            interval,
+            values,
+        }
+    }
+
+    /// Creates a spectrum with the same value at every wavelength.
+    pub fn constant(value: Float) -> Self {
+        Self::new(
+            WAVELENGTH_MIN,
+            WAVELENGTH_MAX - WAVELENGTH_MIN,
+            vec![value; 2],
+        )
+    }
+
+    pub fn start(&self) -> Float {
+        self.start
+    }
+
+    pub fn end(&self) -> Float {
+        self.start + self.interval * self.values.len().saturating_sub(1) as Float
+    }
+
+    pub fn interval(&self) -> Float {
+        self.interval
+    }
+
+    pub fn values(&self) -> &[Float] {
+        &self.values
+    }
+
+    /// Returns the value at the given wavelength. Values between samples are linearly
+    /// interpolated and values outside of the measured range repeat the nearest sample, which is
+    /// the extrapolation recommended by CIE 15 for truncated measurements.
+    pub fn sample(&self, wavelength: Float) -> Float {
+        let last = match self.values.len() {
+            0 => return 0.,
+            n => n - 1,
+        };
+        if wavelength <= self.start || self.interval <= 0. {
+            return self.values[0];
+        }
+        let pos = (wavelength - self.start) / self.interval;
+        let i = pos.floor() as usize;
+        if i >= last {
+            return self.values[last];
+        }
+        let t = pos - i as Float;
+        self.values[i] + (self.values[i + 1] - self.values[i]) * t
+    }
+
+    /// Integrates this spectrum as a reflectance under the `illuminant` SPD as seen by the
+    /// `observer`. The result is normalized so that a perfect reflecting diffuser has `Y = 1`.
+    pub fn to_xyz(&self, illuminant: &Spectrum, observer: Observer) -> Xyz {
+        let mut xyz = [0.; 3];
+        let mut norm = 0.;
+        let steps = ((WAVELENGTH_MAX - WAVELENGTH_MIN) / INTEGRATION_INTERVAL) as usize;
+        for step in 0..=steps {
+            let wavelength = WAVELENGTH_MIN + step as Float * INTEGRATION_INTERVAL;
+            let power = illuminant.sample(wavelength);
+            let reflected = power * self.sample(wavelength);
+            let cmf = observer.cmf(wavelength);
+            xyz[0] += reflected * cmf[0];
+            xyz[1] += reflected * cmf[1];
+            xyz[2] += reflected * cmf[2];
+            norm += power * cmf[1];
+        }
+        if norm == 0. {
+            return Xyz::new(0., 0., 0.);
+        }
+        Xyz::new(xyz[0] / norm, xyz[1] / norm, xyz[2] / norm)
+    }
+
+    /// Returns the white point of this spectrum used as an illuminant, normalized to `Y = 1`.
+    pub fn white_point(&self, observer: Observer) -> Xyz {
+        Spectrum::constant(1.).to_xyz(self, observer)
+    }
+}
+
+impl Illuminant {
+    /// Returns the relative spectral power distribution of this illuminant normalized to 100 at
+    /// 560nm, or `None` if no spectral data is available for it.
+    pub fn spd(&self) -> Option<Spectrum> {
+        match self {
+            Illuminant::A => Some(planckian(2848.)),
+            Illuminant::D50 => Some(daylight(5003.)),
+            Illuminant::D55 => Some(daylight(5503.)),
+            Illuminant::D65 => Some(Spectrum::new(WAVELENGTH_MIN, TABLE_INTERVAL, D65.to_vec())),
+            Illuminant::D75 => Some(daylight(7504.)),
+            Illuminant::E => Some(Spectrum::constant(100.)),
+            Illuminant::F11 => Some(Spectrum::new(WAVELENGTH_MIN, 5., F11.to_vec())),
+            Illuminant::B | Illuminant::C | Illuminant::F2 | Illuminant::F7 => None,
+        }
+    }
+}
+
+/// Relative SPD of a Planckian radiator as defined for CIE illuminant A.
+// always computed in double precision, the casts are no-ops with the `f64` feature
+#[allow(clippy::unnecessary_cast)]
+fn planckian(temperature: Float) -> Spectrum {
+    const C2: f64 = 1.435e7;
+    let t = temperature as f64;
+    let values = (0..D65.len())
+        .map(|i| {
+            let wavelength = WAVELENGTH_MIN as f64 + i as f64 * TABLE_INTERVAL as f64;
+            let v = 100.
+                * (560. / wavelength).powi(5)
+                * ((C2 / (t * 560.)).exp_m1() / (C2 / (t * wavelength)).exp_m1());
+            v as Float
+        })
+        .collect();
+    Spectrum::new(WAVELENGTH_MIN, TABLE_INTERVAL, values)
+}
+
+/// Relative SPD of a CIE daylight illuminant with the given correlated color temperature.
+// always computed in double precision, the casts are no-ops with the `f64` feature
+#[allow(clippy::unnecessary_cast)]
+fn daylight(cct: Float) -> Spectrum {
+    let t = cct as f64;
+    let x = if t <= 7000. {
+        -4.6070e9 / t.powi(3) + 2.9678e6 / t.powi(2) + 0.09911e3 / t + 0.244063
+    } else {
+        -2.0064e9 / t.powi(3) + 1.9018e6 / t.powi(2) + 0.24748e3 / t + 0.237040
+    };
+    let y = -3. * x * x + 2.87 * x - 0.275;
+    let m = 0.0241 + 0.2562 * x - 0.7341 * y;
+    // CIE 15 rounds the coefficients to three decimals
+    let m1 = ((-1.3515 - 1.7703 * x + 5.9114 * y) / m * 1000.).round() / 1000.;
+    let m2 = ((0.0300 - 31.4424 * x + 30.0717 * y) / m * 1000.).round() / 1000.;
+
+    let values = DAYLIGHT_S
+        .iter()
+        .map(|[s0, s1, s2]| (*s0 as f64 + m1 * *s1 as f64 + m2 * *s2 as f64) as Float)
+        .collect();
+    Spectrum::new(WAVELENGTH_MIN, TABLE_INTERVAL, values)
+}
+
+/// CIE 1931 2° standard observer, 380-780nm in 10nm steps.
+#[rustfmt::skip]
+#[allow(clippy::excessive_precision)]
+static CIE_1931_2: [[Float; 3]; 41] = [
+    [0.001368, 0.000039, 0.006450],
+    [0.004243, 0.000120, 0.020050],
+    [0.014310, 0.000396, 0.067850],
+    [0.043510, 0.001210, 0.207400],
+    [0.134380, 0.004000, 0.645600],
+    [0.283900, 0.011600, 1.385600],
+    [0.348280, 0.023000, 1.747060],
+    [0.336200, 0.038000, 1.772110],
+    [0.290800, 0.060000, 1.669200],
+    [0.195360, 0.090980, 1.287640],
+    [0.095640, 0.139020, 0.812950],
+    [0.032010, 0.208020, 0.465180],
+    [0.004900, 0.323000, 0.272000],
+    [0.009300, 0.503000, 0.158200],
+    [0.063270, 0.710000, 0.078250],
+    [0.165500, 0.862000, 0.042160],
+    [0.290400, 0.954000, 0.020300],
+    [0.433450, 0.994950, 0.008750],
+    [0.594500, 0.995000, 0.003900],
+    [0.762100, 0.952000, 0.002100],
+    [0.916300, 0.870000, 0.001650],
+    [1.026300, 0.757000, 0.001100],
+    [1.062200, 0.631000, 0.000800],
+    [1.002600, 0.503000, 0.000340],
+    [0.854450, 0.381000, 0.000190],
+    [0.642400, 0.265000, 0.000050],
+    [0.447900, 0.175000, 0.000020],
+    [0.283500, 0.107000, 0.000000],
+    [0.164900, 0.061000, 0.000000],
+    [0.087400, 0.032000, 0.000000],
+    [0.046770, 0.017000, 0.000000],
+    [0.022700, 0.008210, 0.000000],
+    [0.011359, 0.004102, 0.000000],
+    [0.005790, 0.002091, 0.000000],
+    [0.002899, 0.001047, 0.000000],
+    [0.001440, 0.000520, 0.000000],
+    [0.000690, 0.000249, 0.000000],
+    [0.000332, 0.000120, 0.000000],
+    [0.000166, 0.000060, 0.000000],
+    [0.000083, 0.000030, 0.000000],
+    [0.000042, 0.000015, 0.000000],
+];
+
+/// CIE 1964 10° supplementary standard observer, 380-780nm in 10nm steps.
+#[rustfmt::skip]
+#[allow(clippy::excessive_precision)]
+static CIE_1964_10: [[Float; 3]; 41] = [
+    [0.000160, 0.000017, 0.000705],
+    [0.002362, 0.000253, 0.010482],
+    [0.019110, 0.002004, 0.086011],
+    [0.084736, 0.008756, 0.389366],
+    [0.204492, 0.021391, 0.972542],
+    [0.314679, 0.038676, 1.553480],
+    [0.383734, 0.062077, 1.967280],
+    [0.370702, 0.089456, 1.994800],
+    [0.302273, 0.128201, 1.745370],
+    [0.195618, 0.185190, 1.317560],
+    [0.080507, 0.253589, 0.772125],
+    [0.016172, 0.339133, 0.415254],
+    [0.003816, 0.460777, 0.218502],
+    [0.037465, 0.606741, 0.112044],
+    [0.117749, 0.761757, 0.060709],
+    [0.236491, 0.875211, 0.030451],
+    [0.376772, 0.961988, 0.013676],
+    [0.529826, 0.991761, 0.003988],
+    [0.705224, 0.997340, 0.000000],
+    [0.878655, 0.955552, 0.000000],
+    [1.014160, 0.868934, 0.000000],
+    [1.118520, 0.777405, 0.000000],
+    [1.123990, 0.658341, 0.000000],
+    [1.030480, 0.527963, 0.000000],
+    [0.856297, 0.398057, 0.000000],
+    [0.647467, 0.283493, 0.000000],
+    [0.431567, 0.179828, 0.000000],
+    [0.268329, 0.107633, 0.000000],
+    [0.152568, 0.060281, 0.000000],
+    [0.081261, 0.031800, 0.000000],
+    [0.040851, 0.015905, 0.000000],
+    [0.019941, 0.007749, 0.000000],
+    [0.009577, 0.003718, 0.000000],
+    [0.004553, 0.001768, 0.000000],
+    [0.002175, 0.000846, 0.000000],
+    [0.001045, 0.000407, 0.000000],
+    [0.000508, 0.000199, 0.000000],
+    [0.000251, 0.000098, 0.000000],
+    [0.000126, 0.000050, 0.000000],
+    [0.000065, 0.000025, 0.000000],
+    [0.000033, 0.000013, 0.000000],
+];
+
+/// CIE standard illuminant D65, 380-780nm in 10nm steps.
+#[rustfmt::skip]
+static D65: [Float; 41] = [
+    49.9755, 54.6482, 82.7549, 91.4860, 93.4318, 86.6823, 104.865, 117.008, 117.812, 114.861,
+    115.923, 108.811, 109.354, 107.802, 104.790, 107.689, 104.405, 104.046, 100.000, 96.3342,
+    95.7880, 88.6856, 90.0062, 89.5991, 87.6987, 83.2886, 83.6992, 80.0268, 80.2146, 82.2778,
+    78.2842, 69.7213, 71.6091, 74.3490, 61.6040, 69.8856, 75.0870, 63.5927, 46.4182, 66.8054,
+    63.3828,
+];
+
+/// `S0`, `S1` and `S2` components of daylight, 380-780nm in 10nm steps.
+#[rustfmt::skip]
+static DAYLIGHT_S: [[Float; 3]; 41] = [
+    [ 63.4,  38.5,  3.0], [ 65.8,  35.0,  1.2], [ 94.8,  43.4, -1.1], [104.8,  46.3, -0.5],
+    [105.9,  43.9, -0.7], [ 96.8,  37.1, -1.2], [113.9,  36.7, -2.6], [125.6,  35.9, -2.9],
+    [125.5,  32.6, -2.8], [121.3,  27.9, -2.6], [121.3,  24.3, -2.6], [113.5,  20.1, -1.8],
+    [113.1,  16.2, -1.5], [110.8,  13.2, -1.3], [106.5,   8.6, -1.2], [108.8,   6.1, -1.0],
+    [105.3,   4.2, -0.5], [104.4,   1.9, -0.3], [100.0,   0.0,  0.0], [ 96.0,  -1.6,  0.2],
+    [ 95.1,  -3.5,  0.5], [ 89.1,  -3.5,  2.1], [ 90.5,  -5.8,  3.2], [ 90.3,  -7.2,  4.1],
+    [ 88.4,  -8.6,  4.7], [ 84.0,  -9.5,  5.1], [ 85.1, -10.9,  6.7], [ 81.9, -10.7,  7.3],
+    [ 82.6, -12.0,  8.6], [ 84.9, -14.0,  9.8], [ 81.3, -13.6, 10.2], [ 71.9, -12.0,  8.3],
+    [ 74.3, -13.3,  9.6], [ 76.4, -12.9,  8.5], [ 63.3, -10.6,  7.0], [ 71.7, -11.6,  7.6],
+    [ 77.0, -12.2,  8.0], [ 65.2, -10.2,  6.7], [ 47.7,  -7.8,  5.2], [ 68.6, -11.2,  7.4],
+    [ 65.0, -10.4,  6.8],
+];
+
+/// CIE standard illuminant F11, 380-780nm in 5nm steps.
+#[rustfmt::skip]
+static F11: [Float; 81] = [
+    0.91, 0.63, 0.46, 0.37, 1.29, 12.68, 1.59, 1.79, 2.46, 3.33,
+    4.49, 33.94, 12.13, 6.95, 7.19, 7.12, 6.72, 6.13, 5.46, 4.79,
+    5.66, 14.29, 14.96, 8.97, 4.72, 2.33, 1.47, 1.10, 0.89, 0.83,
+    1.18, 4.90, 39.59, 72.84, 32.61, 7.52, 2.83, 1.96, 1.67, 4.43,
+    11.28, 14.76, 12.73, 9.74, 7.33, 9.72, 55.27, 42.58, 13.18, 13.16,
+    12.26, 5.11, 2.07, 2.34, 3.58, 3.01, 2.48, 2.14, 1.54, 1.33,
+    1.46, 1.94, 2.00, 1.20, 1.35, 4.10, 5.58, 2.51, 0.57, 0.27,
+    0.23, 0.21, 0.24, 0.24, 0.20, 0.24, 0.32, 0.26, 0.16, 0.12,
+    0.09,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_xyz(got: Xyz, want: (Float, Float, Float), tolerance: Float) {
+        assert!(
+            (got.x() - want.0).abs() < tolerance
+                && (got.y() - want.1).abs() < tolerance
+                && (got.z() - want.2).abs() < tolerance,
+            "got {:?}, want {:?}",
+            got,
+            want
+        );
+    }
+
+    #[test]
+    fn illuminant_white_points() {
+        for illuminant in [
+            Illuminant::A,
+            Illuminant::D50,
+            Illuminant::D55,
+            Illuminant::D65,
+            Illuminant::D75,
+            Illuminant::E,
+            Illuminant::F11,
+        ] {
+            let spd = illuminant.spd().unwrap();
+            let white = illuminant.xyz();
+            assert_xyz(
+                spd.white_point(Observer::Cie1931),
+                (white.x(), white.y(), white.z()),
+                5e-3,
+            );
+        }
+
+        // 10° white points of D65 and A from ASTM E308
+        assert_xyz(
+            Illuminant::D65
+                .spd()
+                .unwrap()
+                .white_point(Observer::Cie1964),
+            (0.94811, 1., 1.07304),
+            2e-3,
+        );
+        assert_xyz(
+            Illuminant::A.spd().unwrap().white_point(Observer::Cie1964),
+            (1.11144, 1., 0.352),
+            2e-3,
+        );
+    }
+
+    #[test]
+    fn reflectance_to_xyz() {
+        let d65 = Illuminant::D65.spd().unwrap();
+        let black = Spectrum::constant(0.).to_xyz(&d65, Observer::Cie1931);
+        assert_xyz(black, (0., 0., 0.), 1e-6);
+
+        // a spectrally flat gray keeps the chromaticity of the illuminant
+        let gray = Spectrum::new(400., 10., vec![0.2; 31]).to_xyz(&d65, Observer::Cie1931);
+        let white = d65.white_point(Observer::Cie1931);
+        assert_xyz(
+            gray,
+            (white.x() * 0.2, white.y() * 0.2, white.z() * 0.2),
+            1e-5,
+        );
+    }
+
+    #[test]
+    fn samples_spectrum() {
+        let spectrum = Spectrum::new(400., 10., vec![0.1, 0.3, 0.5]);
+        assert_eq!(spectrum.end(), 420.);
+        assert_eq!(spectrum.sample(380.), 0.1);
+        assert!((spectrum.sample(405.) - 0.2).abs() < 1e-6);
+        assert_eq!(spectrum.sample(420.), 0.5);
+        assert_eq!(spectrum.sample(700.), 0.5);
+    }
+}
@@ -0,0 +1,101 @@
+use super::{entries, palette_from_entries};
+use crate::{
+    color::{Cmyk, Color, Hsv, NamedPalette, Rgb},
+    math::Float,
+};
+
+use anyhow::{anyhow, bail, Result};
+
+const RGB: u16 = 0;
+const HSB: u16 = 1;
+const CMYK: u16 = 2;
+
+/// Writes a version 1 section followed by a version 2 section with the color names
+pub fn export(palette: &NamedPalette) -> Vec<u8> {
+    let mut aco = vec![];
+    for version in [1u16, 2] {
+        aco.extend_from_slice(&version.to_be_bytes());
+        aco.extend_from_slice(&(palette.palette.len() as u16).to_be_bytes());
+        for (color, name) in entries(palette) {
+            let (r, g, b) = color.as_rgb_triplet_scaled();
+            for value in [RGB, r as u16 * 257, g as u16 * 257, b as u16 * 257, 0] {
+                aco.extend_from_slice(&value.to_be_bytes());
+            }
+            if version == 2 {
+                let name: Vec<u16> = name.unwrap_or_default().encode_utf16().chain([0]).collect();
+                aco.extend_from_slice(&(name.len() as u32).to_be_bytes());
+                for unit in name {
+                    aco.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+        }
+    }
+    aco
+}
+
+/// Reads the colors of the version 2 section if present, otherwise of the version 1 section.
+/// Colors in spaces other than RGB, HSB and CMYK are skipped.
+pub fn import(data: &[u8], name: &str) -> Result<NamedPalette> {
+    let mut reader = Reader { data, pos: 0 };
+    let mut colors = read_section(&mut reader, 1)?;
+    if reader.pos < data.len() {
+        colors = read_section(&mut reader, 2)?;
+    }
+    Ok(palette_from_entries(name, colors))
+}
+
+fn read_section(reader: &mut Reader<'_>, expected: u16) -> Result<Vec<(Color, Option<String>)>> {
+    let version = reader.u16()?;
+    if version != expected {
+        bail!("unexpected swatches version {}", version);
+    }
+    let count = reader.u16()?;
+    let mut colors = vec![];
+    for _ in 0..count {
+        let space = reader.u16()?;
+        let values = [reader.u16()?, reader.u16()?, reader.u16()?, reader.u16()?];
+        let [w, x, y, z] = values.map(|v| v as Float / u16::MAX as Float);
+        let name = if version == 2 {
+            let len = reader.u32()? as usize;
+            let units = (0..len).map(|_| reader.u16()).collect::<Result<Vec<_>>>()?;
+            let name = String::from_utf16_lossy(&units);
+            Some(name.trim_end_matches('\0').to_string())
+        } else {
+            None
+        };
+        let color: Color = match space {
+            RGB => Rgb::new(w, x, y).into(),
+            HSB => Hsv::new(w, x, y).into(),
+            // 0 is full ink
+            CMYK => Cmyk::new(1. - w, 1. - x, 1. - y, 1. - z).into(),
+            _ => continue,
+        };
+        colors.push((color, name));
+    }
+    Ok(colors)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + N)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| anyhow!("truncated swatches file"))?;
+        self.pos += N;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        self.take().map(u16::from_be_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        self.take().map(u32::from_be_bytes)
+    }
+}
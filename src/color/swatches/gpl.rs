@@ -0,0 +1,38 @@
+use super::palette_from_entries;
+use crate::color::{NamedPalette, Rgb};
+
+use anyhow::{bail, Result};
+
+/// Reads GIMP palettes, which are also the palettes of Inkscape
+pub fn import(text: &str, name: &str) -> Result<NamedPalette> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim) != Some("GIMP Palette") {
+        bail!("missing `GIMP Palette` header");
+    }
+
+    let mut palette_name = name.to_string();
+    let mut colors = vec![];
+    for (i, line) in lines.enumerate() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix("Name:") {
+            palette_name = name.trim().trim_end_matches(".gpl").to_string();
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let mut channel = || fields.next().and_then(|c| c.parse::<u8>().ok());
+        let (r, g, b) = match (channel(), channel(), channel()) {
+            (Some(r), Some(g), Some(b)) => (r, g, b),
+            _ => bail!("invalid color `{}` on line {}", line, i + 2),
+        };
+        let color_name = fields.collect::<Vec<_>>().join(" ");
+        // names given by GIMP and by the palette export of epick aren't worth keeping
+        let color_name = (color_name != "Untitled"
+            && color_name != format!("color {}", colors.len()))
+        .then_some(color_name);
+        colors.push((Rgb::new_scaled(r, g, b).into(), color_name));
+    }
+    Ok(palette_from_entries(&palette_name, colors))
+}
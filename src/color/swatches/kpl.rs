@@ -0,0 +1,90 @@
+use super::{
+    entries, palette_from_entries, xml_elements, xml_escape,
+    zip::{self, ZipEntry},
+};
+use crate::{
+    color::{Color, NamedPalette, Rgb},
+    math::Float,
+};
+
+use anyhow::{anyhow, Result};
+use std::fmt::Write as _;
+
+const MIMETYPE: &str = "krita/x-colorset";
+const COLORSET: &str = "colorset.xml";
+const COLUMNS: usize = 8;
+
+pub fn export(palette: &NamedPalette) -> Result<Vec<u8>> {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Colorset version=\"1.0\" name=\"{}\" \
+         comment=\"{}\" columns=\"{}\" rows=\"{}\" readonly=\"false\">\n",
+        xml_escape(&palette.name),
+        xml_escape(&palette.meta.description),
+        COLUMNS,
+        palette.palette.len().div_ceil(COLUMNS),
+    );
+    for (i, (color, name)) in entries(palette).enumerate() {
+        let rgb = color.rgb();
+        let _ = write!(
+            xml,
+            " <ColorSetEntry name=\"{}\" id=\"{}\" spot=\"false\" bitdepth=\"U8\">\n  \
+             <RGB space=\"sRGB-elle-V2-srgbtrc.icc\" r=\"{}\" g=\"{}\" b=\"{}\"/>\n  \
+             <Position row=\"{}\" column=\"{}\"/>\n </ColorSetEntry>\n",
+            xml_escape(name.unwrap_or_default()),
+            i,
+            rgb.r(),
+            rgb.g(),
+            rgb.b(),
+            i / COLUMNS,
+            i % COLUMNS,
+        );
+    }
+    xml.push_str("</Colorset>\n");
+
+    zip::write(&[
+        ZipEntry {
+            name: "mimetype",
+            data: MIMETYPE.as_bytes(),
+            stored: true,
+        },
+        ZipEntry {
+            name: COLORSET,
+            data: xml.as_bytes(),
+            stored: false,
+        },
+        ZipEntry {
+            name: "profiles.xml",
+            data: b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Profiles/>\n",
+            stored: false,
+        },
+    ])
+}
+
+pub fn import(data: &[u8], name: &str) -> Result<NamedPalette> {
+    let colorset = zip::read_file(data, COLORSET)?
+        .ok_or_else(|| anyhow!("missing `{}` in the Krita palette", COLORSET))?;
+    let xml = String::from_utf8_lossy(&colorset);
+
+    let mut palette_name = name.to_string();
+    let mut entry_name = None;
+    let mut colors: Vec<(Color, Option<String>)> = vec![];
+    for element in xml_elements(&xml) {
+        match element.name {
+            "Colorset" => {
+                if let Some(name) = element.attribute("name").filter(|name| !name.is_empty()) {
+                    palette_name = name.to_string();
+                }
+            }
+            "ColorSetEntry" => entry_name = element.attribute("name").map(String::from),
+            // colors in other spaces like CMYK or Lab are skipped
+            "RGB" => {
+                let channel = |c| element.attribute(c).and_then(|c| c.parse::<Float>().ok());
+                if let (Some(r), Some(g), Some(b)) = (channel("r"), channel("g"), channel("b")) {
+                    colors.push((Rgb::new(r, g, b).into(), entry_name.take()));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(palette_from_entries(&palette_name, colors))
+}
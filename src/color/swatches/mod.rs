@@ -0,0 +1,309 @@
+//! Palette files of other applications.
+
+mod aco;
+mod gpl;
+mod kpl;
+mod paint_net;
+mod procreate;
+mod soc;
+mod zip;
+
+use crate::color::{import_tokens, Color, NamedPalette, Palette, Rgb, TokenFormat};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Palette formats of other applications that can be imported and exported
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SwatchFormat {
+    /// Krita `.kpl`, a zip archive with an XML color set
+    Krita,
+    /// LibreOffice and OpenOffice `.soc` XML color table
+    LibreOffice,
+    /// Paint.NET `.txt` palette of `aarrggbb` hex codes
+    PaintNet,
+    /// Procreate `.swatches`, a zip archive with JSON swatches
+    Procreate,
+    /// Photoshop `.aco` color swatches
+    Photoshop,
+}
+
+impl SwatchFormat {
+    pub const ALL: [SwatchFormat; 5] = [
+        SwatchFormat::Krita,
+        SwatchFormat::LibreOffice,
+        SwatchFormat::PaintNet,
+        SwatchFormat::Procreate,
+        SwatchFormat::Photoshop,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SwatchFormat::Krita => "kpl",
+            SwatchFormat::LibreOffice => "soc",
+            SwatchFormat::PaintNet => "txt",
+            SwatchFormat::Procreate => "swatches",
+            SwatchFormat::Photoshop => "aco",
+        }
+    }
+
+    /// Whether the files are text that can be copied to the clipboard
+    pub fn is_text(&self) -> bool {
+        matches!(self, SwatchFormat::LibreOffice | SwatchFormat::PaintNet)
+    }
+
+    pub fn export(&self, palette: &NamedPalette) -> Result<Vec<u8>> {
+        match self {
+            SwatchFormat::Krita => kpl::export(palette),
+            SwatchFormat::LibreOffice => Ok(soc::export(palette).into_bytes()),
+            SwatchFormat::PaintNet => Ok(paint_net::export(palette).into_bytes()),
+            SwatchFormat::Procreate => procreate::export(palette),
+            SwatchFormat::Photoshop => Ok(aco::export(palette)),
+        }
+    }
+
+    /// Reads a palette from a file of this format, `name` is used when the file doesn't name
+    /// the palette.
+    pub fn import(&self, data: &[u8], name: &str) -> Result<NamedPalette> {
+        match self {
+            SwatchFormat::Krita => kpl::import(data, name),
+            SwatchFormat::LibreOffice => soc::import(&text(data)?, name),
+            SwatchFormat::PaintNet => paint_net::import(&text(data)?, name),
+            SwatchFormat::Procreate => procreate::import(data, name),
+            SwatchFormat::Photoshop => aco::import(data, name),
+        }
+    }
+}
+
+impl AsRef<str> for SwatchFormat {
+    fn as_ref(&self) -> &str {
+        match self {
+            SwatchFormat::Krita => "Krita (kpl)",
+            SwatchFormat::LibreOffice => "LibreOffice (soc)",
+            SwatchFormat::PaintNet => "Paint.NET (txt)",
+            SwatchFormat::Procreate => "Procreate (swatches)",
+            SwatchFormat::Photoshop => "Photoshop (aco)",
+        }
+    }
+}
+
+/// Extensions of the palette files that can be imported, GIMP and Inkscape palettes, design
+/// tokens and the palettes of [`SwatchFormat`].
+pub fn import_extensions() -> Vec<&'static str> {
+    let mut extensions = vec!["gpl"];
+    for format in TokenFormat::ALL {
+        if !extensions.contains(&format.extension()) {
+            extensions.push(format.extension());
+        }
+    }
+    extensions.extend(SwatchFormat::ALL.iter().map(|format| format.extension()));
+    extensions
+}
+
+/// Reads the palettes of a file, the format is chosen by the extension of `path` and the file
+/// name is used for palettes that aren't named in the file.
+pub fn import_palette_file(path: &Path, data: &[u8]) -> Result<Vec<NamedPalette>> {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let mut palettes = if extension == "gpl" {
+        vec![gpl::import(&text(data)?, &name)?]
+    } else if TokenFormat::ALL
+        .iter()
+        .any(|format| format.extension() == extension)
+    {
+        import_tokens(&text(data)?, &name)?
+    } else {
+        let format = SwatchFormat::ALL
+            .into_iter()
+            .find(|format| format.extension() == extension)
+            .ok_or_else(|| anyhow!("unsupported palette file extension `{}`", extension))?;
+        vec![format.import(data, &name)?]
+    };
+    for palette in &mut palettes {
+        palette.meta.source = path.display().to_string();
+    }
+    Ok(palettes)
+}
+
+fn text(data: &[u8]) -> Result<String> {
+    let text = String::from_utf8(data.to_vec()).map_err(|_| anyhow!("file is not UTF-8 text"))?;
+    Ok(text.trim_start_matches('\u{feff}').to_string())
+}
+
+/// Creates a palette from imported colors with their optional names. Colors are rounded to
+/// 8 bits per channel so that colors stored as floats keep their hex codes.
+fn palette_from_entries(
+    name: &str,
+    entries: impl IntoIterator<Item = (Color, Option<String>)>,
+) -> NamedPalette {
+    let mut palette = NamedPalette::new(name, Palette::default());
+    for (color, color_name) in entries {
+        let rgb = color.rgb();
+        let [r, g, b] = [rgb.r(), rgb.g(), rgb.b()].map(|c| (c.clamp(0., 1.) * 255.).round() as u8);
+        let color = Rgb::new_scaled(r, g, b).into();
+        if palette.palette.add(color) {
+            if let Some(color_name) = color_name {
+                palette.set_color_name(&color, color_name.trim());
+            }
+        }
+    }
+    palette
+}
+
+/// Returns the color names of the palette in the order of its colors
+fn entries(palette: &NamedPalette) -> impl Iterator<Item = (&Color, Option<&str>)> {
+    palette
+        .palette
+        .iter()
+        .map(|color| (color, palette.color_name(color)))
+}
+
+/// An element of an XML document with its attributes
+struct XmlElement<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, String)>,
+}
+
+impl XmlElement<'_> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Returns the start and empty element tags of an XML document in order. This is far from a
+/// complete XML parser but palette files are simple enough.
+fn xml_elements(xml: &str) -> Vec<XmlElement<'_>> {
+    let mut elements = vec![];
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let end = match rest.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = rest[..end].trim_end_matches('/');
+        rest = &rest[end + 1..];
+        if tag.starts_with(['?', '!', '/']) {
+            continue;
+        }
+
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let mut attributes = vec![];
+        let mut attrs = &tag[name_end..];
+        while let Some(eq) = attrs.find('=') {
+            let attribute = attrs[..eq].trim();
+            let value = attrs[eq + 1..].trim_start();
+            let quote = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => break,
+            };
+            let value = &value[1..];
+            let value_end = match value.find(quote) {
+                Some(value_end) => value_end,
+                None => break,
+            };
+            attributes.push((attribute, xml_unescape(&value[..value_end])));
+            attrs = &value[value_end + 1..];
+        }
+        elements.push(XmlElement {
+            name: &tag[..name_end],
+            attributes,
+        });
+    }
+    elements
+}
+
+pub(crate) fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/color/swatches/fixtures")
+            .join(name);
+        std::fs::read(path).unwrap()
+    }
+
+    fn colors(palette: &NamedPalette) -> Vec<(String, String)> {
+        entries(palette)
+            .map(|(color, name)| (color.as_hex(), name.unwrap_or_default().to_string()))
+            .collect()
+    }
+
+    fn expected(names: bool) -> Vec<(String, String)> {
+        [
+            ("#ff0000", "Red"),
+            ("#00ff00", "Green & \"Lime\""),
+            ("#1a2b3c", "Dark <blue>"),
+        ]
+        .into_iter()
+        .map(|(hex, name)| (hex.to_string(), if names { name } else { "" }.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn imports_and_exports_application_palettes() {
+        for (format, file, palette_name, names) in [
+            (SwatchFormat::Krita, "fixture.kpl", "Fixture colors", true),
+            (SwatchFormat::LibreOffice, "fixture.soc", "fixture", true),
+            (SwatchFormat::PaintNet, "fixture.txt", "fixture", false),
+            (
+                SwatchFormat::Procreate,
+                "fixture.swatches",
+                "Fixture colors",
+                false,
+            ),
+            (SwatchFormat::Photoshop, "fixture.aco", "fixture", true),
+        ] {
+            let palette = format.import(&fixture(file), "fixture").unwrap();
+            assert_eq!(palette.name, palette_name, "{}", file);
+            assert_eq!(colors(&palette), expected(names), "{}", file);
+
+            let exported = format.export(&palette).unwrap();
+            let reimported = format.import(&exported, "fixture").unwrap();
+            assert_eq!(reimported.name, palette_name, "{}", file);
+            assert_eq!(colors(&reimported), expected(names), "{}", file);
+        }
+    }
+
+    #[test]
+    fn imports_palette_files_by_extension() {
+        let palettes =
+            import_palette_file(Path::new("dir/fixture.gpl"), &fixture("fixture.gpl")).unwrap();
+        assert_eq!(palettes[0].name, "Fixture colors");
+        assert_eq!(palettes[0].meta.source, "dir/fixture.gpl");
+        assert_eq!(colors(&palettes[0]), expected(true));
+
+        let palettes =
+            import_palette_file(Path::new("fixture.aco"), &fixture("fixture.aco")).unwrap();
+        assert_eq!(colors(&palettes[0]), expected(true));
+        assert!(import_palette_file(Path::new("fixture.png"), &[]).is_err());
+        assert!(import_extensions().contains(&"kpl"));
+    }
+}
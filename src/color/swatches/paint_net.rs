@@ -0,0 +1,41 @@
+use super::palette_from_entries;
+use crate::color::{Color, NamedPalette};
+
+use anyhow::{bail, Result};
+use std::fmt::Write as _;
+
+const HEADER: &str = "; paint.net Palette File
+; Lines that start with a semicolon are comments
+; Colors are written as 8-digit hexadecimal numbers: aarrggbb
+";
+
+pub fn export(palette: &NamedPalette) -> String {
+    let mut text = HEADER.to_string();
+    let _ = writeln!(text, "; {}", palette.name);
+    for color in palette.palette.iter() {
+        let (r, g, b) = color.as_rgb_triplet_scaled();
+        let _ = writeln!(text, "FF{:02X}{:02X}{:02X}", r, g, b);
+    }
+    text
+}
+
+/// Reads `aarrggbb` lines, `#rrggbb` hex lists are accepted as well
+pub fn import(text: &str, name: &str) -> Result<NamedPalette> {
+    let mut colors = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+        let hex = line.trim_start_matches('#');
+        let hex = match hex.len() {
+            8 => &hex[2..],
+            _ => hex,
+        };
+        match Color::from_hex(hex).filter(|_| hex.chars().all(|c| c.is_ascii_hexdigit())) {
+            Some(color) => colors.push((color, None)),
+            None => bail!("invalid color `{}` on line {}", line, i + 1),
+        }
+    }
+    Ok(palette_from_entries(name, colors))
+}
@@ -0,0 +1,67 @@
+use super::{
+    palette_from_entries,
+    zip::{self, ZipEntry},
+};
+use crate::{
+    color::{Hsv, NamedPalette},
+    math::Float,
+};
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+const SWATCHES: &str = "Swatches.json";
+
+pub fn export(palette: &NamedPalette) -> Result<Vec<u8>> {
+    let swatches: Vec<_> = palette
+        .palette
+        .iter()
+        .map(|color| {
+            let hsv = color.hsv();
+            json!({
+                "hue": hsv.h(),
+                "saturation": hsv.s(),
+                "brightness": hsv.v(),
+                "alpha": 1,
+                "colorSpace": 0,
+            })
+        })
+        .collect();
+    let json = json!([{ "name": palette.name, "swatches": swatches }]);
+    zip::write(&[ZipEntry {
+        name: SWATCHES,
+        data: json.to_string().as_bytes(),
+        stored: false,
+    }])
+}
+
+pub fn import(data: &[u8], name: &str) -> Result<NamedPalette> {
+    let swatches = zip::read_file(data, SWATCHES)?
+        .ok_or_else(|| anyhow!("missing `{}` in the Procreate swatches", SWATCHES))?;
+    let json: Value = serde_json::from_slice(&swatches)?;
+    // a single palette or a list of palettes of which the first one is used
+    let json = match &json {
+        Value::Array(palettes) => palettes.first().unwrap_or(&Value::Null),
+        json => json,
+    };
+
+    let palette_name = json["name"]
+        .as_str()
+        .filter(|name| !name.is_empty())
+        .unwrap_or(name);
+    let colors = json["swatches"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        // empty slots of the palette are null
+        .filter_map(|swatch| {
+            let channel = |c: &str| swatch[c].as_f64().map(|c| c as Float);
+            let hsv = Hsv::new(
+                channel("hue")?,
+                channel("saturation")?,
+                channel("brightness")?,
+            );
+            Some((hsv.into(), None))
+        });
+    Ok(palette_from_entries(palette_name, colors))
+}
@@ -0,0 +1,40 @@
+use super::{entries, palette_from_entries, xml_elements, xml_escape};
+use crate::color::{Color, NamedPalette};
+
+use std::fmt::Write as _;
+
+use anyhow::Result;
+
+const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ooo:color-table xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:draw="urn:oasis:names:tc:opendocument:xmlns:drawing:1.0" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:svg="http://www.w3.org/2000/svg" xmlns:ooo="http://openoffice.org/2004/office">
+"#;
+
+pub fn export(palette: &NamedPalette) -> String {
+    let mut xml = HEADER.to_string();
+    for (i, (color, name)) in entries(palette).enumerate() {
+        let name = match name {
+            Some(name) => xml_escape(name),
+            None => format!("{} {}", xml_escape(&palette.name), i + 1),
+        };
+        let _ = writeln!(
+            xml,
+            "  <draw:color draw:name=\"{}\" draw:color=\"{}\"/>",
+            name,
+            color.as_hex()
+        );
+    }
+    xml.push_str("</ooo:color-table>\n");
+    xml
+}
+
+pub fn import(xml: &str, name: &str) -> Result<NamedPalette> {
+    let colors = xml_elements(xml)
+        .into_iter()
+        .filter(|element| element.name == "draw:color")
+        .filter_map(|element| {
+            let hex = element.attribute("draw:color")?;
+            let color = Color::from_hex(hex.trim_start_matches('#'))?;
+            Some((color, element.attribute("draw:name").map(String::from)))
+        });
+    Ok(palette_from_entries(name, colors))
+}
@@ -0,0 +1,242 @@
+//! Just enough of the zip format to read and write the archives of Krita and Procreate palettes.
+
+use anyhow::{anyhow, bail, Context, Result};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use std::io::{Read, Write};
+
+const LOCAL_HEADER: u32 = 0x04034b50;
+const CENTRAL_HEADER: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIR: u32 = 0x06054b50;
+const END_OF_CENTRAL_DIR_LEN: usize = 22;
+
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+/// Largest uncompressed file read from an archive, palettes are far smaller. The sizes in the
+/// headers aren't trusted and only the requested file is decompressed so that a small crafted
+/// archive can't exhaust the memory.
+const MAX_FILE_LEN: usize = 16 << 20;
+
+pub struct ZipEntry<'a> {
+    pub name: &'a str,
+    pub data: &'a [u8],
+    /// Store the data uncompressed, required for the `mimetype` entry of Krita palettes
+    pub stored: bool,
+}
+
+pub fn write(entries: &[ZipEntry<'_>]) -> Result<Vec<u8>> {
+    let mut zip = vec![];
+    let mut central_dir = vec![];
+    for entry in entries {
+        let (method, data) = if entry.stored {
+            (STORED, entry.data.to_vec())
+        } else {
+            let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+            encoder.write_all(entry.data)?;
+            (DEFLATED, encoder.finish()?)
+        };
+        let crc = crc32fast::hash(entry.data);
+        let offset = zip.len() as u32;
+
+        // fields shared by the local and central headers, starting at the version needed
+        let mut fields = vec![];
+        put_u16(&mut fields, 20);
+        put_u16(&mut fields, 0);
+        put_u16(&mut fields, method);
+        put_u16(&mut fields, 0);
+        // 1980-01-01, the earliest date zip can store
+        put_u16(&mut fields, 0x21);
+        put_u32(&mut fields, crc);
+        put_u32(&mut fields, data.len() as u32);
+        put_u32(&mut fields, entry.data.len() as u32);
+        put_u16(&mut fields, entry.name.len() as u16);
+        put_u16(&mut fields, 0);
+
+        put_u32(&mut zip, LOCAL_HEADER);
+        zip.extend_from_slice(&fields);
+        zip.extend_from_slice(entry.name.as_bytes());
+        zip.extend_from_slice(&data);
+
+        put_u32(&mut central_dir, CENTRAL_HEADER);
+        put_u16(&mut central_dir, 20);
+        central_dir.extend_from_slice(&fields);
+        // comment length, disk number, internal and external attributes
+        put_u16(&mut central_dir, 0);
+        put_u16(&mut central_dir, 0);
+        put_u16(&mut central_dir, 0);
+        put_u32(&mut central_dir, 0);
+        put_u32(&mut central_dir, offset);
+        central_dir.extend_from_slice(entry.name.as_bytes());
+    }
+
+    let central_dir_offset = zip.len() as u32;
+    zip.extend_from_slice(&central_dir);
+    put_u32(&mut zip, END_OF_CENTRAL_DIR);
+    put_u16(&mut zip, 0);
+    put_u16(&mut zip, 0);
+    put_u16(&mut zip, entries.len() as u16);
+    put_u16(&mut zip, entries.len() as u16);
+    put_u32(&mut zip, central_dir.len() as u32);
+    put_u32(&mut zip, central_dir_offset);
+    put_u16(&mut zip, 0);
+    Ok(zip)
+}
+
+/// Returns the uncompressed data of the file `name` in the archive, only this file is
+/// decompressed
+pub fn read_file(zip: &[u8], name: &str) -> Result<Option<Vec<u8>>> {
+    let end = (0..=zip.len().saturating_sub(END_OF_CENTRAL_DIR_LEN))
+        .rev()
+        .find(|&i| u32_at(zip, i) == Some(END_OF_CENTRAL_DIR))
+        .ok_or_else(|| anyhow!("not a zip archive"))?;
+    let count = u16_at(zip, end + 10).unwrap_or_default() as usize;
+    let mut pos = u32_at(zip, end + 16).unwrap_or_default() as usize;
+
+    let truncated = || anyhow!("truncated zip archive");
+    // offsets are summed with `checked_add` as they could wrap around on 32 bit targets
+    let add = |a: usize, b: usize| a.checked_add(b).ok_or_else(truncated);
+    for _ in 0..count {
+        if u32_at(zip, pos) != Some(CENTRAL_HEADER) {
+            bail!("invalid zip central directory");
+        }
+        let method = u16_at(zip, pos + 10).ok_or_else(truncated)?;
+        let compressed_len = u32_at(zip, pos + 20).ok_or_else(truncated)? as usize;
+        let name_len = u16_at(zip, pos + 28).ok_or_else(truncated)? as usize;
+        let extra_len = u16_at(zip, pos + 30).ok_or_else(truncated)? as usize;
+        let comment_len = u16_at(zip, pos + 32).ok_or_else(truncated)? as usize;
+        let offset = u32_at(zip, pos + 42).ok_or_else(truncated)? as usize;
+        let name_start = add(pos, 46)?;
+        let entry_name = zip
+            .get(name_start..add(name_start, name_len)?)
+            .ok_or_else(truncated)?;
+        pos = add(add(add(name_start, name_len)?, extra_len)?, comment_len)?;
+        if entry_name != name.as_bytes() {
+            continue;
+        }
+
+        if u32_at(zip, offset) != Some(LOCAL_HEADER) {
+            bail!("invalid zip entry `{}`", name);
+        }
+        let data_start = add(
+            add(offset, 30)?,
+            add(
+                u16_at(zip, add(offset, 26)?).ok_or_else(truncated)? as usize,
+                u16_at(zip, add(offset, 28)?).ok_or_else(truncated)? as usize,
+            )?,
+        )?;
+        let data = zip
+            .get(data_start..add(data_start, compressed_len)?)
+            .ok_or_else(truncated)?;
+        let data = match method {
+            STORED => data.to_vec(),
+            DEFLATED => {
+                let mut decompressed = vec![];
+                DeflateDecoder::new(data)
+                    .take(MAX_FILE_LEN as u64 + 1)
+                    .read_to_end(&mut decompressed)
+                    .with_context(|| format!("failed to decompress `{}`", name))?;
+                decompressed
+            }
+            method => bail!("unsupported compression method {} of `{}`", method, name),
+        };
+        if data.len() > MAX_FILE_LEN {
+            bail!("`{}` is larger than {} MiB", name, MAX_FILE_LEN >> 20);
+        }
+        return Ok(Some(data));
+    }
+    Ok(None)
+}
+
+fn put_u16(buf: &mut Vec<u8>, n: u16) {
+    buf.extend_from_slice(&n.to_le_bytes());
+}
+
+fn put_u32(buf: &mut Vec<u8>, n: u32) {
+    buf.extend_from_slice(&n.to_le_bytes());
+}
+
+fn u16_at(buf: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        buf.get(pos..pos.checked_add(2)?)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(buf: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        buf.get(pos..pos.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_the_sizes_in_the_headers() {
+        let mut zip = write(&[ZipEntry {
+            name: "palette.json",
+            data: b"[]",
+            stored: false,
+        }])
+        .unwrap();
+        // uncompressed size in the central directory
+        let pos = zip.len() - END_OF_CENTRAL_DIR_LEN - 46 - "palette.json".len() + 24;
+        zip[pos..pos + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            read_file(&zip, "palette.json").unwrap(),
+            Some(b"[]".to_vec())
+        );
+    }
+
+    #[test]
+    fn limits_the_size_of_decompressed_files() {
+        let data = vec![0; MAX_FILE_LEN + 1];
+        let zip = write(&[ZipEntry {
+            name: "bomb",
+            data: &data,
+            stored: false,
+        }])
+        .unwrap();
+        assert_eq!(
+            read_file(&zip, "bomb").unwrap_err().to_string(),
+            "`bomb` is larger than 16 MiB"
+        );
+    }
+
+    #[test]
+    fn only_decompresses_the_requested_file() {
+        let data = vec![0; MAX_FILE_LEN + 1];
+        let zip = write(&[
+            ZipEntry {
+                name: "bomb",
+                data: &data,
+                stored: false,
+            },
+            ZipEntry {
+                name: "palette.json",
+                data: b"[]",
+                stored: false,
+            },
+        ])
+        .unwrap();
+        assert_eq!(
+            read_file(&zip, "palette.json").unwrap(),
+            Some(b"[]".to_vec())
+        );
+        assert_eq!(read_file(&zip, "missing.json").unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_offsets_past_the_end() {
+        let mut zip = write(&[ZipEntry {
+            name: "palette.json",
+            data: b"[]",
+            stored: false,
+        }])
+        .unwrap();
+        // offset of the local header in the central directory
+        let pos = zip.len() - END_OF_CENTRAL_DIR_LEN - "palette.json".len() - 4;
+        zip[pos..pos + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(read_file(&zip, "palette.json").is_err());
+    }
+}
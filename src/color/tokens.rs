@@ -0,0 +1,961 @@
+use crate::{
+    color::{
+        nearest_name, parse_color, Color, ColorFormat, FormatOptions, Hsl, NamedPalette, Palette,
+        Rgb,
+    },
+    math::Float,
+};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, fmt::Write as _};
+
+/// Maximum length of a chain of token references, longer chains are treated as cycles
+const MAX_REFERENCE_DEPTH: usize = 32;
+
+/// Design token file formats a palette can be exported to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TokenFormat {
+    /// W3C Design Tokens Community Group JSON
+    #[default]
+    Dtcg,
+    StyleDictionary,
+    Css,
+    Scss,
+    Less,
+}
+
+impl TokenFormat {
+    pub const ALL: [TokenFormat; 5] = [
+        TokenFormat::Dtcg,
+        TokenFormat::StyleDictionary,
+        TokenFormat::Css,
+        TokenFormat::Scss,
+        TokenFormat::Less,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TokenFormat::Dtcg | TokenFormat::StyleDictionary => "json",
+            TokenFormat::Css => "css",
+            TokenFormat::Scss => "scss",
+            TokenFormat::Less => "less",
+        }
+    }
+}
+
+impl AsRef<str> for TokenFormat {
+    fn as_ref(&self) -> &str {
+        match self {
+            TokenFormat::Dtcg => "Design tokens (DTCG)",
+            TokenFormat::StyleDictionary => "Style Dictionary",
+            TokenFormat::Css => "CSS variables",
+            TokenFormat::Scss => "SCSS map",
+            TokenFormat::Less => "Less variables",
+        }
+    }
+}
+
+/// Where the name of each token comes from, colors named in the palette keep their names
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TokenNameSource {
+    /// `color-1`, `color-2`...
+    #[default]
+    Index,
+    /// The nearest color name from the color names database
+    ColorName,
+    /// The hex code of the color without the `#`
+    Hex,
+}
+
+impl TokenNameSource {
+    fn words(&self, i: usize, color: &Color) -> Vec<String> {
+        match self {
+            TokenNameSource::Index => vec!["color".into(), (i + 1).to_string()],
+            TokenNameSource::ColorName => nearest_name(color)
+                .map(|(name, _)| words(&name))
+                .unwrap_or_default(),
+            TokenNameSource::Hex => vec![color.as_hex().trim_start_matches('#').to_string()],
+        }
+    }
+}
+
+impl AsRef<str> for TokenNameSource {
+    fn as_ref(&self) -> &str {
+        match self {
+            TokenNameSource::Index => "index",
+            TokenNameSource::ColorName => "color name",
+            TokenNameSource::Hex => "hex code",
+        }
+    }
+}
+
+/// How the words of a token name are joined
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TokenCase {
+    /// `light-blue`
+    #[default]
+    Kebab,
+    /// `light_blue`
+    Snake,
+    /// `lightBlue`
+    Camel,
+    /// `LightBlue`
+    Pascal,
+}
+
+impl TokenCase {
+    pub(crate) fn join(&self, words: &[String]) -> String {
+        match self {
+            TokenCase::Kebab => words.join("-"),
+            TokenCase::Snake => words.join("_"),
+            TokenCase::Camel | TokenCase::Pascal => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) if i > 0 || *self == TokenCase::Pascal => {
+                            first.to_uppercase().chain(chars).collect()
+                        }
+                        _ => word.clone(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl AsRef<str> for TokenCase {
+    fn as_ref(&self) -> &str {
+        match self {
+            TokenCase::Kebab => "kebab-case",
+            TokenCase::Snake => "snake_case",
+            TokenCase::Camel => "camelCase",
+            TokenCase::Pascal => "PascalCase",
+        }
+    }
+}
+
+/// Representation of the color value of each token
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TokenValue {
+    #[default]
+    Hex,
+    HexUppercase,
+    CssRgb,
+    CssHsl,
+    /// A saved custom color format with its name and format text
+    Custom(String, String),
+}
+
+impl TokenValue {
+    pub(crate) fn color_format(&self) -> ColorFormat<'_> {
+        match self {
+            TokenValue::Hex => ColorFormat::Hex,
+            TokenValue::HexUppercase => ColorFormat::HexUpercase,
+            TokenValue::CssRgb => ColorFormat::CssRgb,
+            TokenValue::CssHsl => ColorFormat::CssHsl {
+                degree_symbol: false,
+            },
+            TokenValue::Custom(_, fmt) => ColorFormat::Custom(fmt),
+        }
+    }
+}
+
+impl AsRef<str> for TokenValue {
+    fn as_ref(&self) -> &str {
+        match self {
+            TokenValue::Hex => "hex",
+            TokenValue::HexUppercase => "hex uppercase",
+            TokenValue::CssRgb => "css rgb",
+            TokenValue::CssHsl => "css hsl",
+            TokenValue::Custom(name, _) => name,
+        }
+    }
+}
+
+/// Exports a palette as design tokens with one token per color
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TokenExport {
+    pub format: TokenFormat,
+    #[serde(default)]
+    pub names: TokenNameSource,
+    #[serde(default)]
+    pub case: TokenCase,
+    #[serde(default)]
+    pub value: TokenValue,
+    /// Nest the tokens in a group named after the palette, or prefix the variable names with
+    /// it in the CSS and Less formats. SCSS maps are always named after the palette.
+    #[serde(default)]
+    pub group: bool,
+}
+
+impl TokenExport {
+    pub fn export(&self, palette: &NamedPalette, options: &FormatOptions) -> String {
+        let format = self.value.color_format();
+        let tokens: Vec<_> = self
+            .tokens(palette)
+            .into_iter()
+            .map(|(name, color)| (name, color.display(format.clone(), options)))
+            .collect();
+        let group = self.case.join(&words(&palette.name));
+
+        match self.format {
+            TokenFormat::Dtcg => {
+                let mut json = String::from("{\n");
+                let indent = if self.group {
+                    let _ = writeln!(json, "  {}: {{", json_string(&group));
+                    if !palette.meta.description.is_empty() {
+                        let _ = writeln!(
+                            json,
+                            "    \"$description\": {},",
+                            json_string(&palette.meta.description)
+                        );
+                    }
+                    let _ = writeln!(json, "    \"$type\": \"color\",");
+                    "    "
+                } else {
+                    "  "
+                };
+                write_json_tokens(&mut json, &tokens, indent, |value| {
+                    if self.group {
+                        format!("{{ \"$value\": {} }}", json_string(value))
+                    } else {
+                        format!(
+                            "{{ \"$type\": \"color\", \"$value\": {} }}",
+                            json_string(value)
+                        )
+                    }
+                });
+                if self.group {
+                    json.push_str("  }\n");
+                }
+                json.push_str("}\n");
+                json
+            }
+            TokenFormat::StyleDictionary => {
+                let mut json = String::from("{\n  \"color\": {\n");
+                let indent = if self.group {
+                    let _ = writeln!(json, "    {}: {{", json_string(&group));
+                    "      "
+                } else {
+                    "    "
+                };
+                write_json_tokens(&mut json, &tokens, indent, |value| {
+                    format!("{{ \"value\": {} }}", json_string(value))
+                });
+                if self.group {
+                    json.push_str("    }\n");
+                }
+                json.push_str("  }\n}\n");
+                json
+            }
+            TokenFormat::Css => {
+                let mut css = String::from(":root {\n");
+                for (name, value) in &tokens {
+                    let _ = writeln!(css, "  --{}: {};", self.prefixed(&group, name), value);
+                }
+                css.push_str("}\n");
+                css
+            }
+            TokenFormat::Scss => {
+                let mut scss = format!("${}: (\n", group);
+                for (name, value) in &tokens {
+                    let _ = writeln!(scss, "  \"{}\": {},", name, value);
+                }
+                scss.push_str(");\n");
+                scss
+            }
+            TokenFormat::Less => tokens
+                .iter()
+                .fold(String::new(), |mut less, (name, value)| {
+                    let _ = writeln!(less, "@{}: {};", self.prefixed(&group, name), value);
+                    less
+                }),
+        }
+    }
+
+    fn prefixed(&self, group: &str, name: &str) -> String {
+        if self.group {
+            self.case.join(&[group.to_string(), name.to_string()])
+        } else {
+            name.to_string()
+        }
+    }
+
+    fn token_names(&self, palette: &NamedPalette) -> Vec<String> {
+        entry_names(palette, self.names, self.case, false)
+    }
+
+    /// Returns the names of the tokens with their colors, the aliases of a color follow it
+    fn tokens<'p>(&self, palette: &'p NamedPalette) -> Vec<(String, &'p Color)> {
+        let mut names = self.token_names(palette);
+        let mut tokens = Vec::with_capacity(names.len());
+        for (i, color) in palette.palette.iter().enumerate() {
+            tokens.push((names[i].clone(), color));
+            for alias in palette.color_aliases(color) {
+                let name = unique_name(words(alias), self.case, &names);
+                names.push(name.clone());
+                tokens.push((name, color));
+            }
+        }
+        tokens
+    }
+}
+
+/// Returns unique names of the colors of the palette in order. Colors named in the palette keep
+/// their names, the others are named by `source`. With `identifier` set, names that would start
+/// with a digit are prefixed with `color` so they can be used as variable names in code.
+pub(crate) fn entry_names(
+    palette: &NamedPalette,
+    source: TokenNameSource,
+    case: TokenCase,
+    identifier: bool,
+) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(palette.palette.len());
+    for (i, color) in palette.palette.iter().enumerate() {
+        let mut name_words = match palette.color_name(color) {
+            Some(name) => words(name),
+            None => source.words(i, color),
+        };
+        if name_words.is_empty() {
+            name_words = vec!["color".into(), (i + 1).to_string()];
+        }
+        if identifier && name_words[0].starts_with(|c: char| c.is_ascii_digit()) {
+            name_words.insert(0, "color".into());
+        }
+        names.push(unique_name(name_words, case, &names));
+    }
+    names
+}
+
+/// Joins the words of a name numbering it if it is already in `names`
+fn unique_name(name_words: Vec<String>, case: TokenCase, names: &[String]) -> String {
+    let mut name = case.join(&name_words);
+    let mut n = 2;
+    while names.contains(&name) {
+        let mut numbered = name_words.clone();
+        numbered.push(n.to_string());
+        name = case.join(&numbered);
+        n += 1;
+    }
+    name
+}
+
+/// Reads color tokens from DTCG or Style Dictionary JSON, CSS custom properties or SCSS and Less
+/// variables. Every token group of the JSON tree and every SCSS map becomes a palette, tokens
+/// outside of any group end up in a palette called `name`. Token names are kept as color names,
+/// or as aliases of the color when an earlier token of the group has the same value. References
+/// to other tokens are resolved and tokens that aren't colors are skipped.
+pub fn import_tokens(data: &str, name: &str) -> Result<Vec<NamedPalette>> {
+    let tokens = if data.trim_start().starts_with('{') {
+        let json: Value = serde_json::from_str(data)?;
+        let mut tokens = vec![];
+        json_tokens(&json, &mut vec![], None, &mut tokens);
+        tokens
+    } else {
+        stylesheet_tokens(data)
+    };
+    let values: HashMap<&str, &str> = tokens
+        .iter()
+        .map(|token| (token.key.as_str(), token.value.as_str()))
+        .collect();
+
+    let mut palettes: Vec<NamedPalette> = vec![];
+    for token in &tokens {
+        let color = match resolve(&token.value, &values, 0) {
+            Some(color) => color,
+            None => continue,
+        };
+        let group = if token.group.is_empty() {
+            name.to_string()
+        } else {
+            token.group.join(".")
+        };
+        let i = match palettes.iter().position(|p| p.name == group) {
+            Some(i) => i,
+            None => {
+                palettes.push(NamedPalette::new(group, Palette::default()));
+                palettes.len() - 1
+            }
+        };
+        let palette = &mut palettes[i];
+        if palette.palette.add(color) {
+            palette.set_color_name(&color, token.name.clone());
+        } else {
+            // the palette holds every color once, tokens with the value of an earlier token
+            // are kept as other names of its color
+            palette.add_color_alias(&color, token.name.clone());
+        }
+    }
+    if palettes.is_empty() {
+        bail!("no color tokens found");
+    }
+    Ok(palettes)
+}
+
+#[derive(Debug)]
+struct RawToken {
+    /// Path of the token group, empty for tokens outside of any group
+    group: Vec<String>,
+    name: String,
+    /// Text other tokens use to reference this token
+    key: String,
+    value: String,
+}
+
+fn json_tokens(
+    json: &Value,
+    path: &mut Vec<String>,
+    kind: Option<&str>,
+    tokens: &mut Vec<RawToken>,
+) {
+    let map = match json {
+        Value::Object(map) => map,
+        _ => return,
+    };
+    let kind = map
+        .get("$type")
+        .or_else(|| map.get("type"))
+        .and_then(Value::as_str)
+        .or(kind);
+
+    if let Some(value) = map.get("$value").or_else(|| map.get("value")) {
+        if kind.is_some_and(|kind| kind != "color") {
+            return;
+        }
+        let value = match value {
+            Value::String(value) => value.clone(),
+            // DTCG color objects like `{"colorSpace": "srgb", "components": [1, 0, 0]}`
+            Value::Object(color) => match color.get("hex").and_then(Value::as_str) {
+                Some(hex) => hex.to_string(),
+                None => match (color.get("colorSpace"), color.get("components")) {
+                    (Some(Value::String(space)), Some(Value::Array(components)))
+                        if space == "srgb" && components.len() == 3 =>
+                    {
+                        let c: Vec<_> = components.iter().filter_map(Value::as_f64).collect();
+                        if c.len() != 3 {
+                            return;
+                        }
+                        Color::from(Rgb::new(c[0] as Float, c[1] as Float, c[2] as Float)).as_hex()
+                    }
+                    _ => return,
+                },
+            },
+            _ => return,
+        };
+        if let Some((name, group)) = path.split_last() {
+            tokens.push(RawToken {
+                group: group.to_vec(),
+                name: name.clone(),
+                key: path.join("."),
+                value,
+            });
+        }
+        return;
+    }
+
+    for (key, child) in map {
+        if key.starts_with('$') {
+            continue;
+        }
+        path.push(key.clone());
+        json_tokens(child, path, kind, tokens);
+        path.pop();
+    }
+}
+
+/// Reads `--name: value` custom properties, `$name: value` SCSS and `@name: value` Less
+/// variables. SCSS maps become a group of tokens named after the map.
+fn stylesheet_tokens(data: &str) -> Vec<RawToken> {
+    let mut tokens = vec![];
+    for statement in strip_comments(data).split(';') {
+        // skip selectors and at-rules preceding the declaration
+        let statement = statement.rsplit(['{', '}']).next().unwrap_or_default();
+        let (key, value) = match statement.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+        let name = match key
+            .strip_prefix("--")
+            .or_else(|| key.strip_prefix('$'))
+            .or_else(|| key.strip_prefix('@'))
+        {
+            Some(name)
+                if !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '-' || c == '_') =>
+            {
+                name
+            }
+            _ => continue,
+        };
+        let value = value
+            .trim_end_matches("!default")
+            .trim_end_matches("!important")
+            .trim();
+
+        match value.strip_prefix('(').and_then(|v| v.strip_suffix(')')) {
+            Some(entries) if key.starts_with('$') => {
+                for entry in split_top_level(entries) {
+                    if let Some((entry_name, value)) = entry.split_once(':') {
+                        let entry_name = entry_name.trim().trim_matches(['"', '\'']);
+                        tokens.push(RawToken {
+                            group: vec![name.to_string()],
+                            name: entry_name.to_string(),
+                            key: format!("{}.{}", key, entry_name),
+                            value: value.trim().to_string(),
+                        });
+                    }
+                }
+            }
+            _ => tokens.push(RawToken {
+                group: vec![],
+                name: name.to_string(),
+                key: key.to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+    tokens
+}
+
+fn strip_comments(data: &str) -> String {
+    let mut stripped = String::with_capacity(data.len());
+    let mut rest = data;
+    while let Some(start) = rest.find("/*") {
+        stripped.push_str(&rest[..start]);
+        rest = rest[start..]
+            .find("*/")
+            .map_or("", |end| &rest[start + end + 2..]);
+    }
+    stripped.push_str(rest);
+    stripped
+        .lines()
+        .map(|line| match line.find("//") {
+            // keep the `//` of URLs
+            Some(i) if !line[..i].ends_with(':') => &line[..i],
+            _ => line,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits on commas outside of parentheses
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+        .into_iter()
+        .filter(|part| !part.trim().is_empty())
+        .collect()
+}
+
+/// Returns the color of a token value following references to other tokens
+fn resolve(value: &str, values: &HashMap<&str, &str>, depth: usize) -> Option<Color> {
+    if depth > MAX_REFERENCE_DEPTH {
+        return None;
+    }
+    let value = value.trim();
+    let lookup = |key: &str| {
+        values
+            .get(key)
+            .and_then(|value| resolve(value, values, depth + 1))
+    };
+
+    // DTCG and Style Dictionary references like `{color.base.red}` or `{color.base.red.value}`
+    if let Some(reference) = value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) {
+        return lookup(reference).or_else(|| {
+            reference
+                .strip_suffix(".value")
+                .or_else(|| reference.strip_suffix(".$value"))
+                .and_then(lookup)
+        });
+    }
+    if let Some(args) = value.strip_prefix("var(").and_then(|v| v.strip_suffix(')')) {
+        let (reference, fallback) = match args.split_once(',') {
+            Some((reference, fallback)) => (reference.trim(), Some(fallback)),
+            None => (args.trim(), None),
+        };
+        return lookup(reference)
+            .or_else(|| fallback.and_then(|fallback| resolve(fallback, values, depth + 1)));
+    }
+    if value.starts_with('$') || value.starts_with('@') {
+        return lookup(value);
+    }
+    parse_token_color(value)
+}
+
+/// Parses hex codes with 3, 4, 6 or 8 digits, `rgb()` and `hsl()` functions and color names.
+/// The alpha channel is ignored.
+fn parse_token_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let hex: String = match hex.len() {
+            3 | 4 => hex.chars().take(3).flat_map(|c| [c, c]).collect(),
+            6 | 8 => hex[..6].to_string(),
+            _ => return None,
+        };
+        return Color::from_hex(&hex);
+    }
+
+    if let Some((function, args)) = value.strip_suffix(')').and_then(|v| v.split_once('(')) {
+        let args: Vec<&str> = args
+            .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+            .filter(|arg| !arg.is_empty())
+            .collect();
+        if args.len() < 3 {
+            return None;
+        }
+        // a percentage or a number in the range 0 ..= `max`, scaled to 0 ..= 1
+        let channel = |arg: &str, max: Float| -> Option<Float> {
+            match arg.strip_suffix('%') {
+                Some(percent) => percent.parse::<Float>().ok().map(|p| p / 100.),
+                None => arg.parse::<Float>().ok().map(|n| n / max),
+            }
+        };
+        return match function.trim().to_ascii_lowercase().as_str() {
+            "rgb" | "rgba" => Some(
+                Rgb::new(
+                    channel(args[0], 255.)?,
+                    channel(args[1], 255.)?,
+                    channel(args[2], 255.)?,
+                )
+                .into(),
+            ),
+            "hsl" | "hsla" => {
+                let hue = args[0].trim_end_matches("deg").parse::<Float>().ok()?;
+                Some(
+                    Hsl::new(
+                        hue.rem_euclid(360.) / 360.,
+                        channel(args[1], 100.)?,
+                        channel(args[2], 100.)?,
+                    )
+                    .into(),
+                )
+            }
+            _ => None,
+        };
+    }
+
+    // only look up names so that numbers like `100000` aren't taken for hex codes
+    if value.chars().all(char::is_alphabetic) {
+        parse_color(value)
+    } else {
+        None
+    }
+}
+
+/// Splits a name into lowercase words on anything that is not alphanumeric and on lower to upper
+/// case changes.
+pub(crate) fn words(name: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            prev_lower = false;
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        if c.is_uppercase() && prev_lower && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        prev_lower = c.is_lowercase() || c.is_numeric();
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn json_string(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| "\"\"".into())
+}
+
+fn write_json_tokens(
+    json: &mut String,
+    tokens: &[(String, String)],
+    indent: &str,
+    token: impl Fn(&str) -> String,
+) {
+    for (i, (name, value)) in tokens.iter().enumerate() {
+        let comma = if i + 1 < tokens.len() { "," } else { "" };
+        let _ = writeln!(
+            json,
+            "{}{}: {}{}",
+            indent,
+            json_string(name),
+            token(value),
+            comma
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::palette::test_palette;
+
+    fn palette() -> NamedPalette {
+        test_palette("Brand Colors", &[(255, 0, 0), (0, 0, 255)], &[])
+    }
+
+    fn export(tokens: &TokenExport) -> String {
+        tokens.export(&palette(), &FormatOptions::default())
+    }
+
+    #[test]
+    fn exports_design_tokens() {
+        let mut tokens = TokenExport {
+            group: true,
+            ..TokenExport::default()
+        };
+        let dtcg = export(&tokens);
+        assert_eq!(
+            dtcg,
+            r##"{
+  "brand-colors": {
+    "$type": "color",
+    "color-1": { "$value": "#ff0000" },
+    "color-2": { "$value": "#0000ff" }
+  }
+}
+"##
+        );
+        let json: serde_json::Value = serde_json::from_str(&dtcg).unwrap();
+        assert_eq!(json["brand-colors"]["color-2"]["$value"], "#0000ff");
+
+        tokens.format = TokenFormat::StyleDictionary;
+        tokens.group = false;
+        let json: serde_json::Value = serde_json::from_str(&export(&tokens)).unwrap();
+        assert_eq!(json["color"]["color-1"]["value"], "#ff0000");
+
+        tokens.format = TokenFormat::Css;
+        tokens.names = TokenNameSource::ColorName;
+        tokens.group = true;
+        assert_eq!(
+            export(&tokens),
+            ":root {\n  --brand-colors-red: #ff0000;\n  --brand-colors-blue: #0000ff;\n}\n"
+        );
+
+        tokens.format = TokenFormat::Scss;
+        tokens.value = TokenValue::CssRgb;
+        assert_eq!(
+            export(&tokens),
+            "$brand-colors: (\n  \"red\": rgb(255,0,0),\n  \"blue\": rgb(0,0,255),\n);\n"
+        );
+
+        tokens.format = TokenFormat::Less;
+        tokens.names = TokenNameSource::Hex;
+        tokens.case = TokenCase::Camel;
+        tokens.value = TokenValue::HexUppercase;
+        assert_eq!(
+            export(&tokens),
+            "@brandColorsFf0000: #FF0000;\n@brandColors0000ff: #0000FF;\n"
+        );
+    }
+
+    #[test]
+    fn makes_unique_token_names() {
+        assert_eq!(
+            words("AliceBlue light_gray 2"),
+            ["alice", "blue", "light", "gray", "2"]
+        );
+        let palette = NamedPalette {
+            palette: Palette::from_iter([
+                Rgb::new_scaled(255, 0, 0).into(),
+                Rgb::new_scaled(254, 0, 0).into(),
+                Rgb::new_scaled(253, 0, 0).into(),
+            ]),
+            ..NamedPalette::default()
+        };
+        let tokens = TokenExport {
+            names: TokenNameSource::ColorName,
+            case: TokenCase::Snake,
+            ..TokenExport::default()
+        };
+        assert_eq!(tokens.token_names(&palette), ["red", "red_2", "red_3"]);
+
+        let mut palette = palette;
+        palette.set_color_name(&Rgb::new_scaled(254, 0, 0).into(), "Brand Primary");
+        assert_eq!(
+            tokens.token_names(&palette),
+            ["red", "brand_primary", "red_2"]
+        );
+    }
+
+    fn colors(palette: &NamedPalette) -> Vec<(String, String)> {
+        palette
+            .palette
+            .iter()
+            .map(|color| {
+                (
+                    palette.color_name(color).unwrap_or_default().to_string(),
+                    color.as_hex(),
+                )
+            })
+            .collect()
+    }
+
+    /// Colors with their names followed by their aliases
+    fn tokens(palette: &NamedPalette) -> Vec<(String, String)> {
+        let mut tokens = vec![];
+        for (color, (name, hex)) in palette.palette.iter().zip(colors(palette)) {
+            tokens.push((name, hex.clone()));
+            for alias in palette.color_aliases(color) {
+                tokens.push((alias.clone(), hex.clone()));
+            }
+        }
+        tokens
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, hex)| (name.to_string(), hex.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn imports_json_tokens() {
+        let dtcg = r##"{
+  "base": {
+    "$type": "color",
+    "red": { "$value": "#f00" },
+    "blue": { "$value": { "colorSpace": "srgb", "components": [0, 0, 1] } }
+  },
+  "brand": {
+    "primary": { "$type": "color", "$value": "{base.blue}" },
+    "danger": { "$type": "color", "$value": "{brand.alert}" },
+    "alert": { "$type": "color", "$value": "{base.red}" },
+    "loop": { "$type": "color", "$value": "{brand.loop}" },
+    "gap": { "$type": "dimension", "$value": "4px" }
+  }
+}"##;
+        let palettes = import_tokens(dtcg, "tokens").unwrap();
+        assert_eq!(palettes.len(), 2);
+        assert_eq!(palettes[0].name, "base");
+        assert_eq!(
+            colors(&palettes[0]),
+            pairs(&[("red", "#ff0000"), ("blue", "#0000ff")])
+        );
+        assert_eq!(palettes[1].name, "brand");
+        assert_eq!(
+            colors(&palettes[1]),
+            pairs(&[("primary", "#0000ff"), ("danger", "#ff0000")])
+        );
+        assert_eq!(
+            tokens(&palettes[1]),
+            pairs(&[
+                ("primary", "#0000ff"),
+                ("danger", "#ff0000"),
+                ("alert", "#ff0000")
+            ])
+        );
+
+        let style_dictionary = r##"{
+  "color": {
+    "base": { "gray": { "value": "rgb(128, 128, 128)" } },
+    "text": { "value": "{color.base.gray.value}" }
+  }
+}"##;
+        let palettes = import_tokens(style_dictionary, "tokens").unwrap();
+        assert_eq!(palettes[0].name, "color.base");
+        assert_eq!(colors(&palettes[0]), pairs(&[("gray", "#808080")]));
+        assert_eq!(palettes[1].name, "color");
+        assert_eq!(colors(&palettes[1]), pairs(&[("text", "#808080")]));
+
+        assert!(import_tokens(r#"{"size": {"value": "4px"}}"#, "tokens").is_err());
+        assert!(import_tokens("{", "tokens").is_err());
+    }
+
+    #[test]
+    fn imports_stylesheet_tokens() {
+        let css = r#"/* brand colors */
+:root {
+  --red: hsl(0deg 100% 50%);
+  --accent: var(--red);
+  --muted: var(--missing, #00ff0080);
+  --spacing: 100000;
+  --url: url(https://example.com);
+}
+"#;
+        let palettes = import_tokens(css, "site").unwrap();
+        assert_eq!(palettes.len(), 1);
+        assert_eq!(palettes[0].name, "site");
+        assert_eq!(
+            colors(&palettes[0]),
+            pairs(&[("red", "#ff0000"), ("muted", "#00ff00")])
+        );
+        assert_eq!(
+            tokens(&palettes[0]),
+            pairs(&[
+                ("red", "#ff0000"),
+                ("accent", "#ff0000"),
+                ("muted", "#00ff00")
+            ])
+        );
+        let tokens_export = TokenExport {
+            format: TokenFormat::Css,
+            ..TokenExport::default()
+        };
+        assert_eq!(
+            tokens_export.export(&palettes[0], &FormatOptions::default()),
+            ":root {\n  --red: #ff0000;\n  --accent: #ff0000;\n  --muted: #00ff00;\n}\n"
+        );
+
+        let scss = r#"// theme
+$white: #fff !default;
+$brand: (
+  "light": $white,
+  "dark": rgba(0, 0, 0, 0.5),
+);
+@less-blue: blue;
+"#;
+        let palettes = import_tokens(scss, "theme").unwrap();
+        assert_eq!(palettes[0].name, "theme");
+        assert_eq!(
+            tokens(&palettes[0]),
+            pairs(&[("white", "#ffffff"), ("less-blue", "#0000ff")])
+        );
+        assert_eq!(palettes[1].name, "brand");
+        assert_eq!(
+            tokens(&palettes[1]),
+            pairs(&[("light", "#ffffff"), ("dark", "#000000")])
+        );
+    }
+
+    #[test]
+    fn round_trips_token_names() {
+        let css = ":root {\n  --primary-blue: #0000ff;\n  --danger: #ff0000;\n}\n";
+        let palettes = import_tokens(css, "brand").unwrap();
+        let tokens = TokenExport {
+            format: TokenFormat::Css,
+            ..TokenExport::default()
+        };
+        let exported = tokens.export(&palettes[0], &FormatOptions::default());
+        assert_eq!(exported, css);
+    }
+}
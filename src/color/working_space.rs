@@ -1,7 +1,7 @@
 #![allow(dead_code)]
 use crate::{
     color::{illuminant::Illuminant, xyY, Rgb},
-    math::{Matrix1x3, Matrix3},
+    math::{Float, Matrix1x3, Matrix3},
 };
 
 use serde::{Deserialize, Serialize};
@@ -83,7 +83,7 @@ impl RgbWorkingSpace {
         self.rgb_matrix().inverse().expect("inverse matrix")
     }
 
-    pub fn gamma(&self) -> f32 {
+    pub fn gamma(&self) -> Float {
         use RgbWorkingSpace::*;
         match &self {
             Adobe => 2.2,
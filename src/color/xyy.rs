@@ -1,17 +1,17 @@
-use crate::color::Xyz;
+use crate::{color::Xyz, math::Float};
 
 use serde::{Deserialize, Serialize};
 
 #[allow(non_camel_case_types)]
 #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub struct xyY {
-    x: f32,
-    y: f32,
-    yy: f32,
+    x: Float,
+    y: Float,
+    yy: Float,
 }
 
 impl xyY {
-    pub fn new(x: f32, y: f32, yy: f32) -> Self {
+    pub fn new(x: Float, y: Float, yy: Float) -> Self {
         let x = if x.is_nan() { 0. } else { x };
         let y = if y.is_nan() { 0. } else { y };
         let yy = if yy.is_nan() { 0. } else { yy };
@@ -21,19 +21,19 @@ impl xyY {
 
     #[inline(always)]
     /// Returns x coordinate
-    pub fn x(&self) -> f32 {
+    pub fn x(&self) -> Float {
         self.x
     }
 
     #[inline(always)]
     /// Returns y coordinate
-    pub fn y(&self) -> f32 {
+    pub fn y(&self) -> Float {
         self.y
     }
 
     #[inline(always)]
     /// Returns Y coordinate
-    pub fn yy(&self) -> f32 {
+    pub fn yy(&self) -> Float {
         self.yy
     }
 }
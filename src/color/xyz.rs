@@ -3,20 +3,20 @@ use crate::{
         chromatic_adaptation::ChromaticAdaptationMethod, illuminant::Illuminant, rgb::Rgb,
         working_space::RgbWorkingSpace, xyy::xyY, CIEColor, LchUV, Luv, CIE_E, CIE_K,
     },
-    math::{Matrix1x3, Matrix3},
+    math::{Float, Matrix1x3, Matrix3},
 };
 
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Xyz {
-    x: f32,
-    y: f32,
-    z: f32,
+    x: Float,
+    y: Float,
+    z: Float,
 }
 
 impl Xyz {
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
+    pub fn new(x: Float, y: Float, z: Float) -> Self {
         let x = if x.is_nan() { 0. } else { x };
         let y = if y.is_nan() { 0. } else { y };
         let z = if z.is_nan() { 0. } else { z };
@@ -25,50 +25,63 @@ impl Xyz {
     }
 
     #[inline(always)]
-    pub fn x(&self) -> f32 {
+    pub fn x(&self) -> Float {
         self.x
     }
 
     #[inline(always)]
-    pub fn y(&self) -> f32 {
+    pub fn y(&self) -> Float {
         self.y
     }
 
     #[inline(always)]
-    pub fn z(&self) -> f32 {
+    pub fn z(&self) -> Float {
         self.z
     }
 
-    pub fn x_scaled(&self) -> f32 {
+    pub fn x_scaled(&self) -> Float {
         self.x * 100.
     }
 
-    pub fn y_scaled(&self) -> f32 {
+    pub fn y_scaled(&self) -> Float {
         self.y * 100.
     }
 
-    pub fn z_scaled(&self) -> f32 {
+    pub fn z_scaled(&self) -> Float {
         self.z * 100.
     }
 
     #[inline(always)]
-    pub fn u(&self) -> f32 {
+    pub fn u(&self) -> Float {
         4. * self.x / (self.x + 15. * self.y + 3. * self.z)
     }
 
     #[inline(always)]
-    pub fn v(&self) -> f32 {
+    pub fn v(&self) -> Float {
         9. * self.y / (self.x + 15. * self.y + 3. * self.z)
     }
 
+    /// Returns linear RGB of the working space. Unlike [`CIEColor::to_rgb`] the channels are not
+    /// limited to 0.0 ..= 1.0 so that colors brighter than the reference white can be represented.
+    pub fn to_linear_rgb(self, working_space: RgbWorkingSpace) -> Rgb {
+        let rgb = working_space.inverse_rgb_matrix() * Matrix1x3::from(self);
+        Rgb::new_unchecked(rgb[0], rgb[1], rgb[2])
+    }
+
     pub fn chromatic_adaptation_transform(
         &self,
         method: ChromaticAdaptationMethod,
         src_white: Illuminant,
         dst_white: Illuminant,
     ) -> Xyz {
-        let src_ref_xyz = Matrix1x3::from(src_white.xyz());
-        let dst_ref_xyz = Matrix1x3::from(dst_white.xyz());
+        self.adapt(method, src_white.xyz(), dst_white.xyz())
+    }
+
+    /// Same as [`Xyz::chromatic_adaptation_transform`] but between arbitrary white points, for
+    /// example ones computed from spectral data.
+    pub fn adapt(&self, method: ChromaticAdaptationMethod, src_white: Xyz, dst_white: Xyz) -> Xyz {
+        let src_ref_xyz = Matrix1x3::from(src_white);
+        let dst_ref_xyz = Matrix1x3::from(dst_white);
 
         let ma = method.adaptation_matrix();
 
@@ -76,9 +89,9 @@ impl Xyz {
         let dst_lms = ma * dst_ref_xyz;
 
         let lms = Matrix3::from([
-            [src_lms[0] / dst_lms[0], 0., 0.],
-            [0., src_lms[1] / dst_lms[1], 0.],
-            [0., 0., src_lms[2] / dst_lms[2]],
+            [dst_lms[0] / src_lms[0], 0., 0.],
+            [0., dst_lms[1] / src_lms[1], 0.],
+            [0., 0., dst_lms[2] / src_lms[2]],
         ]);
 
         let m = ma.inverse().expect("inverse adaptation matrix") * lms * ma;
@@ -116,7 +129,7 @@ impl From<Luv> for Xyz {
 
         let a = ((52. * l / (u + 13. * l * Illuminant::D65.reference_u())) - 1.) / 3.;
         let b = -5. * y;
-        let c = -(1.0f32 / 3.);
+        let c = -(1. as Float / 3.);
         let d = y * ((39. * l / (v + 13. * l * Illuminant::D65.reference_v())) - 5.);
 
         let x = (d - b) / (a - c);
@@ -181,7 +194,14 @@ mod tests {
             ($ws:expr; Rgb: $r:expr, $g:expr, $b:expr; Xyz: $x:expr, $y:expr, $z:expr) => {
                 let expected = Xyz::new($x, $y, $z);
                 let got = Xyz::from_rgb(Rgb::new($r, $g, $b), $ws);
-                assert_eq!(got, expected);
+                assert!(
+                    (got.x() - expected.x()).abs() < 1e-6
+                        && (got.y() - expected.y()).abs() < 1e-6
+                        && (got.z() - expected.z()).abs() < 1e-6,
+                    "{:?} != {:?}",
+                    got,
+                    expected
+                );
             };
         }
 
@@ -0,0 +1,198 @@
+use crate::{color::Rgb, math::Float};
+
+use serde::{Deserialize, Serialize};
+
+/// Recommendation defining the luma coefficients used to derive YCbCr from R'G'B'.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum YCbCrStandard {
+    #[serde(rename = "BT.601")]
+    Bt601,
+    #[default]
+    #[serde(rename = "BT.709")]
+    Bt709,
+    #[serde(rename = "BT.2020")]
+    Bt2020,
+}
+
+impl YCbCrStandard {
+    /// Returns the red and blue luma coefficients `(Kr, Kb)`
+    pub fn coefficients(&self) -> (Float, Float) {
+        match self {
+            YCbCrStandard::Bt601 => (0.299, 0.114),
+            YCbCrStandard::Bt709 => (0.2126, 0.0722),
+            YCbCrStandard::Bt2020 => (0.2627, 0.0593),
+        }
+    }
+}
+
+impl AsRef<str> for YCbCrStandard {
+    fn as_ref(&self) -> &str {
+        match self {
+            YCbCrStandard::Bt601 => "BT.601",
+            YCbCrStandard::Bt709 => "BT.709",
+            YCbCrStandard::Bt2020 => "BT.2020",
+        }
+    }
+}
+
+/// Range of 8 bit code values that YCbCr components are quantized to.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum YCbCrRange {
+    /// 0 ..= 255 for all components, as used by JPEG and most computer graphics
+    #[default]
+    Full,
+    /// 16 ..= 235 for luma and 16 ..= 240 for chroma, as used by broadcast video
+    Limited,
+}
+
+impl YCbCrRange {
+    /// Returns the offset and scale of luma code values
+    fn luma(&self) -> (Float, Float) {
+        match self {
+            YCbCrRange::Full => (0., 255.),
+            YCbCrRange::Limited => (16., 219.),
+        }
+    }
+
+    /// Returns the offset and scale of chroma code values
+    fn chroma(&self) -> (Float, Float) {
+        match self {
+            YCbCrRange::Full => (128., 255.),
+            YCbCrRange::Limited => (128., 224.),
+        }
+    }
+}
+
+impl AsRef<str> for YCbCrRange {
+    fn as_ref(&self) -> &str {
+        match self {
+            YCbCrRange::Full => "full",
+            YCbCrRange::Limited => "limited",
+        }
+    }
+}
+
+/// Luma and blue/red difference chroma computed from gamma encoded RGB of the working space.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct YCbCr {
+    y: Float,
+    cb: Float,
+    cr: Float,
+}
+
+impl YCbCr {
+    /// Takes in luma in the range of 0.0 ..= 1.0 and chroma in the range of -0.5 ..= 0.5
+    pub fn new(y: Float, cb: Float, cr: Float) -> Self {
+        let y = if y.is_nan() { 0. } else { y };
+        let cb = if cb.is_nan() { 0. } else { cb };
+        let cr = if cr.is_nan() { 0. } else { cr };
+
+        Self { y, cb, cr }
+    }
+
+    /// Creates the color from 8 bit code values quantized with `range`.
+    pub fn from_code_values(y: Float, cb: Float, cr: Float, range: YCbCrRange) -> Self {
+        let (y_offset, y_scale) = range.luma();
+        let (c_offset, c_scale) = range.chroma();
+        YCbCr::new(
+            (y - y_offset) / y_scale,
+            (cb - c_offset) / c_scale,
+            (cr - c_offset) / c_scale,
+        )
+    }
+
+    #[inline(always)]
+    /// Returns Luma in the range of 0.0 ..= 1.0
+    pub fn y(&self) -> Float {
+        self.y
+    }
+
+    #[inline(always)]
+    /// Returns blue difference Chroma in the range of -0.5 ..= 0.5
+    pub fn cb(&self) -> Float {
+        self.cb
+    }
+
+    #[inline(always)]
+    /// Returns red difference Chroma in the range of -0.5 ..= 0.5
+    pub fn cr(&self) -> Float {
+        self.cr
+    }
+
+    /// Returns Luma as an 8 bit code value
+    pub fn y_code(&self, range: YCbCrRange) -> Float {
+        let (offset, scale) = range.luma();
+        (offset + self.y * scale).clamp(0., 255.)
+    }
+
+    /// Returns blue difference Chroma as an 8 bit code value
+    pub fn cb_code(&self, range: YCbCrRange) -> Float {
+        let (offset, scale) = range.chroma();
+        (offset + self.cb * scale).clamp(0., 255.)
+    }
+
+    /// Returns red difference Chroma as an 8 bit code value
+    pub fn cr_code(&self, range: YCbCrRange) -> Float {
+        let (offset, scale) = range.chroma();
+        (offset + self.cr * scale).clamp(0., 255.)
+    }
+
+    pub fn from_rgb(rgb: Rgb, standard: YCbCrStandard) -> Self {
+        let (kr, kb) = standard.coefficients();
+        let y = kr * rgb.r() + (1. - kr - kb) * rgb.g() + kb * rgb.b();
+        let cb = (rgb.b() - y) / (2. * (1. - kb));
+        let cr = (rgb.r() - y) / (2. * (1. - kr));
+
+        YCbCr::new(y, cb, cr)
+    }
+
+    pub fn to_rgb(self, standard: YCbCrStandard) -> Rgb {
+        let (kr, kb) = standard.coefficients();
+        let r = self.y + 2. * (1. - kr) * self.cr;
+        let b = self.y + 2. * (1. - kb) * self.cb;
+        let g = (self.y - kr * r - kb * b) / (1. - kr - kb);
+
+        Rgb::new(r, g, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb_to_ycbcr() {
+        macro_rules! test_case {
+            ($rgb:expr, $standard:ident, $range:ident => $y:expr, $cb:expr, $cr:expr) => {
+                let rgb = Rgb::new_scaled($rgb.0, $rgb.1, $rgb.2);
+                let ycbcr = YCbCr::from_rgb(rgb, YCbCrStandard::$standard);
+                let range = YCbCrRange::$range;
+                let got = (
+                    ycbcr.y_code(range).round(),
+                    ycbcr.cb_code(range).round(),
+                    ycbcr.cr_code(range).round(),
+                );
+                assert_eq!(got, ($y, $cb, $cr));
+
+                let back = YCbCr::from_code_values(
+                    ycbcr.y_code(range),
+                    ycbcr.cb_code(range),
+                    ycbcr.cr_code(range),
+                    range,
+                )
+                .to_rgb(YCbCrStandard::$standard);
+                // full range chroma of saturated colors gets clipped at 255
+                assert!((back.r() - rgb.r()).abs() < 1. / 255.);
+                assert!((back.g() - rgb.g()).abs() < 1. / 255.);
+                assert!((back.b() - rgb.b()).abs() < 1. / 255.);
+            };
+        }
+
+        test_case!((255, 255, 255), Bt709, Full => 255., 128., 128.);
+        test_case!((255, 255, 255), Bt709, Limited => 235., 128., 128.);
+        test_case!((0, 0, 0), Bt2020, Limited => 16., 128., 128.);
+        test_case!((255, 0, 0), Bt601, Full => 76., 85., 255.);
+        test_case!((255, 0, 0), Bt709, Limited => 63., 102., 240.);
+        test_case!((0, 0, 255), Bt2020, Limited => 29., 240., 119.);
+    }
+}
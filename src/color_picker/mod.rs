@@ -2,15 +2,16 @@ mod sliders;
 
 use crate::{
     color::{
-        CIEColor, Cmyk, Color, Hsl, Hsv, Illuminant, Lab, LchAB, LchUV, Luv, Rgb, RgbWorkingSpace,
-        Xyz, U8_MAX, U8_MIN,
+        code_value, CIEColor, Cam16, Cam16Ucs, Cmyk, Color, HdrColor, Hpluv, Hsl, Hsluv, Hsv, Hwb,
+        ICtCp, Illuminant, Lab, LchAB, LchUV, Luv, Rgb, RgbWorkingSpace, Xyz, YCbCr, YCbCrRange,
+        PQ_PEAK_NITS, U8_MAX, U8_MIN,
     },
-    math,
+    math::{self, Float},
     ui::{slider_1d, slider_2d},
 };
 use sliders::ColorSliders;
 
-use egui::{color::Hsva, CollapsingHeader, DragValue, Grid, Ui};
+use egui::{CollapsingHeader, DragValue, Grid, Ui};
 use serde::{Deserialize, Serialize};
 use std::mem;
 
@@ -33,7 +34,7 @@ macro_rules! slider {
             let it_copy = it;
             $ui.add(DragValue::new(&mut it));
             if it != it_copy {
-                $it.sliders.$field = it as f32;
+                $it.sliders.$field = it as Float;
             }
     };
 }
@@ -91,9 +92,9 @@ impl ColorPicker {
         let r = self.sliders.r;
         let g = self.sliders.g;
         let b = self.sliders.b;
-        if !math::eq_f32(r, rgb.r_scaled())
-            || !math::eq_f32(g, rgb.g_scaled())
-            || !math::eq_f32(b, rgb.b_scaled())
+        if !math::eq_float(r, rgb.r_scaled())
+            || !math::eq_float(g, rgb.g_scaled())
+            || !math::eq_float(b, rgb.b_scaled())
         {
             self.saved_sliders = None;
             self.set_cur_color(Rgb::new(r / U8_MAX, g / U8_MAX, b / U8_MAX));
@@ -105,12 +106,12 @@ impl ColorPicker {
 
     fn cmyk_changed(&mut self) -> bool {
         let cmyk = Cmyk::from(self.current_color);
-        if !math::eq_f32(self.sliders.c, cmyk.c_scaled())
-            || !math::eq_f32(self.sliders.m, cmyk.m_scaled())
-            || !math::eq_f32(self.sliders.y, cmyk.y_scaled())
-            || !math::eq_f32(self.sliders.k, cmyk.k_scaled())
+        if !math::eq_float(self.sliders.c, cmyk.c_scaled())
+            || !math::eq_float(self.sliders.m, cmyk.m_scaled())
+            || !math::eq_float(self.sliders.y, cmyk.y_scaled())
+            || !math::eq_float(self.sliders.k, cmyk.k_scaled())
         {
-            if math::eq_f32(self.sliders.k, 100.) {
+            if math::eq_float(self.sliders.k, 100.) {
                 self.save_sliders_if_unsaved();
             } else if self.sliders.k < 100. {
                 self.restore_sliders_if_saved();
@@ -129,20 +130,19 @@ impl ColorPicker {
 
     fn hsv_changed(&mut self) -> bool {
         let hsv = Hsv::from(self.current_color);
-        if !math::eq_f32(self.sliders.hue, hsv.h_scaled())
-            || !math::eq_f32(self.sliders.sat, hsv.s_scaled())
-            || !math::eq_f32(self.sliders.val, hsv.v_scaled())
+        if !math::eq_float(self.sliders.hue, hsv.h_scaled())
+            || !math::eq_float(self.sliders.sat, hsv.s_scaled())
+            || !math::eq_float(self.sliders.val, hsv.v_scaled())
         {
             if self.sliders.val == 0. {
                 self.save_sliders_if_unsaved();
             } else if self.sliders.val > 0. {
                 self.restore_sliders_if_saved();
             }
-            self.set_cur_color(Hsva::new(
+            self.set_cur_color(Hsv::new(
                 self.sliders.hue / 360.,
                 self.sliders.sat / 100.,
                 self.sliders.val / 100.,
-                1.,
             ));
             true
         } else {
@@ -152,9 +152,9 @@ impl ColorPicker {
 
     fn hsl_changed(&mut self) -> bool {
         let hsl = Hsl::from(self.current_color);
-        if !math::eq_f32(self.sliders.hsl_h, hsl.h_scaled())
-            || !math::eq_f32(self.sliders.hsl_s, hsl.s_scaled())
-            || !math::eq_f32(self.sliders.hsl_l, hsl.l_scaled())
+        if !math::eq_float(self.sliders.hsl_h, hsl.h_scaled())
+            || !math::eq_float(self.sliders.hsl_s, hsl.s_scaled())
+            || !math::eq_float(self.sliders.hsl_l, hsl.l_scaled())
         {
             self.set_cur_color(Hsl::new(
                 self.sliders.hsl_h / 360.,
@@ -167,11 +167,28 @@ impl ColorPicker {
         }
     }
 
+    fn hwb_changed(&mut self) -> bool {
+        let hwb = Hwb::from(self.current_color);
+        if !math::eq_float(self.sliders.hwb_h, hwb.h_scaled())
+            || !math::eq_float(self.sliders.hwb_w, hwb.w_scaled())
+            || !math::eq_float(self.sliders.hwb_b, hwb.b_scaled())
+        {
+            self.set_cur_color(Hwb::new(
+                self.sliders.hwb_h / 360.,
+                self.sliders.hwb_w / 100.,
+                self.sliders.hwb_b / 100.,
+            ));
+            true
+        } else {
+            false
+        }
+    }
+
     fn luv_changed(&mut self) -> bool {
         let luv = Luv::from(self.current_color.xyz(self.sliders.rgb_working_space));
-        if !math::eq_f32(self.sliders.luv_l, luv.l())
-            || !math::eq_f32(self.sliders.luv_u, luv.u())
-            || !math::eq_f32(self.sliders.luv_v, luv.v())
+        if !math::eq_float(self.sliders.luv_l, luv.l())
+            || !math::eq_float(self.sliders.luv_u, luv.u())
+            || !math::eq_float(self.sliders.luv_v, luv.v())
         {
             self.set_cie_color(Xyz::from(Luv::new(
                 self.sliders.luv_l,
@@ -186,9 +203,9 @@ impl ColorPicker {
 
     fn lch_uv_changed(&mut self) -> bool {
         let lch = LchUV::from(self.current_color.xyz(self.sliders.rgb_working_space));
-        if !math::eq_f32(self.sliders.lch_uv_l, lch.l())
-            || !math::eq_f32(self.sliders.lch_uv_c, lch.c())
-            || !math::eq_f32(self.sliders.lch_uv_h, lch.h())
+        if !math::eq_float(self.sliders.lch_uv_l, lch.l())
+            || !math::eq_float(self.sliders.lch_uv_c, lch.c())
+            || !math::eq_float(self.sliders.lch_uv_h, lch.h())
         {
             self.set_cie_color(Xyz::from(LchUV::new(
                 self.sliders.lch_uv_l,
@@ -201,15 +218,57 @@ impl ColorPicker {
         }
     }
 
+    fn hsluv_changed(&mut self) -> bool {
+        let ws = self.sliders.rgb_working_space;
+        let hsluv = self.current_color.hsluv(ws);
+        if !math::eq_float(self.sliders.hsluv_h, hsluv.h())
+            || !math::eq_float(self.sliders.hsluv_s, hsluv.s())
+            || !math::eq_float(self.sliders.hsluv_l, hsluv.l())
+        {
+            self.set_cur_color(Color::Hsluv(
+                Hsluv::new(
+                    self.sliders.hsluv_h,
+                    self.sliders.hsluv_s,
+                    self.sliders.hsluv_l,
+                ),
+                ws,
+            ));
+            true
+        } else {
+            false
+        }
+    }
+
+    fn hpluv_changed(&mut self) -> bool {
+        let ws = self.sliders.rgb_working_space;
+        let hpluv = self.current_color.hpluv(ws);
+        if !math::eq_float(self.sliders.hpluv_h, hpluv.h())
+            || !math::eq_float(self.sliders.hpluv_p, hpluv.p())
+            || !math::eq_float(self.sliders.hpluv_l, hpluv.l())
+        {
+            self.set_cur_color(Color::Hpluv(
+                Hpluv::new(
+                    self.sliders.hpluv_h,
+                    self.sliders.hpluv_p,
+                    self.sliders.hpluv_l,
+                ),
+                ws,
+            ));
+            true
+        } else {
+            false
+        }
+    }
+
     fn lab_changed(&mut self) -> bool {
         let lab = self.current_color.lab(
             self.sliders.rgb_working_space,
             self.sliders.illuminant,
             self.sliders.chromatic_adaptation_method,
         );
-        if !math::eq_f32(self.sliders.lab_l, lab.l())
-            || !math::eq_f32(self.sliders.lab_a, lab.a())
-            || !math::eq_f32(self.sliders.lab_b, lab.b())
+        if !math::eq_float(self.sliders.lab_l, lab.l())
+            || !math::eq_float(self.sliders.lab_a, lab.a())
+            || !math::eq_float(self.sliders.lab_b, lab.b())
         {
             let xyz = Lab::new(self.sliders.lab_l, self.sliders.lab_a, self.sliders.lab_b)
                 .to_xyz(self.sliders.illuminant);
@@ -226,9 +285,9 @@ impl ColorPicker {
             self.sliders.illuminant,
             self.sliders.chromatic_adaptation_method,
         );
-        if !math::eq_f32(self.sliders.lch_ab_l, lch.l())
-            || !math::eq_f32(self.sliders.lch_ab_c, lch.c())
-            || !math::eq_f32(self.sliders.lch_ab_h, lch.h())
+        if !math::eq_float(self.sliders.lch_ab_l, lch.l())
+            || !math::eq_float(self.sliders.lch_ab_c, lch.c())
+            || !math::eq_float(self.sliders.lch_ab_h, lch.h())
         {
             self.set_cie_color(
                 LchAB::new(
@@ -244,6 +303,99 @@ impl ColorPicker {
         }
     }
 
+    fn cam16_changed(&mut self) -> bool {
+        let ws = self.sliders.rgb_working_space;
+        let white = self.sliders.illuminant;
+        let method = self.sliders.chromatic_adaptation_method;
+        let vc = self.sliders.viewing_conditions;
+        let cam16 = self.current_color.cam16(ws, white, method, &vc);
+        if !math::eq_float(self.sliders.cam16_j, cam16.j())
+            || !math::eq_float(self.sliders.cam16_c, cam16.c())
+            || !math::eq_float(self.sliders.cam16_h, cam16.h())
+        {
+            let cam16 = Cam16::from_jch(
+                self.sliders.cam16_j,
+                self.sliders.cam16_c,
+                self.sliders.cam16_h,
+                white,
+                &vc,
+            );
+            self.set_cur_color(Color::from_cam16(cam16, ws, white, method, &vc));
+            true
+        } else {
+            false
+        }
+    }
+
+    fn cam16_ucs_changed(&mut self) -> bool {
+        let ws = self.sliders.rgb_working_space;
+        let white = self.sliders.illuminant;
+        let method = self.sliders.chromatic_adaptation_method;
+        let vc = self.sliders.viewing_conditions;
+        let ucs = self.current_color.cam16_ucs(ws, white, method, &vc);
+        if !math::eq_float(self.sliders.cam16_ucs_j, ucs.j())
+            || !math::eq_float(self.sliders.cam16_ucs_a, ucs.a())
+            || !math::eq_float(self.sliders.cam16_ucs_b, ucs.b())
+        {
+            let ucs = Cam16Ucs::new(
+                self.sliders.cam16_ucs_j,
+                self.sliders.cam16_ucs_a,
+                self.sliders.cam16_ucs_b,
+            );
+            self.set_cur_color(Color::from_cam16_ucs(ucs, ws, white, method, &vc));
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ycbcr_changed(&mut self) -> bool {
+        let range = self.sliders.ycbcr_range;
+        let ycbcr = self.current_color.ycbcr(self.sliders.ycbcr_standard);
+        if !math::eq_float(self.sliders.ycbcr_y, ycbcr.y_code(range))
+            || !math::eq_float(self.sliders.ycbcr_cb, ycbcr.cb_code(range))
+            || !math::eq_float(self.sliders.ycbcr_cr, ycbcr.cr_code(range))
+        {
+            self.set_cur_color(
+                YCbCr::from_code_values(
+                    self.sliders.ycbcr_y,
+                    self.sliders.ycbcr_cb,
+                    self.sliders.ycbcr_cr,
+                    range,
+                )
+                .to_rgb(self.sliders.ycbcr_standard),
+            );
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ictcp_changed(&mut self) -> bool {
+        let ws = self.sliders.rgb_working_space;
+        let method = self.sliders.chromatic_adaptation_method;
+        let reference_white = self.sliders.reference_white;
+        let ictcp = self.current_color.ictcp(ws, method, reference_white);
+        if !math::eq_float(self.sliders.ictcp_i, ictcp.i())
+            || !math::eq_float(self.sliders.ictcp_ct, ictcp.ct())
+            || !math::eq_float(self.sliders.ictcp_cp, ictcp.cp())
+        {
+            self.set_cur_color(Color::from_ictcp(
+                ICtCp::new(
+                    self.sliders.ictcp_i,
+                    self.sliders.ictcp_ct,
+                    self.sliders.ictcp_cp,
+                ),
+                ws,
+                method,
+                reference_white,
+            ));
+            true
+        } else {
+            false
+        }
+    }
+
     fn workspace_changed(&mut self) -> bool {
         if let Some(ws) = mem::take(&mut self.new_workspace) {
             self.sliders.rgb_working_space = ws;
@@ -283,16 +435,37 @@ impl ColorPicker {
         if self.hsl_changed() {
             return true;
         }
+        if self.hwb_changed() {
+            return true;
+        }
         if self.luv_changed() {
             return true;
         }
         if self.lch_uv_changed() {
             return true;
         }
+        if self.hsluv_changed() {
+            return true;
+        }
+        if self.hpluv_changed() {
+            return true;
+        }
         if self.lab_changed() {
             return true;
         }
-        self.lch_ab_changed()
+        if self.lch_ab_changed() {
+            return true;
+        }
+        if self.cam16_changed() {
+            return true;
+        }
+        if self.cam16_ucs_changed() {
+            return true;
+        }
+        if self.ycbcr_changed() {
+            return true;
+        }
+        self.ictcp_changed()
     }
 
     pub fn check_for_change(&mut self) {
@@ -430,6 +603,33 @@ impl ColorPicker {
             });
     }
 
+    pub fn hwb_sliders(&mut self, ui: &mut Ui) {
+        let opaque = self.current_color.hwb();
+        CollapsingHeader::new("HWB")
+            .default_open(false)
+            .show(ui, |ui| {
+                Grid::new("HWB sliders")
+                    .spacing((8., 8.))
+                    .show(ui, |mut ui| {
+                        slider!(self, ui, hwb_h, "hue", 0. ..=360., |mut h| {
+                            h /= 360.;
+                            Hsv::from(Hwb::new(h, opaque.w(), opaque.b())).into()
+                        });
+                        ui.end_row();
+                        slider!(self, ui, hwb_w, "whiteness", 0. ..=100., |mut w| {
+                            w /= 100.;
+                            Hsv::from(Hwb::new(opaque.h(), w, opaque.b())).into()
+                        });
+                        ui.end_row();
+                        slider!(self, ui, hwb_b, "blackness", 0. ..=100., |mut b| {
+                            b /= 100.;
+                            Hsv::from(Hwb::new(opaque.h(), opaque.w(), b)).into()
+                        });
+                        ui.end_row();
+                    });
+            });
+    }
+
     pub fn luv_sliders(&mut self, ui: &mut Ui) {
         let ws = self.sliders.rgb_working_space;
         let opaque = self.current_color.luv(ws);
@@ -492,6 +692,90 @@ impl ColorPicker {
             });
     }
 
+    pub fn hsluv_sliders(&mut self, ui: &mut Ui) {
+        let ws = self.sliders.rgb_working_space;
+        let opaque = self.current_color.hsluv(ws);
+        CollapsingHeader::new("HSLuv")
+            .default_open(false)
+            .show(ui, |ui| {
+                Grid::new("HSLuv sliders")
+                    .spacing((8., 8.))
+                    .show(ui, |mut ui| {
+                        slider!(self, ui, hsluv_h, "hue", 0. ..=360., |h| {
+                            Hsluv::new(h, opaque.s(), opaque.l())
+                                .to_xyz(ws)
+                                .to_rgb(ws)
+                                .into()
+                        });
+                        ui.end_row();
+                        slider!(self, ui, hsluv_s, "saturation", 0. ..=100., |s| {
+                            Hsluv::new(opaque.h(), s, opaque.l())
+                                .to_xyz(ws)
+                                .to_rgb(ws)
+                                .into()
+                        });
+                        ui.end_row();
+                        slider!(self, ui, hsluv_l, "light", 0. ..=100., |l| {
+                            Hsluv::new(opaque.h(), opaque.s(), l)
+                                .to_xyz(ws)
+                                .to_rgb(ws)
+                                .into()
+                        });
+                        ui.end_row();
+                    });
+                slider_2d::color(
+                    ui,
+                    &mut self.sliders.hsluv_h,
+                    &mut self.sliders.hsluv_s,
+                    0.0..=360.,
+                    0.0..=100.,
+                    |h, s| Hsluv::new(h, s, opaque.l()).to_xyz(ws).to_rgb(ws).into(),
+                )
+            });
+    }
+
+    pub fn hpluv_sliders(&mut self, ui: &mut Ui) {
+        let ws = self.sliders.rgb_working_space;
+        let opaque = self.current_color.hpluv(ws);
+        CollapsingHeader::new("HPLuv")
+            .default_open(false)
+            .show(ui, |ui| {
+                Grid::new("HPLuv sliders")
+                    .spacing((8., 8.))
+                    .show(ui, |mut ui| {
+                        slider!(self, ui, hpluv_h, "hue", 0. ..=360., |h| {
+                            Hpluv::new(h, opaque.p(), opaque.l())
+                                .to_xyz(ws)
+                                .to_rgb(ws)
+                                .into()
+                        });
+                        ui.end_row();
+                        slider!(self, ui, hpluv_p, "saturation", 0. ..=100., |p| {
+                            Hpluv::new(opaque.h(), p, opaque.l())
+                                .to_xyz(ws)
+                                .to_rgb(ws)
+                                .into()
+                        });
+                        ui.end_row();
+                        slider!(self, ui, hpluv_l, "light", 0. ..=100., |l| {
+                            Hpluv::new(opaque.h(), opaque.p(), l)
+                                .to_xyz(ws)
+                                .to_rgb(ws)
+                                .into()
+                        });
+                        ui.end_row();
+                    });
+                slider_2d::color(
+                    ui,
+                    &mut self.sliders.hpluv_h,
+                    &mut self.sliders.hpluv_p,
+                    0.0..=360.,
+                    0.0..=100.,
+                    |h, p| Hpluv::new(h, p, opaque.l()).to_xyz(ws).to_rgb(ws).into(),
+                )
+            });
+    }
+
     pub fn lab_sliders(&mut self, ui: &mut Ui) {
         let ws = self.sliders.rgb_working_space;
         let ref_white = self.sliders.illuminant;
@@ -568,4 +852,282 @@ impl ColorPicker {
                     });
             });
     }
+
+    pub fn cam16_sliders(&mut self, ui: &mut Ui) {
+        let ws = self.sliders.rgb_working_space;
+        let white = self.sliders.illuminant;
+        let method = self.sliders.chromatic_adaptation_method;
+        let vc = self.sliders.viewing_conditions;
+        let opaque = self.current_color.cam16(ws, white, method, &vc);
+        CollapsingHeader::new("CAM16")
+            .default_open(false)
+            .show(ui, |ui| {
+                Grid::new("CAM16 sliders")
+                    .spacing((8., 8.))
+                    .show(ui, |mut ui| {
+                        slider!(self, ui, cam16_j, "lightness", 0. ..=100., |j| {
+                            Color::from_cam16(
+                                Cam16::from_jch(j, opaque.c(), opaque.h(), white, &vc),
+                                ws,
+                                white,
+                                method,
+                                &vc,
+                            )
+                            .into()
+                        });
+                        ui.end_row();
+                        slider!(self, ui, cam16_c, "chroma", 0. ..=120., |c| {
+                            Color::from_cam16(
+                                Cam16::from_jch(opaque.j(), c, opaque.h(), white, &vc),
+                                ws,
+                                white,
+                                method,
+                                &vc,
+                            )
+                            .into()
+                        });
+                        ui.end_row();
+                        slider!(self, ui, cam16_h, "hue", 0. ..=360., |h| {
+                            Color::from_cam16(
+                                Cam16::from_jch(opaque.j(), opaque.c(), h, white, &vc),
+                                ws,
+                                white,
+                                method,
+                                &vc,
+                            )
+                            .into()
+                        });
+                        ui.end_row();
+                    });
+                ui.label(format!(
+                    "M: {:.2}  s: {:.2}  Q: {:.2}",
+                    opaque.m(),
+                    opaque.s(),
+                    opaque.q()
+                ));
+            });
+    }
+
+    pub fn cam16_ucs_sliders(&mut self, ui: &mut Ui) {
+        let ws = self.sliders.rgb_working_space;
+        let white = self.sliders.illuminant;
+        let method = self.sliders.chromatic_adaptation_method;
+        let vc = self.sliders.viewing_conditions;
+        let opaque = self.current_color.cam16_ucs(ws, white, method, &vc);
+        CollapsingHeader::new("CAM16-UCS")
+            .default_open(false)
+            .show(ui, |ui| {
+                Grid::new("CAM16-UCS sliders")
+                    .spacing((8., 8.))
+                    .show(ui, |mut ui| {
+                        slider!(self, ui, cam16_ucs_j, "lightness", 0. ..=100., |j| {
+                            Color::from_cam16_ucs(
+                                Cam16Ucs::new(j, opaque.a(), opaque.b()),
+                                ws,
+                                white,
+                                method,
+                                &vc,
+                            )
+                            .into()
+                        });
+                        ui.end_row();
+                        slider!(self, ui, cam16_ucs_a, "a", -50. ..=50., |a| {
+                            Color::from_cam16_ucs(
+                                Cam16Ucs::new(opaque.j(), a, opaque.b()),
+                                ws,
+                                white,
+                                method,
+                                &vc,
+                            )
+                            .into()
+                        });
+                        ui.end_row();
+                        slider!(self, ui, cam16_ucs_b, "b", -50. ..=50., |b| {
+                            Color::from_cam16_ucs(
+                                Cam16Ucs::new(opaque.j(), opaque.a(), b),
+                                ws,
+                                white,
+                                method,
+                                &vc,
+                            )
+                            .into()
+                        });
+                        ui.end_row();
+                    });
+            });
+    }
+
+    pub fn ycbcr_sliders(&mut self, ui: &mut Ui) {
+        let standard = self.sliders.ycbcr_standard;
+        let range = self.sliders.ycbcr_range;
+        let opaque = self.current_color.ycbcr(standard);
+        let (y, cb, cr) = (
+            opaque.y_code(range),
+            opaque.cb_code(range),
+            opaque.cr_code(range),
+        );
+        CollapsingHeader::new(format!(
+            "YCbCr ({}, {} range)",
+            standard.as_ref(),
+            range.as_ref()
+        ))
+        .id_source("YCbCr")
+        .default_open(false)
+        .show(ui, |ui| {
+            Grid::new("YCbCr sliders")
+                .spacing((8., 8.))
+                .show(ui, |mut ui| {
+                    slider!(int self, ui, ycbcr_y, "Y", 0. ..=255., |y| {
+                        YCbCr::from_code_values(y, cb, cr, range)
+                            .to_rgb(standard)
+                            .into()
+                    });
+                    ui.end_row();
+                    slider!(int self, ui, ycbcr_cb, "Cb", 0. ..=255., |cb| {
+                        YCbCr::from_code_values(y, cb, cr, range)
+                            .to_rgb(standard)
+                            .into()
+                    });
+                    ui.end_row();
+                    slider!(int self, ui, ycbcr_cr, "Cr", 0. ..=255., |cr| {
+                        YCbCr::from_code_values(y, cb, cr, range)
+                            .to_rgb(standard)
+                            .into()
+                    });
+                    ui.end_row();
+                });
+        });
+    }
+
+    pub fn ictcp_sliders(&mut self, ui: &mut Ui) {
+        let ws = self.sliders.rgb_working_space;
+        let method = self.sliders.chromatic_adaptation_method;
+        let reference_white = self.sliders.reference_white;
+        let opaque = self.current_color.ictcp(ws, method, reference_white);
+        CollapsingHeader::new("ICtCp")
+            .default_open(false)
+            .show(ui, |ui| {
+                Grid::new("ICtCp sliders")
+                    .spacing((8., 8.))
+                    .show(ui, |mut ui| {
+                        slider!(self, ui, ictcp_i, "intensity", 0. ..=1., |i| {
+                            Color::from_ictcp(
+                                ICtCp::new(i, opaque.ct(), opaque.cp()),
+                                ws,
+                                method,
+                                reference_white,
+                            )
+                            .into()
+                        });
+                        ui.end_row();
+                        slider!(self, ui, ictcp_ct, "Ct", -0.5..=0.5, |ct| {
+                            Color::from_ictcp(
+                                ICtCp::new(opaque.i(), ct, opaque.cp()),
+                                ws,
+                                method,
+                                reference_white,
+                            )
+                            .into()
+                        });
+                        ui.end_row();
+                        slider!(self, ui, ictcp_cp, "Cp", -0.5..=0.5, |cp| {
+                            Color::from_ictcp(
+                                ICtCp::new(opaque.i(), opaque.ct(), cp),
+                                ws,
+                                method,
+                                reference_white,
+                            )
+                            .into()
+                        });
+                        ui.end_row();
+                    });
+            });
+    }
+
+    /// Returns the current color as HDR light with the exposure of the HDR sliders applied
+    pub fn hdr_color(&self) -> HdrColor {
+        self.current_color
+            .hdr(
+                self.sliders.rgb_working_space,
+                self.sliders.chromatic_adaptation_method,
+                self.sliders.reference_white,
+            )
+            .exposed(self.sliders.hdr_exposure)
+    }
+
+    /// Returns the HDR color tone mapped to the working space for display
+    pub fn hdr_preview(&self) -> Color {
+        Color::from_hdr_tone_mapped(
+            self.hdr_color(),
+            self.sliders.rgb_working_space,
+            self.sliders.chromatic_adaptation_method,
+            self.sliders.reference_white,
+        )
+    }
+
+    pub fn hdr_sliders(&mut self, ui: &mut Ui) {
+        let ws = self.sliders.rgb_working_space;
+        let method = self.sliders.chromatic_adaptation_method;
+        let reference_white = self.sliders.reference_white;
+        let transfer = self.sliders.transfer_function;
+        let sdr = self.current_color.hdr(ws, method, reference_white);
+        let max_exposure = (PQ_PEAK_NITS / reference_white).log2();
+        self.sliders.hdr_exposure = self.sliders.hdr_exposure.clamp(0., max_exposure);
+
+        CollapsingHeader::new(format!("HDR ({})", transfer.as_ref()))
+            .id_source("HDR")
+            .default_open(false)
+            .show(ui, |ui| {
+                Grid::new("HDR sliders")
+                    .spacing((8., 8.))
+                    .show(ui, |mut ui| {
+                        slider!(
+                            self,
+                            ui,
+                            hdr_exposure,
+                            "exposure (stops)",
+                            0. ..=max_exposure,
+                            |stops| {
+                                Color::from_hdr_tone_mapped(
+                                    sdr.exposed(stops),
+                                    ws,
+                                    method,
+                                    reference_white,
+                                )
+                                .into()
+                            }
+                        );
+                        ui.end_row();
+                    });
+
+                let hdr = self.hdr_color();
+                let linear = Color::linear_rgb_from_hdr(hdr, ws, method, reference_white);
+                let signal = hdr.encode(transfer);
+                Grid::new("HDR values").spacing((8., 4.)).show(ui, |ui| {
+                    ui.label("luminance:");
+                    ui.label(format!("{:.1} cd/m²", hdr.luminance()));
+                    ui.end_row();
+                    ui.label("linear RGB:");
+                    ui.label(format!(
+                        "{:.4}, {:.4}, {:.4}",
+                        linear.r(),
+                        linear.g(),
+                        linear.b()
+                    ));
+                    ui.end_row();
+                    ui.label(format!("{} signal:", transfer.as_ref()));
+                    ui.label(format!(
+                        "{:.4}, {:.4}, {:.4}",
+                        signal[0], signal[1], signal[2]
+                    ));
+                    ui.end_row();
+                    for range in [YCbCrRange::Full, YCbCrRange::Limited] {
+                        ui.label(format!("10 bit {} range:", range.as_ref()));
+                        let [r, g, b] = signal.map(|s| code_value(s, 10, range));
+                        ui.label(format!("{}, {}, {}", r, g, b));
+                        ui.end_row();
+                    }
+                });
+            });
+    }
 }
@@ -1,4 +1,10 @@
-use crate::color::{ChromaticAdaptationMethod, Color, Illuminant, RgbWorkingSpace};
+use crate::{
+    color::{
+        Cam16Ucs, ChromaticAdaptationMethod, Color, Illuminant, RgbWorkingSpace, TransferFunction,
+        ViewingConditions, YCbCrRange, YCbCrStandard, DEFAULT_REFERENCE_WHITE,
+    },
+    math::Float,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -7,31 +13,59 @@ pub struct ColorSliders {
     pub rgb_working_space: RgbWorkingSpace,
     pub illuminant: Illuminant,
     pub chromatic_adaptation_method: ChromaticAdaptationMethod,
-    pub r: f32,
-    pub g: f32,
-    pub b: f32,
-    pub c: f32,
-    pub m: f32,
-    pub y: f32,
-    pub k: f32,
-    pub hue: f32,
-    pub sat: f32,
-    pub val: f32,
-    pub hsl_h: f32,
-    pub hsl_s: f32,
-    pub hsl_l: f32,
-    pub luv_l: f32,
-    pub luv_u: f32,
-    pub luv_v: f32,
-    pub lch_uv_l: f32,
-    pub lch_uv_c: f32,
-    pub lch_uv_h: f32,
-    pub lab_l: f32,
-    pub lab_a: f32,
-    pub lab_b: f32,
-    pub lch_ab_l: f32,
-    pub lch_ab_c: f32,
-    pub lch_ab_h: f32,
+    pub viewing_conditions: ViewingConditions,
+    pub ycbcr_standard: YCbCrStandard,
+    pub ycbcr_range: YCbCrRange,
+    pub reference_white: Float,
+    pub transfer_function: TransferFunction,
+    pub r: Float,
+    pub g: Float,
+    pub b: Float,
+    pub c: Float,
+    pub m: Float,
+    pub y: Float,
+    pub k: Float,
+    pub hue: Float,
+    pub sat: Float,
+    pub val: Float,
+    pub hsl_h: Float,
+    pub hsl_s: Float,
+    pub hsl_l: Float,
+    pub hwb_h: Float,
+    pub hwb_w: Float,
+    pub hwb_b: Float,
+    pub luv_l: Float,
+    pub luv_u: Float,
+    pub luv_v: Float,
+    pub lch_uv_l: Float,
+    pub lch_uv_c: Float,
+    pub lch_uv_h: Float,
+    pub hsluv_h: Float,
+    pub hsluv_s: Float,
+    pub hsluv_l: Float,
+    pub hpluv_h: Float,
+    pub hpluv_p: Float,
+    pub hpluv_l: Float,
+    pub lab_l: Float,
+    pub lab_a: Float,
+    pub lab_b: Float,
+    pub lch_ab_l: Float,
+    pub lch_ab_c: Float,
+    pub lch_ab_h: Float,
+    pub cam16_j: Float,
+    pub cam16_c: Float,
+    pub cam16_h: Float,
+    pub cam16_ucs_j: Float,
+    pub cam16_ucs_a: Float,
+    pub cam16_ucs_b: Float,
+    pub ycbcr_y: Float,
+    pub ycbcr_cb: Float,
+    pub ycbcr_cr: Float,
+    pub ictcp_i: Float,
+    pub ictcp_ct: Float,
+    pub ictcp_cp: Float,
+    /// Stops above the reference white of the HDR color
+    pub hdr_exposure: Float,
 }
 
 impl Default for ColorSliders {
@@ -41,6 +75,11 @@ impl Default for ColorSliders {
             rgb_working_space: ws,
             illuminant: ws.reference_illuminant(),
             chromatic_adaptation_method: ChromaticAdaptationMethod::default(),
+            viewing_conditions: ViewingConditions::default(),
+            ycbcr_standard: YCbCrStandard::default(),
+            ycbcr_range: YCbCrRange::default(),
+            reference_white: DEFAULT_REFERENCE_WHITE,
+            transfer_function: TransferFunction::default(),
             r: 0.,
             g: 0.,
             b: 0.,
@@ -54,18 +93,40 @@ impl Default for ColorSliders {
             hsl_h: 0.,
             hsl_s: 0.,
             hsl_l: 0.,
+            hwb_h: 0.,
+            hwb_w: 0.,
+            hwb_b: 100.,
             luv_l: 0.,
             luv_u: 0.,
             luv_v: 0.,
             lch_uv_l: 0.,
             lch_uv_c: 0.,
             lch_uv_h: 180.,
+            hsluv_h: 0.,
+            hsluv_s: 0.,
+            hsluv_l: 0.,
+            hpluv_h: 0.,
+            hpluv_p: 0.,
+            hpluv_l: 0.,
             lab_l: 0.,
             lab_a: 0.,
             lab_b: 0.,
             lch_ab_l: 0.,
             lch_ab_c: 0.,
             lch_ab_h: 0.,
+            cam16_j: 0.,
+            cam16_c: 0.,
+            cam16_h: 0.,
+            cam16_ucs_j: 0.,
+            cam16_ucs_a: 0.,
+            cam16_ucs_b: 0.,
+            ycbcr_y: 0.,
+            ycbcr_cb: 128.,
+            ycbcr_cr: 128.,
+            ictcp_i: 0.,
+            ictcp_ct: 0.,
+            ictcp_cp: 0.,
+            hdr_exposure: 0.,
         }
     }
 }
@@ -89,6 +150,10 @@ impl ColorSliders {
         self.hsl_h = hsl.h_scaled();
         self.hsl_s = hsl.s_scaled();
         self.hsl_l = hsl.l_scaled();
+        let hwb = color.hwb();
+        self.hwb_h = hwb.h_scaled();
+        self.hwb_w = hwb.w_scaled();
+        self.hwb_b = hwb.b_scaled();
         let luv = color.luv(self.rgb_working_space);
         self.luv_l = luv.l();
         self.luv_u = luv.u();
@@ -97,6 +162,14 @@ impl ColorSliders {
         self.lch_uv_l = lch_uv.l();
         self.lch_uv_c = lch_uv.c();
         self.lch_uv_h = lch_uv.h();
+        let hsluv = color.hsluv(self.rgb_working_space);
+        self.hsluv_h = hsluv.h();
+        self.hsluv_s = hsluv.s();
+        self.hsluv_l = hsluv.l();
+        let hpluv = color.hpluv(self.rgb_working_space);
+        self.hpluv_h = hpluv.h();
+        self.hpluv_p = hpluv.p();
+        self.hpluv_l = hpluv.l();
         let lab = color.lab(
             self.rgb_working_space,
             self.illuminant,
@@ -113,6 +186,31 @@ impl ColorSliders {
         self.lch_ab_l = lch_ab.l();
         self.lch_ab_c = lch_ab.c();
         self.lch_ab_h = lch_ab.h();
+        let cam16 = color.cam16(
+            self.rgb_working_space,
+            self.illuminant,
+            self.chromatic_adaptation_method,
+            &self.viewing_conditions,
+        );
+        self.cam16_j = cam16.j();
+        self.cam16_c = cam16.c();
+        self.cam16_h = cam16.h();
+        let cam16_ucs = Cam16Ucs::from(cam16);
+        self.cam16_ucs_j = cam16_ucs.j();
+        self.cam16_ucs_a = cam16_ucs.a();
+        self.cam16_ucs_b = cam16_ucs.b();
+        let ycbcr = color.ycbcr(self.ycbcr_standard);
+        self.ycbcr_y = ycbcr.y_code(self.ycbcr_range);
+        self.ycbcr_cb = ycbcr.cb_code(self.ycbcr_range);
+        self.ycbcr_cr = ycbcr.cr_code(self.ycbcr_range);
+        let ictcp = color.ictcp(
+            self.rgb_working_space,
+            self.chromatic_adaptation_method,
+            self.reference_white,
+        );
+        self.ictcp_i = ictcp.i();
+        self.ictcp_ct = ictcp.ct();
+        self.ictcp_cp = ictcp.cp();
     }
 
     pub fn restore(&mut self, other: Self) {
@@ -129,17 +227,39 @@ impl ColorSliders {
         self.hsl_h = other.hsl_h;
         self.hsl_s = other.hsl_s;
         self.hsl_l = other.hsl_l;
+        self.hwb_h = other.hwb_h;
+        self.hwb_w = other.hwb_w;
+        self.hwb_b = other.hwb_b;
         self.luv_l = other.luv_l;
         self.luv_u = other.luv_u;
         self.luv_v = other.luv_v;
         self.lch_uv_l = other.lch_uv_l;
         self.lch_uv_c = other.lch_uv_c;
         self.lch_uv_h = other.lch_uv_h;
+        self.hsluv_h = other.hsluv_h;
+        self.hsluv_s = other.hsluv_s;
+        self.hsluv_l = other.hsluv_l;
+        self.hpluv_h = other.hpluv_h;
+        self.hpluv_p = other.hpluv_p;
+        self.hpluv_l = other.hpluv_l;
         self.lab_l = other.lab_l;
         self.lab_a = other.lab_a;
         self.lab_b = other.lab_b;
         self.lch_ab_l = other.lch_ab_l;
         self.lch_ab_c = other.lch_ab_c;
         self.lch_ab_h = other.lch_ab_h;
+        self.cam16_j = other.cam16_j;
+        self.cam16_c = other.cam16_c;
+        self.cam16_h = other.cam16_h;
+        self.cam16_ucs_j = other.cam16_ucs_j;
+        self.cam16_ucs_a = other.cam16_ucs_a;
+        self.cam16_ucs_b = other.cam16_ucs_b;
+        self.ycbcr_y = other.ycbcr_y;
+        self.ycbcr_cb = other.ycbcr_cb;
+        self.ycbcr_cr = other.ycbcr_cr;
+        self.ictcp_i = other.ictcp_i;
+        self.ictcp_ct = other.ictcp_ct;
+        self.ictcp_cp = other.ictcp_cp;
+        self.hdr_exposure = other.hdr_exposure;
     }
 }
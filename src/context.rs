@@ -1,6 +1,9 @@
 use crate::{
     app::{CentralPanelTab, DARK_VISUALS, LIGHT_VISUALS},
-    color::{Color, ColorFormat, Palettes},
+    color::{
+        parse_color, Color, ColorFormat, ColorMatcher, ColorNames, CustomColorFormat,
+        FormatOptions, PaletteDir, Palettes, COLOR_NAMES,
+    },
     color_picker::ColorPicker,
     error::append_global_error,
     render::{TextureAllocator, TextureManager},
@@ -13,6 +16,14 @@ use eframe::{CreationContext, Storage};
 use egui::CursorIcon;
 use serde::{Deserialize, Serialize};
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+/// Seconds between synchronizations with the palettes directory. Files are only read when the
+/// directory watcher reported changes, or on every synchronization if it couldn't be watched.
+#[cfg(not(target_arch = "wasm32"))]
+const PALETTE_DIR_SYNC_INTERVAL: u64 = 2;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AppCtx {
     pub settings: Settings,
@@ -22,6 +33,12 @@ pub struct AppCtx {
     pub palettes: Palettes,
     pub palettes_tab_color_size: f32,
     pub palettes_tab_display_label: bool,
+    /// Files of the palettes when a palettes directory is set
+    #[serde(skip)]
+    pub palette_dir: Option<PaletteDir>,
+    /// Timestamp of the last synchronization with the palettes directory
+    #[serde(skip)]
+    pub palette_dir_synced: u64,
 
     pub screen_size: ScreenSize,
     pub cursor_icon: CursorIcon,
@@ -62,6 +79,8 @@ impl Default for AppCtx {
             palettes: Palettes::default(),
             palettes_tab_color_size: 50.,
             palettes_tab_display_label: false,
+            palette_dir: None,
+            palette_dir_synced: 0,
 
             screen_size: ScreenSize::Desktop(0., 0.),
             cursor_icon: CursorIcon::default(),
@@ -85,6 +104,8 @@ impl AppCtx {
 
     /// Initialize a new context
     pub fn new(context: &CreationContext) -> Self {
+        Self::load_color_names();
+
         Self {
             settings: settings::load_global(context.storage).unwrap_or_default(),
 
@@ -93,6 +114,8 @@ impl AppCtx {
             palettes: Palettes::default(),
             palettes_tab_color_size: 50.,
             palettes_tab_display_label: false,
+            palette_dir: None,
+            palette_dir_synced: 0,
 
             screen_size: ScreenSize::Desktop(0., 0.),
             cursor_icon: CursorIcon::default(),
@@ -111,6 +134,21 @@ impl AppCtx {
         }
     }
 
+    /// Adds the name lists from the configuration directory to the global color names
+    fn load_color_names() {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(dir) = Settings::dir("epick") {
+            let dir = dir.join(ColorNames::DIR_NAME);
+            if dir.is_dir() {
+                if let Ok(mut names) = COLOR_NAMES.write() {
+                    if let Err(e) = names.load_dir(&dir) {
+                        append_global_error(e);
+                    }
+                }
+            }
+        }
+    }
+
     /// Current color display format
     pub fn display_format(&self) -> ColorFormat {
         match self.settings.color_display_format {
@@ -131,13 +169,25 @@ impl AppCtx {
         }
     }
 
+    /// Color conversion settings used by the fields of custom formats
+    pub fn format_options(&self) -> FormatOptions {
+        FormatOptions {
+            ws: self.settings.rgb_working_space,
+            illuminant: self.settings.illuminant,
+            reference_white: self.settings.reference_white,
+            method: self.settings.chromatic_adaptation_method,
+            viewing_conditions: self.settings.viewing_conditions,
+            hdr_exposure: if self.settings.hdr_mode {
+                self.picker.sliders.hdr_exposure
+            } else {
+                0.
+            },
+        }
+    }
+
     /// Format a color as a string using display color format from settings
     pub fn display_color(&self, color: &Color) -> String {
-        color.display(
-            self.display_format(),
-            self.settings.rgb_working_space,
-            self.settings.illuminant,
-        )
+        color.display(self.display_format(), &self.format_options())
     }
 
     /// Format a color as a string using clipboard color format from settings
@@ -163,15 +213,63 @@ impl AppCtx {
                 }
             }
         };
-        color.display(
-            format,
-            self.settings.rgb_working_space,
-            self.settings.illuminant,
-        )
+        color.display(format, &self.format_options())
+    }
+
+    /// Matchers of the saved custom color formats that can be read back, the display and
+    /// clipboard formats come first
+    pub fn color_matchers(&self) -> Vec<ColorMatcher> {
+        let mut names: Vec<&String> = self.settings.saved_color_formats.keys().collect();
+        names.sort_by_key(|name| {
+            let is_used = |format: Option<&ColorDisplayFmtEnum>| {
+                matches!(format, Some(ColorDisplayFmtEnum::Custom(used)) if used == *name)
+            };
+            (
+                !is_used(Some(&self.settings.color_display_format)),
+                !is_used(self.settings.color_clipboard_format.as_ref()),
+                name.as_str(),
+            )
+        });
+        names
+            .into_iter()
+            .filter_map(|name| {
+                CustomColorFormat::cached(&self.settings.saved_color_formats[name])
+                    .and_then(|format| format.matcher())
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Reads a color from a hex code, a color name or text in one of the saved custom formats
+    pub fn parse_color_text(&self, text: &str) -> Option<Color> {
+        self.parse_color_texts([text]).pop().flatten()
+    }
+
+    /// Reads colors like [`AppCtx::parse_color_text`] with the formats compiled once
+    pub fn parse_color_texts<'t>(
+        &self,
+        texts: impl IntoIterator<Item = &'t str>,
+    ) -> Vec<Option<Color>> {
+        let matchers = self.color_matchers();
+        texts
+            .into_iter()
+            .map(|text| {
+                parse_color(text).or_else(|| {
+                    matchers
+                        .iter()
+                        .find_map(|matcher| matcher.parse(text, &self.format_options()))
+                })
+            })
+            .collect()
     }
 
     /// Load palettes from appropriate location based on the target arch
     pub fn load_palettes(&mut self, _storage: Option<&dyn Storage>) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(dir) = self.settings.palettes_dir.clone() {
+            self.open_palette_dir(dir);
+            return;
+        }
         if self.settings.cache_colors {
             #[cfg(target_arch = "wasm32")]
             if let Some(storage) = _storage {
@@ -189,10 +287,102 @@ impl AppCtx {
                 }
             }
         }
+        #[cfg(target_arch = "wasm32")]
+        self.import_url_palette();
+    }
+
+    /// Adds the palette shared in the page address, unless it was already added
+    #[cfg(target_arch = "wasm32")]
+    fn import_url_palette(&mut self) {
+        let code = match crate::url_hash() {
+            Some(code) => code,
+            None => return,
+        };
+        match crate::color::NamedPalette::from_code(&code) {
+            Ok(palette) => {
+                let name = if self
+                    .palettes
+                    .iter()
+                    .any(|p| p.name == palette.name && p.palette == palette.palette)
+                {
+                    palette.name
+                } else {
+                    self.palettes.add_unique(palette)
+                };
+                self.palettes.move_to_name(name);
+            }
+            Err(e) => append_global_error(format!("failed to import palette from URL, {e:?}")),
+        }
+    }
+
+    /// Starts storing palettes as separate files in `dir`. Palettes found in the directory replace
+    /// the current palettes, otherwise the current palettes are written to it.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_palette_dir(&mut self, dir: PathBuf) {
+        let mut store = PaletteDir::new(dir, self.settings.palette_file_format);
+        let result = if store.has_palettes() {
+            store.load().map(|palettes| self.palettes = palettes)
+        } else {
+            store.save(&self.palettes)
+        };
+        if let Err(e) = result {
+            append_global_error(format!("failed to open palettes directory, {e:?}"));
+        }
+        if let Err(e) = store.watch() {
+            append_global_error(format!(
+                "failed to watch palettes directory, checking the files for changes every \
+                 {PALETTE_DIR_SYNC_INTERVAL} seconds instead, {e:?}"
+            ));
+        }
+        self.palette_dir = Some(store);
+    }
+
+    /// Loads the palette files changed by other programs and saves the palettes changed in the
+    /// app if a palettes directory is set.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn sync_palette_dir(&mut self) {
+        let now = crate::get_timestamp();
+        if now < self.palette_dir_synced + PALETTE_DIR_SYNC_INTERVAL {
+            return;
+        }
+        self.palette_dir_synced = now;
+        if let Some(store) = &mut self.palette_dir {
+            let conflicts = store.conflicts().len();
+            if store.has_changes() {
+                if let Err(e) = store.sync(&mut self.palettes) {
+                    append_global_error(format!("failed to load palettes, {e:?}"));
+                }
+            }
+            if let Err(e) = store.save(&self.palettes) {
+                append_global_error(format!("failed to save palettes, {e:?}"));
+            }
+            for conflict in &store.conflicts()[conflicts..] {
+                append_global_error(format!(
+                    "palette `{}` was changed both in the app and in `{}`, choose the version to \
+                     keep in the palettes tab",
+                    conflict.name,
+                    conflict.path.display()
+                ));
+            }
+        }
+    }
+
+    /// Writes a backup of the palettes to the cache directory if enough time has passed since
+    /// the last one
+    #[cfg(not(target_arch = "wasm32"))]
+    fn backup_palettes(&self) {
+        if let Some(dir) = Palettes::dir("epick") {
+            let dir = dir.join(Palettes::BACKUP_DIR);
+            if let Err(e) = self.palettes.backup(dir, crate::get_timestamp()) {
+                append_global_error(format!("failed to back up palettes, {e:?}"));
+            }
+        }
     }
 
     /// Save palettes to appropriate location based on the target arch
-    pub fn save_palettes(&self, _storage: &mut dyn Storage) {
+    pub fn save_palettes(&mut self, _storage: &mut dyn Storage) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.backup_palettes();
         #[cfg(target_arch = "wasm32")]
         if self.settings.cache_colors {
             if let Err(e) = self.palettes.save_to_storage(_storage) {
@@ -200,6 +390,13 @@ impl AppCtx {
             }
         }
         #[cfg(not(target_arch = "wasm32"))]
+        if let Some(store) = &mut self.palette_dir {
+            if let Err(e) = store.save(&self.palettes) {
+                append_global_error(format!("failed to save palettes, {e:?}"));
+            }
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
         if let Some(dir) = Palettes::dir("epick") {
             if !dir.exists() {
                 let _ = std::fs::create_dir_all(&dir);
@@ -212,10 +409,12 @@ impl AppCtx {
 
     /// Adds a color to the currently selected palette
     pub fn add_color(&mut self, color: Color) {
-        if !self.palettes.current_mut().palette.add(color) {
+        let current = self.palettes.current_mut();
+        if !current.palette.add(color) {
             let color_str = self.display_color(&color);
             append_global_error(format!("Color {} already saved!", color_str));
         } else {
+            current.touch();
             self.sidepanel.show = true;
         }
     }
@@ -240,6 +439,48 @@ impl AppCtx {
             self.picker.sliders.chromatic_adaptation_method =
                 self.settings.chromatic_adaptation_method;
         }
+        if self.settings.viewing_conditions != self.picker.sliders.viewing_conditions {
+            self.picker.sliders.viewing_conditions = self.settings.viewing_conditions;
+            self.picker.sliders.set_color(self.picker.current_color);
+        }
+        if self.settings.ycbcr_standard != self.picker.sliders.ycbcr_standard
+            || self.settings.ycbcr_range != self.picker.sliders.ycbcr_range
+        {
+            self.picker.sliders.ycbcr_standard = self.settings.ycbcr_standard;
+            self.picker.sliders.ycbcr_range = self.settings.ycbcr_range;
+            self.picker.sliders.set_color(self.picker.current_color);
+        }
+        if self.settings.reference_white != self.picker.sliders.reference_white
+            || self.settings.transfer_function != self.picker.sliders.transfer_function
+        {
+            self.picker.sliders.reference_white = self.settings.reference_white;
+            self.picker.sliders.transfer_function = self.settings.transfer_function;
+            self.picker.sliders.set_color(self.picker.current_color);
+        }
+        let metric = self.settings.color_name_metric;
+        if COLOR_NAMES
+            .read()
+            .is_ok_and(|names| names.metric() != metric)
+        {
+            if let Ok(mut names) = COLOR_NAMES.write() {
+                names.set_metric(metric);
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.palette_dir.as_ref().map(|store| store.dir())
+            != self.settings.palettes_dir.as_deref()
+        {
+            match self.settings.palettes_dir.clone() {
+                Some(dir) => self.open_palette_dir(dir),
+                None => self.palette_dir = None,
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(store) = &mut self.palette_dir {
+            if store.format() != self.settings.palette_file_format {
+                store.set_format(self.settings.palette_file_format);
+            }
+        }
         if self.settings.rgb_working_space != self.picker.sliders.rgb_working_space {
             self.picker.new_workspace = Some(self.settings.rgb_working_space);
             if self.settings.illuminant != self.picker.sliders.illuminant {
@@ -72,11 +72,10 @@ pub fn default_keybindings() -> KeyBindings {
                     str_key: "s",
                     key: egui::Key::S,
                     binding: Box::new(|ctx| {
-                        ctx.app
-                            .palettes
-                            .current_mut()
-                            .palette
-                            .add(ctx.app.cursor_pick_color);
+                        let current = ctx.app.palettes.current_mut();
+                        if current.palette.add(ctx.app.cursor_pick_color) {
+                            current.touch();
+                        }
                     }),
                 },
             ),
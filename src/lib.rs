@@ -1,4 +1,6 @@
 mod app;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
 mod color;
 mod color_picker;
 mod context;
@@ -13,6 +15,14 @@ mod ui;
 mod zoom_picker;
 
 pub use app::App as Epick;
+#[cfg(not(target_arch = "wasm32"))]
+pub use cli::run as run_cli;
+
+/// Items measured by the benchmarks in `benches/`
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::color::{Color, ColorFormat, CustomColorFormat, FormatOptions, Rgb};
+}
 
 use anyhow::{Context, Error};
 
@@ -43,6 +53,49 @@ fn get_timestamp() -> u64 {
     (js_sys::Date::now() / 1000.) as u64
 }
 
+/// Returns the fragment of the page address without the leading `#`
+#[cfg(target_arch = "wasm32")]
+fn url_hash() -> Option<String> {
+    use js_sys::{JsString, Reflect};
+    let location = Reflect::get(&js_sys::global(), &JsString::from("location")).ok()?;
+    let hash = Reflect::get(&location, &JsString::from("hash"))
+        .ok()?
+        .as_string()?;
+    Some(hash.trim_start_matches('#').to_string()).filter(|hash| !hash.is_empty())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn set_url_hash(hash: &str) {
+    use js_sys::{JsString, Reflect};
+    if let Ok(location) = Reflect::get(&js_sys::global(), &JsString::from("location")) {
+        let _ = Reflect::set(&location, &JsString::from("hash"), &JsString::from(hash));
+    }
+}
+
 fn elapsed(timestamp: u64) -> u64 {
     get_timestamp() - timestamp
 }
+
+/// Formats a unix timestamp as a date and time in UTC
+fn format_timestamp(timestamp: u64) -> String {
+    let days = timestamp / 86400;
+    let seconds = timestamp % 86400;
+    // days since the epoch to a civil date, from Howard Hinnant's date algorithms
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
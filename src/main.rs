@@ -9,6 +9,16 @@ fn main() {
     const APP_ICON_DATA: &[u8] = include_bytes!("../assets/icon.png");
     const APP_ICON_WIDTH: u32 = 48;
     const APP_ICON_HEIGHT: u32 = APP_ICON_WIDTH;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(result) = epick::run_cli(&args) {
+        if let Err(e) = result {
+            eprintln!("error: {:?}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let mut opts = NativeOptions::default();
 
     //pretty_env_logger::init();
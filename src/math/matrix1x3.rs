@@ -1,16 +1,17 @@
+use crate::math::Float;
 use std::ops::Index;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
-pub struct Matrix1x3(pub [f32; 3]);
+pub struct Matrix1x3(pub [Float; 3]);
 
-impl From<[f32; 3]> for Matrix1x3 {
-    fn from(arr: [f32; 3]) -> Self {
+impl From<[Float; 3]> for Matrix1x3 {
+    fn from(arr: [Float; 3]) -> Self {
         Self(arr)
     }
 }
 
 impl Index<usize> for Matrix1x3 {
-    type Output = f32;
+    type Output = Float;
 
     fn index(&self, index: usize) -> &Self::Output {
         &self.0[index]
@@ -1,23 +1,23 @@
-use crate::math::matrix1x3::Matrix1x3;
+use crate::math::{matrix1x3::Matrix1x3, Float};
 use std::ops::{Index, Mul, Neg};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
-pub struct Matrix3(pub [[f32; 3]; 3]);
+pub struct Matrix3(pub [[Float; 3]; 3]);
 
-impl From<[[f32; 3]; 3]> for Matrix3 {
-    fn from(arr: [[f32; 3]; 3]) -> Self {
+impl From<[[Float; 3]; 3]> for Matrix3 {
+    fn from(arr: [[Float; 3]; 3]) -> Self {
         Self(arr)
     }
 }
 
 impl Matrix3 {
-    pub fn determinant(&self) -> f32 {
+    pub fn determinant(&self) -> Float {
         self[0][0] * (self[1][1] * self[2][2] - self[1][2] * self[2][1])
             - self[0][1] * (self[1][0] * self[2][2] - self[1][2] * self[2][0])
             + self[0][2] * (self[1][0] * self[2][1] - self[1][1] * self[2][0])
     }
 
-    pub fn mul_by(&mut self, n: f32) {
+    pub fn mul_by(&mut self, n: Float) {
         self.0[0][0] *= n;
         self.0[0][1] *= n;
         self.0[0][2] *= n;
@@ -72,7 +72,7 @@ impl Mul<Matrix1x3> for Matrix3 {
 }
 
 impl Index<usize> for Matrix3 {
-    type Output = [f32; 3];
+    type Output = [Float; 3];
 
     fn index(&self, index: usize) -> &Self::Output {
         &self.0[index]
@@ -106,14 +106,14 @@ impl Mul for Matrix3 {
 
 #[cfg(test)]
 mod tests {
-    use crate::math::{Matrix1x3, Matrix3};
+    use crate::math::{Float, Matrix1x3, Matrix3};
 
     #[test]
     fn matrix3_determinant() {
         let got = Matrix3::from([[1., 2., 3.], [4., 5., 6.], [7., 2., 9.]]).determinant();
-        let want = -36.0f32;
+        let want: Float = -36.;
 
-        assert!((got - want).abs() < f32::EPSILON);
+        assert!((got - want).abs() < Float::EPSILON);
     }
 
     #[test]
@@ -137,7 +137,7 @@ mod tests {
     fn matrix3_mul_by_1x3() {
         let got = Matrix3::from([[1., 2., 3.], [4., 5., 6.], [7., 2., 9.]])
             * Matrix1x3::from([2., 3., 4.]);
-        let want = Matrix1x3([20.0f32, 47., 56.]);
+        let want = Matrix1x3([20., 47., 56.]);
         assert_eq!(got, want);
     }
 
@@ -4,36 +4,55 @@ mod matrix3x3;
 pub use matrix1x3::Matrix1x3;
 pub use matrix3x3::Matrix3;
 
+/// Floating point type used by the color math. Enabling the `f64` feature switches all color
+/// conversions to double precision, rendering always happens in `f32`.
+#[cfg(not(feature = "f64"))]
+pub type Float = f32;
+#[cfg(feature = "f64")]
+pub type Float = f64;
+
+#[cfg(not(feature = "f64"))]
+pub use std::f32::consts;
+#[cfg(feature = "f64")]
+pub use std::f64::consts;
+
+/// Converts a value of the color math to `f32` for rendering
+#[inline(always)]
+#[allow(clippy::unnecessary_cast)]
+pub fn to_f32(num: Float) -> f32 {
+    num as f32
+}
+
 #[inline(always)]
-pub fn wrap_f32(num: f32) -> f32 {
+pub fn wrap_float(num: Float) -> Float {
     (num + 1.).fract()
 }
 
 #[inline(always)]
-pub fn eq_f32(lhs: f32, rhs: f32) -> bool {
-    (lhs - rhs).abs() < f32::EPSILON
+pub fn eq_float(lhs: Float, rhs: Float) -> bool {
+    (lhs - rhs).abs() < Float::EPSILON
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{eq_f32, wrap_f32};
+    use super::{eq_float, wrap_float, Float};
 
     #[test]
     fn it_wraps() {
-        assert!(eq_f32(wrap_f32(0.0f32), 0.0f32));
-        assert!(eq_f32(wrap_f32(-1. / 8.), 7. / 8.));
-        assert!(eq_f32(wrap_f32(-1. / 2.), 1. / 2.));
-        assert!(eq_f32(wrap_f32(-1.), 0.));
-        assert!(eq_f32(wrap_f32(1. / 8.), 1. / 8.));
-        assert!(eq_f32(wrap_f32(1. / 2.), 1. / 2.));
-        assert!(eq_f32(wrap_f32(1.), 0.));
+        assert!(eq_float(wrap_float(0.0), 0.0));
+        assert!(eq_float(wrap_float(-1. / 8.), 7. / 8.));
+        assert!(eq_float(wrap_float(-1. / 2.), 1. / 2.));
+        assert!(eq_float(wrap_float(-1.), 0.));
+        assert!(eq_float(wrap_float(1. / 8.), 1. / 8.));
+        assert!(eq_float(wrap_float(1. / 2.), 1. / 2.));
+        assert!(eq_float(wrap_float(1.), 0.));
     }
 
     #[test]
     fn it_eqs() {
-        assert!(eq_f32(0., 0.));
-        assert!(!eq_f32(0., f32::NAN));
-        assert!(!eq_f32(0., 1.));
-        assert!(eq_f32(1., 1.));
+        assert!(eq_float(0., 0.));
+        assert!(!eq_float(0., Float::NAN));
+        assert!(!eq_float(0., 1.));
+        assert!(eq_float(1., 1.));
     }
 }
@@ -1,8 +1,10 @@
 use crate::{
     color::{
-        ChromaticAdaptationMethod, ColorFormat, ColorHarmony, CustomPaletteFormat, Illuminant,
-        PaletteFormat, RgbWorkingSpace,
+        ChromaticAdaptationMethod, ColorFormat, ColorHarmony, CustomPaletteFormat, DeltaE,
+        Illuminant, Observer, PaletteFileFormat, PaletteFormat, RgbWorkingSpace, TransferFunction,
+        ViewingConditions, YCbCrRange, YCbCrStandard, DEFAULT_REFERENCE_WHITE,
     },
+    math::Float,
     ui::layout::HarmonyLayout,
 };
 
@@ -72,6 +74,42 @@ fn is_default_harmony(it: &ColorHarmony) -> bool {
     *it == ColorHarmony::default()
 }
 
+fn is_default_viewing_conditions(it: &ViewingConditions) -> bool {
+    *it == ViewingConditions::default()
+}
+
+fn is_default_observer(it: &Observer) -> bool {
+    *it == Observer::default()
+}
+
+fn is_default_ycbcr_standard(it: &YCbCrStandard) -> bool {
+    *it == YCbCrStandard::default()
+}
+
+fn is_default_ycbcr_range(it: &YCbCrRange) -> bool {
+    *it == YCbCrRange::default()
+}
+
+fn is_default_transfer_function(it: &TransferFunction) -> bool {
+    *it == TransferFunction::default()
+}
+
+fn is_default_delta_e(it: &DeltaE) -> bool {
+    *it == DeltaE::default()
+}
+
+fn is_default_palette_file_format(it: &PaletteFileFormat) -> bool {
+    *it == PaletteFileFormat::default()
+}
+
+fn is_default_reference_white(it: &Float) -> bool {
+    *it == DEFAULT_REFERENCE_WHITE
+}
+
+fn default_reference_white() -> Float {
+    DEFAULT_REFERENCE_WHITE
+}
+
 fn is_default_color_size(it: &f32) -> bool {
     *it == DEFAULT_COLOR_SIZE
 }
@@ -104,10 +142,31 @@ pub struct ColorSpaceSettings {
     pub lch_uv: bool,
     #[serde(default)]
     #[serde(skip_serializing_if = "is_false")]
+    pub hsluv: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub hpluv: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
     pub lab: bool,
     #[serde(default)]
     #[serde(skip_serializing_if = "is_false")]
     pub lch_ab: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub cam16: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub cam16_ucs: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub hwb: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub ycbcr: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub ictcp: bool,
 }
 
 impl Default for ColorSpaceSettings {
@@ -119,8 +178,15 @@ impl Default for ColorSpaceSettings {
             hsl: true,
             luv: false,
             lch_uv: false,
+            hsluv: false,
+            hpluv: false,
             lab: false,
             lch_ab: false,
+            cam16: false,
+            cam16_ucs: false,
+            hwb: false,
+            ycbcr: false,
+            ictcp: false,
         }
     }
 }
@@ -147,9 +213,49 @@ pub struct Settings {
     pub chromatic_adaptation_method: ChromaticAdaptationMethod,
     #[serde(default)]
     pub illuminant: Illuminant,
+    /// Viewing conditions used by the CAM16 color appearance model
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default_viewing_conditions")]
+    pub viewing_conditions: ViewingConditions,
+    /// Standard observer used when computing colors from spectral data
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default_observer")]
+    pub observer: Observer,
+    /// Luma coefficients used by the YCbCr sliders
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default_ycbcr_standard")]
+    pub ycbcr_standard: YCbCrStandard,
+    /// Range of code values displayed by the YCbCr sliders
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default_ycbcr_range")]
+    pub ycbcr_range: YCbCrRange,
+    /// Show the HDR sliders where the color can be brighter than the reference white
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub hdr_mode: bool,
+    /// Luminance in cd/m² of the working space white when encoding HDR signals and ICtCp
+    #[serde(default = "default_reference_white")]
+    #[serde(skip_serializing_if = "is_default_reference_white")]
+    pub reference_white: Float,
+    /// Transfer function used for HDR signal and code values
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default_transfer_function")]
+    pub transfer_function: TransferFunction,
+    /// Metric used to find the nearest color name
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default_delta_e")]
+    pub color_name_metric: DeltaE,
     #[serde(default = "enabled")]
     #[serde(skip_serializing_if = "is_true")]
     pub cache_colors: bool,
+    /// Directory storing every palette in a separate file instead of a single palettes file
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub palettes_dir: Option<PathBuf>,
+    /// Format of new palette files in the palettes directory
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default_palette_file_format")]
+    pub palette_file_format: PaletteFileFormat,
     #[serde(default)]
     #[serde(skip_serializing_if = "is_default_harmony")]
     pub harmony: ColorHarmony,
@@ -198,7 +304,17 @@ impl Default for Settings {
             rgb_working_space: ws,
             chromatic_adaptation_method: ChromaticAdaptationMethod::default(),
             illuminant: ws.reference_illuminant(),
+            viewing_conditions: ViewingConditions::default(),
+            observer: Observer::default(),
+            ycbcr_standard: YCbCrStandard::default(),
+            ycbcr_range: YCbCrRange::default(),
+            hdr_mode: false,
+            reference_white: DEFAULT_REFERENCE_WHITE,
+            transfer_function: TransferFunction::default(),
+            color_name_metric: DeltaE::default(),
             cache_colors: true,
+            palettes_dir: None,
+            palette_file_format: PaletteFileFormat::default(),
             is_dark_mode: true,
             harmony: ColorHarmony::default(),
             harmony_layout: HarmonyLayout::default(),
@@ -295,7 +411,6 @@ impl ColorDisplayFmtEnum {
 mod tests {
     use crate::{
         color::{ChromaticAdaptationMethod, ColorHarmony, Illuminant, RgbWorkingSpace},
-        math::eq_f32,
         settings::{Settings, DEFAULT_COLOR_SIZE},
         ui::layout::HarmonyLayout,
     };
@@ -341,7 +456,7 @@ harmony_layout: gradient
 
         assert_eq!(settings.harmony, ColorHarmony::default());
         assert_eq!(settings.harmony_layout, HarmonyLayout::Gradient);
-        assert!(eq_f32(settings.harmony_color_size, DEFAULT_COLOR_SIZE));
+        assert!((settings.harmony_color_size - DEFAULT_COLOR_SIZE).abs() < f32::EPSILON);
         assert!(!settings.harmony_display_color_label);
 
         let path = tmp.path().join("new_settings.yaml");
@@ -0,0 +1,198 @@
+//! Text edits of custom color and palette formats with highlighting and field completion.
+
+use crate::color::{
+    complete_field, format_fields, highlight_format, FieldHelp, FormatKind, FormatSpanKind,
+    TemplateError, FUNCTIONS,
+};
+
+use egui::{
+    text::{CCursor, CCursorRange, LayoutJob, TextFormat},
+    CollapsingHeader, Color32, Grid, Id, Key, Modifiers, Response, RichText, ScrollArea, Stroke,
+    TextEdit, TextStyle, Ui,
+};
+use std::ops::Range;
+
+/// Number of completions shown below a format edit
+const MAX_COMPLETIONS: usize = 12;
+
+/// Edits a format with highlighted fields, `Tab` completes the partly typed field name before
+/// the cursor
+pub fn format_edit(
+    ui: &mut Ui,
+    text: &mut String,
+    id: Id,
+    kind: FormatKind,
+    multiline: bool,
+) -> Response {
+    if ui.memory().has_focus(id) {
+        if let Some((range, fields)) =
+            cursor(ui, text, id).and_then(|cursor| complete_field(text, cursor, kind))
+        {
+            if ui.input_mut().consume_key(Modifiers::NONE, Key::Tab) {
+                complete(ui, text, id, range, fields[0]);
+            }
+        }
+    }
+
+    let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
+        let mut job = highlight_job(ui, text, kind);
+        job.wrap.max_width = wrap_width;
+        ui.fonts().layout_job(job)
+    };
+    let edit = if multiline {
+        TextEdit::multiline(text).desired_rows(1)
+    } else {
+        TextEdit::singleline(text)
+    };
+    edit.id(id)
+        .font(TextStyle::Monospace)
+        .lock_focus(true)
+        .layouter(&mut layouter)
+        .show(ui)
+        .response
+}
+
+/// Shows the fields completing the partly typed name before the cursor of the format edit
+pub fn format_completions(ui: &mut Ui, text: &mut String, id: Id, kind: FormatKind) {
+    let (range, fields) =
+        match cursor(ui, text, id).and_then(|cursor| complete_field(text, cursor, kind)) {
+            Some(completion) => completion,
+            None => return,
+        };
+    ui.horizontal_wrapped(|ui| {
+        for field in fields.iter().take(MAX_COMPLETIONS) {
+            if ui
+                .small_button(RichText::new(field.name).monospace())
+                .on_hover_text(field.description)
+                .clicked()
+            {
+                complete(ui, text, id, range.clone(), field);
+            }
+        }
+        if fields.len() > MAX_COMPLETIONS {
+            ui.weak(format!("and {} more", fields.len() - MAX_COMPLETIONS));
+        }
+    });
+}
+
+/// Shows the error of a format with its position
+pub fn format_error(ui: &mut Ui, error: &TemplateError) {
+    ui.colored_label(Color32::RED, error.to_string());
+}
+
+/// Lists the fields the format can use, clicking one inserts it at the cursor of the format edit
+pub fn field_reference(ui: &mut Ui, text: &mut String, id: Id, kind: FormatKind) {
+    CollapsingHeader::new("Fields")
+        .id_source(id.with("fields"))
+        .show(ui, |ui| {
+            ScrollArea::vertical().max_height(200.).show(ui, |ui| {
+                Grid::new(id.with("fields_grid"))
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for field in format_fields(kind) {
+                            if ui
+                                .small_button(RichText::new(field.name).monospace())
+                                .on_hover_text("Insert at the cursor")
+                                .clicked()
+                            {
+                                insert(ui, text, id, field);
+                            }
+                            ui.label(field.description);
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+}
+
+/// Byte offset of the cursor of the format edit, kept after it loses focus
+fn cursor(ui: &Ui, text: &str, id: Id) -> Option<usize> {
+    let index = TextEdit::load_state(ui.ctx(), id)?
+        .ccursor_range()?
+        .primary
+        .index;
+    Some(
+        text.char_indices()
+            .nth(index)
+            .map_or(text.len(), |(i, _)| i),
+    )
+}
+
+fn complete(ui: &Ui, text: &mut String, id: Id, range: Range<usize>, field: &FieldHelp) {
+    if is_function(field) {
+        replace(ui, text, id, range, &format!("{}()", field.name), 1);
+    } else {
+        replace(ui, text, id, range, field.name, 0);
+    }
+}
+
+fn insert(ui: &Ui, text: &mut String, id: Id, field: &FieldHelp) {
+    let cursor = cursor(ui, text, id).unwrap_or(text.len());
+    let in_field = text[..cursor]
+        .rfind('{')
+        .is_some_and(|brace| !text[brace..cursor].contains('}'));
+    let (insertion, cursor_back) = match (in_field, is_function(field)) {
+        (true, true) => (format!("{}()", field.name), 1),
+        (true, false) => (field.name.to_string(), 0),
+        (false, true) => (format!("{{{}()}}", field.name), 2),
+        (false, false) => (format!("{{{}}}", field.name), 0),
+    };
+    replace(ui, text, id, cursor..cursor, &insertion, cursor_back);
+}
+
+fn is_function(field: &FieldHelp) -> bool {
+    FUNCTIONS.contains(field)
+}
+
+/// Replaces the range of the text and moves the cursor of the edit to `cursor_back` characters
+/// before the end of the insertion
+fn replace(
+    ui: &Ui,
+    text: &mut String,
+    id: Id,
+    range: Range<usize>,
+    insertion: &str,
+    cursor_back: usize,
+) {
+    let start = range.start;
+    text.replace_range(range, insertion);
+    let cursor = text[..start + insertion.len()].chars().count() - cursor_back;
+    let mut state = TextEdit::load_state(ui.ctx(), id).unwrap_or_default();
+    state.set_ccursor_range(Some(CCursorRange::one(CCursor::new(cursor))));
+    TextEdit::store_state(ui.ctx(), id, state);
+    ui.memory().request_focus(id);
+}
+
+fn highlight_job(ui: &Ui, text: &str, kind: FormatKind) -> LayoutJob {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let visuals = ui.visuals();
+    let mut job = LayoutJob::default();
+    for span in highlight_format(text, kind) {
+        let color = span_color(span.kind, visuals.dark_mode, visuals.text_color());
+        let mut format = TextFormat::simple(font_id.clone(), color);
+        if span.kind == FormatSpanKind::Error {
+            format.underline = Stroke::new(1., color);
+        }
+        job.append(&text[span.range], 0., format);
+    }
+    job
+}
+
+fn span_color(kind: FormatSpanKind, dark_mode: bool, text: Color32) -> Color32 {
+    let (dark, light) = match kind {
+        FormatSpanKind::Text => return text,
+        FormatSpanKind::Delimiter | FormatSpanKind::Operator => {
+            ((0x9a, 0xa5, 0xb1), (0x6a, 0x73, 0x7d))
+        }
+        FormatSpanKind::Symbol => ((0x79, 0xb8, 0xff), (0x00, 0x5c, 0xc5)),
+        FormatSpanKind::Function | FormatSpanKind::Keyword => {
+            ((0xd2, 0xa8, 0xff), (0x6f, 0x42, 0xc1))
+        }
+        FormatSpanKind::Number => ((0xff, 0xab, 0x70), (0xb3, 0x50, 0x00)),
+        FormatSpanKind::DigitFormat => ((0x85, 0xe8, 0x9d), (0x22, 0x86, 0x3a)),
+        FormatSpanKind::Error => ((0xff, 0x7b, 0x72), (0xcf, 0x22, 0x2e)),
+    };
+    let (r, g, b) = if dark_mode { dark } else { light };
+    Color32::from_rgb(r, g, b)
+}
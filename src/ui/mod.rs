@@ -1,9 +1,10 @@
 pub mod colorbox;
+pub mod format_editor;
 pub mod layout;
 pub mod slider_1d;
 pub mod slider_2d;
 
-use crate::color::{Color, ColorFormat, Illuminant, RgbWorkingSpace};
+use crate::color::{nearest_name, Color, ColorFormat, FormatOptions};
 
 use egui::{
     color,
@@ -24,6 +25,8 @@ pub mod icon {
     pub static SETTINGS: &str = "\u{2699}";
     pub static EXPAND: &str = "\u{2B0C}";
     pub static EXPORT: &str = "\u{1F5B9}";
+    pub static IMPORT: &str = "\u{1F4E5}";
+    pub static ILLUMINANT: &str = "\u{1F4A1}";
     pub static CLEAR: &str = "\u{1F5D1}";
     pub static DELETE: &str = "\u{1F5D9}";
     pub static PLAY: &str = "\u{25B6}";
@@ -32,6 +35,10 @@ pub mod icon {
     pub static HELP: &str = "\u{FF1F}";
     pub static EDIT: &str = "\u{270F}";
     pub static APPLY: &str = "\u{2714}";
+    pub static TOOLS: &str = "\u{1F527}";
+    pub static INFO: &str = "\u{2139}";
+    pub static RESTORE: &str = "\u{21BA}";
+    pub static SHARE: &str = "\u{1F517}";
 }
 
 #[allow(dead_code)]
@@ -163,13 +170,16 @@ pub fn drop_target<R>(
 pub fn color_tooltip(
     color: &Color,
     display_format: ColorFormat,
-    ws: RgbWorkingSpace,
-    illuminant: Illuminant,
+    options: &FormatOptions,
     text: Option<&str>,
 ) -> String {
+    let name = nearest_name(color)
+        .map(|(name, delta_e)| format!("\n{} (ΔE {:.1})", name, delta_e))
+        .unwrap_or_default();
     format!(
-        "{}\n\n{}",
-        color.display(display_format, ws, illuminant),
+        "{}{}\n\n{}",
+        color.display(display_format, options),
+        name,
         text.unwrap_or_default()
     )
 }
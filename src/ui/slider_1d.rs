@@ -1,4 +1,7 @@
-use crate::color::Color;
+use crate::{
+    color::Color,
+    math::{self, Float},
+};
 use eframe::egui::{epaint::Mesh, lerp, remap_clamp, Shape, Stroke};
 use egui::{pos2, vec2, Color32, CursorIcon, Response, Sense, Ui};
 use std::ops::{Neg, RangeInclusive};
@@ -10,9 +13,9 @@ pub const NUM_OF_VERTICES: u32 = 6 * 6;
 
 pub fn color(
     ui: &mut Ui,
-    value: &mut f32,
-    range: RangeInclusive<f32>,
-    color_at: impl Fn(f32) -> Color32,
+    value: &mut Float,
+    range: RangeInclusive<Float>,
+    color_at: impl Fn(Float) -> Color32,
 ) -> Response {
     let width = ui.spacing().slider_width * 2.;
 
@@ -29,7 +32,11 @@ pub fn color(
     let (rect, mut response) = ui.allocate_at_least(desired_size, Sense::click_and_drag());
 
     if let Some(mpos) = response.interact_pointer_pos() {
-        *value = remap_clamp(mpos.x, rect.left()..=rect.right(), range);
+        *value = remap_clamp(
+            mpos.x as Float,
+            rect.left() as Float..=rect.right() as Float,
+            range,
+        );
     }
 
     let visuals = ui.style().interact(&response);
@@ -39,7 +46,7 @@ pub fn color(
         let mut mesh = Mesh::default();
         for i in 0..=NUM_OF_VERTICES {
             let pos = i as f32 / (NUM_OF_VERTICES as f32);
-            let color_pos = lerp(range_start..=_range_end, pos);
+            let color_pos = lerp(range_start..=_range_end, pos as Float);
             let color = color_at(color_pos);
             let mesh_pos = lerp(rect.left()..=rect.right(), pos);
             mesh.colored_vertex(pos2(mesh_pos, rect.top()), color);
@@ -62,7 +69,7 @@ pub fn color(
         } else {
             x
         };
-        let x = rect.left() + (x / range_end) * width;
+        let x = rect.left() + math::to_f32(x / range_end) * width;
         let r = rect.height() / 4.0;
 
         // Show where the slider is at:
@@ -1,6 +1,9 @@
 use super::slider_1d::NUM_OF_VERTICES;
 
-use crate::color::Color;
+use crate::{
+    color::Color,
+    math::{self, Float},
+};
 use eframe::egui::{epaint::Mesh, lerp, remap_clamp, Sense, Shape, Stroke, Vec2};
 use egui::{pos2, Color32, CursorIcon, Response, Ui};
 use epaint::CircleShape;
@@ -8,19 +11,27 @@ use std::ops::RangeInclusive;
 
 pub fn color(
     ui: &mut Ui,
-    x_value: &mut f32,
-    y_value: &mut f32,
-    x_range: RangeInclusive<f32>,
-    y_range: RangeInclusive<f32>,
-    color_at: impl Fn(f32, f32) -> Color32,
+    x_value: &mut Float,
+    y_value: &mut Float,
+    x_range: RangeInclusive<Float>,
+    y_range: RangeInclusive<Float>,
+    color_at: impl Fn(Float, Float) -> Color32,
 ) -> Response {
     let width = ui.spacing().slider_width * 2.;
     let desired_size = Vec2::new(width, width * 2. / 3.);
     let (rect, mut response) = ui.allocate_at_least(desired_size, Sense::click_and_drag());
 
     if let Some(mpos) = response.interact_pointer_pos() {
-        *x_value = remap_clamp(mpos.x, rect.left()..=rect.right(), x_range.clone());
-        *y_value = remap_clamp(mpos.y, rect.bottom()..=rect.top(), y_range.clone());
+        *x_value = remap_clamp(
+            mpos.x as Float,
+            rect.left() as Float..=rect.right() as Float,
+            x_range.clone(),
+        );
+        *y_value = remap_clamp(
+            mpos.y as Float,
+            rect.bottom() as Float..=rect.top() as Float,
+            y_range.clone(),
+        );
     }
 
     let visuals = ui.style().interact(&response);
@@ -30,8 +41,8 @@ pub fn color(
         for yi in 0..=NUM_OF_VERTICES {
             let xt = xi as f32 / (NUM_OF_VERTICES as f32);
             let yt = yi as f32 / (NUM_OF_VERTICES as f32);
-            let color_x = lerp(x_range.clone(), xt);
-            let color_y = lerp(y_range.clone(), yt);
+            let color_x = lerp(x_range.clone(), xt as Float);
+            let color_y = lerp(y_range.clone(), yt as Float);
             let color = color_at(color_x, color_y);
             let x = lerp(rect.left()..=rect.right(), xt);
             let y = lerp(rect.bottom()..=rect.top(), yt);
@@ -50,8 +61,16 @@ pub fn color(
 
     ui.painter().rect_stroke(rect, 0.0, visuals.bg_stroke); // outline
 
-    let x = remap_clamp(*x_value, x_range, rect.left()..=rect.right());
-    let y = remap_clamp(*y_value, y_range, rect.bottom()..=rect.top());
+    let x = math::to_f32(remap_clamp(
+        *x_value,
+        x_range,
+        rect.left() as Float..=rect.right() as Float,
+    ));
+    let y = math::to_f32(remap_clamp(
+        *y_value,
+        y_range,
+        rect.bottom() as Float..=rect.top() as Float,
+    ));
 
     let picked_color = Color::Color32(color_at(*x_value, *y_value));
 